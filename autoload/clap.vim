@@ -33,8 +33,12 @@ let s:provider_alias = {
 
 let s:provider_alias = extend(s:provider_alias, get(g:, 'clap_provider_alias', {}))
 let g:clap#provider_alias = s:provider_alias
+
+" Provider aliases defined in config.toml, the preset arguments are handled by maple.
+let s:config_provider_alias = {}
 let g:clap_disable_run_rooter = get(g:, 'clap_disable_run_rooter', v:false)
 let g:clap_disable_bottom_top = get(g:, 'clap_disable_bottom_top', 0)
+let g:clap_enable_msgpack = get(g:, 'clap_enable_msgpack', has('nvim'))
 let g:clap_enable_debug = get(g:, 'clap_enable_debug', v:false)
 let g:clap_forerunner_status_sign = get(g:, 'clap_forerunner_status_sign', {'done': '•', 'running': '!', 'using_cache': '*'})
 
@@ -77,6 +81,7 @@ let g:clap_popup_border = get(g:, 'clap_popup_border', has('nvim') ? 'single' :
 let g:clap_preview_size = get(g:, 'clap_preview_size', 5)
 let g:clap_preview_direction = get(g:, 'clap_preview_direction', 'AUTO')
 let g:clap_insert_mode_only = get(g:, 'clap_insert_mode_only', v:false)
+let g:clap_input_history_with_arrows = get(g:, 'clap_input_history_with_arrows', v:false)
 let g:clap_background_shadow_blend = get(g:, 'clap_background_shadow_blend', 50)
 let g:clap_providers_relaunch_code = get(g:, 'clap_providers_relaunch_code', '@@')
 let g:clap_enable_background_shadow = get(g:, 'clap_enable_background_shadow', v:false)
@@ -249,9 +254,17 @@ function! s:try_register_is_ok(provider_id) abort
   return s:validate_provider(registration_info)
 endfunction
 
+function! clap#set_config_provider_alias(aliases) abort
+  let s:config_provider_alias = a:aliases
+endfunction
+
 function! clap#for(provider_id_or_alias) abort
+  let g:clap.provider.alias = v:null
   if has_key(s:provider_alias, a:provider_id_or_alias)
     let provider_id = s:provider_alias[a:provider_id_or_alias]
+  elseif has_key(s:config_provider_alias, a:provider_id_or_alias)
+    let provider_id = s:config_provider_alias[a:provider_id_or_alias]
+    let g:clap.provider.alias = a:provider_id_or_alias
   else
     let provider_id = a:provider_id_or_alias
   endif
@@ -297,6 +310,16 @@ function! clap#for(provider_id_or_alias) abort
   call clap#indicator#render()
 endfunction
 
+function! s:resume(snapshot, error) abort
+  if a:error isnot v:null || a:snapshot is v:null
+    call clap#helper#echo_warn('No session to resume')
+    return
+  endif
+  let g:clap.context.resume = v:true
+  let g:clap.provider.args = a:snapshot.args
+  call clap#for(a:snapshot.provider_id)
+endfunction
+
 function! clap#(bang, ...) abort
   if !exists('g:clap')
     call clap#init#()
@@ -331,6 +354,12 @@ function! clap#(bang, ...) abort
     elseif a:000 == ['debug+']
       call clap#debugging#info_to_clipboard()
       return
+    elseif a:1 ==# 'log-level'
+      call clap#debugging#set_log_level(join(a:000[1:]))
+      return
+    elseif a:000 == ['++resume']
+      call clap#client#request_async('session/last', function('s:resume'))
+      return
     endif
     if a:1 ==# '!'
       let g:clap.context['no-cache'] = v:true
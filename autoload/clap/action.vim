@@ -29,18 +29,53 @@ function! s:invoke_action() abort
   endif
 endfunction
 
-function! clap#action#invoke() abort
-  if !has_key(g:clap.provider._(), 'action')
-    call clap#helper#echo_warn('action not implemented in provider '.g:clap.provider.id)
-    return ''
-  endif
+function! s:open_action_menu(provider_action) abort
   if has('nvim')
-    call clap#floating_win#action#create()
+    call clap#floating_win#action#create(a:provider_action)
   else
-    call clap#popup#action#invoke()
+    call clap#popup#action#invoke(a:provider_action)
+  endif
+endfunction
+
+function! clap#action#invoke() abort
+  if has_key(g:clap.provider._(), 'action')
+    call s:open_action_menu(g:clap.provider._().action)
+  elseif clap#job#daemon#is_running()
+    " The actions applicable to the current entry are sent back by maple.
+    call clap#client#notify_provider('entry_action')
+  else
+    call clap#helper#echo_warn('action not implemented in provider '.g:clap.provider.id)
   endif
   return ''
 endfunction
 
+" Opens the menu of the entry actions, a list of [title, name] sent by maple.
+function! clap#action#invoke_entry_actions(actions) abort
+  let provider_action = {}
+  for [title, name] in a:actions
+    let provider_action[title] = function('clap#client#notify_provider', ['entry_action', {'action': name}])
+  endfor
+  call s:open_action_menu(provider_action)
+endfunction
+
+function! clap#action#set_clipboard(text) abort
+  call setreg('"', a:text)
+  if has('clipboard')
+    call setreg('+', a:text)
+  endif
+  call clap#helper#echo_info('Copied '.a:text)
+endfunction
+
+function! s:open_filer(dir, _timer) abort
+  execute 'Clap filer' fnameescape(a:dir)
+endfunction
+
+" Exits clap and opens the filer in `dir`.
+function! clap#action#reveal_in_filer(dir) abort
+  call clap#handler#exit()
+  " The new provider can only be opened once the current one is closed.
+  call timer_start(0, function('s:open_filer', [a:dir]))
+endfunction
+
 let &cpoptions = s:save_cpo
 unlet s:save_cpo
@@ -67,6 +67,13 @@ function! s:api.display_getcurline() abort
   return [g:clap.display.getcurline(), get(g:, '__clap_icon_added_by_maple', v:false)]
 endfunction
 
+" Returns all the lines in display window.
+function! s:api.display_matched_lines() abort
+  let lnums = range(1, getbufinfo(g:clap.display.bufnr)[0].linecount)
+  let lines = filter(map(lnums, 'clap#api#get_origin_line_at(v:val)'), '!empty(v:val)')
+  return [lines, get(g:, '__clap_icon_added_by_maple', v:false)]
+endfunction
+
 function! s:api.display_set_lines(lines) abort
   call g:clap.display.set_lines(a:lines)
 endfunction
@@ -131,6 +138,10 @@ function! s:api.append_and_write(lnum, text) abort
   silent noautocmd write
 endfunction
 
+function! s:api.preview_hide() abort
+  call g:clap.preview.hide()
+endfunction
+
 function! s:api.show_lines_in_preview(lines) abort
   if type(a:lines) is v:t_string
     call g:clap.preview.show([a:lines])
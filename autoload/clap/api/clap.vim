@@ -359,8 +359,12 @@ function! s:init_provider() abort
     return get(self._(), 'filter', v:null)
   endfunction
 
+  " `'multi_select': v:true` opts in the multi-selection, the multi-selected entries
+  " are accepted one by one if the provider has no `sink*`.
   function! provider.support_multi_select() abort
-    return has_key(self._(), 'sink*') || has_key(get(self._(), 'mappings', {}), "<Tab>")
+    return has_key(self._(), 'sink*')
+          \ || has_key(get(self._(), 'mappings', {}), "<Tab>")
+          \ || get(self._(), 'multi_select', v:false)
   endfunction
 
   function! provider.support_open_action() abort
@@ -429,7 +433,7 @@ function! s:matchaddpos(lnum) abort
   if exists('w:clap_preview_hi_id')
     call matchdelete(w:clap_preview_hi_id)
   endif
-  let w:clap_preview_hi_id = matchaddpos('Search', [[a:lnum]])
+  let w:clap_preview_hi_id = matchaddpos('ClapPreviewTargetLine', [[a:lnum]])
 endfunction
 
 function! clap#api#clap#init() abort
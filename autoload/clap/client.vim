@@ -9,7 +9,11 @@ let s:handlers = get(s:, 'handlers', {})
 let s:session_id = get(s:, 'session_id', 0)
 
 function! clap#client#handle(msg) abort
-  let decoded = json_decode(a:msg)
+  call clap#client#handle_decoded(json_decode(a:msg))
+endfunction
+
+function! clap#client#handle_decoded(decoded) abort
+  let decoded = a:decoded
 
   if has_key(decoded, 'deprecated_method')
     call call(decoded.deprecated_method, [decoded])
@@ -67,6 +71,11 @@ function! clap#client#notify(method, ...) abort
   endif
 endfunction
 
+" Sends the autocmd event along with the visible line range of current window.
+function! clap#client#notify_autocmd(autocmd) abort
+  call clap#client#notify(a:autocmd, [bufnr(''), win_getid(), line('w0'), line('w$')])
+endfunction
+
 " Optional argument: params: v:null, List, Dict
 function! clap#client#request_async(method, callback, ...) abort
   call s:request_async(a:method, get(a:000, 0, v:null))
@@ -91,6 +100,8 @@ function! clap#client#notify_on_init(...) abort
         \   'debounce': get(g:clap.provider._(), 'debounce', v:true),
         \   'no_cache': has_key(g:clap.context, 'no-cache') ? v:true : v:false,
         \   'start_buffer_path': expand('#'.g:clap.start.bufnr.':p'),
+        \   'alias': get(g:clap.provider, 'alias', v:null),
+        \   'resume': get(g:clap.context, 'resume', v:false),
         \ }
   if a:0 > 0
     call extend(params, a:1)
@@ -110,5 +110,20 @@ function! clap#debugging#info_to_clipboard() abort
   echohl Normal   | echon ' copied to your clipboard' | echohl NONE
 endfunction
 
+function! s:on_log_level_set(result, error) abort
+  if a:error isnot v:null
+    call clap#helper#echo_warn(string(a:error))
+  elseif has_key(a:result, 'error')
+    call clap#helper#echo_warn(a:result.error)
+  else
+    call clap#helper#echo_info('Log filter: '.a:result.filter)
+  endif
+endfunction
+
+" Adjusts the log filter of maple at runtime, e.g., `maple_core::searcher=trace`.
+function! clap#debugging#set_log_level(filter) abort
+  call clap#client#request_async('system/set-log-level', function('s:on_log_level_set'), {'filter': a:filter})
+endfunction
+
 let &cpoptions = s:save_cpo
 unlet s:save_cpo
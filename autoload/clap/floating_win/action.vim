@@ -5,10 +5,11 @@ scriptencoding utf-8
 let s:save_cpo = &cpoptions
 set cpoptions&vim
 
-function! clap#floating_win#action#create() abort
+function! clap#floating_win#action#create(provider_action) abort
   let buf = nvim_create_buf(v:false, v:true)
 
-  let provider_action = g:clap.provider._().action
+  let s:provider_action = a:provider_action
+  let provider_action = a:provider_action
   if has_key(provider_action, 'title')
     let title = provider_action['title']()
   else
@@ -61,7 +62,7 @@ endfunction
 
 function! clap#floating_win#action#apply_choice() abort
   if has_key(s:lnum2key, line('.'))
-    let provider_action = g:clap.provider._().action
+    let provider_action = s:provider_action
     let action_key = s:lnum2key[line('.')]
     call clap#util#nvim_win_close_safe(s:action_winid)
     " TODO: add `action*` for performing actions against multi-selected entries?
@@ -68,6 +68,9 @@ function! s:provider_sink() abort
 
   let preserved_selections = clap#sign#preserved_selections()
 
+  " Sent to maple along with the exit notification for the `on-entry-accepted` hook.
+  let g:__clap_accepted_entries = type(sink_args) == v:t_list ? copy(sink_args) : [sink_args]
+
   call s:internal_exit()
 
   try
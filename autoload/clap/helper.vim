@@ -44,7 +44,7 @@ function! clap#helper#complete(ArgLead, CmdLine, P) abort
     endif
   endif
   let registered = exists('g:clap') ? keys(g:clap.registrar) : []
-  let registered += ['install-binary', 'install-binary!', 'debug', 'debug+']
+  let registered += ['install-binary', 'install-binary!', 'debug', 'debug+', '++resume', 'log-level']
   if !exists('s:autoload_providers')
     let s:autoload_providers = map(split(globpath(&runtimepath, 'autoload/clap/provider/*.vim'), "\n"), 'fnamemodify(v:val, ":t:r")')
   endif
@@ -22,13 +22,14 @@ if has('nvim')
   "
   " TODO: Once the default highlight priority of nvim_buf_add_highlight() is
   " higher, we could use the same impl with vim's s:apply_highlight().
-  function! s:add_display_highlights_inner(hl_lines) abort
-    " We should not use clearmatches() here.
-    call g:clap.display.matchdelete()
-
-    let w:clap_match_ids = []
+  function! s:add_display_highlights_inner(hl_lines, offset) abort
+    if a:offset == 0
+      " We should not use clearmatches() here.
+      call g:clap.display.matchdelete()
+      let w:clap_match_ids = []
+    endif
 
-    let lnum = 0
+    let lnum = a:offset
     for indices in a:hl_lines
       let group_idx = 1
       for idx in indices
@@ -44,8 +45,8 @@ if has('nvim')
   endfunction
 
   if exists('*win_execute')
-    function! s:add_display_highlights(hl_lines) abort
-      call win_execute(g:clap.display.winid, 'call s:add_display_highlights_inner(a:hl_lines)')
+    function! s:add_display_highlights(hl_lines, offset) abort
+      call win_execute(g:clap.display.winid, 'call s:add_display_highlights_inner(a:hl_lines, a:offset)')
     endfunction
 
     " This is same with g:clap.display.clear_highlight()
@@ -53,9 +54,9 @@ if has('nvim')
       call win_execute(g:clap.display.winid, 'call g:clap.display.matchdelete()')
     endfunction
   else
-    function! s:add_display_highlights(hl_lines) abort
+    function! s:add_display_highlights(hl_lines, offset) abort
       noautocmd call g:clap.display.goto_win()
-      call s:add_display_highlights_inner(a:hl_lines)
+      call s:add_display_highlights_inner(a:hl_lines, a:offset)
       noautocmd call g:clap.input.goto_win()
     endfunction
 
@@ -71,14 +72,14 @@ else
     call prop_add(a:lnum+1, a:col+1, {'length': 1, 'type': a:hl_group, 'bufnr': g:clap.display.bufnr})
   endfunction
 
-  function! s:add_display_highlights(hl_lines) abort
+  function! s:add_display_highlights(hl_lines, offset) abort
     " Avoid the error invalid buf
     if !bufexists(g:clap.display.bufnr)
       return
     endif
     " We do not have to clear the previous matches like neovim
     " as the previous lines have been deleted, and the associated text_props have also been removed.
-    let lnum = 0
+    let lnum = a:offset
     for indices in a:hl_lines
       let group_idx = 1
       for idx in indices
@@ -99,7 +100,17 @@ endif
 
 function! clap#highlighter#add_highlights(hl_lines) abort
   try
-    call s:add_display_highlights(a:hl_lines)
+    call s:add_display_highlights(a:hl_lines, 0)
+  catch
+    return
+  endtry
+endfunction
+
+" Adds the highlights of the lines starting from the 0-based `offset`, the
+" existing highlights are kept.
+function! clap#highlighter#add_highlights_from(offset, hl_lines) abort
+  try
+    call s:add_display_highlights(a:hl_lines, a:offset)
   catch
     return
   endtry
@@ -138,7 +138,12 @@ function! s:generic_hi_icons(head_only) abort
     let group = 'ClapIcon'.idx
     call add(groups, group)
     execute 'syntax match' group pat_prefix.icons[idx].'/' 'contained'
-    execute 'hi!' group s:get_attrs(s:linked_groups[lk_idx])
+    " The icon highlight defined in the palette of config.toml takes precedence.
+    if hlexists('ClapIcon')
+      execute 'hi! link' group 'ClapIcon'
+    else
+      execute 'hi!' group s:get_attrs(s:linked_groups[lk_idx])
+    endif
     let lk_idx += 1
     let lk_idx = lk_idx % s:linked_groups_len
   endfor
@@ -16,11 +16,45 @@ if has('nvim')
 
   let s:round_message = ''
   let s:content_length = 0
+  " The last line of stdout data is incomplete until the next stdout callback.
+  let s:partial_line = ''
+  " Remaining bytes of the MessagePack payload including the trailing newline, -1 if no
+  " MessagePack message is being read.
+  let s:msgpack_remaining = -1
+  let s:msgpack_chunks = []
+
+  " The MessagePack payload may contain the newline bytes, it's collected as the
+  " readfile()-style list accepted by msgpackparse().
+  function! s:read_msgpack(line) abort
+    call add(s:msgpack_chunks, a:line)
+    let s:msgpack_remaining -= strlen(a:line) + 1
+
+    if s:msgpack_remaining > 0
+      return
+    endif
+
+    let chunks = s:msgpack_chunks
+    let s:msgpack_chunks = []
+    let s:msgpack_remaining = -1
+    try
+      call clap#client#handle_decoded(msgpackparse(chunks)[0])
+    catch
+      call clap#helper#echo_error('[daemon]Failed to handle msgpack message:'.v:exception.', throwpoint:'.v:throwpoint)
+    endtry
+  endfunction
 
   function! s:handle_stdout(lines) abort
+    let a:lines[0] = s:partial_line.a:lines[0]
+    let s:partial_line = remove(a:lines, -1)
+
     while !empty(a:lines)
       let line = remove(a:lines, 0)
 
+      if s:msgpack_remaining >= 0
+        call s:read_msgpack(line)
+        continue
+      endif
+
       if line ==# ''
         continue
       elseif s:content_length == 0
@@ -30,6 +64,10 @@ if has('nvim')
           call clap#helper#echo_error('This should not happen, unknown message:'.line)
         endif
         continue
+      elseif empty(s:round_message) && line ==# 'Content-type: msgpack'
+        let s:msgpack_remaining = s:content_length + 1
+        let s:content_length = 0
+        continue
       endif
 
       if s:content_length < strlen(l:line)
@@ -74,7 +112,10 @@ if has('nvim')
 
   function! s:start_service_job(cmd) abort
     call clap#job#daemon#stop()
+    " `$NVIM` is used by maple to attach to the msgpack-rpc server of Neovim, which is
+    " only set automatically since Neovim 0.8.
     let s:job_id = jobstart(a:cmd, {
+          \ 'env': {'NVIM': v:servername},
           \ 'on_exit': function('s:on_event'),
           \ 'on_stdout': function('s:on_event'),
           \ 'on_stderr': function('s:on_event'),
@@ -135,7 +176,11 @@ endfunction
 
 function! clap#job#daemon#start() abort
   let s:MessageHandler = function('clap#client#handle')
-  call s:start_service_job(clap#maple#build_cmd('rpc'))
+  call s:start_service_job(clap#maple#build_rpc_cmd())
+  " Only Neovim can decode the MessagePack messages, Vim always uses JSON.
+  if has('nvim') && g:clap_enable_msgpack
+    call clap#rpc#notify('rpc/set-encoding', ['msgpack'])
+  endif
 endfunction
 
 let &cpoptions = s:save_cpo
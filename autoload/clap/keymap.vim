@@ -0,0 +1,97 @@
+" Description: Map the key notations bound in the keymaps of the config, the
+" keys are dispatched to maple as `custom_key`.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+" Key sequence => key notation in the config, used by the popup filter.
+let s:custom_keys = {}
+let s:pending_keys = ''
+let s:mapped_keys = []
+" Buffer-local mappings of the input window overridden by the mapped keys.
+let s:saved_maps = []
+
+function! clap#keymap#notify(key) abort
+  call clap#client#notify_provider('custom_key', {'key': a:key})
+  return ''
+endfunction
+
+" Returns the key sequence of the key notation, e.g., "\<C-x>\<C-o>" for
+" `<C-x><C-o>`.
+function! s:key_sequence(key) abort
+  return eval('"'.substitute(escape(a:key, '\"'), '<', '\\<', 'g').'"')
+endfunction
+
+function! s:unmap_keys() abort
+  for key in s:mapped_keys
+    let lhs = substitute(key, '|', '<Bar>', 'g')
+    execute 'silent! iunmap <buffer> '.lhs
+    execute 'silent! nunmap <buffer> '.lhs
+  endfor
+  if exists('*mapset')
+    for saved in s:saved_maps
+      call mapset(saved.mode, v:false, saved)
+    endfor
+  endif
+  let s:saved_maps = []
+endfunction
+
+function! s:map_keys() abort
+  let s:saved_maps = []
+  for key in s:mapped_keys
+    for mode in ['i', 'n']
+      let saved = maparg(key, mode, v:false, v:true)
+      if !empty(saved) && get(saved, 'buffer', 0)
+        call add(s:saved_maps, saved)
+      endif
+    endfor
+  endfor
+  for key in s:mapped_keys
+    let lhs = substitute(key, '|', '<Bar>', 'g')
+    let arg = substitute(string(key), '<', '<lt>', 'g')
+    execute 'inoremap <silent> <buffer> '.lhs.' <C-R>=clap#keymap#notify('.arg.')<CR>'
+    execute 'nnoremap <silent> <buffer> '.lhs.' :<c-u>call clap#keymap#notify('.arg.')<CR>'
+  endfor
+endfunction
+
+" Maps the key notations, e.g., `<C-x><C-o>`, `<M-1>`, bound in the keymaps of
+" the config, the keys of the previous session are unmapped.
+function! clap#keymap#define(keys) abort
+  if has('nvim')
+    call win_execute(g:clap.input.winid, 'call s:unmap_keys()')
+    let s:mapped_keys = a:keys
+    call win_execute(g:clap.input.winid, 'call s:map_keys()')
+  else
+    let s:custom_keys = {}
+    let s:pending_keys = ''
+    for key in a:keys
+      let s:custom_keys[s:key_sequence(key)] = key
+    endfor
+  endif
+endfunction
+
+" Returns v:true if the key typed in the popup is consumed as part of a bound
+" key sequence.
+function! clap#keymap#filter(key) abort
+  if empty(s:custom_keys)
+    return v:false
+  endif
+
+  let typed = s:pending_keys.a:key
+  if has_key(s:custom_keys, typed)
+    let s:pending_keys = ''
+    call clap#keymap#notify(s:custom_keys[typed])
+    return v:true
+  endif
+
+  if !empty(filter(keys(s:custom_keys), 'stridx(v:val, typed) == 0'))
+    let s:pending_keys = typed
+    return v:true
+  endif
+
+  let s:pending_keys = ''
+  return v:false
+endfunction
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
@@ -9,8 +9,13 @@ let s:bin_suffix = has('win32') ? '.exe' : ''
 let s:maple_bin_localbuilt = fnamemodify(g:clap#autoload_dir, ':h').'/target/release/maple'.s:bin_suffix
 let s:maple_bin_prebuilt = fnamemodify(g:clap#autoload_dir, ':h').'/bin/maple'.s:bin_suffix
 
+let s:remote = get(g:, 'clap_remote', {})
+
+" Run maple on the remote host via ssh.
+if !empty(get(s:remote, 'host', ''))
+  let s:maple_bin = get(s:remote, 'maple', 'maple')
 " Check the local built.
-if executable(s:maple_bin_localbuilt)
+elseif executable(s:maple_bin_localbuilt)
   let s:maple_bin = s:maple_bin_localbuilt
 " Check the prebuilt binary.
 elseif executable(s:maple_bin_prebuilt)
@@ -21,9 +26,28 @@ else
   let s:maple_bin = v:null
 endif
 
+" Snapshot of the session restored by `:Clap ++resume`.
+function! s:session_snapshot() abort
+  return {
+        \ 'provider_id': g:clap.provider.id,
+        \ 'args': get(g:clap.provider, 'args', []),
+        \ 'query': g:clap.input.get(),
+        \ 'lines': g:clap.display.get_lines(),
+        \ 'curlnum': g:clap.display.getcurlnum(),
+        \ 'topline': getwininfo(g:clap.display.winid)[0].topline,
+        \ 'truncated_map': get(g:, '__clap_lines_truncated_map', {}),
+        \ 'icon_added': get(g:, '__clap_icon_added_by_maple', v:false),
+        \ }
+endfunction
+
 if s:maple_bin isnot v:null
   function! clap#maple#clean_up() abort
-    call clap#client#notify_provider('exit')
+    let params = {'accepted_entries': get(g:, '__clap_accepted_entries', [])}
+    unlet! g:__clap_accepted_entries
+    if g:clap.provider.id !=# 'providers' && g:clap.display.win_is_valid()
+      let params.snapshot = s:session_snapshot()
+    endif
+    call clap#client#notify_provider('exit', params)
   endfunction
 else
   function! clap#maple#clean_up() abort
@@ -38,12 +62,42 @@ function! clap#maple#is_available() abort
   return s:maple_bin isnot v:null
 endfunction
 
+" Quotes the argument for the POSIX shell on the remote host.
+function! s:remote_quote(arg) abort
+  return "'".substitute(a:arg, "'", "'\\\\''", 'g')."'"
+endfunction
+
+function! clap#maple#is_remote() abort
+  return !empty(get(s:remote, 'host', ''))
+endfunction
+
+" The binary is left unquoted so that `~` in it is expanded by the remote shell.
+function! s:with_remote(cmd_list) abort
+  if !clap#maple#is_remote()
+    return a:cmd_list
+  endif
+  return ['ssh', '-T', s:remote.host, a:cmd_list[0]] + map(a:cmd_list[1:], 's:remote_quote(v:val)')
+endfunction
+
 function! clap#maple#build_cmd(...) abort
-  return [s:maple_bin] + a:000
+  return s:with_remote([s:maple_bin] + a:000)
 endfunction
 
 function! clap#maple#build_cmd_list(cmd_list) abort
-  return insert(a:cmd_list, s:maple_bin)
+  return s:with_remote(insert(a:cmd_list, s:maple_bin))
+endfunction
+
+" The paths under the local mount points are translated to the remote ones by maple.
+function! clap#maple#build_rpc_cmd() abort
+  " The path maps are per editor, hence the shared daemon is not used in the remote mode.
+  if get(g:, 'clap_enable_shared_daemon', v:false) && !clap#maple#is_remote()
+    return [s:maple_bin, 'serve']
+  endif
+  let path_maps = []
+  for [local_path, remote_path] in items(get(s:remote, 'path_map', {}))
+    call extend(path_maps, ['--path-map', fnamemodify(expand(local_path), ':p').'='.remote_path])
+  endfor
+  return s:with_remote([s:maple_bin, 'rpc'] + path_maps)
 endfunction
 
 let &cpoptions = s:save_cpo
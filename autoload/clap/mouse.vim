@@ -0,0 +1,57 @@
+" Description: Forward the mouse events in the clap windows to maple.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+" Handles the mouse event locally when maple is unavailable.
+function! s:handle_locally(kind, pos) abort
+  if a:kind ==# 'wheel-up'
+    call clap#navigation#linewise_scroll('up')
+  elseif a:kind ==# 'wheel-down'
+    call clap#navigation#linewise_scroll('down')
+  elseif a:pos.winid == g:clap.display.winid
+    if a:kind ==# 'click'
+      call clap#mouse#select_line(a:pos.line)
+      call g:clap.provider.on_move()
+    else
+      call clap#mouse#accept_line(a:pos.line)
+    endif
+  endif
+endfunction
+
+" Returns the window and the line under the mouse, getmousepos() is unavailable
+" in the old Vim and Neovim.
+function! s:mouse_pos() abort
+  if exists('*getmousepos')
+    return getmousepos()
+  endif
+  return {'winid': v:mouse_winid, 'line': v:mouse_lnum}
+endfunction
+
+" kind: 'click', 'double-click', 'wheel-up' or 'wheel-down'.
+function! clap#mouse#notify(kind) abort
+  let pos = s:mouse_pos()
+  if clap#job#daemon#is_running()
+    call clap#client#notify_provider('mouse', {'kind': a:kind, 'winid': pos.winid, 'lnum': pos.line})
+  else
+    call s:handle_locally(a:kind, pos)
+  endif
+  return ''
+endfunction
+
+function! clap#mouse#select_line(lnum) abort
+  if a:lnum < 1 || a:lnum > g:clap.display.line_count()
+    return
+  endif
+  call win_execute(g:clap.display.winid, 'noautocmd call cursor(a:lnum, 1)')
+  let g:__clap_display_curlnum = a:lnum
+  call clap#sign#toggle_cursorline()
+endfunction
+
+function! clap#mouse#accept_line(lnum) abort
+  call clap#mouse#select_line(a:lnum)
+  call clap#handler#handle_mapping("\<CR>")
+endfunction
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
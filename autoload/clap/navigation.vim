@@ -49,7 +49,10 @@ function! s:linewise_scroll(direction) abort
     if empty(g:clap.display.cache)
           \ || get(g:, '__clap_do_not_use_cache', v:false)
 
-      if !g:clap_disable_bottom_top
+      if get(g:, '__clap_has_more_results', v:false)
+        " The next page is appended by maple asynchronously.
+        call clap#client#notify_provider('load_more')
+      elseif !g:clap_disable_bottom_top
         noautocmd normal! 1gg
       endif
     else
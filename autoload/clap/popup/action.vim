@@ -31,8 +31,8 @@ function! s:highlight_shortcut() abort
   call map(s:key_indices, 'matchaddpos("Function", [[v:key+1, v:val+1]])')
 endfunction
 
-function! clap#popup#action#invoke() abort
-  let s:provider_action = g:clap.provider._().action
+function! clap#popup#action#invoke(provider_action) abort
+  let s:provider_action = a:provider_action
   if has_key(s:provider_action, 'title')
     let title = s:provider_action['title']()
   else
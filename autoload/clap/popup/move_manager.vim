@@ -86,6 +86,14 @@ function! s:move_manager.ctrl_p(_winwid) abort
   call clap#client#notify_provider('ctrl-p')
 endfunction
 
+function! s:move_manager.ctrl_q(_winwid) abort
+  call clap#client#notify_provider('ctrl-q')
+endfunction
+
+function! s:move_manager.ctrl_r(_winwid) abort
+  call clap#client#notify_provider('ctrl-r')
+endfunction
+
 function! s:move_manager.shift_up(_winwid) abort
   call clap#client#notify_provider('shift-up')
 endfunction
@@ -181,14 +189,21 @@ let s:move_manager["\<C-K>"] = s:move_manager.linewise_scroll_up
 let s:move_manager["\<C-L>"] = s:move_manager.ctrl_l
 let s:move_manager["\<C-N>"] = s:move_manager.ctrl_n
 let s:move_manager["\<C-P>"] = s:move_manager.ctrl_p
+let s:move_manager["\<C-Q>"] = s:move_manager.ctrl_q
+let s:move_manager["\<C-R>"] = s:move_manager.ctrl_r
 let s:move_manager["\<C-U>"] = s:move_manager.ctrl_u
 let s:move_manager["\<C-W>"] = s:move_manager.ctrl_w
 let s:move_manager["\<BS>"] = s:move_manager.bs
 let s:move_manager["\<C-H>"] = s:move_manager.bs
 let s:move_manager["\<Esc>"] = { _winid -> clap#handler#exit() }
 let s:move_manager["\<C-G>"] = s:move_manager["\<Esc>"]
-let s:move_manager["\<Up>"] = s:move_manager["\<C-K>"]
-let s:move_manager["\<Down>"] = s:move_manager["\<C-J>"]
+if g:clap_input_history_with_arrows
+  let s:move_manager["\<Up>"] = s:move_manager.ctrl_p
+  let s:move_manager["\<Down>"] = s:move_manager.ctrl_n
+else
+  let s:move_manager["\<Up>"] = s:move_manager["\<C-K>"]
+  let s:move_manager["\<Down>"] = s:move_manager["\<C-J>"]
+endif
 let s:move_manager["\<Home>"] = s:move_manager.ctrl_a
 let s:move_manager["\<Left>"] = s:move_manager.ctrl_b
 let s:move_manager["\<Right>"] = s:move_manager.ctrl_f
@@ -200,10 +215,11 @@ let s:move_manager["\<S-Up>"] = s:move_manager.shift_up
 let s:move_manager["\<S-Down>"] = s:move_manager.shift_down
 let s:move_manager["\<PageUp>"] = s:move_manager.scroll_up
 let s:move_manager["\<PageDown>"] = s:move_manager.scroll_down
-let s:move_manager["\<LeftMouse>"] = s:move_manager["\<Tab>"]
+let s:move_manager["\<LeftMouse>"] = { _winid -> clap#mouse#notify('click') }
+let s:move_manager["\<2-LeftMouse>"] = { _winid -> clap#mouse#notify('double-click') }
 let s:move_manager["\<RightMouse>"] = s:move_manager["\<Tab>"]
-let s:move_manager["\<ScrollWheelUp>"] = s:move_manager.linewise_scroll_up
-let s:move_manager["\<ScrollWheelDown>"] = s:move_manager.linewise_scroll_down
+let s:move_manager["\<ScrollWheelUp>"] = { _winid -> clap#mouse#notify('wheel-up') }
+let s:move_manager["\<ScrollWheelDown>"] = { _winid -> clap#mouse#notify('wheel-down') }
 
 function! s:define_open_action_filter() abort
   for k in keys(g:clap_open_action)
@@ -279,6 +295,10 @@ endfunction
 
 function! clap#popup#move_manager#filter(winid, key) abort
   try
+    if clap#keymap#filter(a:key)
+      return 1
+    endif
+
     if has_key(s:move_manager, a:key)
       call s:move_manager[a:key](a:winid)
       return 1
@@ -315,10 +335,12 @@ function! clap#popup#move_manager#get_input() abort
   return s:input
 endfunction
 
-function! clap#popup#move_manager#set_input(input) abort
+" Optional argument: cursor index, the end of line by default.
+function! clap#popup#move_manager#set_input(input, ...) abort
   let s:input = a:input
-  " Move cursor to the end of line.
-  let s:cursor_idx = strchars(s:input, 1)
+  let len = strchars(s:input, 1)
+  let cursor_idx = get(a:000, 0, -1)
+  let s:cursor_idx = cursor_idx < 0 || cursor_idx > len ? len : cursor_idx
   call s:mock_input()
 endfunction
 
@@ -172,7 +172,7 @@ endfunction
 
 function! clap#preview#inject_title_opt(opts, width) abort
   let opts = a:opts
-  let should_enable_title = ['grep', 'live_grep', 'dumb_jump', 'files', 'git_files', 'proj_tags', 'coc_location', 'recent_files']
+  let should_enable_title = ['grep', 'live_grep', 'dumb_jump', 'files', 'git_files', 'proj_tags', 'coc_location', 'recent_files', 'smart_open']
   if index(should_enable_title, g:clap.provider.id) > -1
     let working_dir = clap#rooter#working_dir()
     let working_dir = fnamemodify(working_dir, ':~')
@@ -0,0 +1,49 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the cache entries of maple.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:cache_info = {}
+
+" The cache key is the last field of line.
+function! s:cache_key(line) abort
+  return matchstr(a:line, '\S\+$')
+endfunction
+
+function! s:action_purge() abort
+  call clap#client#notify('purge-cache-entry', [s:cache_key(s:current_line)])
+  call g:clap.display.deletecurline()
+  call clap#indicator#update_on_deletecurline()
+  call g:clap#display_win.shrink_if_undersize()
+endfunction
+
+function! s:action_purge_project() abort
+  call clap#client#notify('purge-project-cache', [s:cache_key(s:current_line)])
+  let project = split(s:current_line)[1]
+  call g:clap.display.set_lines(filter(g:clap.display.get_lines(), 'split(v:val)[1] !=# project'))
+  call clap#indicator#update_matched(g:clap.display.line_count())
+  call g:clap#display_win.shrink_if_undersize()
+endfunction
+
+function! s:actions_title() abort
+  let s:current_line = g:clap.display.getcurline()
+  return 'Choose action for cache entry '.s:cache_key(s:current_line).':'
+endfunction
+
+function! s:cache_info.sink(selected) abort
+  call clap#helper#echo_info(a:selected)
+endfunction
+
+let s:cache_info.init = { -> clap#client#notify_on_init() }
+let s:cache_info.on_typed = { -> clap#client#notify_provider('on_typed') }
+let s:cache_info.action = {
+      \ 'title': function('s:actions_title'),
+      \ '&Purge': function('s:action_purge'),
+      \ 'Purge&Project': function('s:action_purge_project'),
+      \ }
+
+let g:clap#provider#cache_info# = s:cache_info
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
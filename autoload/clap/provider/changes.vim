@@ -0,0 +1,50 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the change list with the preview.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:changes = {}
+
+function! s:changes.source() abort
+  let cout = clap#api#win_execute(g:clap.start.winid, 'changes')
+  let s:changelist = split(cout, '\n')
+  return [s:changelist[0]] + reverse(s:changelist[1:])
+endfunction
+
+function! s:changes.sink(line) abort
+  if empty(a:line)
+    return
+  endif
+  let idx = index(s:changelist, a:line)
+  if idx == -1
+    return
+  endif
+  let pointer = match(s:changelist, '\v^\s*\>')
+  " The pointer is after the last change if there is no `>` in the list.
+  if pointer == -1
+    let pointer = len(s:changelist)
+  endif
+  let delta = idx - pointer
+  if delta == 0
+    return
+  endif
+  let cmd = delta < 0 ? abs(delta).'g;' : delta.'g,'
+  execute 'normal!' cmd
+  normal! zz
+endfunction
+
+function! s:changes.on_move() abort
+  let curline = g:clap.display.getcurline()
+  let matched = matchlist(curline, '^[ >]\s*\(\d\+\)\s\+\(\d\+\)\s\+\(\d\+\)\s\+\(.*\)$')
+  if len(matched) < 5
+    return
+  endif
+  call clap#provider#marks#preview_impl(matched[2], matched[3], matched[4])
+endfunction
+
+let s:changes.syntax = 'clap_changes'
+let g:clap#provider#changes# = s:changes
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
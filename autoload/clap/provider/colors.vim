@@ -26,20 +26,57 @@ function! s:colors.source() abort
 endfunction
 
 function! s:colors.on_enter() abort
-  let s:old_color = execute('colorscheme')
+  let s:old_color = trim(execute('colorscheme'))
   let s:old_bg = &background
+  let s:should_restore_color = v:true
 endfunction
 
-" Preview the colorscheme on move
-function! s:colors.on_move() abort
+" Applies the colorscheme `name` while the picker is open.
+function! clap#provider#colors#apply(name) abort
+  if !g:clap.display.win_is_valid() || empty(a:name)
+    return
+  endif
+  " Some colorschemes depend on the original 'background'.
+  let &background = s:old_bg
   " This is neccessary
   noautocmd call g:clap.start.goto_win()
-  execute 'color' g:clap.display.getcurline()
-  do Syntax
-  noautocmd call g:clap.input.goto_win()
+  try
+    execute 'color' a:name
+    do Syntax
+  catch
+    call clap#helper#echo_error('Failed to apply colorscheme '.a:name.': '.v:exception)
+  finally
+    noautocmd call g:clap.input.goto_win()
+  endtry
+endfunction
+
+" Restores the colorscheme in use before opening the picker.
+function! clap#provider#colors#revert() abort
+  if exists('s:apply_timer')
+    call timer_stop(s:apply_timer)
+  endif
+  if empty(s:old_color) || trim(execute('colorscheme')) ==# s:old_color
+    return
+  endif
+  execute 'color' s:old_color
+  let &background = s:old_bg
+endfunction
+
+" Preview the colorscheme on move, debounced as applying a colorscheme is
+" expensive when scrolling through the list quickly.
+function! s:colors.on_move() abort
+  if exists('s:apply_timer')
+    call timer_stop(s:apply_timer)
+  endif
+  let name = g:clap.display.getcurline()
+  let s:apply_timer = timer_start(50, { -> clap#provider#colors#apply(name) })
 endfunction
 
 function! s:colors.sink(selected) abort
+  if exists('s:apply_timer')
+    call timer_stop(s:apply_timer)
+  endif
+  let &background = s:old_bg
   execute 'color' a:selected
   " Reload syntax
   " https://stackoverflow.com/questions/8674387/vim-how-to-reload-syntax-highlighting
@@ -50,9 +87,7 @@ endfunction
 function! s:colors.on_exit() abort
   if get(s:, 'should_restore_color', v:true)
     noautocmd call g:clap.start.goto_win()
-    execute 'color' trim(s:old_color)
-    let &background = s:old_bg
-    let s:should_restore_color = v:true
+    call clap#provider#colors#revert()
   endif
 endfunction
 
@@ -8,9 +8,18 @@ set cpoptions&vim
 let s:command_header   = v:null
 let s:command_list = []
 
+function! s:extract_command(selected) abort
+  return matchstr(a:selected, '\v^\s*\S+\s+\zs\S+')
+endfunction
+
 function! s:sink(selected) abort
   " :h command, note the characters in the first two columns
-  let cmd = matchstr(a:selected, '\v^\s*\S+\s+\zs\S+')
+  let cmd = s:extract_command(a:selected)
+  if has_key(s:builtin_commands, cmd)
+    " The builtin commands may take arguments, pre-fill the cmdline.
+    call feedkeys(':' . cmd . ' ', 'n')
+    return
+  endif
   let list = split(execute('command ' . cmd), "\n")
   let command = s:parse_command(list[1])
   if command.args ==# '0'
@@ -20,16 +29,63 @@ function! s:sink(selected) abort
   end
 endfunction
 
+" Parses the builtin ex commands along with the description from index.txt,
+" e.g., `|:abclear|	:abc[lear]	remove all abbreviations`.
+function! s:load_builtin_commands() abort
+  if exists('s:builtin_commands')
+    return
+  endif
+  let s:builtin_commands = {}
+  let index_file = $VIMRUNTIME.'/doc/index.txt'
+  if !filereadable(index_file)
+    return
+  endif
+  for line in readfile(index_file)
+    let matched = matchlist(line, '^|:\([^|]\+\)|\t\+\(\S\+\)\t\+\(.*\)$')
+    if !empty(matched) && matched[1] =~# '^\a'
+      let s:builtin_commands[matched[1]] = {'abbr': matched[2], 'description': matched[3]}
+    endif
+  endfor
+endfunction
+
 function! s:source() abort
   let margin = 9
   let list = split(execute('command'), "\n")
   let s:command_header = list[0]
   let s:command_list = list[1:]
   call map(s:command_list, {key, val -> s:parse_command(val)})
-  return map(copy(s:command_list), {key, val ->
+  let user_commands = map(copy(s:command_list), {key, val ->
         \ s:left_pad(printf('[%s] ', val.args), margin)
         \ . s:right_pad(val.name, 30)
         \ . ' ' . val.rest})
+  call s:load_builtin_commands()
+  let builtin_commands = map(sort(keys(s:builtin_commands)), {key, val ->
+        \ s:left_pad('[:] ', margin)
+        \ . s:right_pad(val, 30)
+        \ . ' ' . s:builtin_commands[val].description})
+  return user_commands + builtin_commands
+endfunction
+
+" Shows the definition and where the user command is defined, or the help of
+" the builtin command.
+function! s:on_move() abort
+  let cmd = s:extract_command(g:clap.display.getcurline())
+  if empty(cmd)
+    return
+  endif
+  if has_key(s:builtin_commands, cmd)
+    let builtin = s:builtin_commands[cmd]
+    call g:clap.preview.show([':'.builtin.abbr, '', builtin.description, '', 'See :help :'.cmd])
+    call clap#preview#highlight_header()
+    return
+  endif
+  let lines = split(execute('verbose command '.cmd), "\n")
+  if len(lines) > 1
+    let command = s:parse_command(lines[1])
+    call g:clap.preview.show([command.name, '', command.rest] + map(lines[2:], 'trim(v:val)'))
+    call g:clap.preview.set_syntax('vim')
+    call clap#preview#highlight_header()
+  endif
 endfunction
 
 
@@ -37,6 +93,7 @@ let s:command = {}
 let s:command.syntax = 'clap_command'
 let s:command.source = function('s:source')
 let s:command.sink = function('s:sink')
+let s:command.on_move = function('s:on_move')
 
 let g:clap#provider#command# = s:command
 
@@ -0,0 +1,69 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the breakpoints of nvim-dap.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:dap_breakpoints = {}
+
+let s:lua_breakpoints = '(function()'
+      \ . ' local items = {}'
+      \ . ' for bufnr, bps in pairs(require("dap.breakpoints").get()) do'
+      \ . '   local path = vim.api.nvim_buf_get_name(bufnr)'
+      \ . '   for _, bp in ipairs(bps) do'
+      \ . '     local text = bp.logMessage and ("log: " .. bp.logMessage) or bp.condition and ("if " .. bp.condition) or ""'
+      \ . '     table.insert(items, { path = path, lnum = bp.line, col = 1, text = text })'
+      \ . '   end'
+      \ . ' end'
+      \ . ' return items'
+      \ . ' end)()'
+
+function! clap#provider#dap_breakpoints#is_available() abort
+  return has('nvim') && luaeval('pcall(require, "dap")')
+endfunction
+
+" Converts the location into the line of the same format with the quickfix provider.
+function! clap#provider#dap_breakpoints#into_line(item) abort
+  return a:item.path.'|'.a:item.lnum.' col '.a:item.col.'| '.a:item.text
+endfunction
+
+function! s:dap_breakpoints.source() abort
+  if !clap#provider#dap_breakpoints#is_available()
+    return ['nvim-dap is unavailable']
+  endif
+  let breakpoints = luaeval(s:lua_breakpoints)
+  if empty(breakpoints)
+    return ['No breakpoints']
+  endif
+  return map(breakpoints, 'clap#provider#dap_breakpoints#into_line(v:val)')
+endfunction
+
+function! clap#provider#dap_breakpoints#sink(selected) abort
+  if a:selected !~# '|\d\+ col \d\+|'
+    return
+  endif
+  let [fpath, lnum, column] = clap#provider#quickfix#extract_position(a:selected)
+  execute 'edit' fnameescape(fpath)
+  noautocmd call cursor(lnum, column)
+  normal! zz
+endfunction
+
+function! clap#provider#dap_breakpoints#on_move_async() abort
+  if g:clap.display.getcurline() !~# '|\d\+ col \d\+|'
+    return
+  endif
+  call clap#client#request_async('quickfix', function('clap#impl#on_move#handler'), {
+        \ 'curline': g:clap.display.getcurline(),
+        \ 'cwd': clap#rooter#working_dir(),
+        \ 'winwidth': winwidth(g:clap.display.winid),
+        \ 'winheight': winheight(g:clap.display.winid),
+        \ })
+endfunction
+
+let s:dap_breakpoints.sink = function('clap#provider#dap_breakpoints#sink')
+let s:dap_breakpoints.on_move_async = function('clap#provider#dap_breakpoints#on_move_async')
+let s:dap_breakpoints.syntax = 'qf'
+let g:clap#provider#dap_breakpoints# = s:dap_breakpoints
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
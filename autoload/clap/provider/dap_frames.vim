@@ -0,0 +1,44 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the stack frames of the threads in the current nvim-dap session.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:dap_frames = {}
+
+" Only the frames of the stopped threads have been fetched by nvim-dap.
+let s:lua_frames = '(function()'
+      \ . ' local session = require("dap").session()'
+      \ . ' if not session then return vim.NIL end'
+      \ . ' local items = {}'
+      \ . ' for _, thread in pairs(session.threads or {}) do'
+      \ . '   for _, frame in ipairs(thread.frames or {}) do'
+      \ . '     if frame.source and frame.source.path then'
+      \ . '       local text = "[" .. thread.name .. "] " .. frame.name'
+      \ . '       table.insert(items, { path = frame.source.path, lnum = frame.line, col = frame.column, text = text })'
+      \ . '     end'
+      \ . '   end'
+      \ . ' end'
+      \ . ' return items'
+      \ . ' end)()'
+
+function! s:dap_frames.source() abort
+  if !clap#provider#dap_breakpoints#is_available()
+    return ['nvim-dap is unavailable']
+  endif
+  let frames = luaeval(s:lua_frames)
+  if frames is v:null
+    return ['No active debug session']
+  elseif empty(frames)
+    return ['No stopped threads']
+  endif
+  return map(frames, 'clap#provider#dap_breakpoints#into_line(v:val)')
+endfunction
+
+let s:dap_frames.sink = function('clap#provider#dap_breakpoints#sink')
+let s:dap_frames.on_move_async = function('clap#provider#dap_breakpoints#on_move_async')
+let s:dap_frames.syntax = 'qf'
+let g:clap#provider#dap_frames# = s:dap_frames
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
@@ -0,0 +1,100 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the docker containers, start/stop them and show the logs.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:docker_containers = {}
+
+function! s:docker_containers.source() abort
+  if !executable('docker')
+    return ['docker executable not found']
+  endif
+  let lines = systemlist('docker ps --all --format "{{.ID}}|{{.Names}}|{{.Image}}|{{.Status}}"')
+  if v:shell_error
+    return ['Failed to list the containers: '.join(lines, ' ')]
+  endif
+  call filter(lines, 'len(split(v:val, "|", v:true)) >= 4')
+  return map(lines, {_, line -> call('printf', ['%-12s  %-24s  %-32s  %s'] + split(line, '|', v:true)[:3])})
+endfunction
+
+function! s:extract_id(line) abort
+  return matchstr(a:line, '^\x\{12}\ze\s')
+endfunction
+
+" Shows the latest logs of the container.
+function! s:docker_containers.on_move() abort
+  let id = s:extract_id(g:clap.display.getcurline())
+  if empty(id)
+    return
+  endif
+  let height = 2 * clap#preview#size_of('docker_containers')
+  let lines = systemlist('docker logs --tail '.height.' '.id.' 2>&1')
+  call g:clap.preview.show(empty(lines) ? ['No logs'] : lines)
+endfunction
+
+" Follows the logs in a terminal.
+function! s:follow_logs(id) abort
+  if has('nvim')
+    execute 'split term://docker logs --follow '.a:id
+  else
+    call term_start(['docker', 'logs', '--follow', a:id])
+  endif
+endfunction
+
+function! s:docker_containers.sink(selected) abort
+  let id = s:extract_id(a:selected)
+  if !empty(id)
+    call s:follow_logs(id)
+  endif
+endfunction
+
+function! s:refresh() abort
+  call clap#sign#reset_all()
+  call clap#client#notify_provider('reload_source')
+endfunction
+
+" Runs `docker {subcommand}` on the container under the cursor.
+function! s:run(subcommand) abort
+  let id = s:extract_id(g:clap.display.getcurline())
+  if empty(id)
+    return
+  endif
+  let output = system('docker '.a:subcommand.' '.id)
+  if v:shell_error
+    call clap#helper#echo_error('Failed to '.a:subcommand.' '.id.': '.trim(output))
+  endif
+  call s:refresh()
+endfunction
+
+function! s:action_start() abort
+  call s:run('start')
+endfunction
+
+function! s:action_stop() abort
+  call s:run('stop')
+endfunction
+
+function! s:action_logs() abort
+  let id = s:extract_id(g:clap.display.getcurline())
+  if !empty(id)
+    call clap#handler#sink_with(function('s:follow_logs'), id)
+  endif
+endfunction
+
+function! s:actions_title() abort
+  return 'Choose action for container ['.s:extract_id(g:clap.display.getcurline()).']:'
+endfunction
+
+let s:docker_containers.syntax = 'clap_docker'
+let s:docker_containers.action = {
+      \ 'title': function('s:actions_title'),
+      \ '&Start': function('s:action_start'),
+      \ 'S&top': function('s:action_stop'),
+      \ '&Logs': function('s:action_logs'),
+      \ }
+
+let g:clap#provider#docker_containers# = s:docker_containers
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
@@ -0,0 +1,87 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the docker images, run or remove them.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:docker_images = {}
+
+function! s:docker_images.source() abort
+  if !executable('docker')
+    return ['docker executable not found']
+  endif
+  let lines = systemlist('docker images --format "{{.ID}}|{{.Repository}}:{{.Tag}}|{{.Size}}|{{.CreatedSince}}"')
+  if v:shell_error
+    return ['Failed to list the images: '.join(lines, ' ')]
+  endif
+  call filter(lines, 'len(split(v:val, "|", v:true)) >= 4')
+  return map(lines, {_, line -> call('printf', ['%-12s  %-48s  %-10s  %s'] + split(line, '|', v:true)[:3])})
+endfunction
+
+function! s:extract_id(line) abort
+  return matchstr(a:line, '^\x\{12}\ze\s')
+endfunction
+
+" Shows the layers of the image.
+function! s:docker_images.on_move() abort
+  let id = s:extract_id(g:clap.display.getcurline())
+  if empty(id)
+    return
+  endif
+  let lines = systemlist('docker history --format "{{.CreatedSince}}  {{.Size}}  {{.CreatedBy}}" '.id.' 2>&1')
+  call g:clap.preview.show(lines)
+endfunction
+
+function! s:docker_images.sink(selected) abort
+  let id = s:extract_id(a:selected)
+  if !empty(id)
+    call setreg('"', id)
+    call clap#helper#echo_info('Image ID '.id.' is copied to the unnamed register')
+  endif
+endfunction
+
+function! s:refresh() abort
+  call clap#sign#reset_all()
+  call clap#client#notify_provider('reload_source')
+endfunction
+
+function! s:action_run() abort
+  let id = s:extract_id(g:clap.display.getcurline())
+  if empty(id)
+    return
+  endif
+  let output = system('docker run --detach '.id)
+  if v:shell_error
+    call clap#helper#echo_error('Failed to run '.id.': '.trim(output))
+  else
+    call clap#helper#echo_info('Container '.trim(output)[:11].' is started')
+  endif
+endfunction
+
+function! s:action_remove() abort
+  let id = s:extract_id(g:clap.display.getcurline())
+  if empty(id)
+    return
+  endif
+  let output = system('docker rmi '.id)
+  if v:shell_error
+    call clap#helper#echo_error('Failed to remove '.id.': '.trim(output))
+  endif
+  call s:refresh()
+endfunction
+
+function! s:actions_title() abort
+  return 'Choose action for image ['.s:extract_id(g:clap.display.getcurline()).']:'
+endfunction
+
+let s:docker_images.syntax = 'clap_docker'
+let s:docker_images.action = {
+      \ 'title': function('s:actions_title'),
+      \ '&Run': function('s:action_run'),
+      \ 'Re&move': function('s:action_remove'),
+      \ }
+
+let g:clap#provider#docker_images# = s:docker_images
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
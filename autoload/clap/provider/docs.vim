@@ -0,0 +1,80 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: Search the offline devdocs documentation of the current filetype.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:docs = {}
+
+if clap#maple#is_available()
+
+  " No source attribute as it's implemented on the Rust side directly.
+
+  function! s:docs.on_typed() abort
+    call clap#client#notify_provider('on_typed')
+  endfunction
+
+  let s:docs.source_type = g:__t_rpc
+  let s:docs.on_move_async = function('clap#impl#on_move#async')
+else
+  function! s:docs.source() abort
+    return ['The docs provider requires maple']
+  endfunction
+endif
+
+" Returns the devdocs url of the line in the format of `name\t[type]\tslug/path`.
+function! s:url_of(line) abort
+  let location = get(split(a:line, "\t"), 2, '')
+  return empty(location) ? '' : 'https://devdocs.io/'.location
+endfunction
+
+function! s:open_url(url) abort
+  if has('win32') || has('win64')
+    let cmd = 'cmd /c start "" '.shellescape(a:url)
+  elseif has('mac')
+    let cmd = 'open '.shellescape(a:url)
+  else
+    let cmd = 'xdg-open '.shellescape(a:url)
+  endif
+  call system(cmd)
+  if v:shell_error
+    call clap#helper#echo_error('Failed to open '.a:url)
+  endif
+endfunction
+
+function! s:docs.sink(selected) abort
+  let url = s:url_of(a:selected)
+  if !empty(url)
+    call s:open_url(url)
+  endif
+endfunction
+
+function! s:actions_title() abort
+  let s:current_url = s:url_of(g:clap.display.getcurline())
+  return 'Choose action for '.s:current_url.':'
+endfunction
+
+function! s:action_open_in_browser() abort
+  if !empty(s:current_url)
+    call clap#handler#sink_with(function('s:open_url'), s:current_url)
+  endif
+endfunction
+
+function! s:action_copy_url() abort
+  if !empty(s:current_url)
+    call setreg('"', s:current_url)
+    call clap#helper#echo_info(s:current_url.' is copied to the unnamed register')
+  endif
+endfunction
+
+let s:docs.syntax = 'clap_docs'
+let s:docs.action = {
+      \ 'title': function('s:actions_title'),
+      \ '&Open in browser': function('s:action_open_in_browser'),
+      \ '&Copy URL': function('s:action_copy_url'),
+      \ }
+
+let g:clap#provider#docs# = s:docs
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
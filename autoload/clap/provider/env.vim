@@ -0,0 +1,104 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the environment variables.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:env = {}
+
+" The environment of the maple process is listed when maple is available,
+" see initialize_provider_source() in maple, otherwise that of Vim.
+function! s:env.source() abort
+  let vars = exists('*environ') ? environ() : {}
+  return sort(map(items(vars), {_, kv -> '[vim] '.kv[0].'='.s:escape(kv[1])}))
+endfunction
+
+" The value is shown in one line, `\` and newline are escaped as `\\` and `\n`.
+function! s:escape(value) abort
+  return substitute(escape(a:value, '\'), "\n", '\\n', 'g')
+endfunction
+
+function! s:unescape(value) abort
+  return substitute(a:value, '\\\(.\)', '\=submatch(1) ==# "n" ? "\n" : submatch(1)', 'g')
+endfunction
+
+" Returns [name, value] of the line in the format of `[source] NAME=value`.
+function! s:parse(line) abort
+  let matched = matchlist(a:line, '^\[\a\+\] \([^=]\+\)=\(.*\)$')
+  if empty(matched)
+    return []
+  endif
+  return [matched[1], s:unescape(matched[2])]
+endfunction
+
+function! s:current_var() abort
+  return s:parse(clap#api#get_origin_line_at(g:clap.display.getcurlnum()))
+endfunction
+
+" Shows the value wrapped, the path list is shown one entry per line.
+function! s:env.on_move() abort
+  let var = s:current_var()
+  if empty(var)
+    return
+  endif
+  let [name, value] = var
+  let separator = has('win32') ? ';' : ':'
+  if name =~# 'PATH$' && stridx(value, separator) != -1
+    let lines = split(value, separator)
+  else
+    let width = max([winwidth(g:clap.display.winid) - 2, 20])
+    let lines = []
+    for line in split(value, "\n", 1)
+      call extend(lines, map(range(0, max([strchars(line) - 1, 0]), width), 'strcharpart(line, v:val, width)'))
+    endfor
+  endif
+  call g:clap.preview.show([name, ''] + lines)
+  call clap#preview#highlight_header()
+endfunction
+
+function! s:copy_value(name, value) abort
+  call setreg('"', a:value)
+  call clap#helper#echo_info('The value of $'.a:name.' is copied to the unnamed register')
+endfunction
+
+function! s:insert_name(name) abort
+  execute 'normal! a$'.a:name
+endfunction
+
+function! s:env.sink(selected) abort
+  let var = s:parse(a:selected)
+  if !empty(var)
+    call s:insert_name(var[0])
+  endif
+endfunction
+
+function! s:actions_title() abort
+  let var = s:current_var()
+  return 'Choose action for $'.get(var, 0, '').':'
+endfunction
+
+function! s:action_copy_value() abort
+  let var = s:current_var()
+  if !empty(var)
+    call clap#handler#sink_with(function('s:copy_value'), var[0], var[1])
+  endif
+endfunction
+
+function! s:action_insert_name() abort
+  let var = s:current_var()
+  if !empty(var)
+    call clap#handler#sink_with(function('s:insert_name'), var[0])
+  endif
+endfunction
+
+let s:env.syntax = 'clap_env'
+let s:env.action = {
+      \ 'title': function('s:actions_title'),
+      \ '&Copy value': function('s:action_copy_value'),
+      \ '&Insert $NAME': function('s:action_insert_name'),
+      \ }
+
+let g:clap#provider#env# = s:env
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
@@ -0,0 +1,21 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the health checks of maple with the fixes of the problems found.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:health = {}
+
+function! s:health.sink(selected) abort
+  let fix = matchstr(a:selected, ', fix: \zs.*$')
+  call clap#helper#echo_info(empty(fix) ? a:selected : fix)
+endfunction
+
+let s:health.init = { -> clap#client#notify_on_init() }
+let s:health.on_typed = { -> clap#client#notify_provider('on_typed') }
+let s:health.syntax = 'clap_health'
+
+let g:clap#provider#health# = s:health
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
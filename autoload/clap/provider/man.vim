@@ -0,0 +1,64 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the man pages via `man -k`, open the selected one in Vim's man viewer.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:man = {}
+
+" `Clap man 3` lists the pages in section 3 only.
+function! s:man.source() abort
+  let section = get(g:clap.provider.args, 0, '')
+  let cmd = executable('apropos') ? 'apropos' : 'man -k'
+  if !empty(section)
+    let cmd .= ' -s '.shellescape(section)
+  endif
+  let lines = systemlist(cmd.' .')
+  if v:shell_error
+    return ['Failed to run `'.cmd.' .`']
+  endif
+  " Normalize `printf (3)   - description` into `printf(3) - description`.
+  return map(lines, 'substitute(v:val, ''^\(\S\+\)\s*(\([^)]\+\))\s*'', ''\1(\2) '', "")')
+endfunction
+
+function! s:extract_page(line) abort
+  let matched = matchlist(a:line, '^\([^ (,]\+\)(\([^)]\+\))')
+  if empty(matched)
+    return []
+  endif
+  return [matched[1], matched[2]]
+endfunction
+
+function! s:man.on_move() abort
+  let page = s:extract_page(g:clap.display.getcurline())
+  if empty(page)
+    return
+  endif
+  let [name, section] = page
+  let height = 2 * clap#preview#size_of('man')
+  let lines = systemlist('MANWIDTH=80 man '.shellescape(section).' '.shellescape(name).' 2>/dev/null | col -bx')[: height]
+  if empty(lines)
+    return
+  endif
+  call g:clap.preview.show(lines)
+  call g:clap.preview.set_syntax('man')
+endfunction
+
+function! s:man.sink(selected) abort
+  let page = s:extract_page(a:selected)
+  if empty(page)
+    return
+  endif
+  let [name, section] = page
+  if !exists(':Man')
+    runtime ftplugin/man.vim
+  endif
+  execute 'Man' section name
+endfunction
+
+let s:man.syntax = 'clap_man'
+
+let g:clap#provider#man# = s:man
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
@@ -95,7 +95,41 @@ function! s:marks.on_move() abort
   call clap#provider#marks#preview_impl(line, col, file_text)
 endfunction
 
+function! s:extract_mark(line) abort
+  if a:line ==# 'mark line  col file/text'
+    return ''
+  endif
+  return matchstr(a:line, '\S')
+endfunction
+
+" Deletes the marks of the selected entries or the current entry.
+function! s:action_delete() abort
+  let selected = clap#sign#get()
+  if empty(selected)
+    let lines = [g:clap.display.getcurline()]
+  else
+    let lines = map(selected, 'clap#api#get_origin_line_at(v:val)')
+  endif
+  let marks = filter(map(lines, 's:extract_mark(v:val)'), '!empty(v:val)')
+  if empty(marks)
+    return
+  endif
+  " The local marks are deleted in the context of the start buffer.
+  call clap#api#win_execute(g:clap.start.winid, 'delmarks '.escape(join(marks, ''), '"'))
+  call clap#sign#reset_all()
+  call g:clap.preview.hide()
+  call clap#client#notify_provider('reload_source')
+endfunction
+
+function! s:actions_title() abort
+  return 'Choose action for mark ['.s:extract_mark(g:clap.display.getcurline()).']:'
+endfunction
+
 let s:marks.syntax = 'clap_marks'
+let s:marks.action = {
+      \ 'title': function('s:actions_title'),
+      \ '&Delete': function('s:action_delete'),
+      \ }
 let s:marks.source_type = g:__t_func_list
 
 let g:clap#provider#marks# = s:marks
@@ -0,0 +1,126 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the running processes and send signals to them.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:processes = {}
+
+let s:is_windows = has('win32') || has('win64')
+
+" Returns the processes sorted by the cpu usage in the format of
+" `pid cpu% mem% command`.
+function! s:list_processes() abort
+  if s:is_windows
+    " tasklist does not report the cpu usage.
+    let processes = []
+    for line in systemlist('tasklist /fo csv /nh')
+      let fields = split(line, '","')
+      if len(fields) >= 5
+        call add(processes, {'pid': fields[1], 'cpu': 0.0, 'mem': '-', 'command': trim(fields[0], '"')})
+      endif
+    endfor
+  else
+    let processes = []
+    for line in systemlist('ps -eo pid=,pcpu=,pmem=,args=')
+      let matched = matchlist(line, '^\s*\(\d\+\)\s\+\([0-9.]\+\)\s\+\([0-9.]\+\)\s\+\(.*\)$')
+      if !empty(matched) && matched[1] != getpid()
+        call add(processes, {'pid': matched[1], 'cpu': str2float(matched[2]), 'mem': matched[3], 'command': matched[4]})
+      endif
+    endfor
+  endif
+  call sort(processes, {a, b -> a.cpu == b.cpu ? 0 : a.cpu < b.cpu ? 1 : -1})
+  return map(processes, {_, p -> printf('%7s %5.1f%% %5s%% %s', p.pid, p.cpu, p.mem, p.command)})
+endfunction
+
+function! s:processes.source() abort
+  return s:list_processes()
+endfunction
+
+function! s:extract_pid(line) abort
+  return matchstr(a:line, '^\s*\zs\d\+')
+endfunction
+
+function! s:processes.on_move() abort
+  let line = g:clap.display.getcurline()
+  let pid = s:extract_pid(line)
+  if empty(pid)
+    return
+  endif
+  let command = matchstr(line, '^\s*\d\+\s\+\S\+\s\+\S\+\s\+\zs.*')
+  " Wrap the possibly long command line.
+  let width = winwidth(g:clap.display.winid)
+  let lines = ['PID: '.pid, '']
+  let lines += map(range(0, strchars(command) - 1, width), 'strcharpart(command, v:val, width)')
+  if !s:is_windows
+    call add(lines, '')
+    let lines += systemlist('ps -o user=,stat=,etime=,lstart= -p '.pid)
+  endif
+  call g:clap.preview.show(lines)
+  call clap#preview#highlight_header()
+endfunction
+
+function! s:processes.sink(selected) abort
+  let pid = s:extract_pid(a:selected)
+  if !empty(pid)
+    call setreg('"', pid)
+    call clap#helper#echo_info('PID '.pid.' is copied to the unnamed register')
+  endif
+endfunction
+
+function! s:refresh() abort
+  call clap#sign#reset_all()
+  call clap#client#notify_provider('reload_source')
+endfunction
+
+" Sends the signal to the selected processes or the process under the cursor.
+function! s:send_signal(signal) abort
+  let selected = clap#sign#get()
+  if empty(selected)
+    let lines = [g:clap.display.getcurline()]
+  else
+    let lines = map(selected, 'clap#api#get_origin_line_at(v:val)')
+  endif
+  let pids = filter(map(lines, 's:extract_pid(v:val)'), '!empty(v:val)')
+  if empty(pids)
+    return
+  endif
+  if s:is_windows
+    let cmd = 'taskkill '.(a:signal ==# 'KILL' ? '/F ' : '').join(map(copy(pids), '"/PID ".v:val'), ' ')
+  else
+    let cmd = 'kill -'.a:signal.' '.join(pids, ' ')
+  endif
+  let output = system(cmd)
+  if v:shell_error
+    call clap#helper#echo_error('Failed to send SIG'.a:signal.': '.trim(output))
+  endif
+  call s:refresh()
+endfunction
+
+function! s:action_terminate() abort
+  call s:send_signal('TERM')
+endfunction
+
+function! s:action_kill() abort
+  call s:send_signal('KILL')
+endfunction
+
+function! s:actions_title() abort
+  return 'Choose action for process ['.s:extract_pid(g:clap.display.getcurline()).']:'
+endfunction
+
+let s:processes.syntax = 'clap_processes'
+let s:processes.action = {
+      \ 'title': function('s:actions_title'),
+      \ '&Terminate': function('s:action_terminate'),
+      \ '&Kill': function('s:action_kill'),
+      \ }
+" <C-l> refreshes the process list.
+let s:processes.mappings = {
+      \ "<C-l>": function('s:refresh'),
+      \ }
+
+let g:clap#provider#processes# = s:processes
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
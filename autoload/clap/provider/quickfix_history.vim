@@ -0,0 +1,110 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the quickfix and location list history, restore the selected one on accept.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:quickfix_history = {}
+
+" Vim does not record when a list is created, the creation time noted on
+" QuickFixCmdPost is used as the age if any.
+"
+" `cmd` is the <amatch> of QuickFixCmdPost, the location list commands start
+" with `l`, e.g., `lvimgrep`, only the list created by it is noted.
+function! clap#provider#quickfix_history#note_created(cmd) abort
+  if !exists('g:__clap_qf_created_at')
+    let g:__clap_qf_created_at = {}
+  endif
+  if a:cmd =~# '^l'
+    let g:__clap_qf_created_at[getloclist(0, {'id': 0}).id] = localtime()
+  else
+    let g:__clap_qf_created_at[getqflist({'id': 0}).id] = localtime()
+  endif
+endfunction
+
+function! s:get_list(kind, what) abort
+  if a:kind ==# 'qf'
+    return getqflist(a:what)
+  endif
+  return getloclist(g:clap.start.winid, a:what)
+endfunction
+
+function! s:format_age(id) abort
+  let created_at = get(get(g:, '__clap_qf_created_at', {}), a:id, -1)
+  if created_at == -1
+    return '-'
+  endif
+  let elapsed = localtime() - created_at
+  if elapsed < 60
+    return elapsed.'s ago'
+  elseif elapsed < 3600
+    return (elapsed / 60).'m ago'
+  elseif elapsed < 86400
+    return (elapsed / 3600).'h ago'
+  endif
+  return (elapsed / 86400).'d ago'
+endfunction
+
+function! s:into_lines(kind) abort
+  let lines = []
+  let last_nr = s:get_list(a:kind, {'nr': '$'}).nr
+  let current_nr = s:get_list(a:kind, {'nr': 0}).nr
+  for nr in range(last_nr, 1, -1)
+    let info = s:get_list(a:kind, {'nr': nr, 'id': 0, 'size': 0, 'title': 0})
+    call add(lines, printf('%s %s %2d  %5d items  %8s  %s',
+          \ nr == current_nr ? '>' : ' ',
+          \ a:kind, nr, info.size, s:format_age(info.id), info.title))
+  endfor
+  return lines
+endfunction
+
+function! s:quickfix_history.source() abort
+  let lines = s:into_lines('qf') + s:into_lines('loc')
+  if empty(lines)
+    return ['No quickfix or location list']
+  endif
+  return lines
+endfunction
+
+function! s:parse_line(line) abort
+  let matched = matchlist(a:line, '^.\s\(qf\|loc\)\s\+\(\d\+\)')
+  if empty(matched)
+    return []
+  endif
+  return [matched[1], str2nr(matched[2])]
+endfunction
+
+function! s:quickfix_history.on_move() abort
+  let parsed = s:parse_line(g:clap.display.getcurline())
+  if empty(parsed)
+    return
+  endif
+  let [kind, nr] = parsed
+  let info = s:get_list(kind, {'nr': nr, 'items': 0, 'title': 0})
+  let lines = map(info.items[: 2 * clap#preview#size_of('quickfix_history')], 'clap#provider#quickfix#into_qf_line(v:val)')
+  call g:clap.preview.show([info.title] + lines)
+  call g:clap.preview.set_syntax('qf')
+  call clap#preview#highlight_header()
+endfunction
+
+function! s:quickfix_history.sink(selected) abort
+  let parsed = s:parse_line(a:selected)
+  if empty(parsed)
+    return
+  endif
+  let [kind, nr] = parsed
+  if kind ==# 'qf'
+    execute 'silent' nr 'chistory'
+    botright copen
+  else
+    execute 'silent' nr 'lhistory'
+    lopen
+  endif
+endfunction
+
+let s:quickfix_history.syntax = 'clap_quickfix_history'
+
+let g:clap#provider#quickfix_history# = s:quickfix_history
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
@@ -6,6 +6,14 @@ set cpoptions&vim
 
 let s:recent_files = {}
 
+function! s:action_purge_missing() abort
+  call clap#client#notify('recent_files.purge-missing')
+endfunction
+
+function! s:action_import_oldfiles() abort
+  call clap#client#notify('recent_files.import-oldfiles')
+endfunction
+
 let s:recent_files.init = { -> clap#client#notify_on_init() }
 let s:recent_files.on_typed = { -> clap#client#notify_provider('on_typed') }
 let s:recent_files.on_move_async = { -> clap#client#notify_provider('on_move') }
@@ -13,6 +21,10 @@ let s:recent_files.sink = function('clap#provider#files#sink_impl')
 let s:recent_files.support_open_action = v:true
 let s:recent_files.icon = 'File'
 let s:recent_files.syntax = 'clap_files'
+let s:recent_files.action = {
+      \ '&PurgeMissing': function('s:action_purge_missing'),
+      \ '&ImportOldfiles': function('s:action_import_oldfiles'),
+      \ }
 
 let g:clap#provider#recent_files# = s:recent_files
 
@@ -39,8 +39,9 @@ function! s:registers.on_move() abort
   let curline = g:clap.display.getcurline()
   let reg = s:extract_reg(curline)
   if !empty(reg)
-    let lines = split(eval('@'.reg), "\n")
-    let preview_title = 'Content for register ['.s:extract_reg(curline).']:'
+    " The multi-line content is shown in full.
+    let lines = split(getreg(reg, 1), "\n", v:true)
+    let preview_title = 'Content for register ['.reg.'] ('.getregtype(reg).'):'
     call g:clap.preview.show([preview_title] + lines)
     call clap#preview#highlight_header()
   endif
@@ -51,7 +52,45 @@ function! s:registers.sink(selected) abort
   execute 'normal!' '"'.reg.'p'
 endfunction
 
+function! s:paste(reg, cmd) abort
+  execute 'normal!' '"'.a:reg.a:cmd
+endfunction
+
+function! s:set_unnamed(reg) abort
+  call setreg('"', getreg(a:reg, 1, v:true), getregtype(a:reg))
+  call clap#helper#echo_info('Register ['.a:reg.'] is set as the unnamed register')
+endfunction
+
+function! s:actions_title() abort
+  let s:current_reg = s:extract_reg(g:clap.display.getcurline())
+  return 'Choose action for register ['.s:current_reg.']:'
+endfunction
+
+function! s:action_paste_after() abort
+  if !empty(s:current_reg)
+    call clap#handler#sink_with(function('s:paste'), s:current_reg, 'p')
+  endif
+endfunction
+
+function! s:action_paste_before() abort
+  if !empty(s:current_reg)
+    call clap#handler#sink_with(function('s:paste'), s:current_reg, 'P')
+  endif
+endfunction
+
+function! s:action_set_unnamed() abort
+  if !empty(s:current_reg)
+    call clap#handler#sink_with(function('s:set_unnamed'), s:current_reg)
+  endif
+endfunction
+
 let s:registers.syntax = 'clap_registers'
+let s:registers.action = {
+      \ 'title': function('s:actions_title'),
+      \ 'Paste&After': function('s:action_paste_after'),
+      \ 'Paste&Before': function('s:action_paste_before'),
+      \ 'Set&Unnamed': function('s:action_set_unnamed'),
+      \ }
 
 let g:clap#provider#registers# = s:registers
 
@@ -0,0 +1,55 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the session files, load the selected one on accept.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:sessions = {}
+
+function! s:sessions_dir() abort
+  if exists('g:clap_provider_sessions_dir')
+    return expand(g:clap_provider_sessions_dir)
+  endif
+  if has('nvim')
+    return stdpath('data').'/sessions'
+  endif
+  return expand('~/.vim/sessions')
+endfunction
+
+function! s:sessions.source() abort
+  let dir = s:sessions_dir()
+  let files = filter(glob(dir.'/*.vim', v:false, v:true), 'filereadable(v:val)')
+  " Most recently saved first.
+  call sort(files, { a, b -> getftime(b) - getftime(a) })
+  let s:session_dir = dir
+  return map(files, 'fnamemodify(v:val, ":t")')
+endfunction
+
+function! s:session_path(selected) abort
+  return s:session_dir.'/'.a:selected
+endfunction
+
+" The buffers contained in the session are added via `badd`.
+function! s:sessions.on_move() abort
+  let path = s:session_path(g:clap.display.getcurline())
+  if !filereadable(path)
+    return
+  endif
+  let buffers = map(filter(readfile(path), 'v:val =~# ''^badd '''), 'substitute(v:val, ''^badd +\d\+ '', "", "")')
+  let props = strftime('%Y-%m-%d %H:%M:%S', getftime(path)).'    '.len(buffers).' buffers'
+  call g:clap.preview.show([path, props] + buffers)
+  call clap#preview#highlight_header()
+endfunction
+
+function! s:sessions.sink(selected) abort
+  let path = s:session_path(a:selected)
+  if get(g:, 'clap_provider_sessions_save_current', v:false) && !empty(v:this_session)
+    execute 'mksession!' fnameescape(v:this_session)
+  endif
+  execute 'source' fnameescape(path)
+endfunction
+
+let g:clap#provider#sessions# = s:sessions
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
@@ -0,0 +1,21 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: Open buffers, recent files and project files in one ranked list.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:smart_open = {}
+
+let s:smart_open.sink = function('clap#provider#files#sink_impl')
+let s:smart_open['sink*'] = function('clap#provider#files#sink_star_impl')
+let s:smart_open.on_move_async = function('clap#impl#on_move#async')
+let s:smart_open.on_typed = { -> clap#client#notify_provider('on_typed') }
+let s:smart_open.enable_rooter = v:true
+let s:smart_open.support_open_action = v:true
+let s:smart_open.icon = 'File'
+let s:smart_open.syntax = 'clap_files'
+
+let g:clap#provider#smart_open# = s:smart_open
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
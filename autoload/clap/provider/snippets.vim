@@ -0,0 +1,147 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the snippets of the current filetype, expand the selected one on accept.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:snippets = {}
+
+" UltiSnips: `snippet trigger "description" options` ... `endsnippet`.
+function! s:parse_ultisnips(file, snippets) abort
+  let snippet = {}
+  for line in readfile(a:file)
+    if empty(snippet)
+      let matched = matchlist(line, '^snippet\s\+\(\S\+\)\s*\("\([^"]*\)"\)\=')
+      if !empty(matched)
+        let snippet = {'trigger': matched[1], 'description': matched[3], 'body': [], 'engine': 'ultisnips', 'file': a:file}
+      endif
+    elseif line =~# '^endsnippet'
+      call add(a:snippets, snippet)
+      let snippet = {}
+    else
+      call add(snippet.body, line)
+    endif
+  endfor
+endfunction
+
+" SnipMate: `snippet trigger description` followed by the tab-indented body.
+function! s:parse_snipmate(file, snippets) abort
+  let snippet = {}
+  for line in readfile(a:file)
+    let matched = matchlist(line, '^snippet\s\+\(\S\+\)\s*\(.*\)$')
+    if !empty(matched)
+      if !empty(snippet)
+        call add(a:snippets, snippet)
+      endif
+      let snippet = {'trigger': matched[1], 'description': matched[2], 'body': [], 'engine': 'snipmate', 'file': a:file}
+    elseif !empty(snippet) && line =~# '^\t'
+      call add(snippet.body, line[1:])
+    elseif !empty(snippet) && !empty(line)
+      call add(a:snippets, snippet)
+      let snippet = {}
+    endif
+  endfor
+  if !empty(snippet)
+    call add(a:snippets, snippet)
+  endif
+endfunction
+
+function! s:luasnip_snippets(ft, snippets) abort
+  if !has('nvim') || !luaeval('pcall(require, "luasnip")')
+    return
+  endif
+  let lua_snippets = luaeval(
+        \ 'vim.tbl_map(function(s) return { trigger = s.trigger, description = table.concat(s.dscr or {}, " "), body = vim.split(s:get_docstring()[1] or "", "\n") } end, require("luasnip").get_snippets(_A) or {})',
+        \ a:ft)
+  for snippet in lua_snippets
+    call add(a:snippets, extend(snippet, {'engine': 'luasnip', 'file': ''}))
+  endfor
+endfunction
+
+function! s:collect(ft) abort
+  let snippets = []
+  for ft in [a:ft, 'all']
+    for file in globpath(&runtimepath, 'UltiSnips/'.ft.'.snippets', v:false, v:true)
+      call s:parse_ultisnips(file, snippets)
+    endfor
+  endfor
+  for ft in [a:ft, '_']
+    for file in globpath(&runtimepath, 'snippets/'.ft.'.snippets', v:false, v:true)
+      call s:parse_snipmate(file, snippets)
+    endfor
+  endfor
+  call s:luasnip_snippets(a:ft, snippets)
+  return snippets
+endfunction
+
+" The snippet of a line is looked up exactly, the file name is appended to
+" tell apart the snippets of the same trigger and description.
+function! s:snippets.source() abort
+  let s:filetype = getbufvar(g:clap.start.bufnr, '&filetype')
+  let snippet_list = s:collect(s:filetype)
+  let s:snippet_map = {}
+  if empty(snippet_list)
+    return ['No snippets found for filetype '.s:filetype]
+  endif
+  let width = max(map(copy(snippet_list), 'strdisplaywidth(v:val.trigger)'))
+  let lines = []
+  for snippet in snippet_list
+    let line = printf('%-*s  %s  [%s]', width, snippet.trigger, snippet.description, snippet.engine)
+    if has_key(s:snippet_map, line)
+      let line .= '  '.fnamemodify(snippet.file, ':t')
+    endif
+    if !has_key(s:snippet_map, line)
+      let s:snippet_map[line] = snippet
+      call add(lines, line)
+    endif
+  endfor
+  return lines
+endfunction
+
+function! s:snippet_of(line) abort
+  return get(s:snippet_map, a:line, {})
+endfunction
+
+function! s:snippets.on_move() abort
+  let snippet = s:snippet_of(g:clap.display.getcurline())
+  if empty(snippet)
+    return
+  endif
+  let header = empty(snippet.file) ? snippet.trigger : fnamemodify(snippet.file, ':~:.')
+  call g:clap.preview.show([header] + snippet.body)
+  call g:clap.preview.set_syntax(s:filetype)
+  call clap#preview#highlight_header()
+endfunction
+
+" Expands the snippet at the cursor by its engine, `plain_body` is inserted
+" as plain text if the engine is not available.
+"
+" Invoked by maple on the `snippets.expand` notification, `plain_body` is the
+" body with the placeholders, e.g., `${1:default}` and `$1`, replaced by the
+" default text.
+function! clap#provider#snippets#expand(snippet, plain_body) abort
+  let snippet = a:snippet
+  if snippet.engine ==# 'ultisnips' && exists('*UltiSnips#ExpandSnippet')
+    execute 'normal! a'.snippet.trigger
+    call feedkeys("a\<C-R>=UltiSnips#ExpandSnippet()\<CR>", 'n')
+  elseif snippet.engine ==# 'luasnip'
+    call luaeval('(function(trigger) local ls = require("luasnip") for _, s in ipairs(ls.get_snippets(_A[2]) or {}) do if s.trigger == trigger then ls.snip_expand(s) return end end end)(_A[1])', [snippet.trigger, snippet.filetype])
+  elseif snippet.engine ==# 'snipmate' && exists('*snipMate#TriggerSnippet')
+    execute 'normal! a'.snippet.trigger
+    call feedkeys("a\<C-R>=snipMate#TriggerSnippet()\<CR>", 'n')
+  else
+    call append(line('.'), a:plain_body)
+  endif
+endfunction
+
+function! s:snippets.sink(selected) abort
+  let snippet = s:snippet_of(a:selected)
+  if !empty(snippet)
+    call clap#client#notify('snippets.expand', [extend(copy(snippet), {'filetype': s:filetype})])
+  endif
+endfunction
+
+let g:clap#provider#snippets# = s:snippets
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
@@ -0,0 +1,40 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the spelling suggestions for the word under the cursor.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:spell_suggest = {}
+
+function! s:spell_suggest.source() abort
+  let s:word = trim(clap#api#win_execute(g:clap.start.winid, 'echo expand("<cword>")'))
+  if empty(s:word)
+    return ['No word under the cursor']
+  endif
+  let max = get(g:, 'clap_provider_spell_suggest_max', 25)
+  " spellsuggest() requires 'spell' to be set for the current buffer.
+  let spell = getwinvar(g:clap.start.winid, '&spell')
+  call setwinvar(g:clap.start.winid, '&spell', 1)
+  let suggestions = spellsuggest(s:word, max)
+  call setwinvar(g:clap.start.winid, '&spell', spell)
+  if empty(suggestions)
+    return ['No suggestions for '.s:word]
+  endif
+  return suggestions
+endfunction
+
+function! s:spell_suggest.sink(selected) abort
+  if empty(get(s:, 'word', '')) || a:selected =~# '^No \(word\|suggestions\)'
+    return
+  endif
+  execute 'normal! "_ciw'.a:selected
+endfunction
+
+function! s:spell_suggest.on_enter() abort
+  call g:clap.preview.hide()
+endfunction
+
+let g:clap#provider#spell_suggest# = s:spell_suggest
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
@@ -0,0 +1,230 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the tasks of the project and run the selected one.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:tasks = {}
+
+" Map of the display line to the task info: {cmd, file, lnum, body}.
+let s:task_map = {}
+
+" Returns the lines following the target line in the recipe style, i.e., the
+" indented lines until the next unindented line.
+function! s:recipe_body(lines, idx) abort
+  let body = []
+  for line in a:lines[a:idx + 1 :]
+    if line !~# '^\s' && !empty(line)
+      break
+    endif
+    call add(body, line)
+  endfor
+  return body
+endfunction
+
+function! s:add_task(kind, name, cmd, file, lnum, body) abort
+  let line = printf('[%s] %s', a:kind, a:name)
+  let s:task_map[line] = {'cmd': a:cmd, 'file': a:file, 'lnum': a:lnum, 'body': a:body}
+endfunction
+
+function! s:collect_make_targets(root) abort
+  for makefile in ['GNUmakefile', 'makefile', 'Makefile']
+    let file = a:root.'/'.makefile
+    if filereadable(file)
+      let lines = readfile(file)
+      for idx in range(len(lines))
+        let target = matchstr(lines[idx], '^\zs[A-Za-z0-9_][A-Za-z0-9_./-]*\ze\s*:\%([^=]\|$\)')
+        if !empty(target)
+          call s:add_task('make', target, 'make '.target, file, idx + 1, s:recipe_body(lines, idx))
+        endif
+      endfor
+      return
+    endif
+  endfor
+endfunction
+
+function! s:collect_package_scripts(root) abort
+  let file = a:root.'/package.json'
+  if !filereadable(file)
+    return
+  endif
+  try
+    let package = json_decode(join(readfile(file), "\n"))
+  catch
+    return
+  endtry
+  if filereadable(a:root.'/pnpm-lock.yaml')
+    let runner = 'pnpm run '
+  elseif filereadable(a:root.'/yarn.lock')
+    let runner = 'yarn run '
+  else
+    let runner = 'npm run '
+  endif
+  let lines = readfile(file)
+  for [name, script] in items(get(package, 'scripts', {}))
+    let lnum = match(lines, '^\s*"'.escape(name, '\.*[]~^$').'"\s*:') + 1
+    call s:add_task('npm', name, runner.name, file, lnum, [script])
+  endfor
+endfunction
+
+function! s:collect_just_recipes(root) abort
+  for justfile in ['justfile', 'Justfile', '.justfile']
+    let file = a:root.'/'.justfile
+    if filereadable(file)
+      let lines = readfile(file)
+      for idx in range(len(lines))
+        let recipe = matchstr(lines[idx], '^@\?\zs[A-Za-z_][A-Za-z0-9_-]*\ze\%(\s[^:=]*\)\?:\%([^=]\|$\)')
+        if !empty(recipe)
+          call s:add_task('just', recipe, 'just '.recipe, file, idx + 1, s:recipe_body(lines, idx))
+        endif
+      endfor
+      return
+    endif
+  endfor
+endfunction
+
+function! s:collect_cargo_aliases(root) abort
+  for config in ['.cargo/config.toml', '.cargo/config']
+    let file = a:root.'/'.config
+    if filereadable(file)
+      let lines = readfile(file)
+      let in_alias = v:false
+      for idx in range(len(lines))
+        let line = lines[idx]
+        if line =~# '^\s*\['
+          let in_alias = line =~# '^\s*\[alias\]'
+        elseif in_alias
+          let matched = matchlist(line, '^\s*\([A-Za-z0-9_-]\+\)\s*=\s*\(.*\)$')
+          if !empty(matched)
+            call s:add_task('cargo', matched[1], 'cargo '.matched[1], file, idx + 1, [matched[2]])
+          endif
+        endif
+      endfor
+      return
+    endif
+  endfor
+endfunction
+
+function! s:tasks.source() abort
+  let s:root = clap#rooter#working_dir()
+  let s:task_map = {}
+  call s:collect_make_targets(s:root)
+  call s:collect_package_scripts(s:root)
+  call s:collect_just_recipes(s:root)
+  call s:collect_cargo_aliases(s:root)
+  if empty(s:task_map)
+    return ['No tasks found in '.s:root]
+  endif
+  return sort(keys(s:task_map))
+endfunction
+
+function! s:tasks.on_move() abort
+  let task = get(s:task_map, g:clap.display.getcurline(), {})
+  if empty(task)
+    return
+  endif
+  let header = '$ '.task.cmd.'    '.fnamemodify(task.file, ':~:.').':'.task.lnum
+  call g:clap.preview.show([header] + task.body)
+  call clap#preview#highlight_header()
+endfunction
+
+" Collects the output of the task into the quickfix list on exit.
+function! s:on_quickfix_job_exit(cmd, output, status) abort
+  call setqflist([], ' ', {'title': a:cmd, 'lines': a:output})
+  copen
+  if a:status == 0
+    call clap#helper#echo_info('Task `'.a:cmd.'` finished')
+  else
+    call clap#helper#echo_error('Task `'.a:cmd.'` exited with '.a:status)
+  endif
+endfunction
+
+" exit_cb of Vim may be invoked before the output is fully read, the output is
+" collected once the channel is closed by close_cb as well.
+function! s:on_quickfix_vim_job_done(cmd, state) abort
+  if has_key(a:state, 'status') && a:state.closed
+    call s:on_quickfix_job_exit(a:cmd, a:state.output, a:state.status)
+  endif
+endfunction
+
+function! s:run_in_quickfix(cmd, root) abort
+  let output = []
+  if has('nvim')
+    call jobstart(a:cmd, {
+          \ 'cwd': a:root,
+          \ 'on_stdout': {_, data, __ -> extend(output, filter(copy(data), '!empty(v:val)'))},
+          \ 'on_stderr': {_, data, __ -> extend(output, filter(copy(data), '!empty(v:val)'))},
+          \ 'on_exit': {_, status, __ -> s:on_quickfix_job_exit(a:cmd, output, status)},
+          \ })
+  else
+    let state = {'output': output, 'closed': v:false}
+    call job_start(clap#job#wrap_cmd(a:cmd), {
+          \ 'cwd': a:root,
+          \ 'in_io': 'null',
+          \ 'out_cb': {_, msg -> add(output, msg)},
+          \ 'err_cb': {_, msg -> add(output, msg)},
+          \ 'close_cb': {_ -> s:on_quickfix_vim_job_done(a:cmd, extend(state, {'closed': v:true}))},
+          \ 'exit_cb': {_, status -> s:on_quickfix_vim_job_done(a:cmd, extend(state, {'status': status}))},
+          \ })
+  endif
+endfunction
+
+function! s:run_in_terminal(cmd, root) abort
+  if has('nvim')
+    botright new
+    call termopen(a:cmd, {'cwd': a:root})
+  else
+    call term_start(clap#job#wrap_cmd(a:cmd), {'cwd': a:root, 'term_finish': 'open'})
+  endif
+endfunction
+
+" The runner is `terminal` by default, `quickfix` runs the task in the
+" background and loads the output into the quickfix list on exit.
+function! s:run_task(cmd, root) abort
+  if get(g:, 'clap_provider_tasks_runner', 'terminal') ==# 'quickfix'
+    call s:run_in_quickfix(a:cmd, a:root)
+  else
+    call s:run_in_terminal(a:cmd, a:root)
+  endif
+endfunction
+
+function! s:tasks.sink(selected) abort
+  let task = get(s:task_map, a:selected, {})
+  if !empty(task)
+    call s:run_task(task.cmd, s:root)
+  endif
+endfunction
+
+function! s:actions_title() abort
+  let s:current_task = get(s:task_map, g:clap.display.getcurline(), {})
+  return 'Choose action for task `'.get(s:current_task, 'cmd', '').'`:'
+endfunction
+
+function! s:edit_task(file, lnum) abort
+  execute 'edit' fnameescape(a:file)
+  call cursor(a:lnum, 1)
+endfunction
+
+function! s:action_run_in_quickfix() abort
+  if !empty(s:current_task)
+    call clap#handler#sink_with(function('s:run_in_quickfix'), s:current_task.cmd, s:root)
+  endif
+endfunction
+
+function! s:action_edit() abort
+  if !empty(s:current_task)
+    call clap#handler#sink_with(function('s:edit_task'), s:current_task.file, s:current_task.lnum)
+  endif
+endfunction
+
+let s:tasks.syntax = 'clap_tasks'
+let s:tasks.action = {
+      \ 'title': function('s:actions_title'),
+      \ 'Run in &quickfix': function('s:action_run_in_quickfix'),
+      \ '&Edit': function('s:action_edit'),
+      \ }
+
+let g:clap#provider#tasks# = s:tasks
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
@@ -0,0 +1,36 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the TODO/FIXME comments in the project.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:todos = {}
+
+" The line is `[TAG] [severity] [@author age] path:lnum:col:text`.
+function! s:extract_position(line) abort
+  let matched = matchlist(a:line, '^\[[^]]*\] \[[^]]*\] \[[^]]*\] \(.\{-}\):\(\d\+\):\(\d\+\):')
+  if empty(matched)
+    return []
+  endif
+  return [matched[1], str2nr(matched[2]), str2nr(matched[3])]
+endfunction
+
+function! s:todos.sink(selected) abort
+  let position = s:extract_position(a:selected)
+  if empty(position)
+    return
+  endif
+  let [fpath, lnum, column] = position
+  call clap#sink#open_file(clap#rooter#working_dir().'/'.fpath, lnum, column)
+endfunction
+
+let s:todos.init = { -> clap#client#notify_on_init() }
+let s:todos.on_typed = { -> clap#client#notify_provider('on_typed') }
+let s:todos.on_move_async = { -> clap#client#notify_provider('on_move') }
+let s:todos.enable_rooter = v:true
+let s:todos.syntax = 'clap_todos'
+
+let g:clap#provider#todos# = s:todos
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
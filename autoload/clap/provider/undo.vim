@@ -0,0 +1,92 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the undo states of the current buffer, revert to the selected one.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:undo = {}
+
+function! s:collect_seqs(entries, seqs) abort
+  for entry in a:entries
+    call add(a:seqs, entry.seq)
+    if has_key(entry, 'alt')
+      call s:collect_seqs(entry.alt, a:seqs)
+    endif
+  endfor
+endfunction
+
+" Returns the hunks turning `base` into `text`, each is `[start, count, lines]`
+" replacing `count` lines of `base` from the 0-based `start` with `lines`.
+" Returns v:null if no diff function is available.
+function! s:diff_hunks(base, text) abort
+  if exists('*diff')
+    return map(diff(a:base, a:text, {'output': 'indices'}),
+          \ '[v:val.from_idx, v:val.from_count, v:val.to_count > 0 ? a:text[v:val.to_idx : v:val.to_idx + v:val.to_count - 1] : []]')
+  elseif has('nvim-0.6')
+    " The start of a hunk is 1-based, or the line before the hunk if the count is 0.
+    let indices = luaeval('vim.diff(table.concat(_A[1], "\n") .. "\n", table.concat(_A[2], "\n") .. "\n", {result_type = "indices"})', [a:base, a:text])
+    let hunks = []
+    for [start_a, count_a, start_b, count_b] in indices
+      let lines = count_b > 0 ? a:text[start_b - 1 : start_b + count_b - 2] : []
+      call add(hunks, [count_a > 0 ? start_a - 1 : start_a, count_a, lines])
+    endfor
+    return hunks
+  endif
+  return v:null
+endfunction
+
+" Returns the undo tree of the buffer in window `winid` along with the current
+" text and the change of the most recent `g:clap_provider_undo_max_states`
+" states and the original text from it, as the diff hunks if possible.
+function! clap#provider#undo#history(winid) abort
+  let bufnr = winbufnr(a:winid)
+  call win_execute(a:winid, 'let s:tree = undotree()')
+  let tree = s:tree
+  let seqs = []
+  call s:collect_seqs(tree.entries, seqs)
+  let max_states = get(g:, 'clap_provider_undo_max_states', 100)
+  let seqs = reverse(sort(seqs, 'n'))[: max_states - 1]
+
+  let seq_cur = tree.seq_cur
+  let current = getbufline(bufnr, 1, '$')
+  let texts = {seq_cur : current}
+  let hunks = {}
+  call win_execute(a:winid, 'let s:view = winsaveview()')
+  try
+    for seq in seqs + [0]
+      if seq != seq_cur
+        call win_execute(a:winid, 'noautocmd silent undo '.seq)
+        let text = getbufline(bufnr, 1, '$')
+        let diff_hunks = s:diff_hunks(current, text)
+        if diff_hunks is v:null
+          let texts[seq] = text
+        else
+          let hunks[seq] = diff_hunks
+        endif
+      endif
+    endfor
+  finally
+    call win_execute(a:winid, 'noautocmd silent undo '.seq_cur)
+    call win_execute(a:winid, 'call winrestview(s:view)')
+  endtry
+
+  return {'tree': tree, 'texts': texts, 'hunks': hunks}
+endfunction
+
+" The line is `[*] seq  age  +inserted -deleted summary`.
+function! s:undo.sink(selected) abort
+  let seq = matchstr(a:selected, '^\*\?\s*\zs\d\+')
+  if !empty(seq)
+    execute 'undo' seq
+  endif
+endfunction
+
+let s:undo.init = { -> clap#client#notify_on_init() }
+let s:undo.on_typed = { -> clap#client#notify_provider('on_typed') }
+let s:undo.on_move_async = { -> clap#client#notify_provider('on_move') }
+let s:undo.syntax = 'clap_undo'
+
+let g:clap#provider#undo# = s:undo
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
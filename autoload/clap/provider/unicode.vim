@@ -0,0 +1,46 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: Pick an emoji or Unicode character by name, alias or codepoint.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+" Each line is `char  U+XXXX  name  :alias:...`, see scripts/unicode/generate_table.py.
+let s:unicode_table = fnamemodify(resolve(expand('<sfile>:p')), ':h:h:h:h').'/scripts/unicode/unicode_table.txt'
+
+let s:unicode = {}
+
+function! s:unicode.source() abort
+  if !filereadable(s:unicode_table)
+    return ['Unicode table not found: '.s:unicode_table]
+  endif
+  return readfile(s:unicode_table)
+endfunction
+
+function! s:unicode.on_move() abort
+  let parts = split(g:clap.display.getcurline(), '  ')
+  if len(parts) < 3
+    return
+  endif
+  let [char, codepoint, name] = parts[:2]
+  let lines = [char, '', name, codepoint]
+  let utf8_bytes = map(range(len(char)), 'printf("%02X", char2nr(char[v:val]))')
+  call add(lines, 'UTF-8: '.join(utf8_bytes, ' '))
+  if len(parts) > 3
+    call add(lines, 'Aliases: '.parts[3])
+  endif
+  call g:clap.preview.show(lines)
+endfunction
+
+function! s:unicode.sink(selected) abort
+  let char = matchstr(a:selected, '^\S\+\ze  U+')
+  if !empty(char)
+    execute 'normal! a'.char
+  endif
+endfunction
+
+let s:unicode.syntax = 'clap_unicode'
+
+let g:clap#provider#unicode# = s:unicode
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
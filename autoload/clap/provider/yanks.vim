@@ -16,6 +16,26 @@ endif
 
 let s:yanks = {}
 
+" Yank history entries sent from maple when the yank-history plugin is enabled,
+" keyed by the display line.
+let s:maple_entries = {}
+
+function! clap#provider#yanks#set_entries(entries) abort
+  let s:maple_entries = {}
+  for entry in a:entries
+    let s:maple_entries[join(entry.regcontents, "\u21b5")] = entry
+  endfor
+endfunction
+
+" Returns the register contents and type of the selected line.
+function! s:get_yanked(line) abort
+  if has_key(s:maple_entries, a:line)
+    let entry = s:maple_entries[a:line]
+    return [entry.regcontents, entry.regtype]
+  endif
+  return [a:line, 'v']
+endfunction
+
 function! clap#provider#yanks#collect() abort
   let last_yanked = getreg('"')
 
@@ -66,6 +86,14 @@ endfunction
 
 function! s:yanks.on_move() abort
   let curline = g:clap.display.getcurline()
+  if has_key(s:maple_entries, curline)
+    let entry = s:maple_entries[curline]
+    call g:clap.preview.show(entry.regcontents[:clap#preview#size_of('yanks') * 2])
+    if !empty(entry.filetype)
+      call g:clap.preview.set_syntax(entry.filetype)
+    endif
+    return
+  endif
   let lines = split(curline, "\n")[:10]
   if !empty(lines)
     call g:clap.preview.show(lines)
@@ -75,13 +103,37 @@ function! s:yanks.on_move() abort
   endif
 endfunction
 
+function! s:paste(line, cmd) abort
+  let [regcontents, regtype] = s:get_yanked(a:line)
+  call setreg('"', regcontents, regtype)
+  execute 'normal!' '""'.a:cmd
+endfunction
+
 function! s:yanks.sink(selected) abort
-  call setreg('"', a:selected)
-  normal! ""p
+  call s:paste(a:selected, 'p')
+endfunction
+
+function! s:actions_title() abort
+  let s:current_line = g:clap.display.getcurline()
+  return 'Choose action for the yanked text:'
+endfunction
+
+function! s:action_paste_after() abort
+  call clap#handler#sink_with(function('s:paste'), s:current_line, 'p')
+endfunction
+
+function! s:action_paste_before() abort
+  call clap#handler#sink_with(function('s:paste'), s:current_line, 'P')
 endfunction
 
+let s:yanks.action = {
+      \ 'title': function('s:actions_title'),
+      \ 'Paste&After': function('s:action_paste_after'),
+      \ 'Paste&Before': function('s:action_paste_before'),
+      \ }
+
 function! s:yanks.on_enter() abort
-  if !get(g:, 'clap_enable_yanks_provider', 1)
+  if !get(g:, 'clap_enable_yanks_provider', 1) && !get(g:, 'clap_plugin_experimental', 0)
     call clap#helper#echo_error('Clap yanks provider is disabled, set g:clap_enable_yanks_provider to 1 to enable.')
     call clap#handler#exit()
     call feedkeys("\<Esc>", 'n')
@@ -0,0 +1,83 @@
+" Author: liuchengxu <xuliuchengxlc@gmail.com>
+" Description: List the frequently used directories from zoxide or the builtin store, :cd on accept.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:zoxide = {}
+
+" The builtin store is used if zoxide is unavailable, each line is
+" `count<Tab>last_visited<Tab>dir`.
+function! s:store_file() abort
+  return clap#cache#location_for('zoxide', 'dirs.txt')
+endfunction
+
+function! s:read_store() abort
+  let store = {}
+  let file = s:store_file()
+  if filereadable(file)
+    for line in readfile(file)
+      let parts = split(line, "\t")
+      if len(parts) == 3
+        let store[parts[2]] = [str2nr(parts[0]), str2nr(parts[1])]
+      endif
+    endfor
+  endif
+  return store
+endfunction
+
+" Notes the directory on DirChanged, see g:clap_provider_zoxide_track_dirs.
+function! clap#provider#zoxide#note_dir(dir) abort
+  if executable('zoxide') || empty(a:dir)
+    return
+  endif
+  let store = s:read_store()
+  let [cnt, _] = get(store, a:dir, [0, 0])
+  let store[a:dir] = [cnt + 1, localtime()]
+  call writefile(map(items(store), 'v:val[1][0]."\t".v:val[1][1]."\t".v:val[0]'), s:store_file())
+endfunction
+
+" Frecency of the builtin store, similar to zoxide's aging of the visit count.
+function! s:frecency(cnt, last_visited) abort
+  let elapsed = localtime() - a:last_visited
+  if elapsed < 3600
+    return a:cnt * 4
+  elseif elapsed < 86400
+    return a:cnt * 2
+  elseif elapsed < 604800
+    return a:cnt / 2
+  endif
+  return a:cnt / 4
+endfunction
+
+function! s:zoxide.source() abort
+  if executable('zoxide')
+    return systemlist('zoxide query --list')
+  endif
+  let store = s:read_store()
+  let dirs = filter(keys(store), 'isdirectory(v:val)')
+  return sort(dirs, { a, b -> s:frecency(store[b][0], store[b][1]) - s:frecency(store[a][0], store[a][1]) })
+endfunction
+
+function! s:zoxide.on_move() abort
+  let dir = g:clap.display.getcurline()
+  if isdirectory(dir)
+    call g:clap.preview.show([dir] + readdir(dir)[: 2 * clap#preview#size_of('zoxide')])
+    call clap#preview#highlight_header()
+  endif
+endfunction
+
+function! s:zoxide.sink(selected) abort
+  execute 'cd' fnameescape(a:selected)
+  if executable('zoxide')
+    call system('zoxide add '.shellescape(a:selected))
+  endif
+  if get(g:, 'clap_provider_zoxide_open_files', v:false)
+    call timer_start(0, { -> execute('Clap files') })
+  endif
+endfunction
+
+let g:clap#provider#zoxide# = s:zoxide
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
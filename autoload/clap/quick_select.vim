@@ -0,0 +1,125 @@
+" Description: Overlay labels on the visible entries, typing a label accepts
+" the entry.
+
+let s:save_cpo = &cpoptions
+set cpoptions&vim
+
+let s:is_nvim = has('nvim')
+let s:labels = {}
+
+if s:is_nvim
+  let s:ns_id = nvim_create_namespace('clap_quick_select')
+
+  function! s:show_labels(labels) abort
+    for [lnum, label] in a:labels
+      call nvim_buf_set_extmark(g:clap.display.bufnr, s:ns_id, lnum - 1, 0, {
+            \ 'virt_text': [[label, 'ClapQuickSelectLabel']],
+            \ 'virt_text_pos': 'overlay',
+            \ })
+    endfor
+  endfunction
+
+  function! s:clear_labels() abort
+    call nvim_buf_clear_namespace(g:clap.display.bufnr, s:ns_id, 0, -1)
+  endfunction
+else
+  let s:label_popups = []
+
+  " The virtual text of Vim is inserted before the line rather than overlaid,
+  " the labels are popups over the display window instead.
+  function! s:show_labels(labels) abort
+    for [lnum, label] in a:labels
+      let pos = screenpos(g:clap.display.winid, lnum, 1)
+      if pos.row == 0
+        continue
+      endif
+      call add(s:label_popups, popup_create(label, {
+            \ 'line': pos.row,
+            \ 'col': pos.col,
+            \ 'highlight': 'ClapQuickSelectLabel',
+            \ 'zindex': 1001,
+            \ }))
+    endfor
+  endfunction
+
+  function! s:clear_labels() abort
+    call map(s:label_popups, 'popup_close(v:val)')
+    let s:label_popups = []
+  endfunction
+endif
+
+" Returns the first and last line in view of the display window.
+function! clap#quick_select#visible_range() abort
+  if g:clap.display.is_empty()
+    return [1, 0]
+  endif
+  let wininfo = getwininfo(g:clap.display.winid)[0]
+  return [wininfo.topline, min([wininfo.botline, g:clap.display.line_count()])]
+endfunction
+
+" Returns the line of the label `typed`, 0 if `typed` is only the prefix of
+" some labels and -1 if it leads to no label.
+function! clap#quick_select#lookup(labels, typed) abort
+  if has_key(a:labels, a:typed)
+    return a:labels[a:typed]
+  endif
+  return empty(filter(keys(a:labels), 'stridx(v:val, a:typed) == 0')) ? -1 : 0
+endfunction
+
+let s:cancel_keys = ["\<Esc>", "\<C-c>", "\<FocusLost>"]
+" Time in seconds to wait for the next key of the label.
+let s:key_timeout = 10
+
+" Reads the label, the entry is accepted once a label is typed in full. The
+" quick-select is cancelled by <Esc>, losing the focus, closing the display
+" window, no key in time or any key not leading to a label.
+function! s:read_label(_timer) abort
+  redraw
+  let typed = ''
+  let lnum = 0
+  let last_key_time = reltime()
+  while lnum == 0
+    let char = getchar(0)
+    if type(char) == v:t_number && char == 0
+      if reltimefloat(reltime(last_key_time)) > s:key_timeout
+            \ || empty(getwininfo(g:clap.display.winid))
+        break
+      endif
+      sleep 20m
+      continue
+    endif
+
+    let key = type(char) == v:t_number ? nr2char(char) : char
+    if index(s:cancel_keys, key) >= 0
+      break
+    endif
+    let typed .= key
+    let lnum = clap#quick_select#lookup(s:labels, typed)
+    let last_key_time = reltime()
+  endwhile
+
+  call s:clear_labels()
+  if lnum > 0
+    call clap#mouse#accept_line(lnum)
+  endif
+endfunction
+
+" labels: List of [lnum, label].
+function! clap#quick_select#start(labels) abort
+  if !s:is_nvim && !exists('*popup_create')
+    call clap#helper#echo_warn('quick-select requires the popup window of Vim 8.2+')
+    return
+  endif
+
+  let s:labels = {}
+  for [lnum, label] in a:labels
+    let s:labels[label] = lnum
+  endfor
+
+  call s:show_labels(a:labels)
+  " Read the label outside of the RPC callback.
+  call timer_start(0, function('s:read_label'))
+endfunction
+
+let &cpoptions = s:save_cpo
+unlet s:save_cpo
@@ -8,10 +8,16 @@ let s:multi_select_enabled = v:false
 let s:support_multi_select = v:false
 let s:UNUSABLE_MULTI_SELECTION = '<Tab> is unusable, set g:clap_multi_selection_warning_silent = 1 to suppress this warning.'
 
+function! s:sink_each(lines) abort
+  for line in a:lines
+    call g:clap.provider.sink(line)
+  endfor
+endfunction
+
 function! clap#selection#get_sink_or_sink_star_params() abort
   let selected = clap#sign#get()
   if s:multi_select_enabled && !empty(selected)
-    let Sink = g:clap.provider.sink_star
+    let Sink = has_key(g:clap.provider._(), 'sink*') ? g:clap.provider.sink_star : function('s:sink_each')
     let sink_args = map(selected, 'clap#api#get_origin_line_at(v:val)')
   else
     let Sink = g:clap.provider.sink
@@ -48,7 +54,14 @@ function! clap#selection#toggle() abort
     return ''
   endif
 
+  let lnum = g:clap.display.getcurlnum()
   noautocmd call clap#sign#toggle_cursorline_multi()
+  " The selection is kept by maple for the bulk actions.
+  call clap#client#notify_provider('selection', {
+        \ 'line': clap#api#get_origin_line_at(lnum),
+        \ 'selected': index(clap#sign#get(), lnum) != -1,
+        \ 'icon_added': get(g:, '__clap_icon_added_by_maple', v:false),
+        \ })
   call clap#navigation#linewise_scroll_down()
   call clap#indicator#render()
 
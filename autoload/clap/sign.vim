@@ -150,6 +150,8 @@ function! clap#sign#reset_on_query_change() abort
     if get(g:, 'clap_preserve_selections_on_query_change', v:false)
       let selected = map(s:signed, 'clap#api#get_origin_line_at(v:val)')
       let s:preserved_selections = extend(s:preserved_selections, selected)
+    else
+      call clap#client#notify_provider('clear_selection')
     endif
     call s:unplace_all_signs()
     let s:signed = []
@@ -32,5 +32,40 @@ function! clap#sink#open_quickfix(qf_entries) abort
   cc
 endfunction
 
+function! s:set_quickfix_list(qf_entries, title) abort
+  call setqflist([], ' ', {'items': a:qf_entries, 'title': a:title})
+  copen
+endfunction
+
+" Exits clap and sends the entries to the quickfix list.
+function! clap#sink#send_to_quickfix(qf_entries, title) abort
+  call clap#handler#sink_with(function('s:set_quickfix_list'), a:qf_entries, a:title)
+endfunction
+
+function! s:open_all(entries, open_cmd) abort
+  for entry in a:entries
+    execute a:open_cmd fnameescape(entry.filename)
+    if entry.lnum > 0
+      noautocmd call cursor(entry.lnum, max([entry.col, 1]))
+      normal! zz
+    endif
+  endfor
+endfunction
+
+" Exits clap and opens each file location with `open_cmd`, e.g., `split`.
+function! clap#sink#open_all(entries, open_cmd) abort
+  call clap#handler#sink_with(function('s:open_all'), a:entries, a:open_cmd)
+endfunction
+
+function! s:add_to_arglist(files) abort
+  execute 'argadd' join(map(copy(a:files), 'fnameescape(v:val)'))
+  echo printf('[vim-clap] %d file(s) added to the arglist', len(a:files))
+endfunction
+
+" Exits clap and adds the files to the arglist.
+function! clap#sink#add_to_arglist(files) abort
+  call clap#handler#sink_with(function('s:add_to_arglist'), a:files)
+endfunction
+
 let &cpoptions = s:save_cpo
 unlet s:save_cpo
@@ -57,6 +57,8 @@ function! clap#state#process_filter_message(decoded_msg, ensure_sign_exists) abo
     let g:__clap_icon_added_by_maple = decoded.icon_added
   endif
 
+  let g:__clap_has_more_results = get(decoded, 'has_more', v:false)
+
   if has_key(decoded, 'display_syntax')
     call setbufvar(g:clap.display.bufnr, '&syntax', decoded.display_syntax)
   endif
@@ -74,6 +76,23 @@ function! clap#state#process_filter_message(decoded_msg, ensure_sign_exists) abo
   endif
 endfunction
 
+" Appends the next page of the results loaded on scrolling to the bottom.
+function! clap#state#append_page(page) abort
+  if !g:clap.display.win_is_valid()
+    return
+  endif
+  let offset = g:clap.display.line_count()
+  call g:clap.display.append_lines_uncheck(a:page.lines)
+  call clap#highlighter#add_highlights_from(offset, a:page.indices)
+  if !empty(a:page.truncated_map)
+    if !exists('g:__clap_lines_truncated_map')
+      let g:__clap_lines_truncated_map = {}
+    endif
+    call extend(g:__clap_lines_truncated_map, a:page.truncated_map)
+  endif
+  let g:__clap_has_more_results = a:page.has_more
+endfunction
+
 function! clap#state#process_progress(matched, processed) abort
   call clap#indicator#update(a:matched, a:processed)
 endfunction
@@ -97,6 +116,40 @@ function! clap#state#process_progress_full(display_lines, matched, processed) ab
   endif
 endfunction
 
+" Applies the incremental update of the filtering results, `partial` is true
+" if more results may come.
+function! clap#state#update_picker(update) abort
+  if !g:clap.display.win_is_valid()
+    return
+  endif
+  call clap#indicator#update(a:update.matched, a:update.processed)
+  if has_key(a:update, 'lines')
+    if a:update.start == 0
+      call g:clap.display.set_lines(a:update.lines)
+    else
+      let lines = g:clap.display.get_lines()[: a:update.start - 1] + a:update.lines
+      call g:clap.display.set_lines(lines[: a:update.total_lines - 1])
+    endif
+  endif
+  call clap#highlighter#add_highlights(a:update.indices)
+  call clap#preview#update_with_delay()
+  if a:update.matched > 0
+    call clap#sign#ensure_exists()
+  endif
+  let g:__clap_icon_added_by_maple = a:update.icon_added
+  " Set by maple once the results beyond the first screen are available.
+  let g:__clap_has_more_results = v:false
+  if !empty(a:update.truncated_map)
+    let g:__clap_lines_truncated_map = a:update.truncated_map
+  elseif exists('g:__clap_lines_truncated_map')
+    unlet g:__clap_lines_truncated_map
+  endif
+  if has_key(a:update, 'render_id')
+    " Report back for measuring the render latency.
+    call clap#client#notify('picker-rendered', [a:update.render_id])
+  endif
+endfunction
+
 function! clap#state#render_preview(preview) abort
   if !g:clap.display.win_is_valid()
     return
@@ -151,6 +204,8 @@ function! clap#state#process_response_on_typed(result) abort
     let g:__clap_icon_added_by_maple = a:result.icon_added
   endif
 
+  let g:__clap_has_more_results = get(a:result, 'has_more', v:false)
+
   call g:clap.display.set_lines(a:result.lines)
   call clap#highlighter#add_highlights(a:result.indices)
   call clap#sign#ensure_exists()
@@ -200,12 +255,27 @@ function! clap#state#init_display(lines, truncated_map, icon_added, using_cache)
     unlet g:__clap_lines_truncated_map
   endif
 
+  " The rest of the source is paged in on scrolling to the bottom.
+  let g:__clap_has_more_results = get(g:clap.display, 'initial_size', 0) > len(a:lines)
+
   call clap#indicator#update_processed(g:clap.display.initial_size)
   call clap#sign#ensure_exists()
   call clap#spinner#refresh()
   call clap#preview#update_with_delay()
 endfunction
 
+" Shows the status of the source command in the prompt, e.g., the exit code and stderr.
+function! clap#state#set_command_status(running, status) abort
+  if a:running
+    let g:__clap_current_forerunner_status = g:clap_forerunner_status_sign.running
+    call clap#spinner#set_busy()
+  else
+    let g:__clap_current_forerunner_status = empty(a:status) ? g:clap_forerunner_status_sign.done : a:status.' '
+    call clap#spinner#set_idle()
+  endif
+  call clap#spinner#refresh()
+endfunction
+
 function! clap#state#update_on_empty_query(lines, truncated_map, icon_added) abort
   if !g:clap.display.win_is_valid()
     return
@@ -218,6 +288,7 @@ function! clap#state#update_on_empty_query(lines, truncated_map, icon_added) abo
     unlet g:__clap_lines_truncated_map
   endif
   let g:__clap_icon_added_by_maple = a:icon_added
+  let g:__clap_has_more_results = v:false
   call clap#sign#ensure_exists()
   call g:clap.display.clear_highlight()
   call clap#indicator#update_matched(0)
@@ -230,6 +301,37 @@ function! clap#state#set_input(new) abort
   call clap#api#win_execute(g:clap.input.winid, 'call cursor(1, 1000)')
 endfunction
 
+" Sets the query from maple and moves the cursor to the character index
+" `cursor`, -1 for the end of the query.
+function! clap#state#set_query(query, cursor) abort
+  if has('nvim')
+    call g:clap.input.set(a:query)
+    let col = a:cursor < 0 ? -1 : byteidx(a:query, a:cursor)
+    let col = col < 0 ? strlen(a:query) + 1 : col + 1
+    call clap#api#win_execute(g:clap.input.winid, 'call cursor(1, '.col.')')
+  else
+    call clap#popup#move_manager#set_input(a:query, a:cursor)
+  endif
+endfunction
+
+" Restores the results and the scroll position of the session resumed by
+" `:Clap ++resume`.
+function! clap#state#restore_session(lines, curlnum, topline, truncated_map, icon_added) abort
+  call g:clap.display.set_lines(a:lines)
+  " The origin lines of the restored lines are looked up as usual.
+  let g:__clap_icon_added_by_maple = a:icon_added
+  if !empty(a:truncated_map)
+    let g:__clap_lines_truncated_map = a:truncated_map
+  elseif exists('g:__clap_lines_truncated_map')
+    unlet g:__clap_lines_truncated_map
+  endif
+  let curlnum = min([a:curlnum, len(a:lines)])
+  call win_execute(g:clap.display.winid,
+        \ 'noautocmd call winrestview({"lnum": curlnum, "topline": a:topline})')
+  let g:__clap_display_curlnum = curlnum
+  call clap#sign#toggle_cursorline()
+endfunction
+
 " Returns the cached source tmp file.
 "
 " Write the providers whose `source` is list-style into a tempfile.
@@ -131,6 +131,7 @@ function! s:apply_default_theme() abort
   hi default link ClapCurrentSelection ClapDefaultCurrentSelection
   hi default link ClapSelectedSign WarningMsg
   hi default link ClapCurrentSelectionSign WarningMsg
+  hi default link ClapQuickSelectLabel IncSearch
 
   execute 'hi default link ClapInput' s:input_default_hi_group
   execute 'hi default link ClapDisplay' s:display_default_hi_group
@@ -206,6 +207,7 @@ endfunction
 
 function! clap#themes#init() abort
   hi default link ClapMatches Search
+  hi default link ClapPreviewTargetLine Search
   hi default link ClapNoMatchesFound ErrorMsg
   hi default link ClapPopupCursor Type
 
@@ -14,7 +14,7 @@ fn prepare_source_items() -> Vec<SourceItem> {
     let largest_cache = find_largest_cache_digest().expect("Cache is empty");
     println!("====  Total items: {}  ====", largest_cache.total);
 
-    std::io::BufReader::new(std::fs::File::open(&largest_cache.cached_path).unwrap())
+    std::io::BufReader::new(utils::open_reader(&largest_cache.cached_path).unwrap())
         .lines()
         .filter_map(|x| x.ok().map(Into::<SourceItem>::into))
         .collect()
@@ -145,7 +145,7 @@ fn bench_regex_searcher(c: &mut Criterion) {
 fn bench_bytecount(c: &mut Criterion) {
     let largest_cache = find_largest_cache_digest().expect("Cache is empty");
     c.bench_function("bytecount", |b| {
-        b.iter(|| count_lines(std::fs::File::open(&largest_cache.cached_path).unwrap()))
+        b.iter(|| count_lines(utils::open_reader(&largest_cache.cached_path).unwrap()))
     });
 }
 
@@ -10,6 +10,9 @@ pub enum RunCmd {
     /// Start the stdio-based service, currently there is only filer support.
     #[clap(name = "rpc")]
     Rpc(command::rpc::Rpc),
+    /// Serve the editors with a daemon shared by all of them.
+    #[clap(name = "serve")]
+    Serve(command::serve::Serve),
     #[clap(name = "grep")]
     Grep(command::grep::Grep),
     /// Execute the ripgrep command to avoid the escape issue
@@ -26,18 +29,45 @@ pub enum RunCmd {
     /// Generate the project-wide tags using ctags.
     #[clap(name = "ctags", subcommand)]
     Ctags(command::ctags::Ctags),
+    /// Generate, update and inspect the project tags cache.
+    #[clap(name = "tags", subcommand)]
+    Tags(command::tags::Tags),
     /// Interact with the cache info.
     #[clap(name = "cache", subcommand)]
     Cache(command::cache::Cache),
+    /// Check the setup and print the fixes of the problems found.
+    #[clap(name = "check")]
+    Check(command::check::Check),
+    /// Fuzzy complete the command line of bash and zsh.
+    #[clap(name = "complete-shell", subcommand)]
+    CompleteShell(command::complete_shell::CompleteShell),
+    /// Print the JSON schema of config.toml.
+    #[clap(name = "config-schema")]
+    ConfigSchema(command::config::ConfigSchema),
     /// Fuzzy filter the input.
     #[clap(name = "filter")]
     Filter(command::filter::Filter),
+    /// Measure the walker and the matcher on a project and suggest the settings.
+    #[clap(name = "benchmark")]
+    Benchmark(command::benchmark::Benchmark),
     /// Filter against current Vim buffer.
     #[clap(name = "blines")]
     Blines(command::blines::Blines),
     /// Generate vim help tags.
     #[clap(name = "helptags")]
     Helptags(command::helptags::Helptags),
+    /// Build the file index of a project ahead of time.
+    #[clap(name = "index")]
+    Index(command::index::Index),
+    /// Pick an item from the lines of stdin interactively.
+    #[clap(name = "pick")]
+    Pick(command::pick::Pick),
+    /// Serve the picker sessions over a unix domain socket or TCP for the external tools.
+    #[clap(name = "picker-daemon")]
+    PickerDaemon(command::picker_daemon::PickerDaemon),
+    /// Run a provider non-interactively and print the ranked results.
+    #[clap(name = "run")]
+    Run(command::run::Run),
     /// Start the forerunner job of grep.
     #[clap(name = "ripgrep-forerunner")]
     RipGrepForerunner(command::grep::RipGrepForerunner),
@@ -120,8 +150,12 @@ impl RunCmd {
             .expect("Failed to configure the rayon global thread pool");
 
         match self {
+            Self::Benchmark(benchmark) => benchmark.run(args),
             Self::Blines(blines) => blines.run(args),
             Self::Cache(cache) => cache.run(),
+            Self::Check(check) => check.run(args),
+            Self::CompleteShell(complete_shell) => complete_shell.run(args),
+            Self::ConfigSchema(config_schema) => config_schema.run(),
             Self::Ctags(ctags) => ctags.run(args),
             Self::DumbJump(dumb_jump) => dumb_jump.run(),
             Self::Exec(exec) => exec.run(args),
@@ -130,8 +164,14 @@ impl RunCmd {
             Self::LiveGrep(live_grep) => live_grep.run(args),
             Self::Gtags(gtags) => gtags.run(args),
             Self::Helptags(helptags) => helptags.run(),
+            Self::Index(index) => index.run(args),
+            Self::Pick(pick) => pick.run(args),
+            Self::PickerDaemon(picker_daemon) => picker_daemon.run(args),
             Self::RipGrepForerunner(rip_grep_forerunner) => rip_grep_forerunner.run(args),
             Self::Rpc(rpc) => rpc.run(args).await,
+            Self::Run(run) => run.run(args),
+            Self::Serve(serve) => serve.run(args).await,
+            Self::Tags(tags) => tags.run(args),
         }
     }
 }
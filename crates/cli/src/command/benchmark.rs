@@ -0,0 +1,273 @@
+use crate::app::Args;
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use maple_core::searcher::walker::{IgnoreWalker, Walker};
+use maple_core::searcher::WalkConfig;
+use maple_core::stdio_server::ProviderId;
+use matcher::Matcher;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use types::{ClapItem, MatchedItem, Query};
+
+/// Queries used when no `--query` is given, common fragments of the file paths.
+const DEFAULT_QUERIES: &[&str] = &["src", "mod", "test", "conf"];
+
+/// Sizes of the source on which the sequential and parallel filtering are compared.
+const SOURCE_SIZES: &[usize] = &[1_000, 10_000, 100_000];
+
+/// Walking slower than this makes the persistent index worthwhile.
+const SLOW_WALK: Duration = Duration::from_millis(500);
+
+/// Measure the walker and the matcher on a project and print the suggested settings.
+#[derive(Parser, Debug, Clone)]
+pub struct Benchmark {
+    /// Root of the project, the current directory by default.
+    #[clap(long, value_parser)]
+    dir: Option<PathBuf>,
+
+    /// Query to filter the project files with, can be repeated.
+    #[clap(long = "query")]
+    queries: Vec<String>,
+
+    /// Number of runs of each measurement, the fastest one is reported.
+    #[clap(long, default_value_t = 3)]
+    rounds: usize,
+}
+
+/// Returns the fastest of `rounds` runs of `f`.
+fn best_of(rounds: usize, mut f: impl FnMut()) -> Duration {
+    (0..rounds.max(1))
+        .map(|_| {
+            let now = Instant::now();
+            f();
+            now.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn walk(walker: &dyn Walker, root: &Path, walk_config: WalkConfig, visit: &(dyn Fn(&Path) + Sync)) {
+    let stop_signal = AtomicBool::new(false);
+    walker.walk_files(vec![root.to_path_buf()], walk_config, &stop_signal, &|| {
+        Box::new(move |path: &Path| {
+            visit(path);
+            true
+        })
+    });
+}
+
+fn walkers() -> Vec<(&'static str, Box<dyn Walker>)> {
+    #[allow(unused_mut)]
+    let mut walkers: Vec<(&'static str, Box<dyn Walker>)> =
+        vec![("ignore", Box::new(IgnoreWalker))];
+    #[cfg(target_os = "linux")]
+    walkers.push((
+        "native",
+        Box::new(maple_core::searcher::walker::NativeWalker),
+    ));
+    walkers
+}
+
+fn filter_sequential(items: &[Arc<dyn ClapItem>], matcher: &Matcher) -> Vec<MatchedItem> {
+    let mut matched_items = items
+        .iter()
+        .filter_map(|item| matcher.match_item(item.clone()))
+        .collect::<Vec<_>>();
+    matched_items.sort_unstable_by(|v1, v2| v2.cmp(v1));
+    matched_items
+}
+
+/// Returns the smallest number of threads within 10% of the fastest filtering.
+fn suggest_num_threads(timings: &[(usize, Duration)]) -> Option<usize> {
+    let fastest = timings.iter().map(|(_, elapsed)| *elapsed).min()?;
+    timings
+        .iter()
+        .filter(|(_, elapsed)| elapsed.as_secs_f64() <= fastest.as_secs_f64() * 1.1)
+        .map(|(num_threads, _)| *num_threads)
+        .min()
+}
+
+/// Returns the smallest source size from which the parallel filtering is faster.
+///
+/// `timings` are `(size, sequential, parallel)` sorted by size.
+fn parallel_threshold(timings: &[(usize, Duration, Duration)]) -> Option<usize> {
+    timings
+        .iter()
+        .find(|(_, sequential, parallel)| parallel < sequential)
+        .map(|(size, _, _)| *size)
+}
+
+/// Returns the `g:clap_popup_input_delay` in milliseconds for a filtering latency.
+///
+/// The delay leaves room for one filtering between two keystrokes, in steps of 10ms.
+fn suggest_input_delay(latency: Duration) -> u64 {
+    let delay = (latency.as_millis() as u64 * 2).clamp(20, 200);
+    (delay + 9) / 10 * 10
+}
+
+impl Benchmark {
+    pub fn run(&self, args: Args) -> Result<()> {
+        maple_core::config::initialize_config_file(args.config_file);
+
+        let dir = match self.dir {
+            Some(ref d) => d.clone(),
+            None => std::env::current_dir()?,
+        };
+
+        let project_config = maple_core::config::load_project_config(&dir);
+        let config = project_config
+            .as_ref()
+            .unwrap_or_else(|| maple_core::config::config());
+        let walk_config = config.walker.walk_config(Some("files"));
+
+        let files = Mutex::new(Vec::new());
+        walk(&IgnoreWalker, &dir, walk_config.clone(), &|path| {
+            let path = path.strip_prefix(&dir).unwrap_or(path);
+            files.lock().unwrap().push(path.display().to_string());
+        });
+        let items = files
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|line| Arc::new(line) as Arc<dyn ClapItem>)
+            .collect::<Vec<_>>();
+
+        if items.is_empty() {
+            return Err(anyhow!("No files found in {}", dir.display()));
+        }
+
+        println!("Project: {} ({} files)", dir.display(), items.len());
+
+        println!("\nWalker:");
+        let mut walk_timings = Vec::new();
+        for (name, walker) in walkers() {
+            let counter = AtomicUsize::new(0);
+            let elapsed = best_of(self.rounds, || {
+                walk(walker.as_ref(), &dir, walk_config.clone(), &|_| {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                });
+            });
+            println!("  {name:<8} {elapsed:?}");
+            walk_timings.push((name, elapsed));
+        }
+
+        let queries = if self.queries.is_empty() {
+            DEFAULT_QUERIES.iter().map(|q| q.to_string()).collect()
+        } else {
+            self.queries.clone()
+        };
+        let matchers = queries
+            .iter()
+            .map(|query| {
+                ProviderId::from("files")
+                    .matcher_builder()
+                    .rank_criteria(config.matcher.rank_criteria())
+                    .case_matching(args.case_matching)
+                    .build(Query::from(query))
+            })
+            .collect::<Vec<_>>();
+
+        let filter_all = |items: &[Arc<dyn ClapItem>], parallel: bool| {
+            for matcher in &matchers {
+                if parallel {
+                    filter::par_filter_items(items, matcher);
+                } else {
+                    filter_sequential(items, matcher);
+                }
+            }
+        };
+
+        println!("\nMatcher ({} queries):", matchers.len());
+        let num_cpus = num_cpus::get();
+        let mut thread_counts = std::iter::successors(Some(1), |n| Some(n * 2))
+            .take_while(|n| *n < num_cpus)
+            .collect::<Vec<_>>();
+        thread_counts.push(num_cpus);
+        let mut thread_timings = Vec::new();
+        for num_threads in thread_counts {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()?;
+            let elapsed = best_of(self.rounds, || pool.install(|| filter_all(&items, true)));
+            println!("  {num_threads:>3} threads  {elapsed:?}");
+            thread_timings.push((num_threads, elapsed));
+        }
+
+        println!("\nSequential vs parallel filtering:");
+        let mut size_timings = Vec::new();
+        for &size in SOURCE_SIZES.iter().filter(|size| **size <= items.len()) {
+            let sequential = best_of(self.rounds, || filter_all(&items[..size], false));
+            let parallel = best_of(self.rounds, || filter_all(&items[..size], true));
+            println!("  {size:>7} items  sequential {sequential:?}, parallel {parallel:?}");
+            size_timings.push((size, sequential, parallel));
+        }
+
+        println!("\nSuggestions:");
+
+        if let Some((backend, walk_elapsed)) = walk_timings
+            .iter()
+            .min_by_key(|(_, elapsed)| *elapsed)
+            .copied()
+        {
+            println!("  config.toml: [walker] backend = \"{backend}\"");
+            if walk_elapsed > SLOW_WALK {
+                println!(
+                    "  config.toml: [indexer] enable = true, the walking takes {walk_elapsed:?}"
+                );
+            }
+        }
+
+        let num_threads = suggest_num_threads(&thread_timings).unwrap_or(num_cpus);
+        println!(
+            "  Environment: RAYON_NUM_THREADS={num_threads}, or `maple --rayon-num-threads {num_threads}`"
+        );
+
+        match parallel_threshold(&size_timings) {
+            Some(size) => println!("  The parallel filtering pays off from {size} items"),
+            None if !size_timings.is_empty() => {
+                println!("  The parallel filtering does not pay off on this project")
+            }
+            None => {}
+        }
+
+        let latency = thread_timings
+            .iter()
+            .find(|(n, _)| *n == num_threads)
+            .map(|(_, elapsed)| *elapsed / matchers.len().max(1) as u32)
+            .unwrap_or_default();
+        println!(
+            "  Vim: let g:clap_popup_input_delay = {}, filtering all the files takes {latency:?}",
+            suggest_input_delay(latency)
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggestions() {
+        let ms = Duration::from_millis;
+
+        assert_eq!(suggest_num_threads(&[]), None);
+        assert_eq!(
+            suggest_num_threads(&[(1, ms(100)), (2, ms(55)), (4, ms(52)), (8, ms(50))]),
+            Some(2)
+        );
+
+        assert_eq!(
+            parallel_threshold(&[(1_000, ms(1), ms(2)), (10_000, ms(10), ms(4))]),
+            Some(10_000)
+        );
+        assert_eq!(parallel_threshold(&[(1_000, ms(1), ms(2))]), None);
+
+        assert_eq!(suggest_input_delay(ms(1)), 20);
+        assert_eq!(suggest_input_delay(ms(33)), 70);
+        assert_eq!(suggest_input_delay(ms(500)), 200);
+    }
+}
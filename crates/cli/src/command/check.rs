@@ -0,0 +1,34 @@
+use crate::app::Args;
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use maple_core::health::Status;
+use std::io::Write;
+
+/// Check the config file, the external tools and the directories used by maple.
+#[derive(Parser, Debug, Clone)]
+pub struct Check;
+
+impl Check {
+    pub fn run(&self, args: Args) -> Result<()> {
+        maple_core::config::initialize_config_file(args.config_file);
+
+        let checks = maple_core::health::run_checks();
+
+        let stdout = std::io::stdout();
+        let mut lock = stdout.lock();
+        for check in &checks {
+            writeln!(lock, "{}", check.display_line())?;
+        }
+
+        let errors = checks
+            .iter()
+            .filter(|check| check.status == Status::Error)
+            .count();
+
+        if errors > 0 {
+            Err(anyhow!("{errors} problem(s) need to be fixed"))
+        } else {
+            Ok(())
+        }
+    }
+}
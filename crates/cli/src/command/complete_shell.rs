@@ -0,0 +1,194 @@
+use super::pick::{filter_items, run_picker, EXIT_INTERRUPTED, EXIT_NO_MATCH};
+use crate::app::Args;
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use maple_core::source_service::{BuiltinSource, Source};
+use std::collections::HashSet;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use types::{CaseMatching, ClapItem};
+
+/// Key bindings of zsh, `^R` for the history and `^T` for the paths.
+const ZSH_INIT: &str = r#"__maple_history() {
+  local selected
+  selected=$(fc -rln 1 | {maple} complete-shell history --query "$LBUFFER") && LBUFFER=$selected
+  zle reset-prompt
+}
+zle -N __maple_history
+bindkey '^R' __maple_history
+
+__maple_paths() {
+  local word=${LBUFFER##* } selected
+  selected=$({maple} complete-shell paths --query "$word") && LBUFFER="${LBUFFER%$word}${(q)selected}"
+  zle reset-prompt
+}
+zle -N __maple_paths
+bindkey '^T' __maple_paths
+"#;
+
+/// Key bindings of bash, `\C-r` for the history and `\C-t` for the paths.
+const BASH_INIT: &str = r#"__maple_history() {
+  local selected
+  selected=$(fc -rln 1 | {maple} complete-shell history --query "$READLINE_LINE") || return
+  READLINE_LINE=$selected
+  READLINE_POINT=${#selected}
+}
+bind -x '"\C-r": __maple_history'
+
+__maple_paths() {
+  local left=${READLINE_LINE:0:$READLINE_POINT} word selected
+  word=${left##* }
+  selected=$({maple} complete-shell paths --query "$word") || return
+  selected=$(printf '%q' "$selected")
+  READLINE_LINE="${left%"$word"}$selected${READLINE_LINE:$READLINE_POINT}"
+  READLINE_POINT=$(( ${#left} - ${#word} + ${#selected} ))
+}
+bind -x '"\C-t": __maple_paths'
+"#;
+
+/// Fuzzy complete the command line of bash and zsh using the matcher of maple.
+///
+/// The selected candidate is printed, the exit code is 1 if nothing matches and 130 if the
+/// picker is aborted.
+#[derive(Subcommand, Debug, Clone)]
+pub enum CompleteShell {
+    History(History),
+    Paths(Paths),
+    Init(Init),
+}
+
+impl CompleteShell {
+    pub fn run(&self, args: Args) -> Result<()> {
+        maple_core::config::initialize_config_file(args.config_file);
+
+        match self {
+            Self::History(history) => history.run(args.case_matching),
+            Self::Paths(paths) => paths.run(args.case_matching),
+            Self::Init(init) => init.run(),
+        }
+    }
+}
+
+/// Picks the candidate matching `query`, without the interactive picker if there is only one.
+fn complete(items: Vec<Arc<dyn ClapItem>>, query: &str, case_matching: CaseMatching) -> Result<()> {
+    let matched_items = filter_items(&items, query, case_matching);
+
+    let selected = match matched_items.as_slice() {
+        [] => std::process::exit(EXIT_NO_MATCH),
+        [only] if !query.is_empty() => only.item.raw_text().to_string(),
+        _ => match run_picker(items, case_matching, query.to_string(), "> ", None)? {
+            Some(selected) => selected,
+            None => std::process::exit(EXIT_INTERRUPTED),
+        },
+    };
+
+    writeln!(std::io::stdout().lock(), "{selected}")?;
+
+    Ok(())
+}
+
+/// Returns the distinct commands of the history lines, the order is preserved.
+///
+/// The lines are expected to be the newest first, e.g., from `fc -rln 1`.
+fn history_items(lines: impl Iterator<Item = String>) -> Vec<Arc<dyn ClapItem>> {
+    let mut seen = HashSet::new();
+    lines
+        .filter_map(|line| {
+            let command = line.trim();
+            (!command.is_empty() && seen.insert(command.to_string()))
+                .then(|| Arc::new(command.to_string()) as Arc<dyn ClapItem>)
+        })
+        .collect()
+}
+
+/// Pick a command from the history lines of stdin, the newest first.
+#[derive(Parser, Debug, Clone)]
+pub struct History {
+    /// Current content of the command line.
+    #[clap(short, long, default_value = "")]
+    query: String,
+}
+
+impl History {
+    fn run(&self, case_matching: CaseMatching) -> Result<()> {
+        let items = history_items(std::io::stdin().lock().lines().map_while(Result::ok));
+        complete(items, &self.query, case_matching)
+    }
+}
+
+/// Pick a file under the working directory.
+#[derive(Parser, Debug, Clone)]
+pub struct Paths {
+    /// Word under the cursor.
+    #[clap(short, long, default_value = "")]
+    query: String,
+
+    /// Working directory, the current directory by default.
+    #[clap(long, value_parser)]
+    cwd: Option<PathBuf>,
+}
+
+impl Paths {
+    fn run(&self, case_matching: CaseMatching) -> Result<()> {
+        let cwd = match self.cwd {
+            Some(ref d) => d.clone(),
+            None => std::env::current_dir()?,
+        };
+
+        let items = Source::Builtin(BuiltinSource::Files)
+            .collect_lines(&cwd)?
+            .into_iter()
+            .map(|line| Arc::new(line) as Arc<dyn ClapItem>)
+            .collect();
+
+        complete(items, &self.query, case_matching)
+    }
+}
+
+/// Print the key bindings of the shell, e.g., `eval "$(maple complete-shell init zsh)"`.
+#[derive(Parser, Debug, Clone)]
+pub struct Init {
+    /// `bash` or `zsh`.
+    #[clap(index = 1)]
+    shell: String,
+}
+
+impl Init {
+    fn run(&self) -> Result<()> {
+        let script = match self.shell.as_str() {
+            "bash" => BASH_INIT,
+            "zsh" => ZSH_INIT,
+            shell => {
+                return Err(anyhow!(
+                    "Unsupported shell {shell}, only bash and zsh are supported"
+                ))
+            }
+        };
+
+        // The widgets keep working when maple is not in $PATH.
+        let maple = std::env::current_exe()?;
+        let maple = format!("'{}'", maple.display().to_string().replace('\'', r"'\''"));
+
+        print!("{}", script.replace("{maple}", &maple));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_items() {
+        let lines = ["\t git status", "cargo test", "", "  git status", "ls"]
+            .into_iter()
+            .map(String::from);
+        let items = history_items(lines)
+            .into_iter()
+            .map(|item| item.raw_text().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(items, vec!["git status", "cargo test", "ls"]);
+    }
+}
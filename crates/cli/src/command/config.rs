@@ -0,0 +1,16 @@
+use anyhow::Result;
+use clap::Parser;
+
+/// Prints the JSON schema of config.toml.
+///
+/// The schema can be used by the editors to validate and autocomplete the config file.
+#[derive(Parser, Debug, Clone)]
+pub struct ConfigSchema;
+
+impl ConfigSchema {
+    pub fn run(&self) -> Result<()> {
+        let schema = maple_core::config::json_schema();
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        Ok(())
+    }
+}
@@ -6,10 +6,10 @@ use clap::Parser;
 use filter::{FilterContext, SequentialSource};
 use itertools::Itertools;
 use maple_core::process::ShellCommand;
-use maple_core::tools::ctags::{ProjectCtagsCommand, CTAGS_HAS_JSON_FEATURE};
+use maple_core::tooling::Tool;
+use maple_core::tools::ctags::ProjectCtagsCommand;
 use matcher::{MatchScope, MatcherBuilder};
 use rayon::prelude::*;
-use std::ops::Deref;
 use std::sync::Arc;
 use types::ClapItem;
 
@@ -65,7 +65,7 @@ impl RecursiveTags {
             ..
         }: Args,
     ) -> Result<()> {
-        if !CTAGS_HAS_JSON_FEATURE.deref() {
+        if !maple_core::tooling::has_feature(Tool::Ctags, "json") {
             return Err(anyhow::anyhow!(
                 "ctags executable is not compiled with +json feature, please recompile it."
             ));
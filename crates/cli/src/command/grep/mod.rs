@@ -5,10 +5,14 @@ use crate::app::Args;
 use anyhow::Result;
 use clap::Parser;
 use filter::{ParallelSource, SequentialSource};
+use maple_core::stdio_server::ProviderId;
 use maple_core::tools::rg::{refresh_cache, rg_shell_command};
 use matcher::MatchScope;
+use serde_json::json;
+use std::io::Write;
 use std::path::PathBuf;
 use subprocess::Exec;
+use types::Query;
 
 pub use self::forerunner::RipGrepForerunner;
 pub use self::live_grep::LiveGrep;
@@ -41,6 +45,13 @@ pub struct Grep {
 
     #[clap(long)]
     ripgrep: bool,
+
+    /// Print the matches ranked the same way as the interactive grep provider as JSON lines,
+    /// `{"text": "...", "path": "...", "line_number": 1, "column": 1, "rank": [...], "indices": [...]}`.
+    ///
+    /// The number of matches can be limited by `--number`.
+    #[clap(long)]
+    json: bool,
 }
 
 impl Grep {
@@ -55,6 +66,10 @@ impl Grep {
             return Ok(());
         }
 
+        if self.json {
+            return self.print_ranked_json(args).await;
+        }
+
         if self.ripgrep {
             let dir = match self.cmd_dir {
                 Some(ref dir) => dir.clone(),
@@ -112,6 +127,54 @@ impl Grep {
         Ok(())
     }
 
+    async fn print_ranked_json(&self, args: Args) -> Result<()> {
+        maple_core::config::initialize_config_file(args.config_file.clone());
+
+        let dir = match self.cmd_dir {
+            Some(ref dir) => dir.clone(),
+            None => std::env::current_dir()?,
+        };
+
+        let project_config = maple_core::config::load_project_config(&dir);
+        let config = project_config
+            .as_ref()
+            .unwrap_or_else(|| maple_core::config::config());
+
+        let matcher = ProviderId::from("grep")
+            .matcher_builder()
+            .rank_criteria(config.matcher.rank_criteria())
+            .case_matching(args.case_matching)
+            .match_scope(MatchScope::Full)
+            .build(Query::from(&self.grep_query));
+
+        let matches = maple_core::searcher::grep::headless_search(
+            vec![dir.clone()],
+            matcher,
+            config.walker.walk_config(Some("grep")),
+            &config.grep,
+            args.number,
+        )
+        .await;
+
+        let mut stdout = std::io::stdout().lock();
+        for grep_result in matches
+            .iter()
+            .filter_map(|file_result| file_result.to_grep_result(&dir))
+        {
+            let line = json!({
+                "text": grep_result.matched_item.item.raw_text(),
+                "path": grep_result.path,
+                "line_number": grep_result.line_number,
+                "column": grep_result.column,
+                "rank": grep_result.matched_item.rank,
+                "indices": grep_result.matched_item.indices,
+            });
+            writeln!(stdout, "{line}")?;
+        }
+
+        Ok(())
+    }
+
     fn usable_cache(&self, args: &Args) -> Option<PathBuf> {
         if !args.no_cache {
             if let Some(digest) = self
@@ -0,0 +1,65 @@
+use crate::app::Args;
+use anyhow::Result;
+use clap::Parser;
+use maple_core::tooling::Tool;
+use maple_core::tools::ctags::ProjectCtagsCommand;
+use std::path::PathBuf;
+
+/// Build the file index of a project ahead of time, e.g., in the project setup scripts or after
+/// a large checkout, so that the first use in the editor is instant.
+#[derive(Parser, Debug, Clone)]
+pub struct Index {
+    /// Root of the project, the current directory by default.
+    #[clap(long, value_parser)]
+    dir: Option<PathBuf>,
+
+    /// Build the project tags cache as well.
+    #[clap(long)]
+    tags: bool,
+}
+
+impl Index {
+    pub fn run(&self, args: Args) -> Result<()> {
+        maple_core::config::initialize_config_file(args.config_file);
+
+        let current_dir = std::env::current_dir()?;
+        let root = match self.dir {
+            Some(ref dir) => current_dir.join(dir),
+            None => current_dir,
+        };
+        // The index is keyed by the root, canonicalize it the same way as the cwd sent by the
+        // editor.
+        let root = maple_core::paths::canonicalize_path(&root)?;
+
+        let config = maple_core::config::config();
+        if !config.indexer.enable {
+            println!("Note: the index is only used when `indexer.enable` is on");
+        }
+
+        let total = maple_core::indexer::build_project_index(
+            &root,
+            config.walker.walk_config(Some("indexer")),
+        )?;
+        println!("Indexed {total} files in {}", root.display());
+
+        if self.tags {
+            if maple_core::tooling::has_feature(Tool::Ctags, "json") {
+                let mut ctags_cmd = ProjectCtagsCommand::with_cwd(root);
+                let up_to_date = ctags_cmd.ctags_cache().is_some()
+                    && ctags_cmd
+                        .stale_files()
+                        .map_or(false, |stale_files| stale_files.is_empty());
+                if up_to_date {
+                    println!("The tags cache is up to date");
+                } else {
+                    let (total, _) = ctags_cmd.par_create_cache()?;
+                    println!("Created {total} tags");
+                }
+            } else {
+                println!("Skipped the tags, Universal Ctags with the json feature is required");
+            }
+        }
+
+        Ok(())
+    }
+}
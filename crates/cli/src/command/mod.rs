@@ -1,5 +1,9 @@
+pub mod benchmark;
 pub mod blines;
 pub mod cache;
+pub mod check;
+pub mod complete_shell;
+pub mod config;
 pub mod ctags;
 pub mod dumb_jump;
 pub mod exec;
@@ -7,4 +11,10 @@ pub mod filter;
 pub mod grep;
 pub mod gtags;
 pub mod helptags;
+pub mod index;
+pub mod pick;
+pub mod picker_daemon;
 pub mod rpc;
+pub mod run;
+pub mod serve;
+pub mod tags;
@@ -0,0 +1,466 @@
+use crate::app::Args;
+use anyhow::Result;
+use clap::Parser;
+use matcher::MatcherBuilder;
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+use types::{CaseMatching, ClapItem, MatchedItem, Query};
+
+/// Exit code when no item is matched, same with fzf.
+pub(crate) const EXIT_NO_MATCH: i32 = 1;
+/// Exit code when the picker is aborted, same with fzf.
+pub(crate) const EXIT_INTERRUPTED: i32 = 130;
+
+/// Pick an item from the lines of stdin interactively, a drop-in replacement of fzf in scripts.
+#[derive(Parser, Debug, Clone)]
+pub struct Pick {
+    /// Initial query.
+    #[clap(short, long, default_value = "")]
+    query: String,
+
+    /// Print the items matching the query without the interactive UI.
+    #[clap(short, long)]
+    filter: Option<String>,
+
+    /// Shell command to preview the current item, `{}` is replaced with the quoted item.
+    #[clap(long)]
+    preview: Option<String>,
+
+    /// Input prompt.
+    #[clap(long, default_value = "> ")]
+    prompt: String,
+
+    /// Print the only match without starting the interactive UI.
+    #[clap(short = '1', long)]
+    select_1: bool,
+
+    /// Exit immediately when there is no match.
+    #[clap(short = '0', long)]
+    exit_0: bool,
+}
+
+pub(crate) fn filter_items(
+    items: &[Arc<dyn ClapItem>],
+    query: &str,
+    case_matching: CaseMatching,
+) -> Vec<MatchedItem> {
+    if query.is_empty() {
+        return items
+            .iter()
+            .map(|item| MatchedItem::new(item.clone(), Default::default(), Vec::new()))
+            .collect();
+    }
+    let matcher = MatcherBuilder::new()
+        .case_matching(case_matching)
+        .build(Query::from(query));
+    filter::par_filter_items(items, &matcher)
+}
+
+/// Returns the output of the preview command for `item`.
+fn preview_item(preview_cmd: &str, item: &str) -> Vec<String> {
+    let quoted = format!("'{}'", item.replace('\'', r"'\''"));
+    match maple_core::process::shell_command(&preview_cmd.replace("{}", &quoted)).output() {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(Into::into)
+            .collect(),
+        Err(err) => vec![format!("Failed to run the preview command: {err}")],
+    }
+}
+
+impl Pick {
+    pub fn run(&self, args: Args) -> Result<()> {
+        let items = std::io::stdin()
+            .lock()
+            .lines()
+            .map_while(Result::ok)
+            .map(|line| Arc::new(line) as Arc<dyn ClapItem>)
+            .collect::<Vec<_>>();
+
+        let mut stdout = std::io::stdout().lock();
+
+        if let Some(query) = &self.filter {
+            let matched_items = filter_items(&items, query, args.case_matching);
+            if matched_items.is_empty() {
+                std::process::exit(EXIT_NO_MATCH);
+            }
+            for matched_item in matched_items {
+                writeln!(stdout, "{}", matched_item.item.raw_text())?;
+            }
+            return Ok(());
+        }
+
+        let matched_items = filter_items(&items, &self.query, args.case_matching);
+        if matched_items.is_empty() && self.exit_0 {
+            std::process::exit(EXIT_NO_MATCH);
+        }
+        if matched_items.len() == 1 && self.select_1 {
+            writeln!(stdout, "{}", matched_items[0].item.raw_text())?;
+            return Ok(());
+        }
+
+        match self.run_interactive(items, args.case_matching)? {
+            Some(selected) => writeln!(stdout, "{selected}")?,
+            None => std::process::exit(EXIT_INTERRUPTED),
+        }
+
+        Ok(())
+    }
+
+    fn run_interactive(
+        &self,
+        items: Vec<Arc<dyn ClapItem>>,
+        case_matching: CaseMatching,
+    ) -> Result<Option<String>> {
+        run_picker(
+            items,
+            case_matching,
+            self.query.clone(),
+            &self.prompt,
+            self.preview.as_deref(),
+        )
+    }
+}
+
+/// Runs the interactive picker on the tty, returns `None` if the picker is aborted.
+#[cfg(unix)]
+pub(crate) fn run_picker(
+    items: Vec<Arc<dyn ClapItem>>,
+    case_matching: CaseMatching,
+    query: String,
+    prompt: &str,
+    preview_cmd: Option<&str>,
+) -> Result<Option<String>> {
+    tui::Picker {
+        items,
+        case_matching,
+        query,
+        prompt,
+        preview_cmd,
+    }
+    .run()
+}
+
+#[cfg(not(unix))]
+pub(crate) fn run_picker(
+    _items: Vec<Arc<dyn ClapItem>>,
+    _case_matching: CaseMatching,
+    _query: String,
+    _prompt: &str,
+    _preview_cmd: Option<&str>,
+) -> Result<Option<String>> {
+    Err(anyhow::anyhow!(
+        "The interactive picker is only supported on unix, use --filter instead"
+    ))
+}
+
+#[cfg(unix)]
+mod tui {
+    use super::{filter_items, preview_item};
+    use anyhow::Result;
+    use printer::Printer;
+    use std::io::Write;
+    use std::os::unix::io::AsRawFd;
+    use std::sync::mpsc::{channel, Sender};
+    use std::sync::Arc;
+    use termion::event::Key;
+    use termion::input::TermRead;
+    use termion::raw::IntoRawMode;
+    use termion::screen::AlternateScreen;
+    use termion::{clear, color, cursor, style};
+    use types::{CaseMatching, ClapItem, MatchedItem};
+
+    /// Returns the `(width, height)` of the terminal, stdout can not be used as it's usually
+    /// redirected in the scripts.
+    fn terminal_size(tty: &std::fs::File) -> (usize, usize) {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        if unsafe { libc::ioctl(tty.as_raw_fd(), libc::TIOCGWINSZ, &mut size) } == 0 {
+            (size.ws_col as usize, size.ws_row as usize)
+        } else {
+            (80, 24)
+        }
+    }
+
+    /// Events of the picker loop, the filtering and the preview run in the worker threads so
+    /// that typing is never blocked by them.
+    enum Event {
+        Key(std::io::Result<Key>),
+        /// No more keys can be read from the tty.
+        KeysClosed,
+        /// Items matching the query.
+        Filtered(String, Vec<MatchedItem>),
+        /// Preview lines of the item.
+        Previewed(String, Vec<String>),
+    }
+
+    /// Spawns a worker handling the requests sent to the returned sender, only the latest
+    /// request is handled if several are pending.
+    fn spawn_worker<T: Send + 'static>(
+        events: Sender<Event>,
+        handle: impl Fn(T) -> Event + Send + 'static,
+    ) -> Sender<T> {
+        let (sender, receiver) = channel::<T>();
+        std::thread::spawn(move || {
+            while let Ok(mut request) = receiver.recv() {
+                while let Ok(newer_request) = receiver.try_recv() {
+                    request = newer_request;
+                }
+                if events.send(handle(request)).is_err() {
+                    break;
+                }
+            }
+        });
+        sender
+    }
+
+    pub struct Picker<'a> {
+        pub items: Vec<Arc<dyn ClapItem>>,
+        pub case_matching: CaseMatching,
+        pub query: String,
+        pub prompt: &'a str,
+        pub preview_cmd: Option<&'a str>,
+    }
+
+    /// Renders the line with the matched positions highlighted.
+    fn highlight_line(line: &str, indices: &[usize]) -> String {
+        let mut output = String::with_capacity(line.len());
+        for (idx, ch) in line.char_indices() {
+            if indices.contains(&idx) {
+                output.push_str(&format!(
+                    "{}{ch}{}",
+                    color::Fg(color::Green),
+                    color::Fg(color::Reset)
+                ));
+            } else {
+                output.push(ch);
+            }
+        }
+        output
+    }
+
+    impl Picker<'_> {
+        /// Runs the picker until an item is selected or the picker is aborted.
+        pub fn run(mut self) -> Result<Option<String>> {
+            let tty = termion::get_tty()?;
+            let keys = tty.try_clone()?.keys();
+            let mut screen = AlternateScreen::from(tty.try_clone()?.into_raw_mode()?);
+
+            let (events, event_receiver) = channel();
+
+            std::thread::spawn({
+                let events = events.clone();
+                move || {
+                    for key in keys {
+                        if events.send(Event::Key(key)).is_err() {
+                            return;
+                        }
+                    }
+                    let _ = events.send(Event::KeysClosed);
+                }
+            });
+
+            let items = Arc::new(std::mem::take(&mut self.items));
+            let total = items.len();
+            let case_matching = self.case_matching;
+            let filter_sender = spawn_worker(events.clone(), move |query: String| {
+                let matched_items = filter_items(&items, &query, case_matching);
+                Event::Filtered(query, matched_items)
+            });
+            let preview_sender = self.preview_cmd.map(|preview_cmd| {
+                let preview_cmd = preview_cmd.to_string();
+                spawn_worker(events, move |item: String| {
+                    let lines = preview_item(&preview_cmd, &item);
+                    Event::Previewed(item, lines)
+                })
+            });
+
+            filter_sender.send(self.query.clone())?;
+
+            let mut matched_items: Vec<MatchedItem> = Vec::new();
+            let mut selected = 0;
+            let mut preview: Option<(String, Vec<String>)> = None;
+            let mut preview_requested: Option<String> = None;
+
+            loop {
+                let (width, height) = terminal_size(&tty);
+
+                let current = matched_items
+                    .get(selected)
+                    .map(|matched_item| matched_item.item.raw_text().to_string());
+
+                if let (Some(preview_sender), Some(current)) = (&preview_sender, &current) {
+                    if preview_requested.as_ref() != Some(current) {
+                        preview_sender.send(current.clone())?;
+                        preview_requested.replace(current.clone());
+                    }
+                }
+
+                // The preview of the previous item is not shown while the current one is loading.
+                let preview_lines: &[String] = match (&preview, &current) {
+                    (Some((item, lines)), Some(current)) if item == current => lines.as_slice(),
+                    _ => &[],
+                };
+
+                self.render(
+                    &mut screen,
+                    &matched_items,
+                    total,
+                    selected,
+                    preview_lines,
+                    (width, height),
+                )?;
+
+                let key = match event_receiver.recv() {
+                    Ok(Event::Key(key)) => key,
+                    Ok(Event::Filtered(query, new_matched_items)) => {
+                        // Skip the outdated results.
+                        if query == self.query {
+                            matched_items = new_matched_items;
+                            selected = 0;
+                        }
+                        continue;
+                    }
+                    Ok(Event::Previewed(item, lines)) => {
+                        preview.replace((item, lines));
+                        continue;
+                    }
+                    Ok(Event::KeysClosed) | Err(_) => return Ok(None),
+                };
+
+                match key? {
+                    Key::Char('\n') => return Ok(current),
+                    Key::Esc | Key::Ctrl('c') | Key::Ctrl('g') => return Ok(None),
+                    Key::Up | Key::Ctrl('k') | Key::Ctrl('p') => {
+                        selected = selected.saturating_sub(1);
+                    }
+                    Key::Down | Key::Ctrl('j') | Key::Ctrl('n') => {
+                        if selected + 1 < matched_items.len() {
+                            selected += 1;
+                        }
+                    }
+                    key => {
+                        match key {
+                            Key::Char(c) => self.query.push(c),
+                            Key::Backspace | Key::Ctrl('h') => {
+                                self.query.pop();
+                            }
+                            Key::Ctrl('u') => self.query.clear(),
+                            _ => continue,
+                        }
+                        filter_sender.send(self.query.clone())?;
+                    }
+                }
+            }
+        }
+
+        fn render(
+            &self,
+            screen: &mut impl Write,
+            matched_items: &[MatchedItem],
+            total: usize,
+            selected: usize,
+            preview_lines: &[String],
+            (width, height): (usize, usize),
+        ) -> Result<()> {
+            // Prompt line and status line.
+            let list_height = if self.preview_cmd.is_some() {
+                height.saturating_sub(3) / 2
+            } else {
+                height.saturating_sub(2)
+            };
+
+            // Keep the selected item in view.
+            let offset = (selected + 1).saturating_sub(list_height);
+            let visible_items = matched_items
+                .iter()
+                .skip(offset)
+                .take(list_height)
+                .cloned()
+                .collect::<Vec<_>>();
+            let display_lines = Printer::new(width.saturating_sub(2), icon::Icon::Null)
+                .to_display_lines(visible_items);
+
+            write!(
+                screen,
+                "{}{}{}{}",
+                clear::All,
+                cursor::Goto(1, 2),
+                style::Faint,
+                format_args!("  {}/{total}", matched_items.len())
+            )?;
+            write!(screen, "{}", style::Reset)?;
+
+            for (idx, (line, indices)) in display_lines
+                .lines
+                .iter()
+                .zip(display_lines.indices.iter())
+                .enumerate()
+            {
+                write!(screen, "{}", cursor::Goto(1, idx as u16 + 3))?;
+                if idx + offset == selected {
+                    write!(
+                        screen,
+                        "{}> {}{}",
+                        style::Bold,
+                        highlight_line(line, indices),
+                        style::Reset
+                    )?;
+                } else {
+                    write!(screen, "  {}", highlight_line(line, indices))?;
+                }
+            }
+
+            if self.preview_cmd.is_some() {
+                let separator_row = list_height + 3;
+                write!(
+                    screen,
+                    "{}{}",
+                    cursor::Goto(1, separator_row as u16),
+                    "─".repeat(width)
+                )?;
+                for (idx, line) in preview_lines
+                    .iter()
+                    .take(height.saturating_sub(separator_row))
+                    .enumerate()
+                {
+                    let line = line.chars().take(width).collect::<String>();
+                    write!(
+                        screen,
+                        "{}{line}",
+                        cursor::Goto(1, (separator_row + idx + 1) as u16)
+                    )?;
+                }
+            }
+
+            write!(
+                screen,
+                "{}{}{}",
+                cursor::Goto(1, 1),
+                self.prompt,
+                self.query
+            )?;
+            screen.flush()?;
+
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_items() {
+        let items = ["src/main.rs", "src/lib.rs", "README.md"]
+            .into_iter()
+            .map(|line| Arc::new(line.to_string()) as Arc<dyn ClapItem>)
+            .collect::<Vec<_>>();
+
+        assert_eq!(filter_items(&items, "", CaseMatching::Smart).len(), 3);
+
+        let matched_items = filter_items(&items, "lib", CaseMatching::Smart);
+        assert_eq!(matched_items.len(), 1);
+        assert_eq!(matched_items[0].item.raw_text(), "src/lib.rs");
+    }
+}
@@ -0,0 +1,21 @@
+use crate::app::Args;
+use anyhow::Result;
+use clap::Parser;
+
+/// Starts the picker daemon for the external tools.
+#[derive(Parser, Debug, Clone)]
+pub struct PickerDaemon {
+    /// Path of the unix domain socket or `host:port` of the TCP socket to listen on.
+    ///
+    /// The TCP socket must be on the loopback interface, the clients must send the token in
+    /// `daemon-<port>.token` under the cache directory as the first line.
+    #[clap(long)]
+    listen: String,
+}
+
+impl PickerDaemon {
+    pub fn run(&self, args: Args) -> Result<()> {
+        maple_core::config::initialize_config_file(args.config_file);
+        maple_core::picker_daemon::run(&self.listen)
+    }
+}
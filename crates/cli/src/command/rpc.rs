@@ -3,65 +3,120 @@ use std::io::IsTerminal;
 use crate::app::Args;
 use anyhow::{anyhow, Result};
 use clap::Parser;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::reload;
 
 /// Starts a RPC service using stdio.
 #[derive(Parser, Debug, Clone)]
-pub struct Rpc;
+pub struct Rpc {
+    /// Translate the paths from the editor under EDITOR_PATH to MAPLE_PATH, e.g., when maple
+    /// runs on the remote host of the project mounted locally.
+    #[clap(long = "path-map", value_name = "EDITOR_PATH=MAPLE_PATH")]
+    path_maps: Vec<maple_core::paths::PathMap>,
+}
+
+/// Parses the log filter directives, e.g., `debug,maple_core::searcher=trace`.
+fn parse_log_filter(directives: &str) -> Result<Targets> {
+    directives
+        .parse()
+        .map_err(|err| anyhow!("invalid log filter {directives:?}: {err}"))
+}
+
+/// Sets up the logging of the server if any log file is specified, the returned guard must be
+/// held until the server exits.
+pub(crate) fn setup_logging(
+    log: Option<std::path::PathBuf>,
+) -> Result<Option<tracing_appender::non_blocking::WorkerGuard>> {
+    let config = maple_core::config::config();
+
+    let maybe_log = if let Some(log_path) = log {
+        Some(log_path)
+    } else if let Ok(log_path) = std::env::var("VIM_CLAP_LOG_PATH").map(std::path::PathBuf::from) {
+        Some(log_path)
+    } else {
+        config
+            .log
+            .log_file
+            .as_deref()
+            .map(maple_core::config::expand_path)
+            .or_else(|| maple_core::dirs::log_dir().map(|dir| dir.join("vimclap.log")))
+    };
+
+    let Some(log_path) = maybe_log else {
+        return Ok(None);
+    };
+
+    if let Ok(metadata) = std::fs::metadata(&log_path) {
+        if log_path.is_file() && metadata.len() > 8 * 1024 * 1024 {
+            std::fs::remove_file(&log_path)?;
+        }
+    }
+
+    let file_name = log_path
+        .file_name()
+        .ok_or_else(|| anyhow!("no file name in {log_path:?}"))?;
+
+    let directory = log_path
+        .parent()
+        .ok_or_else(|| anyhow!("{log_path:?} has no parent"))?;
+    std::fs::create_dir_all(directory)?;
+
+    let file_appender = tracing_appender::rolling::never(directory, file_name);
+    let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
+
+    let max_level = config
+        .log
+        .max_level
+        .parse()
+        .unwrap_or(tracing::Level::DEBUG);
+
+    // The filter can be adjusted at runtime via `system/set-log-level`.
+    let (filter, reload_handle) = reload::Layer::new(Targets::new().with_default(max_level));
+
+    let subscriber = tracing_subscriber::registry().with(filter).with(
+        tracing_subscriber::fmt::layer()
+            .with_line_number(true)
+            .with_writer(non_blocking)
+            .with_ansi(std::io::stdout().is_terminal()),
+    );
+
+    tracing::subscriber::set_global_default(subscriber)?;
+
+    maple_core::log_filter::set_reloader(max_level.to_string().to_lowercase(), move |directives| {
+        let targets = parse_log_filter(directives)?;
+        reload_handle.reload(targets)?;
+        Ok(())
+    });
+
+    Ok(Some(guard))
+}
 
 impl Rpc {
     pub async fn run(&self, args: Args) -> Result<()> {
         maple_core::config::initialize_config_file(args.config_file.clone());
+        maple_core::paths::set_path_maps(self.path_maps.clone());
 
-        let config = maple_core::config::config();
-
-        let maybe_log = if let Some(log_path) = args.log {
-            Some(log_path)
-        } else if let Ok(log_path) =
-            std::env::var("VIM_CLAP_LOG_PATH").map(std::path::PathBuf::from)
-        {
-            Some(log_path)
-        } else {
-            config.log.log_file.as_ref().map(std::path::PathBuf::from)
-        };
-
-        if let Some(log_path) = maybe_log {
-            if let Ok(metadata) = std::fs::metadata(&log_path) {
-                if log_path.is_file() && metadata.len() > 8 * 1024 * 1024 {
-                    std::fs::remove_file(&log_path)?;
-                }
-            }
-
-            let file_name = log_path
-                .file_name()
-                .ok_or_else(|| anyhow!("no file name in {log_path:?}"))?;
-
-            let directory = log_path
-                .parent()
-                .ok_or_else(|| anyhow!("{log_path:?} has no parent"))?;
-
-            let file_appender = tracing_appender::rolling::never(directory, file_name);
-            let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
-
-            let max_level = config
-                .log
-                .max_level
-                .parse()
-                .unwrap_or(tracing::Level::DEBUG);
-
-            let subscriber = tracing_subscriber::FmtSubscriber::builder()
-                .with_max_level(max_level)
-                .with_line_number(true)
-                .with_writer(non_blocking)
-                .with_ansi(std::io::stdout().is_terminal())
-                .finish();
-
-            tracing::subscriber::set_global_default(subscriber)?;
-
-            maple_core::stdio_server::start().await;
-        } else {
-            maple_core::stdio_server::start().await;
-        }
+        let _guard = setup_logging(args.log)?;
+
+        maple_core::stdio_server::start().await;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::Level;
+
+    #[test]
+    fn test_parse_log_filter() {
+        let targets = parse_log_filter("info,maple_core::searcher=trace").unwrap();
+        assert!(targets.would_enable("maple_core::searcher::grep", &Level::TRACE));
+        assert!(!targets.would_enable("maple_core::stdio_server", &Level::DEBUG));
+        assert!(targets.would_enable("maple_core::stdio_server", &Level::INFO));
+
+        assert!(parse_log_filter("maple_core=loud").is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use crate::app::Args;
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use maple_core::source_service::{BuiltinSource, Source};
+use maple_core::stdio_server::ProviderId;
+use serde_json::json;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use types::{CaseMatching, ClapItem, MatchedItem, RankCriterion};
+
+/// Run a provider non-interactively and print the ranked results.
+///
+/// The results are ranked the same way as the interactive picker.
+#[derive(Parser, Debug, Clone)]
+pub struct Run {
+    /// Id of the provider, `files`, `git_files`, `recent_files` or `grep`.
+    #[clap(index = 1)]
+    provider: String,
+
+    /// Query applied to the source items of the provider.
+    #[clap(short, long, default_value = "")]
+    query: String,
+
+    /// Print the results as JSON lines, `{"text": "...", "rank": [...], "indices": [...]}`.
+    #[clap(long)]
+    json: bool,
+
+    /// Working directory of the provider, the current directory by default.
+    #[clap(long)]
+    cwd: Option<PathBuf>,
+}
+
+/// Returns the items matching `query` in the order of the interactive picker.
+fn rank_items(
+    provider_id: &ProviderId,
+    items: &[Arc<dyn ClapItem>],
+    query: &str,
+    rank_criteria: Vec<RankCriterion>,
+    case_matching: CaseMatching,
+) -> Vec<MatchedItem> {
+    if query.is_empty() {
+        return items.iter().cloned().map(MatchedItem::from).collect();
+    }
+    let matcher = provider_id
+        .matcher_builder()
+        .rank_criteria(rank_criteria)
+        .case_matching(case_matching)
+        .build(query.into());
+    filter::par_filter_items(items, &matcher)
+}
+
+impl Run {
+    pub fn run(&self, args: Args) -> Result<()> {
+        maple_core::config::initialize_config_file(args.config_file.clone());
+
+        let builtin_source = BuiltinSource::from_provider_id(&self.provider)
+            .ok_or_else(|| anyhow!("Provider {} can not be run headless", self.provider))?;
+
+        let cwd = match &self.cwd {
+            Some(cwd) => cwd.clone(),
+            None => std::env::current_dir()?,
+        };
+
+        let items = Source::Builtin(builtin_source)
+            .collect_lines(&cwd)?
+            .into_iter()
+            .map(|line| Arc::new(line) as Arc<dyn ClapItem>)
+            .collect::<Vec<_>>();
+
+        let provider_id = ProviderId::from(&self.provider);
+        let mut matched_items = rank_items(
+            &provider_id,
+            &items,
+            &self.query,
+            maple_core::config::config().matcher.rank_criteria(),
+            args.case_matching,
+        );
+        if let Some(number) = args.number {
+            matched_items.truncate(number);
+        }
+
+        let mut stdout = std::io::stdout().lock();
+        for matched_item in matched_items {
+            if self.json {
+                let line = json!({
+                    "text": matched_item.item.raw_text(),
+                    "rank": matched_item.rank,
+                    "indices": matched_item.indices,
+                });
+                writeln!(stdout, "{line}")?;
+            } else {
+                writeln!(stdout, "{}", matched_item.item.raw_text())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_items() {
+        let items = ["src/main.rs", "crates/lib.rs", "lib/mod.rs"]
+            .into_iter()
+            .map(|line| Arc::new(line.to_string()) as Arc<dyn ClapItem>)
+            .collect::<Vec<_>>();
+        let provider_id = ProviderId::from("files");
+        let rank = |query| {
+            rank_items(
+                &provider_id,
+                &items,
+                query,
+                vec![RankCriterion::Score],
+                CaseMatching::Smart,
+            )
+        };
+
+        assert_eq!(rank("").len(), 3);
+
+        let matched_items = rank("lib");
+        assert_eq!(matched_items.len(), 2);
+        // The match in the file name is preferred by the files provider.
+        assert_eq!(matched_items[0].item.raw_text(), "crates/lib.rs");
+    }
+}
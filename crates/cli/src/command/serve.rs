@@ -0,0 +1,259 @@
+use crate::app::Args;
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use maple_core::ipc::Address;
+use maple_core::stdio_server::{DaemonHello, DaemonReply};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::Shutdown;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Serves the editors with one maple process shared by all of them.
+///
+/// Without `--daemon`, the stdio is relayed to the daemon, which is started on demand, so that
+/// the editor can use it the same way as `maple rpc`. The editor is served by the relay process
+/// itself if the daemon runs with another config file or environment.
+#[derive(Parser, Debug, Clone)]
+pub struct Serve {
+    /// Run the shared daemon in the foreground.
+    #[clap(long)]
+    daemon: bool,
+
+    /// Path of the unix domain socket or `host:port` of the TCP socket of the daemon.
+    ///
+    /// By default, `maple.sock` in the cache directory on unix and `127.0.0.1:43117` on Windows.
+    /// The TCP socket must be on the loopback interface, the clients are authenticated with the
+    /// token in `daemon-<port>.token` under the cache directory.
+    #[clap(long)]
+    listen: Option<String>,
+
+    /// Exit the daemon once it has had no client for this long, e.g. `15m`.
+    #[clap(long, default_value = "15m")]
+    idle_timeout: maple_core::config::HumanDuration,
+}
+
+/// How long to wait for the daemon started on demand to be ready.
+const DAEMON_STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn default_address() -> String {
+    if cfg!(unix) {
+        maple_core::dirs::clap_cache_dir()
+            .unwrap_or_else(|_| maple_core::dirs::cache_dir().to_path_buf())
+            .join("maple.sock")
+            .display()
+            .to_string()
+    } else {
+        "127.0.0.1:43117".to_string()
+    }
+}
+
+struct Connection {
+    reader: BufReader<Box<dyn Read + Send>>,
+    writer: Box<dyn Write + Send>,
+    /// Signals the end of the input to the daemon.
+    shutdown_write: Box<dyn FnOnce() + Send>,
+}
+
+fn connect(address: &str) -> std::io::Result<Connection> {
+    match Address::parse(address)? {
+        #[cfg(unix)]
+        Address::Unix(path) => {
+            let stream = std::os::unix::net::UnixStream::connect(path)?;
+            let writer = stream.try_clone()?;
+            let closer = stream.try_clone()?;
+            Ok(Connection {
+                reader: BufReader::new(Box::new(stream)),
+                writer: Box::new(writer),
+                shutdown_write: Box::new(move || {
+                    let _ = closer.shutdown(Shutdown::Write);
+                }),
+            })
+        }
+        Address::Tcp(socket_addr) => {
+            let stream = std::net::TcpStream::connect(socket_addr)?;
+            let mut writer = stream.try_clone()?;
+            let closer = stream.try_clone()?;
+            // The daemon creates the token before it starts listening.
+            let token = maple_core::ipc::read_token(&socket_addr)?;
+            writeln!(writer, "{token}")?;
+            Ok(Connection {
+                reader: BufReader::new(Box::new(stream)),
+                writer: Box::new(writer),
+                shutdown_write: Box::new(move || {
+                    let _ = closer.shutdown(Shutdown::Write);
+                }),
+            })
+        }
+    }
+}
+
+/// Starts the daemon in the background, detached from the editor.
+fn spawn_daemon(
+    address: &str,
+    config_file: Option<PathBuf>,
+    idle_timeout: Duration,
+) -> std::io::Result<()> {
+    let mut cmd = Command::new(std::env::current_exe()?);
+    if let Some(config_file) = config_file {
+        cmd.arg("--config-file").arg(config_file);
+    }
+    cmd.args(["serve", "--daemon", "--listen", address])
+        .arg("--idle-timeout")
+        .arg(format!("{}s", idle_timeout.as_secs()))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    // Otherwise the daemon is killed along with the editor which starts it.
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+
+    cmd.spawn()?;
+
+    Ok(())
+}
+
+fn connect_or_spawn_daemon(
+    address: &str,
+    config_file: Option<PathBuf>,
+    idle_timeout: Duration,
+) -> Result<Connection> {
+    if let Ok(connection) = connect(address) {
+        return Ok(connection);
+    }
+
+    spawn_daemon(address, config_file, idle_timeout)?;
+
+    let started = Instant::now();
+    loop {
+        std::thread::sleep(Duration::from_millis(50));
+        match connect(address) {
+            Ok(connection) => return Ok(connection),
+            Err(err) if started.elapsed() > DAEMON_STARTUP_TIMEOUT => {
+                return Err(anyhow!(
+                    "Failed to connect to the daemon at {address}: {err}"
+                ));
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+/// Sends the hello of this process to the daemon, returns the reason if the daemon refuses to
+/// serve it.
+fn say_hello(connection: &mut Connection, hello: &DaemonHello) -> Result<Option<String>> {
+    serde_json::to_writer(&mut connection.writer, hello)?;
+    connection.writer.write_all(b"\n")?;
+    connection.writer.flush()?;
+
+    let mut line = String::new();
+    connection.reader.read_line(&mut line)?;
+    let reply: DaemonReply = serde_json::from_str(&line)
+        .map_err(|err| anyhow!("Invalid reply from the daemon {line:?}: {err}"))?;
+
+    Ok(reply.error)
+}
+
+/// Relays `input` to the daemon and the daemon output to `output` until either side is closed.
+fn relay(
+    connection: Connection,
+    mut input: impl Read + Send + 'static,
+    mut output: impl Write,
+) -> Result<()> {
+    let Connection {
+        mut reader,
+        mut writer,
+        shutdown_write,
+    } = connection;
+
+    std::thread::spawn(move || {
+        let _ = std::io::copy(&mut input, &mut writer);
+        shutdown_write();
+    });
+
+    let mut buffer = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            return Ok(());
+        }
+        // The messages must reach the editor as soon as they arrive.
+        output.write_all(&buffer[..n])?;
+        output.flush()?;
+    }
+}
+
+impl Serve {
+    pub async fn run(&self, args: Args) -> Result<()> {
+        maple_core::config::initialize_config_file(args.config_file.clone());
+
+        let address = self.listen.clone().unwrap_or_else(default_address);
+        let idle_timeout = self.idle_timeout.0;
+
+        if self.daemon {
+            let _guard = super::rpc::setup_logging(args.log)?;
+            maple_core::stdio_server::start_daemon(&address, idle_timeout).await?;
+            return Ok(());
+        }
+
+        let hello = DaemonHello::current()?;
+        let config_file = args.config_file;
+        let refused = tokio::task::spawn_blocking(move || {
+            let mut connection = connect_or_spawn_daemon(&address, config_file, idle_timeout)?;
+            match say_hello(&mut connection, &hello)? {
+                Some(reason) => Ok::<_, anyhow::Error>(Some(reason)),
+                None => {
+                    relay(connection, std::io::stdin(), std::io::stdout().lock())?;
+                    Ok(None)
+                }
+            }
+        })
+        .await??;
+
+        // Serve the editor by this process instead, the same as `maple rpc`.
+        if let Some(reason) = refused {
+            let _guard = super::rpc::setup_logging(args.log)?;
+            tracing::debug!(%reason, "Refused by the shared daemon, serving the editor in-process");
+            maple_core::stdio_server::start().await;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixStream;
+
+    #[test]
+    fn test_relay() {
+        let (client, mut daemon) = UnixStream::pair().unwrap();
+
+        // Echo the input back as the daemon does with the responses.
+        let echo = std::thread::spawn(move || {
+            let mut received = Vec::new();
+            daemon.read_to_end(&mut received).unwrap();
+            daemon.write_all(&received).unwrap();
+            received
+        });
+
+        let closer = client.try_clone().unwrap();
+        let connection = Connection {
+            reader: BufReader::new(Box::new(client.try_clone().unwrap())),
+            writer: Box::new(client),
+            shutdown_write: Box::new(move || {
+                let _ = closer.shutdown(Shutdown::Write);
+            }),
+        };
+
+        let input = b"{\"id\":1,\"method\":\"foo\"}\n".to_vec();
+        let mut output = Vec::new();
+        relay(connection, std::io::Cursor::new(input.clone()), &mut output).unwrap();
+
+        // The end of the input is passed on so that the daemon side is closed too.
+        assert_eq!(echo.join().unwrap(), input);
+        assert_eq!(output, input);
+    }
+}
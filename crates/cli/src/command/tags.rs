@@ -0,0 +1,184 @@
+use crate::app::Args;
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use maple_core::tooling::Tool;
+use maple_core::tools::ctags::ProjectCtagsCommand;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+#[derive(Parser, Debug, Clone)]
+pub struct ProjectArgs {
+    /// Root of the project, the current directory by default.
+    #[clap(long, value_parser)]
+    dir: Option<PathBuf>,
+}
+
+impl ProjectArgs {
+    fn ctags_cmd(&self) -> Result<ProjectCtagsCommand> {
+        let dir = match self.dir {
+            Some(ref d) => d.clone(),
+            None => std::env::current_dir()?,
+        };
+        Ok(ProjectCtagsCommand::with_cwd(dir))
+    }
+}
+
+/// Generate, update and inspect the project tags cache used by `proj_tags`.
+#[derive(Subcommand, Debug, Clone)]
+pub enum Tags {
+    Generate(Generate),
+    Update(Update),
+    Stats(Stats),
+    Stale(Stale),
+}
+
+impl Tags {
+    pub fn run(&self, args: Args) -> Result<()> {
+        maple_core::config::initialize_config_file(args.config_file);
+
+        if !maple_core::tooling::has_feature(Tool::Ctags, "json") {
+            return Err(anyhow!(
+                "Universal Ctags with the json feature is required for the project tags"
+            ));
+        }
+
+        match self {
+            Self::Generate(generate) => generate.run(),
+            Self::Update(update) => update.run(),
+            Self::Stats(stats) => stats.run(),
+            Self::Stale(stale) => stale.run(),
+        }
+    }
+}
+
+fn create_cache(mut ctags_cmd: ProjectCtagsCommand) -> Result<()> {
+    let (total, cache_path) = ctags_cmd.par_create_cache()?;
+    println!("Created {total} tags in {}", cache_path.display());
+    Ok(())
+}
+
+/// Create the tags cache, replacing the existing one if any.
+#[derive(Parser, Debug, Clone)]
+pub struct Generate {
+    #[clap(flatten)]
+    project: ProjectArgs,
+}
+
+impl Generate {
+    fn run(&self) -> Result<()> {
+        create_cache(self.project.ctags_cmd()?)
+    }
+}
+
+/// Create the tags cache only if it's missing, outdated or any file has been modified since.
+#[derive(Parser, Debug, Clone)]
+pub struct Update {
+    #[clap(flatten)]
+    project: ProjectArgs,
+}
+
+impl Update {
+    fn run(&self) -> Result<()> {
+        let ctags_cmd = self.project.ctags_cmd()?;
+
+        let up_to_date = ctags_cmd.ctags_cache().is_some()
+            && ctags_cmd
+                .stale_files()
+                .map_or(false, |stale_files| stale_files.is_empty());
+
+        if up_to_date {
+            println!("The tags cache is up to date");
+            Ok(())
+        } else {
+            create_cache(ctags_cmd)
+        }
+    }
+}
+
+/// Print the stats of the tags cache.
+#[derive(Parser, Debug, Clone)]
+pub struct Stats {
+    #[clap(flatten)]
+    project: ProjectArgs,
+}
+
+impl Stats {
+    fn run(&self) -> Result<()> {
+        let ctags_cmd = self.project.ctags_cmd()?;
+
+        let stdout = std::io::stdout();
+        let mut lock = stdout.lock();
+
+        writeln!(lock, "Project: {}", ctags_cmd.cwd().display())?;
+
+        let Some(digest) = ctags_cmd.cache_digest() else {
+            writeln!(lock, "No tags cache")?;
+            return Ok(());
+        };
+
+        let size = std::fs::metadata(&digest.cached_path)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        let age = SystemTime::now()
+            .duration_since(SystemTime::from(digest.execution_time))
+            .unwrap_or_default();
+        let stale_files = ctags_cmd.stale_files().unwrap_or_default();
+
+        writeln!(lock, "Cache file: {}", digest.cached_path.display())?;
+        writeln!(lock, "Tags: {}", digest.total)?;
+        writeln!(lock, "Size: {size} bytes")?;
+        writeln!(
+            lock,
+            "Created at: {} ({}s ago)",
+            digest.execution_time,
+            age.as_secs()
+        )?;
+        writeln!(lock, "Usable: {}", digest.is_usable())?;
+        writeln!(lock, "Stale files: {}", stale_files.len())?;
+
+        Ok(())
+    }
+}
+
+/// List the files modified since the tags cache was created.
+#[derive(Parser, Debug, Clone)]
+pub struct Stale {
+    #[clap(flatten)]
+    project: ProjectArgs,
+}
+
+impl Stale {
+    fn run(&self) -> Result<()> {
+        let ctags_cmd = self.project.ctags_cmd()?;
+
+        let stale_files = ctags_cmd
+            .stale_files()
+            .ok_or_else(|| anyhow!("No tags cache for {}", ctags_cmd.cwd().display()))?;
+
+        let stdout = std::io::stdout();
+        let mut lock = stdout.lock();
+        for file in stale_files {
+            writeln!(lock, "{}", file.display())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::RunCmd;
+
+    #[test]
+    fn test_tags_command() {
+        let run_cmd = RunCmd::parse_from(["", "tags", "update", "--dir", "/tmp/project"]);
+        match run_cmd {
+            RunCmd::Tags(Tags::Update(update)) => {
+                assert_eq!(update.project.dir, Some(PathBuf::from("/tmp/project")));
+            }
+            _ => unreachable!(""),
+        }
+    }
+}
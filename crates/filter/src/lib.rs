@@ -9,6 +9,7 @@
 mod parallel_worker;
 mod sequential_source;
 mod sequential_worker;
+mod shared_lines;
 
 use icon::Icon;
 use matcher::{Bonus, MatchScope, Matcher, MatcherBuilder};
@@ -17,11 +18,12 @@ use std::sync::Arc;
 use types::{ClapItem, FileNameItem, GrepItem};
 
 pub use self::parallel_worker::{
-    par_dyn_run, par_dyn_run_inprocess, par_dyn_run_list, BestItems, ParallelSource,
-    StdioProgressor,
+    initial_update_time, par_dyn_run, par_dyn_run_inprocess, par_dyn_run_list, BestItems,
+    MatchSink, ParallelSource, StdioProgressor,
 };
 pub use self::sequential_source::{filter_sequential, SequentialSource};
 pub use self::sequential_worker::dyn_run;
+pub use self::shared_lines::SharedBytes;
 pub use matcher;
 pub use types::{CaseMatching, MatchedItem, Query, SourceItem};
 
@@ -48,7 +50,7 @@ impl From<Vec<MatchedItem>> for MatchedItems {
 }
 
 /// Converts the raw line into a clap item.
-pub(crate) fn to_clap_item(match_scope: MatchScope, line: String) -> Option<Arc<dyn ClapItem>> {
+pub fn to_clap_item(match_scope: MatchScope, line: String) -> Option<Arc<dyn ClapItem>> {
     match match_scope {
         MatchScope::GrepLine => {
             GrepItem::try_new(line).map(|item| Arc::new(item) as Arc<dyn ClapItem>)
@@ -1,5 +1,6 @@
 //! Convert the source item stream to a parallel iterator and run the filtering in parallel.
 
+use crate::shared_lines::{shared_lines, SharedBytes};
 use crate::{to_clap_item, FilterContext};
 use anyhow::Result;
 use parking_lot::Mutex;
@@ -20,6 +21,7 @@ use types::{ClapItem, MatchedItem, Query};
 pub enum ParallelSource {
     File(PathBuf),
     Exec(Box<Exec>),
+    Shared(SharedBytes),
 }
 
 /// Returns the ranked results after applying fuzzy filter given the query string and a list of candidates.
@@ -38,7 +40,7 @@ pub fn par_dyn_run(
             par_dyn_run_inner::<Empty<_>, _>(
                 query,
                 filter_context,
-                ParSourceInner::Lines(std::fs::File::open(file)?),
+                ParSourceInner::Lines(utils::open_reader(file)?),
             )?;
         }
         ParallelSource::Exec(exec) => {
@@ -48,6 +50,13 @@ pub fn par_dyn_run(
                 ParSourceInner::Lines(exec.stream_stdout()?),
             )?;
         }
+        ParallelSource::Shared(bytes) => {
+            par_dyn_run_inner::<Empty<_>, std::io::Empty>(
+                query,
+                filter_context,
+                ParSourceInner::Shared(bytes),
+            )?;
+        }
     }
 
     Ok(())
@@ -64,6 +73,18 @@ pub fn par_dyn_run_list<'a, 'b: 'a>(
         .expect("Matching items in parallel can not fail");
 }
 
+/// Delay of sending the first partial results, so that the picker is not left blank while
+/// filtering a huge source.
+pub const FIRST_UPDATE_DELAY: Duration = Duration::from_millis(50);
+
+/// Returns the time of a fake last update, so that the first update happens after
+/// [`FIRST_UPDATE_DELAY`] instead of `update_interval`.
+pub fn initial_update_time(update_interval: Duration) -> Instant {
+    let now = Instant::now();
+    now.checked_sub(update_interval.saturating_sub(FIRST_UPDATE_DELAY))
+        .unwrap_or(now)
+}
+
 #[derive(Debug)]
 pub struct BestItems<P: ProgressUpdate<DisplayLines>> {
     /// Time of last notification.
@@ -87,7 +108,7 @@ impl<P: ProgressUpdate<DisplayLines>> BestItems<P> {
     ) -> Self {
         Self {
             printer,
-            past: Instant::now(),
+            past: initial_update_time(update_interval),
             items: Vec::with_capacity(max_capacity),
             last_lines: Vec::with_capacity(max_capacity),
             last_visible_highlights: Vec::with_capacity(max_capacity),
@@ -240,6 +261,7 @@ impl ProgressUpdate<DisplayLines> for StdioProgressor {
 enum ParSourceInner<I: IntoParallelIterator<Item = Arc<dyn ClapItem>>, R: Read + Send> {
     Items(I),
     Lines(R),
+    Shared(SharedBytes),
 }
 
 /// Perform the matching on a stream of [`Source::File`] and `[Source::Exec]` in parallel.
@@ -305,6 +327,12 @@ where
                     }
                 });
         }
+        ParSourceInner::Shared(bytes) => {
+            shared_lines(&bytes, matcher.match_scope()).for_each(|item| {
+                let processed = processed_count.fetch_add(1, Ordering::SeqCst);
+                process_item(item, processed);
+            });
+        }
     }
 
     let total_matched = matched_count.into_inner();
@@ -325,14 +353,22 @@ where
     Ok(())
 }
 
+/// Receiver of every matched item of [`par_dyn_run_inprocess`], not only the best ones.
+pub trait MatchSink: Send + Sync {
+    fn on_match(&self, matched_item: &MatchedItem);
+}
+
 /// Similar to `[par_dyn_run]`, but used in the process which means we need to cancel the command
 /// creating the items manually in order to cancel the task ASAP.
+///
+/// Each matched item is sent to `match_sink` if any, before it's ranked against the best items.
 pub fn par_dyn_run_inprocess<P>(
     query: &str,
     filter_context: FilterContext,
     par_source: ParallelSource,
     progressor: P,
     stop_signal: Arc<AtomicBool>,
+    match_sink: Option<&dyn MatchSink>,
 ) -> Result<()>
 where
     P: ProgressUpdate<DisplayLines> + Send,
@@ -364,6 +400,10 @@ where
 
     let process_item = |item: Arc<dyn ClapItem>, processed: usize| {
         if let Some(matched_item) = matcher.match_item(item) {
+            if let Some(match_sink) = match_sink {
+                match_sink.on_match(&matched_item);
+            }
+
             let matched = matched_count.fetch_add(1, Ordering::SeqCst);
 
             // TODO: not use mutex?
@@ -373,9 +413,32 @@ where
         }
     };
 
+    let stopped = || {
+        let stopped = stop_signal.load(Ordering::SeqCst);
+        if stopped {
+            tracing::debug!(?matcher, "[par_dyn_run_inprocess] stop signal received");
+        }
+        // Note that even the stop signal has been received, the thread created by
+        // rayon does not exit actually, it just tries to stop the work ASAP.
+        stopped
+    };
+
     let read: Box<dyn std::io::Read + Send> = match par_source {
-        ParallelSource::File(file) => Box::new(std::fs::File::open(file)?),
+        ParallelSource::File(file) => utils::open_reader(file)?,
         ParallelSource::Exec(exec) => Box::new(exec.detached().stream_stdout()?), // TODO: kill the exec command ASAP/ Run the exec command in another blocking task.
+        ParallelSource::Shared(bytes) => {
+            // Feed the lines sliced from the shared bytes to the matcher directly.
+            let res = shared_lines(&bytes, matcher.match_scope()).try_for_each(|item| {
+                if stopped() {
+                    Err(())
+                } else {
+                    let processed = processed_count.fetch_add(1, Ordering::SeqCst);
+                    process_item(item, processed);
+                    Ok(())
+                }
+            });
+            return finish_inprocess(res, matched_count, processed_count, best_items);
+        }
     };
 
     // To avoid Err(Custom { kind: InvalidData, error: "stream did not contain valid UTF-8" })
@@ -385,10 +448,7 @@ where
         .map_while(Result::ok)
         .par_bridge()
         .try_for_each(|line: String| {
-            if stop_signal.load(Ordering::SeqCst) {
-                tracing::debug!(?matcher, "[par_dyn_run_inprocess] stop signal received");
-                // Note that even the stop signal has been received, the thread created by
-                // rayon does not exit actually, it just tries to stop the work ASAP.
+            if stopped() {
                 Err(())
             } else {
                 let processed = processed_count.fetch_add(1, Ordering::SeqCst);
@@ -399,6 +459,15 @@ where
             }
         });
 
+    finish_inprocess(res, matched_count, processed_count, best_items)
+}
+
+fn finish_inprocess<P: ProgressUpdate<DisplayLines>>(
+    res: std::result::Result<(), ()>,
+    matched_count: AtomicUsize,
+    processed_count: AtomicUsize,
+    best_items: Mutex<BestItems<P>>,
+) -> Result<()> {
     let total_matched = matched_count.into_inner();
     let total_processed = processed_count.into_inner();
 
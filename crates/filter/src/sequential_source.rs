@@ -43,7 +43,7 @@ pub fn filter_sequential<I: Iterator<Item = Arc<dyn ClapItem>>>(
                 .map(|line| Arc::new(SourceItem::from(line)) as Arc<dyn ClapItem>),
         ),
         SequentialSource::File(path) => Box::new(
-            std::io::BufReader::new(std::fs::File::open(path)?)
+            std::io::BufReader::new(utils::open_reader(path)?)
                 .lines()
                 .map_while(Result::ok)
                 .map(|line| Arc::new(SourceItem::from(line)) as Arc<dyn ClapItem>),
@@ -351,7 +351,7 @@ pub fn dyn_run<I: Iterator<Item = Arc<dyn ClapItem>>>(
                 .filter_map(|line| to_clap_item(matcher.match_scope(), line)),
         ),
         SequentialSource::File(path) => Box::new(
-            std::io::BufReader::new(std::fs::File::open(path)?)
+            std::io::BufReader::new(utils::open_reader(path)?)
                 .lines()
                 .map_while(Result::ok)
                 .filter_map(|line| to_clap_item(matcher.match_scope(), line)),
@@ -0,0 +1,176 @@
+//! Zero-copy lines of the shared bytes, e.g., a memory-mapped cache file.
+
+use icon::Icon;
+use rayon::prelude::*;
+use std::sync::Arc;
+use types::{ClapItem, FuzzyText, MatchScope};
+
+/// Bytes shared across the filter runs, e.g., a memory-mapped cache file.
+#[derive(Clone)]
+pub struct SharedBytes(pub Arc<dyn AsRef<[u8]> + Send + Sync>);
+
+impl std::fmt::Debug for SharedBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedBytes")
+            .field("len", &self.as_ref().len())
+            .finish()
+    }
+}
+
+impl AsRef<[u8]> for SharedBytes {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref().as_ref()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum LineKind {
+    Plain,
+    Grep {
+        end_of_path: usize,
+        start_of_line: usize,
+    },
+    FileName {
+        file_name_offset: usize,
+    },
+}
+
+/// A line sliced from [`SharedBytes`] without copying, equivalent to the item created by
+/// `to_clap_item()` from the same line.
+#[derive(Debug)]
+struct SharedLine {
+    bytes: SharedBytes,
+    start: usize,
+    end: usize,
+    kind: LineKind,
+}
+
+impl SharedLine {
+    fn try_new(
+        bytes: SharedBytes,
+        start: usize,
+        end: usize,
+        match_scope: MatchScope,
+    ) -> Option<Self> {
+        let line = std::str::from_utf8(&bytes.as_ref()[start..end]).ok()?;
+
+        let kind = match match_scope {
+            MatchScope::GrepLine => {
+                let (end_of_path, start_of_line) = pattern::parse_grep_item(line)?;
+                LineKind::Grep {
+                    end_of_path,
+                    start_of_line,
+                }
+            }
+            MatchScope::FileName => {
+                let (_file_name, file_name_offset) = pattern::extract_file_name(line)?;
+                LineKind::FileName { file_name_offset }
+            }
+            _ => LineKind::Plain,
+        };
+
+        Some(Self {
+            bytes,
+            start,
+            end,
+            kind,
+        })
+    }
+}
+
+impl ClapItem for SharedLine {
+    fn raw_text(&self) -> &str {
+        // SAFETY: the line has been validated as UTF-8 on creation and the shared bytes are
+        // immutable.
+        unsafe { std::str::from_utf8_unchecked(&self.bytes.as_ref()[self.start..self.end]) }
+    }
+
+    fn fuzzy_text(&self, match_scope: MatchScope) -> Option<FuzzyText<'_>> {
+        match self.kind {
+            LineKind::Plain => types::extract_fuzzy_text(self.raw_text(), match_scope),
+            LineKind::Grep { start_of_line, .. } => Some(FuzzyText::new(
+                &self.raw_text()[start_of_line..],
+                start_of_line,
+            )),
+            LineKind::FileName { file_name_offset } => Some(FuzzyText::new(
+                &self.raw_text()[file_name_offset..],
+                file_name_offset,
+            )),
+        }
+    }
+
+    fn bonus_text(&self) -> &str {
+        match self.kind {
+            LineKind::Grep { start_of_line, .. } => &self.raw_text()[start_of_line..],
+            _ => self.raw_text(),
+        }
+    }
+
+    fn icon(&self, icon: Icon) -> Option<icon::IconType> {
+        match self.kind {
+            LineKind::Plain => icon
+                .icon_kind()
+                .map(|icon_kind| icon_kind.icon(self.raw_text())),
+            LineKind::Grep { end_of_path, .. } => {
+                Some(icon::file_icon(&self.raw_text()[..end_of_path]))
+            }
+            LineKind::FileName { .. } => Some(icon::file_icon(self.raw_text())),
+        }
+    }
+}
+
+/// Returns a parallel iterator of the lines in `bytes`, the line text refers to `bytes`
+/// directly instead of being copied into a new String.
+///
+/// The empty lines and the lines that are not valid UTF-8 are skipped.
+pub(crate) fn shared_lines(
+    bytes: &SharedBytes,
+    match_scope: MatchScope,
+) -> impl ParallelIterator<Item = Arc<dyn ClapItem>> + '_ {
+    let base = bytes.as_ref().as_ptr() as usize;
+
+    bytes
+        .as_ref()
+        .par_split(|b| *b == b'\n')
+        .filter_map(move |line| {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if line.is_empty() {
+                return None;
+            }
+            let start = line.as_ptr() as usize - base;
+            let end = start + line.len();
+            SharedLine::try_new(bytes.clone(), start, end, match_scope)
+                .map(|item| Arc::new(item) as Arc<dyn ClapItem>)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_lines() {
+        let content =
+            "src/lib.rs:1:2:fn main() {}\r\nnot a grep line\n\nsrc/main.rs:3:4:let x = 1;\n";
+        let bytes = SharedBytes(Arc::new(content.as_bytes().to_vec()));
+
+        let mut lines = shared_lines(&bytes, MatchScope::Full)
+            .map(|item| item.raw_text().to_string())
+            .collect::<Vec<_>>();
+        lines.sort();
+        assert_eq!(
+            lines,
+            vec![
+                "not a grep line",
+                "src/lib.rs:1:2:fn main() {}",
+                "src/main.rs:3:4:let x = 1;"
+            ]
+        );
+
+        let grep_lines = shared_lines(&bytes, MatchScope::GrepLine).collect::<Vec<_>>();
+        assert_eq!(grep_lines.len(), 2);
+        assert!(grep_lines.iter().all(|item| item
+            .fuzzy_text(MatchScope::GrepLine)
+            .is_some_and(|text| text.text.starts_with("fn") || text.text.starts_with("let"))));
+    }
+}
@@ -1,8 +1,14 @@
+mod shared_source;
+
 use crate::datastore::CACHE_INFO_IN_MEMORY;
 use crate::process::ShellCommand;
+use crate::tooling::Tool;
 use crate::UtcTime;
 use chrono::prelude::*;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+pub use self::shared_source::shared_source;
 
 pub const MAX_DIGESTS: usize = 100;
 
@@ -24,13 +30,107 @@ pub struct Digest {
     pub total_executions: usize,
     /// File persistent on the disk for caching the results.
     pub cached_path: PathBuf,
+    /// Commit id of git HEAD when the command was executed if the `cwd` is in a git repo.
+    ///
+    /// The cache is outdated once HEAD moves, e.g., checkout, rebase, pull.
+    #[serde(default)]
+    pub git_head: Option<String>,
+    /// Fingerprint of the cache file for detecting the modification.
+    #[serde(default)]
+    pub content_digest: Option<ContentDigest>,
+    /// Fingerprint of the files under `cwd` when the command was executed.
+    ///
+    /// The cache is outdated once the files are changed, e.g., a file is created or edited.
+    #[serde(default)]
+    pub source_digest: Option<u64>,
+}
+
+/// Maximum number of files to fingerprint outside of git repo, the larger directories rely on
+/// the ttl only.
+const MAX_SOURCE_FILES: usize = 100_000;
+
+/// Returns the fingerprint of the files under `dir`, which changes once any of them is created,
+/// removed or modified.
+///
+/// In a git repo, HEAD and the output of `git status` are hashed, otherwise the paths, sizes
+/// and modification times of the files not ignored.
+pub fn source_digest(dir: &Path) -> Option<u64> {
+    if let Some(head) = crate::tools::git::head_commit(dir) {
+        let output = std::process::Command::new("git")
+            .args(["status", "--porcelain", "--untracked-files=normal"])
+            .current_dir(dir)
+            .stderr(std::process::Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+        return Some(utils::calculate_hash(&(head, output.stdout)));
+    }
+
+    let mut files = Vec::new();
+    for entry in ignore::WalkBuilder::new(dir).build() {
+        let entry = entry.ok()?;
+        if entry
+            .file_type()
+            .map_or(true, |file_type| file_type.is_dir())
+        {
+            continue;
+        }
+        let metadata = entry.metadata().ok()?;
+        files.push((entry.into_path(), metadata.len(), metadata.modified().ok()));
+        if files.len() > MAX_SOURCE_FILES {
+            return None;
+        }
+    }
+
+    Some(utils::calculate_hash(&files))
+}
+
+/// Cheap fingerprint of a file, consisting of the size, modification time and the hash of a few
+/// sampled blocks, without reading the whole file.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ContentDigest {
+    size: u64,
+    modified: SystemTime,
+    sample_hash: u64,
+}
+
+impl ContentDigest {
+    pub fn new(path: &Path) -> std::io::Result<Self> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        const BLOCK_SIZE: u64 = 4096;
+
+        let mut file = std::fs::File::open(path)?;
+        let metadata = file.metadata()?;
+        let size = metadata.len();
+        let modified = metadata.modified()?;
+
+        // Sample the blocks at the start, middle and end of file.
+        let mut samples = Vec::with_capacity(3 * BLOCK_SIZE as usize);
+        for offset in [0, size / 2, size.saturating_sub(BLOCK_SIZE)] {
+            file.seek(SeekFrom::Start(offset))?;
+            (&mut file).take(BLOCK_SIZE).read_to_end(&mut samples)?;
+        }
+
+        Ok(Self {
+            size,
+            modified,
+            sample_hash: utils::calculate_hash(&samples),
+        })
+    }
 }
 
 impl Digest {
     /// Creates an instance of [`Digest`].
     pub fn new(shell_cmd: ShellCommand, total: usize, cached_path: PathBuf) -> Self {
         let now = Utc::now();
+        let git_head = crate::tools::git::head_commit(&shell_cmd.cwd);
+        let content_digest = ContentDigest::new(&cached_path).ok();
+        let source_digest = source_digest(&shell_cmd.cwd);
         Self {
+            content_digest,
+            source_digest,
+            git_head,
             shell_cmd,
             total,
             cached_path,
@@ -57,15 +157,65 @@ impl Digest {
     pub fn is_usable(&self) -> bool {
         let now = Utc::now();
 
-        const EXECUTION_EXPIRATION_DAYS: i64 = 3;
+        let ttl = crate::config::config().cache.ttl(self.provider());
+
+        if now
+            .signed_duration_since(self.execution_time)
+            .to_std()
+            .map(|elapsed| elapsed > ttl)
+            .unwrap_or(false)
+        {
+            return false;
+        }
+
+        if self.git_head.is_some()
+            && self.git_head != crate::tools::git::head_commit(&self.shell_cmd.cwd)
+        {
+            return false;
+        }
 
-        if now.signed_duration_since(self.execution_time).num_days() > EXECUTION_EXPIRATION_DAYS {
+        if self.source_digest.is_some() && self.source_digest != source_digest(&self.shell_cmd.cwd)
+        {
             return false;
         }
 
         // TODO: when the preview content mismatches the line, the cache is outdated and should be updated.
 
-        self.cached_path.exists()
+        self.is_intact()
+    }
+
+    /// Returns `true` if the cache file exists and has not been modified since created.
+    ///
+    /// The digests created before [`ContentDigest`] was introduced fall back to checking the
+    /// number of lines.
+    pub fn is_intact(&self) -> bool {
+        match &self.content_digest {
+            Some(content_digest) => ContentDigest::new(&self.cached_path)
+                .map(|current| &current == content_digest)
+                .unwrap_or(false),
+            None => utils::open_reader(&self.cached_path)
+                .and_then(utils::count_lines)
+                .map(|total| total == self.total)
+                .unwrap_or(false),
+        }
+    }
+
+    /// Returns the provider which created this cache, the program of shell command is
+    /// returned if it's not created by a known provider.
+    pub fn provider(&self) -> &str {
+        let command = self.shell_cmd.command.as_str();
+        if command == crate::tools::rg::RG_EXEC_CMD {
+            "grep"
+        } else if command.starts_with("ctags") {
+            "proj_tags"
+        } else {
+            command.split_whitespace().next().unwrap_or(command)
+        }
+    }
+
+    /// Returns the file name of the cache file, used as the key of cache entry.
+    pub fn cache_key(&self) -> Option<&str> {
+        self.cached_path.file_name().and_then(|s| s.to_str())
     }
 }
 
@@ -92,13 +242,9 @@ impl CacheInfo {
 
         self.digests.retain(|digest| {
             if digest.shell_cmd.cwd.exists()
-                && digest.cached_path.exists()
                 && now.signed_duration_since(digest.last_visit).num_days() < MAX_DAYS
-                // In case the cache was not created completely.
-                && std::fs::File::open(&digest.cached_path)
-                    .and_then(utils::count_lines)
-                    .map(|total| total == digest.total)
-                    .unwrap_or(false)
+                // In case the cache was not created completely or modified.
+                && digest.is_intact()
             {
                 true
             } else {
@@ -109,6 +255,15 @@ impl CacheInfo {
         });
     }
 
+    /// Points the cached files under `from` to `to`, used after the cache directory is moved.
+    pub fn relocate(&mut self, from: &Path, to: &Path) {
+        for digest in self.digests.iter_mut() {
+            if let Ok(relative) = digest.cached_path.strip_prefix(from) {
+                digest.cached_path = to.join(relative);
+            }
+        }
+    }
+
     /// Finds the digest given `shell_cmd`.
     fn find_digest(&self, shell_cmd: &ShellCommand) -> Option<usize> {
         self.digests.iter().position(|d| &d.shell_cmd == shell_cmd)
@@ -166,6 +321,21 @@ impl CacheInfo {
     pub fn to_digests(&self) -> Vec<Digest> {
         self.digests.clone()
     }
+
+    /// Removes the digests satisfying `predicate` as well as their cache files.
+    ///
+    /// Also writes the memory cached info back to the disk.
+    pub fn purge(&mut self, predicate: impl Fn(&Digest) -> bool) -> std::io::Result<()> {
+        self.digests.retain(|digest| {
+            if predicate(digest) {
+                let _ = std::fs::remove_file(&digest.cached_path);
+                false
+            } else {
+                true
+            }
+        });
+        crate::datastore::store_cache_info(self)
+    }
 }
 
 /// Pushes the digest of the results of new fresh run to [`CACHE_INFO_IN_MEMORY`].
@@ -187,12 +357,27 @@ pub fn find_largest_cache_digest() -> Option<Digest> {
     digests.last().cloned()
 }
 
+/// Compresses the newly created cache file if `cache.compress` is enabled, returns the path
+/// of the cache file in use.
+///
+/// The cache file is always read through [`utils::open_reader`], which handles both the
+/// compressed and uncompressed ones.
+pub fn compress_cache_file(cache_file: PathBuf) -> std::io::Result<PathBuf> {
+    if crate::config::config().cache.compress {
+        utils::compress_file(cache_file)
+    } else {
+        Ok(cache_file)
+    }
+}
+
 pub fn store_cache_digest(
     shell_cmd: ShellCommand,
     new_created_cache: PathBuf,
 ) -> std::io::Result<Digest> {
     let total = utils::count_lines(std::fs::File::open(&new_created_cache)?)?;
 
+    let new_created_cache = compress_cache_file(new_created_cache)?;
+
     let digest = Digest::new(shell_cmd, total, new_created_cache);
 
     let cache_info = crate::datastore::CACHE_INFO_IN_MEMORY.clone();
@@ -201,3 +386,351 @@ pub fn store_cache_digest(
 
     Ok(digest)
 }
+
+fn readable_size(size: u64) -> String {
+    if size > 1024 * 1024 {
+        format!("{}MB", size / 1024 / 1024)
+    } else if size > 1024 {
+        format!("{}KB", size / 1024)
+    } else {
+        format!("{size}B")
+    }
+}
+
+fn readable_age(age: chrono::Duration) -> String {
+    if age.num_days() > 0 {
+        format!("{}d ago", age.num_days())
+    } else if age.num_hours() > 0 {
+        format!("{}h ago", age.num_hours())
+    } else if age.num_minutes() > 0 {
+        format!("{}m ago", age.num_minutes())
+    } else {
+        "just now".to_string()
+    }
+}
+
+/// Returns the lines of cache entries for the `cache_info` provider, most recently visited first.
+///
+/// Each line is in the form of `provider project size age hits cache_key`.
+pub fn cache_entry_lines() -> Vec<String> {
+    let mut digests = CACHE_INFO_IN_MEMORY.lock().to_digests();
+    digests.sort_unstable_by_key(|digest| std::cmp::Reverse(digest.last_visit));
+
+    let now = Utc::now();
+
+    digests
+        .iter()
+        .filter_map(|digest| {
+            let cache_key = digest.cache_key()?;
+            let size = std::fs::metadata(&digest.cached_path)
+                .map(|metadata| metadata.len())
+                .unwrap_or_default();
+            Some(format!(
+                "{:<12} {:<40} {:>7} {:>9} {:>5} hits  {cache_key}",
+                digest.provider(),
+                crate::paths::truncate_absolute_path(
+                    &digest.shell_cmd.cwd.display().to_string(),
+                    40
+                ),
+                readable_size(size),
+                readable_age(now - digest.execution_time),
+                digest.total_visits,
+            ))
+        })
+        .collect()
+}
+
+/// Removes the cache entry identified by `cache_key`.
+pub fn purge_cache_entry(cache_key: &str) -> std::io::Result<()> {
+    CACHE_INFO_IN_MEMORY
+        .lock()
+        .purge(|digest| digest.cache_key() == Some(cache_key))
+}
+
+/// Removes all the cache entries of the same project as the entry identified by `cache_key`.
+pub fn purge_project_cache(cache_key: &str) -> std::io::Result<()> {
+    let mut cache_info = CACHE_INFO_IN_MEMORY.lock();
+    let Some(project) = cache_info
+        .digests
+        .iter()
+        .find(|digest| digest.cache_key() == Some(cache_key))
+        .map(|digest| digest.shell_cmd.cwd.clone())
+    else {
+        return Ok(());
+    };
+    cache_info.purge(|digest| digest.shell_cmd.cwd == project)
+}
+
+/// Builds the caches of `project` unless they are still usable.
+async fn warm_up_project(project: PathBuf) {
+    if crate::tooling::is_available(Tool::Rg) {
+        let rg_cmd = crate::tools::rg::RgTokioCommand::new(project.clone());
+        if rg_cmd.cache_digest().is_none() {
+            if let Err(err) = rg_cmd.create_cache().await {
+                tracing::debug!(?err, ?project, "Failed to warm up the grep cache");
+            }
+        }
+    }
+
+    if crate::tooling::has_feature(Tool::Ctags, "json") {
+        let mut ctags_cmd = crate::tools::ctags::ProjectCtagsCommand::with_cwd(project.clone());
+        if ctags_cmd.ctags_cache().is_none() {
+            match tokio::task::spawn_blocking(move || ctags_cmd.par_create_cache()).await {
+                Ok(Err(err)) => {
+                    tracing::debug!(?err, ?project, "Failed to warm up the project tags cache");
+                }
+                Err(err) => tracing::debug!(?err, "Warm-up task panicked"),
+                Ok(Ok(_)) => {}
+            }
+        }
+    }
+}
+
+/// Pre-builds the caches of the projects in `cache.warm-up` config on startup and periodically
+/// if an interval is configured, so that the first search in a huge project is fast.
+pub async fn run_cache_warm_up() {
+    loop {
+        let warm_up = &crate::config::config().cache.warm_up;
+
+        for project in &warm_up.projects {
+            warm_up_project(project.clone().into()).await;
+        }
+
+        match warm_up.interval {
+            Some(interval) => tokio::time::sleep(interval.0).await,
+            None => return,
+        }
+    }
+}
+
+/// A file in the cache directory.
+#[derive(Debug, Clone)]
+struct CacheFile {
+    path: PathBuf,
+    size: u64,
+    last_used: SystemTime,
+}
+
+/// Returns the files to evict so that the total size of `cache_files` is within `budget`.
+///
+/// The least recently used files are evicted first, the files used within `protected_period`
+/// are kept as they could be still in use.
+fn select_files_to_evict(
+    mut cache_files: Vec<CacheFile>,
+    budget: u64,
+    protected_period: Duration,
+) -> Vec<CacheFile> {
+    let mut total_size: u64 = cache_files.iter().map(|f| f.size).sum();
+
+    if total_size <= budget {
+        return Vec::new();
+    }
+
+    let now = SystemTime::now();
+    cache_files.sort_unstable_by_key(|f| f.last_used);
+
+    cache_files
+        .into_iter()
+        .filter(|f| {
+            now.duration_since(f.last_used)
+                .map(|elapsed| elapsed > protected_period)
+                .unwrap_or(false)
+        })
+        .take_while(|f| {
+            let over_budget = total_size > budget;
+            total_size = total_size.saturating_sub(f.size);
+            over_budget
+        })
+        .collect()
+}
+
+/// Collects the files in `dir` recursively.
+fn collect_dir_files(dir: &Path, cache_files: &mut Vec<CacheFile>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let path = entry.path();
+        if metadata.is_dir() {
+            collect_dir_files(&path, cache_files)?;
+        } else {
+            cache_files.push(CacheFile {
+                path,
+                size: metadata.len(),
+                last_used: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the cache files created by maple, i.e., the cached command outputs of the cache
+/// digests and the project indexes.
+///
+/// The cache directory may be shared with the other programs, the other files in it are left
+/// alone, so are the token files of the daemon.
+fn collect_cache_files() -> std::io::Result<Vec<CacheFile>> {
+    let digests = CACHE_INFO_IN_MEMORY
+        .lock()
+        .digests
+        .iter()
+        .map(|digest| (digest.cached_path.clone(), digest.last_visit.into()))
+        .collect::<Vec<(PathBuf, SystemTime)>>();
+
+    let mut cache_files = digests
+        .into_iter()
+        .filter_map(|(path, last_used)| {
+            let size = std::fs::metadata(&path).ok()?.len();
+            Some(CacheFile {
+                path,
+                size,
+                last_used,
+            })
+        })
+        .collect();
+
+    let index_dir = crate::indexer::index_dir();
+    if index_dir.is_dir() {
+        collect_dir_files(&index_dir, &mut cache_files)?;
+    }
+
+    Ok(cache_files)
+}
+
+/// Evicts the least recently used cache files until their total size is within `budget`
+/// bytes, returns the number of bytes freed.
+///
+/// Only the cache files created by maple are counted and evicted, see [`collect_cache_files`].
+/// The last visit time of the cache digests is used for the cached command outputs, the
+/// modification time for the indexes.
+pub fn enforce_cache_budget(budget: u64) -> std::io::Result<u64> {
+    // Files used within this period are never evicted.
+    const PROTECTED_PERIOD: Duration = Duration::from_secs(10 * 60);
+
+    let cache_files = collect_cache_files()?;
+
+    let to_evict = select_files_to_evict(cache_files, budget, PROTECTED_PERIOD);
+
+    if to_evict.is_empty() {
+        return Ok(0);
+    }
+
+    let mut freed = 0;
+    for cache_file in &to_evict {
+        match std::fs::remove_file(&cache_file.path) {
+            Ok(()) => freed += cache_file.size,
+            Err(err) => {
+                tracing::debug!(?err, path = ?cache_file.path, "Failed to evict cache file")
+            }
+        }
+    }
+
+    let mut cache_info = CACHE_INFO_IN_MEMORY.lock();
+    cache_info
+        .digests
+        .retain(|digest| digest.cached_path.exists());
+    crate::datastore::store_cache_info(&cache_info)?;
+
+    Ok(freed)
+}
+
+/// Keeps the cache directory within the size budget in config, checked periodically.
+pub async fn run_cache_janitor() {
+    const INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+    let mut interval = tokio::time::interval(INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let budget = crate::config::config().cache.max_size;
+
+        match tokio::task::spawn_blocking(move || enforce_cache_budget(budget)).await {
+            Ok(Ok(freed)) if freed > 0 => {
+                tracing::debug!(freed, budget, "Evicted the least recently used cache files");
+            }
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => tracing::debug!(?err, "Failed to enforce the cache budget"),
+            Err(err) => tracing::debug!(?err, "Cache janitor task panicked"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache");
+        std::fs::write(&path, "src/lib.rs\nsrc/main.rs\n").unwrap();
+        let content_digest = ContentDigest::new(&path).unwrap();
+        assert_eq!(ContentDigest::new(&path).unwrap(), content_digest);
+
+        // In-place edit with the same size.
+        std::fs::write(&path, "src/lib.rs\nsrc/mod.rs\n").unwrap();
+        assert_ne!(ContentDigest::new(&path).unwrap(), content_digest);
+
+        // Deletion.
+        std::fs::write(&path, "src/lib.rs\n").unwrap();
+        assert_ne!(ContentDigest::new(&path).unwrap(), content_digest);
+    }
+
+    #[test]
+    fn test_source_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "fn foo() {}").unwrap();
+        let digest = source_digest(dir.path()).unwrap();
+        assert_eq!(source_digest(dir.path()), Some(digest));
+
+        // New file.
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        let new_digest = source_digest(dir.path()).unwrap();
+        assert_ne!(new_digest, digest);
+
+        // Edit.
+        std::fs::write(dir.path().join("main.rs"), "fn main() { foo() }").unwrap();
+        assert_ne!(source_digest(dir.path()), Some(new_digest));
+    }
+
+    #[test]
+    fn test_select_files_to_evict() {
+        let now = SystemTime::now();
+        let hours_ago = |hours: u64| now - Duration::from_secs(hours * 60 * 60);
+        let cache_file = |name: &str, size: u64, last_used: SystemTime| CacheFile {
+            path: PathBuf::from(name),
+            size,
+            last_used,
+        };
+
+        let cache_files = vec![
+            cache_file("recent", 100, hours_ago(1)),
+            cache_file("oldest", 100, hours_ago(3)),
+            cache_file("in-use", 100, now),
+            cache_file("old", 100, hours_ago(2)),
+        ];
+
+        let evict = |budget| {
+            select_files_to_evict(cache_files.clone(), budget, Duration::from_secs(60))
+                .into_iter()
+                .map(|f| f.path)
+                .collect::<Vec<_>>()
+        };
+
+        assert!(evict(400).is_empty());
+        assert_eq!(evict(300), vec![PathBuf::from("oldest")]);
+        assert_eq!(
+            evict(250),
+            vec![PathBuf::from("oldest"), PathBuf::from("old")]
+        );
+        // The file in use is never evicted.
+        assert_eq!(
+            evict(0),
+            vec![
+                PathBuf::from("oldest"),
+                PathBuf::from("old"),
+                PathBuf::from("recent")
+            ]
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Large cached sources are memory-mapped and shared across the provider sessions, so that a
+//! huge file list is not re-read from the disk on every keystroke and every time the provider
+//! opens.
+
+use filter::SharedBytes;
+use memmap2::Mmap;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Files smaller than this are read directly as it's fast enough.
+const MIN_MMAP_SIZE: u64 = 1024 * 1024;
+
+/// Maximum number of the memory-mapped files kept alive.
+const MAX_SHARED_SOURCES: usize = 8;
+
+#[derive(Debug)]
+struct SharedSource {
+    len: u64,
+    modified: SystemTime,
+    last_used: SystemTime,
+    mmap: Arc<Mmap>,
+}
+
+static SHARED_SOURCES: Lazy<Mutex<HashMap<PathBuf, SharedSource>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn map_file(path: &Path) -> std::io::Result<Arc<Mmap>> {
+    let file = std::fs::File::open(path)?;
+    // SAFETY: the cache files are never modified in place, a new cache file is written to
+    // a temporary file and then renamed, which leaves the mapped one intact.
+    let mmap = unsafe { Mmap::map(&file)? };
+    Ok(Arc::new(mmap))
+}
+
+/// Returns the memory-mapped content of `path` shared across the sessions.
+///
+/// Returns `None` if the file is small or compressed, which should be read as usual.
+pub fn shared_source(path: &Path) -> Option<SharedBytes> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let len = metadata.len();
+    let modified = metadata.modified().ok()?;
+
+    if len < MIN_MMAP_SIZE {
+        return None;
+    }
+
+    let mut shared_sources = SHARED_SOURCES.lock();
+
+    let now = SystemTime::now();
+
+    if let Some(shared) = shared_sources.get_mut(path) {
+        if shared.len == len && shared.modified == modified {
+            shared.last_used = now;
+            return Some(SharedBytes(shared.mmap.clone()));
+        }
+    }
+
+    let mmap = match map_file(path) {
+        Ok(mmap) => mmap,
+        Err(err) => {
+            tracing::debug!(?err, ?path, "Failed to map the cache file");
+            return None;
+        }
+    };
+
+    if utils::is_zstd_compressed(&mmap) {
+        return None;
+    }
+
+    if shared_sources.len() >= MAX_SHARED_SOURCES {
+        if let Some(least_recent) = shared_sources
+            .iter()
+            .min_by_key(|(_, shared)| shared.last_used)
+            .map(|(path, _)| path.clone())
+        {
+            shared_sources.remove(&least_recent);
+        }
+    }
+
+    shared_sources.insert(
+        path.to_path_buf(),
+        SharedSource {
+            len,
+            modified,
+            last_used: now,
+            mmap: mmap.clone(),
+        },
+    );
+
+    Some(SharedBytes(mmap))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("source");
+        let line = "crates/maple_core/src/cache/shared_source.rs\n";
+        let content = line.repeat(MIN_MMAP_SIZE as usize / line.len() + 1);
+        std::fs::write(&path, &content).unwrap();
+
+        let first = shared_source(&path).unwrap();
+        let second = shared_source(&path).unwrap();
+        assert_eq!(first.as_ref(), content.as_bytes());
+        assert!(Arc::ptr_eq(&first.0, &second.0));
+    }
+}
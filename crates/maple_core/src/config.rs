@@ -1,9 +1,12 @@
 use crate::dirs::PROJECT_DIRS;
 use crate::paths::AbsPathBuf;
-use once_cell::sync::OnceCell;
-use serde::{Deserialize, Serialize};
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::RwLock;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 static CONFIG_FILE: OnceCell<PathBuf> = OnceCell::new();
 
@@ -24,34 +27,367 @@ pub fn initialize_config_file(specified_file: Option<PathBuf>) {
     CONFIG_FILE.set(config_file).ok();
 }
 
+/// Expands `~` and the environment variables in the form of `$VAR` or `${VAR}` in a
+/// path-valued config.
+///
+/// The undefined environment variables are left as is.
+pub fn expand_path(path: &str) -> PathBuf {
+    let mut expanded = String::with_capacity(path.len());
+    let mut rest = path;
+
+    while let Some(idx) = rest.find('$') {
+        expanded.push_str(&rest[..idx]);
+        let after_dollar = &rest[idx + 1..];
+
+        let (var, var_end) = if let Some(braced) = after_dollar.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            let end = after_dollar
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(after_dollar.len());
+            (&after_dollar[..end], end)
+        };
+
+        match std::env::var(var) {
+            Ok(value) if !var.is_empty() => expanded.push_str(&value),
+            _ => expanded.push_str(&rest[idx..idx + 1 + var_end]),
+        }
+
+        rest = &after_dollar[var_end..];
+    }
+
+    expanded.push_str(rest);
+
+    if expanded == "~" {
+        crate::dirs::BASE_DIRS.home_dir().to_path_buf()
+    } else {
+        crate::paths::expand_tilde(expanded)
+    }
+}
+
 pub fn config_file() -> &'static PathBuf {
     CONFIG_FILE.get().expect("Config file uninitialized")
 }
 
-// TODO: reload-config
+/// Returns the current config if the config file has been initialized, e.g., not in the
+/// subcommands which don't read the config.
+pub fn try_config() -> Option<&'static Config> {
+    CONFIG_FILE.get().map(|_| config())
+}
+
+fn load_config(config_file: &Path) -> std::io::Result<Config> {
+    let contents = std::fs::read_to_string(config_file)?;
+    toml::from_str(&contents).map_err(|err| {
+        std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!("Error occurred at reading config.toml: {err}"),
+        )
+    })
+}
+
+/// Current config, replaced as a whole on reloading.
+///
+/// The old config is leaked on reloading so that the references returned by [`config()`] stay
+/// valid, which is fine as the config is only reloaded on editing the config file, by a single
+/// watcher per process.
+static CONFIG: Lazy<RwLock<&'static Config>> = Lazy::new(|| {
+    let config = load_config(config_file())
+        .map_err(|err| {
+            // TODO: Notify the config error.
+            tracing::debug!(
+                ?err,
+                "Error while deserializing config.toml, using the default config"
+            );
+        })
+        .unwrap_or_default();
+    RwLock::new(Box::leak(Box::new(config)))
+});
+
 pub fn config() -> &'static Config {
-    static CONFIG: OnceCell<Config> = OnceCell::new();
-
-    CONFIG.get_or_init(|| {
-        std::fs::read_to_string(CONFIG_FILE.get().expect("Config file uninitialized!"))
-            .and_then(|contents| {
-                toml::from_str(&contents).map_err(|err| {
-                    // TODO: Notify the config error.
-                    tracing::debug!(
-                        ?err,
-                        "Error while deserializing config.toml, using the default config"
-                    );
-                    std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        format!("Error occurred at reading config.toml: {err}"),
-                    )
-                })
-            })
-            .unwrap_or_default()
+    *CONFIG.read()
+}
+
+/// Merges `overlay` into `base` recursively, the values in `overlay` take precedence.
+fn merge_toml(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(base_value) => merge_toml(base_value, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn read_toml(path: &Path) -> std::io::Result<toml::Value> {
+    let contents = std::fs::read_to_string(path)?;
+    toml::from_str(&contents).map_err(|err| {
+        std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!("Error occurred at reading {}: {err}", path.display()),
+        )
     })
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Returns the config of the project at `project_root`, which is the global config with the
+/// project-local config file deep-merged over it.
+///
+/// Returns `None` if the project-local config does not exist or is invalid, in which case the
+/// global config should be used.
+pub fn load_project_config(project_root: &Path) -> Option<Config> {
+    let ProjectConfig { enable, file_name } = &config().project_config;
+
+    if !enable {
+        return None;
+    }
+
+    let project_config_file = project_root.join(expand_path(file_name));
+
+    if !project_config_file.is_file() {
+        return None;
+    }
+
+    let mut config_value =
+        read_toml(config_file()).unwrap_or_else(|_| toml::Value::Table(toml::value::Table::new()));
+
+    let project_config = read_toml(&project_config_file).and_then(|overlay| {
+        merge_toml(&mut config_value, overlay);
+        config_value.try_into::<Config>().map_err(|err| {
+            std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!(
+                    "Error occurred at merging {}: {err}",
+                    project_config_file.display()
+                ),
+            )
+        })
+    });
+
+    match project_config {
+        Ok(project_config) => Some(project_config),
+        Err(err) => {
+            tracing::debug!(
+                ?err,
+                "Failed to load the project config, using the global config"
+            );
+            None
+        }
+    }
+}
+
+/// Returns the JSON schema of the config file.
+pub fn json_schema() -> schemars::schema::RootSchema {
+    schemars::schema_for!(Config)
+}
+
+/// A problem found in the config file.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ConfigDiagnostic {
+    /// Dotted path of the offending key, e.g., `plugin.highlight-cursor-word.enabel`.
+    ///
+    /// Empty if the problem is not about a specific key, e.g., a syntax error.
+    pub key: String,
+    pub message: String,
+    /// Full path of the key that was probably meant.
+    pub suggestion: Option<String>,
+}
+
+/// Checks the config file, returns all the unknown or misplaced keys in it.
+///
+/// Unlike the deserialization which stops at the first error, all the unknown keys are
+/// collected, along with the nearest known keys as the suggestions.
+pub fn config_diagnostics() -> Vec<ConfigDiagnostic> {
+    match read_toml(config_file()) {
+        Ok(value) => {
+            let mut diagnostics = find_unknown_keys(&value);
+            if diagnostics.is_empty() {
+                if let Err(err) = value.try_into::<Config>() {
+                    diagnostics.push(ConfigDiagnostic {
+                        key: String::new(),
+                        message: err.to_string(),
+                        suggestion: None,
+                    });
+                }
+            }
+            diagnostics
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => vec![ConfigDiagnostic {
+            key: String::new(),
+            message: err.to_string(),
+            suggestion: None,
+        }],
+    }
+}
+
+fn find_unknown_keys(value: &toml::Value) -> Vec<ConfigDiagnostic> {
+    use schemars::schema::{Schema, SchemaObject};
+
+    type Definitions = schemars::Map<String, Schema>;
+
+    /// Resolves the schema to the one describing the actual value, following the references.
+    fn resolve<'a>(schema: &'a Schema, definitions: &'a Definitions) -> Option<&'a SchemaObject> {
+        let Schema::Object(schema_object) = schema else {
+            return None;
+        };
+        if let Some(reference) = &schema_object.reference {
+            let name = reference.trim_start_matches("#/definitions/");
+            return definitions
+                .get(name)
+                .and_then(|schema| resolve(schema, definitions));
+        }
+        if let Some(subschemas) = &schema_object.subschemas {
+            let candidates = subschemas.all_of.iter().chain(subschemas.any_of.iter());
+            if let Some(resolved) = candidates
+                .flatten()
+                .filter_map(|schema| resolve(schema, definitions))
+                .find(|schema_object| schema_object.object.is_some())
+            {
+                return Some(resolved);
+            }
+        }
+        Some(schema_object)
+    }
+
+    /// Collects the paths of all the keys known by the schema, the map entries are excluded.
+    fn collect_known_keys(
+        schema_object: &SchemaObject,
+        definitions: &Definitions,
+        prefix: &str,
+        known_keys: &mut Vec<String>,
+    ) {
+        if let Some(object) = &schema_object.object {
+            for (key, schema) in &object.properties {
+                let path = join_key(prefix, key);
+                if let Some(schema_object) = resolve(schema, definitions) {
+                    collect_known_keys(schema_object, definitions, &path, known_keys);
+                }
+                known_keys.push(path);
+            }
+        }
+    }
+
+    fn check_table(
+        table: &toml::value::Table,
+        schema_object: &SchemaObject,
+        definitions: &Definitions,
+        prefix: &str,
+        known_keys: &[String],
+        diagnostics: &mut Vec<ConfigDiagnostic>,
+    ) {
+        let Some(object) = &schema_object.object else {
+            return;
+        };
+
+        for (key, value) in table {
+            let path = join_key(prefix, key);
+
+            let value_schema = object.properties.get(key).or_else(|| {
+                object
+                    .additional_properties
+                    .as_deref()
+                    .filter(|schema| matches!(schema, Schema::Object(_)))
+            });
+
+            match value_schema {
+                Some(schema) => {
+                    if let (toml::Value::Table(table), Some(schema_object)) =
+                        (value, resolve(schema, definitions))
+                    {
+                        check_table(
+                            table,
+                            schema_object,
+                            definitions,
+                            &path,
+                            known_keys,
+                            diagnostics,
+                        );
+                    }
+                }
+                None => {
+                    let suggestion = suggest_key(key, object.properties.keys())
+                        .map(|similar_key| join_key(prefix, similar_key))
+                        .or_else(|| {
+                            known_keys
+                                .iter()
+                                .find(|known_key| {
+                                    known_key.rsplit('.').next() == Some(key.as_str())
+                                })
+                                .cloned()
+                        });
+                    let message = match &suggestion {
+                        Some(suggestion) => {
+                            format!("unknown key `{path}`, did you mean `{suggestion}`?")
+                        }
+                        None => format!("unknown key `{path}`"),
+                    };
+                    diagnostics.push(ConfigDiagnostic {
+                        key: path,
+                        message,
+                        suggestion,
+                    });
+                }
+            }
+        }
+    }
+
+    fn join_key(prefix: &str, key: &str) -> String {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{prefix}.{key}")
+        }
+    }
+
+    let toml::Value::Table(table) = value else {
+        return Vec::new();
+    };
+
+    let root_schema = json_schema();
+    let definitions = &root_schema.definitions;
+
+    let mut known_keys = Vec::new();
+    collect_known_keys(&root_schema.schema, definitions, "", &mut known_keys);
+
+    let mut diagnostics = Vec::new();
+    check_table(
+        table,
+        &root_schema.schema,
+        definitions,
+        "",
+        &known_keys,
+        &mut diagnostics,
+    );
+    diagnostics
+}
+
+/// Returns the candidate most similar to `key`, if it's close enough to be a typo.
+fn suggest_key<'a>(key: &str, candidates: impl Iterator<Item = &'a String>) -> Option<&'a str> {
+    candidates
+        .map(|candidate| (strsim::damerau_levenshtein(key, candidate), candidate))
+        .filter(|(distance, candidate)| *distance <= (candidate.len() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+/// Reloads the config file.
+///
+/// The current config stays unchanged if the config file is invalid.
+pub fn reload_config() -> std::io::Result<()> {
+    let new_config = load_config(config_file())?;
+    *CONFIG.write() = Box::leak(Box::new(new_config));
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
 pub struct MatcherConfig {
     pub tiebreak: String,
@@ -65,9 +401,20 @@ impl Default for MatcherConfig {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl MatcherConfig {
+    /// Returns the rank criteria parsed from `tiebreak`, the invalid ones are ignored.
+    pub fn rank_criteria(&self) -> Vec<types::RankCriterion> {
+        self.tiebreak
+            .split(',')
+            .filter_map(|s| types::parse_criteria(s.trim()))
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
 pub struct LogConfig {
+    /// Path of the log file, `~` and environment variables like `$VAR`/`${VAR}` are expanded.
     pub log_file: Option<String>,
     pub max_level: String,
 }
@@ -81,7 +428,36 @@ impl Default for LogConfig {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Directories of the files generated by vim-clap, `~` and environment variables like
+/// `$VAR`/`${VAR}` are expanded.
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct DirsConfig {
+    /// Directory of the persistent data, e.g., the recent files, the input history and the tags.
+    ///
+    /// The data is stored in its `vimclap` subdirectory, e.g., `~/.local/share/vimclap` for
+    /// `~/.local/share`, the same applies to `$VIM_CLAP_DATA_DIR` which overrides it.
+    ///
+    /// Defaults to `$XDG_DATA_HOME/vimclap` if set, otherwise the platform data directory,
+    /// e.g., `~/.local/share/vimclap` on Linux.
+    pub data_dir: Option<String>,
+
+    /// Directory of the caches, e.g., the cached outputs and the file indexes.
+    ///
+    /// The caches are stored in its `vimclap` subdirectory, e.g., `~/.cache/vimclap` for
+    /// `~/.cache`, the same applies to `$VIM_CLAP_CACHE_DIR` which overrides it.
+    ///
+    /// Defaults to `$XDG_CACHE_HOME/vimclap` if set, otherwise the platform cache directory,
+    /// e.g., `~/.cache/vimclap` on Linux.
+    pub cache_dir: Option<String>,
+
+    /// Directory of the log file `vimclap.log`, the logging is enabled if set.
+    ///
+    /// Overridden by `$VIM_CLAP_LOG_DIR`, `log.log-file` takes precedence.
+    pub log_dir: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
 pub struct HighlightCursorWordConfig {
     /// Whether to enable this plugin.
@@ -90,6 +466,14 @@ pub struct HighlightCursorWordConfig {
     pub ignore_comment_line: bool,
     /// Disable the plugin when the file matches this pattern.
     pub ignore_files: String,
+    /// Disable the plugin for these filetypes.
+    pub ignore_filetypes: Vec<String>,
+    /// Skip the cursor word if it's in one of these syntax groups, e.g., `["Comment", "String"]`.
+    ///
+    /// Both the syntax item under the cursor and the highlight group it's linked to are checked.
+    pub ignore_syntax_groups: Vec<String>,
+    /// Skip the cursor word if it's shorter than this number of chars.
+    pub min_word_len: usize,
 }
 
 impl Default for HighlightCursorWordConfig {
@@ -98,25 +482,73 @@ impl Default for HighlightCursorWordConfig {
             enable: false,
             ignore_comment_line: false,
             ignore_files: "*.toml,*.json,*.yml,*.log,tmp".to_string(),
+            ignore_filetypes: Vec::new(),
+            ignore_syntax_groups: Vec::new(),
+            min_word_len: 0,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
 #[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
 pub struct MarkdownTocConfig {
     /// Whether to enable this plugin.
     pub enable: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct YankHistoryConfig {
+    /// Whether to enable this plugin.
+    ///
+    /// The yanked text is recorded on `TextYankPost` and persisted in the data directory, which
+    /// is then listed by the yanks provider.
+    pub enable: bool,
+    /// Maximum number of the yank history entries.
+    pub max_entries: usize,
+    /// Skip the yanked text if it's shorter than this number of chars.
+    pub min_len: usize,
+}
+
+impl Default for YankHistoryConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            max_entries: 100,
+            min_len: 1,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
 #[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
 pub struct PluginConfig {
     pub highlight_cursor_word: HighlightCursorWordConfig,
     pub markdown_toc: MarkdownTocConfig,
+    pub yank_history: YankHistoryConfig,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Whether to load the project-local config file.
+    pub enable: bool,
+    /// Path of the project-local config file relative to the project root.
+    ///
+    /// The project-local config is deep-merged over the global config.
+    pub file_name: String,
+}
+
+impl Default for ProjectConfig {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            file_name: ".vim-clap/config.toml".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
 #[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
 pub struct IgnoreConfig {
     /// Whether to ignore the comment line when it's possible.
@@ -129,14 +561,674 @@ pub struct IgnoreConfig {
     pub file_path_pattern: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
 #[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
 pub struct InputHistoryConfig {
     /// Whether to share the input history of each provider.
     pub share_all_inputs: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct NeovimConfig {
+    /// Whether to attach to the msgpack-rpc server of Neovim specified by `$NVIM`.
+    ///
+    /// Once attached, the buffer lines are read via the Neovim API directly and the
+    /// highlights of plugins are added as extmarks.
+    pub attach_rpc: bool,
+}
+
+/// Actions that can be bound to the keys in the provider.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProviderAction {
+    ScrollPreviewUp,
+    ScrollPreviewDown,
+    NextInput,
+    PreviousInput,
+    /// Show or hide the preview window.
+    TogglePreview,
+    /// Cycle the case matching: smart -> ignore -> respect.
+    CycleCaseMatching,
+    /// Send the multi-selected entries or all the matched entries to the quickfix list.
+    SendToQuickfix,
+    /// Kill the source command of the provider if it's still running and run it again.
+    RestartCommand,
+    /// Include or skip the git submodules in the files and grep searches.
+    ToggleSubmodules,
+    /// Switch `recent_files` between the files under the project root and all the files.
+    ToggleProjectScope,
+    /// Open the files of the multi-selected entries, or the current entry, in splits.
+    OpenInSplits,
+    /// Open the files of the multi-selected entries, or the current entry, in vertical splits.
+    OpenInVsplits,
+    /// Open the files of the multi-selected entries, or the current entry, in tabs.
+    OpenInTabs,
+    /// Add the files of the multi-selected entries, or the current entry, to the arglist.
+    AddToArglist,
+    /// Overlay labels on the visible entries, typing a label accepts the entry.
+    QuickSelect,
+    /// Prompt for the log filter and apply it at runtime, e.g., `maple_core::searcher=trace`.
+    SetLogLevel,
+}
+
+/// Alias of a provider with the preset arguments.
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ProviderAlias {
+    /// Id of the provider to invoke.
+    pub provider: String,
+    /// Arguments prepended to the ones specified on invoking the alias.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct TodosConfig {
+    /// Tags searched by the `todos` provider.
+    ///
+    /// `FIXME` and `XXX` are classified as high severity, `HACK` as medium, the others as low.
+    pub tags: Vec<String>,
+}
+
+impl Default for TodosConfig {
+    fn default() -> Self {
+        Self {
+            tags: ["TODO", "FIXME", "HACK", "XXX"]
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct DocsConfig {
+    /// Directory of the docs downloaded from devdocs, each doc is stored in a sub-directory named
+    /// by its slug containing `index.json` and `db.json`.
+    ///
+    /// Default is `devdocs` under the data directory.
+    pub dir: Option<String>,
+    /// Slugs of the docs searched for each filetype, e.g., `python = ["python~3.11"]`.
+    ///
+    /// By default, the docs whose slug is the filetype or starts with `{filetype}~` are searched.
+    pub filetypes: HashMap<String, Vec<String>>,
+}
+
+/// Style of displaying the file paths in the list.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum PathDisplayStyle {
+    /// As is.
+    #[default]
+    Full,
+    /// `~` for the home directory.
+    Tilde,
+    /// Relative to the project root, `~` for the home directory outside of it.
+    Relative,
+    /// Like `relative` with the parent directories shortened to their first chars, e.g.,
+    /// `s/c/p/file.rs`.
+    Shorten,
+}
+
+impl PathDisplayStyle {
+    /// Returns the [`printer::PathDisplay`] in the project `root`, `None` if the paths are
+    /// displayed as is.
+    pub fn path_display(&self, root: &Path) -> Option<printer::PathDisplay> {
+        let home = Some(crate::dirs::HOME.to_string_lossy().into_owned());
+        let root = Some(root.to_string_lossy().into_owned());
+        match self {
+            Self::Full => None,
+            Self::Tilde => Some(printer::PathDisplay::new(home, None, false)),
+            Self::Relative => Some(printer::PathDisplay::new(home, root, false)),
+            Self::Shorten => Some(printer::PathDisplay::new(home, root, true)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct RecentFilesConfig {
+    /// Time after which the weight of a visit is halved, e.g., `"30d"`.
+    pub half_life: HumanDuration,
+    /// Weight of a visit by opening the file in the editor.
+    pub open_weight: f64,
+    /// Weight of a visit by accepting the file in a picker.
+    pub picker_weight: f64,
+    /// Weight of a visit by writing the file.
+    pub edit_weight: f64,
+    /// Only list the recent files under the project root, the `toggle-project-scope` action
+    /// bound to `ctrl-r` switches to the global list.
+    pub project_scoped: bool,
+}
+
+impl Default for RecentFilesConfig {
+    fn default() -> Self {
+        Self {
+            half_life: HumanDuration(Duration::from_secs(30 * 24 * 60 * 60)),
+            open_weight: 1.0,
+            picker_weight: 1.5,
+            edit_weight: 2.0,
+            project_scoped: true,
+        }
+    }
+}
+
+/// Editor-side callbacks invoked on the provider events, specified by the function name.
+///
+/// Each callback is called with a dict containing `provider_id` and the event-specific
+/// fields. Lua functions can be specified as `v:lua.func_name` in Neovim.
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct ProviderHooks {
+    /// Invoked once the provider is initialized.
+    pub on_initialize_done: Vec<String>,
+    /// Invoked with `entries` once the entries are accepted.
+    pub on_entry_accepted: Vec<String>,
+    /// Invoked once the provider session ends.
+    pub on_session_terminated: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct ProviderConfig {
+    /// Maximum number of the matched items retained in memory.
+    ///
+    /// The ranked items beyond this cap are spilled to a temp file and paged back on scroll.
+    pub max_retained_items: usize,
+
+    /// Key bindings for all the providers, e.g., `ctrl-n = "toggle-preview"`.
+    ///
+    /// Available keys: `tab`, `backspace`, `cr`, `ctrl-n`, `ctrl-p`, `ctrl-q`, `ctrl-r`,
+    /// `shift-up`, `shift-down`. `ctrl-q` is bound to `send-to-quickfix` and `ctrl-r` to
+    /// `restart-command` by default, or to `toggle-project-scope` in `recent_files`.
+    ///
+    /// Any key notation of Vim can be bound as well, e.g., `"<C-x><C-o>" = "open-in-splits"`,
+    /// `"<M-1>" = "toggle-preview"`, which is mapped in the input window on starting the
+    /// provider.
+    #[serde(deserialize_with = "deserialize_keymaps")]
+    pub keymaps: HashMap<String, ProviderAction>,
+
+    /// Key bindings per provider, which take precedence over `keymaps`.
+    #[serde(deserialize_with = "deserialize_provider_keymaps")]
+    pub provider_keymaps: HashMap<String, HashMap<String, ProviderAction>>,
+
+    /// Provider aliases, e.g., `rust_files = { provider = "files", args = ["--ext", "rs"] }`
+    /// makes `:Clap rust_files` available.
+    pub aliases: HashMap<String, ProviderAlias>,
+
+    /// Callbacks on the provider events.
+    pub hooks: ProviderHooks,
+
+    /// Style of displaying the file paths per provider, e.g., `recent_files = "tilde"`.
+    ///
+    /// Only applies to the providers listing the file paths, e.g., `files`, `git_files`,
+    /// `recent_files`, `smart_open` and `grep`. The highlights follow the displayed paths and
+    /// the real paths are used on selection.
+    pub path_display: HashMap<String, PathDisplayStyle>,
+
+    /// Configuration of the `todos` provider.
+    pub todos: TodosConfig,
+
+    /// Configuration of the `docs` provider.
+    pub docs: DocsConfig,
+
+    /// Frecency of the `recent_files` provider.
+    pub recent_files: RecentFilesConfig,
+}
+
+impl Default for ProviderConfig {
+    fn default() -> Self {
+        Self {
+            max_retained_items: 100_000,
+            keymaps: HashMap::new(),
+            provider_keymaps: HashMap::new(),
+            aliases: HashMap::new(),
+            hooks: ProviderHooks::default(),
+            path_display: HashMap::new(),
+            todos: TodosConfig::default(),
+            docs: DocsConfig::default(),
+            recent_files: RecentFilesConfig::default(),
+        }
+    }
+}
+
+/// Rejects the keys which are neither a named key, e.g., `ctrl-n`, nor a key notation of Vim,
+/// e.g., `<C-x><C-o>`.
+fn check_keymaps<E: serde::de::Error>(keymaps: &HashMap<String, ProviderAction>) -> Result<(), E> {
+    match keymaps.keys().find(|key| {
+        crate::stdio_server::KeyEvent::from_name(key).is_none()
+            && !(key.starts_with('<') && key.ends_with('>'))
+    }) {
+        Some(key) => Err(E::custom(format!(
+            "invalid key `{key}` in the keymaps, expected a key name like `ctrl-n` or a key \
+            notation like `<C-x><C-o>`"
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn deserialize_keymaps<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, ProviderAction>, D::Error> {
+    let keymaps = HashMap::deserialize(deserializer)?;
+    check_keymaps::<D::Error>(&keymaps)?;
+    Ok(keymaps)
+}
+
+fn deserialize_provider_keymaps<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, HashMap<String, ProviderAction>>, D::Error> {
+    let provider_keymaps: HashMap<String, HashMap<String, ProviderAction>> =
+        HashMap::deserialize(deserializer)?;
+    provider_keymaps
+        .values()
+        .try_for_each(check_keymaps::<D::Error>)?;
+    Ok(provider_keymaps)
+}
+
+impl ProviderConfig {
+    /// Returns the key bindings in effect for the provider `provider_id`.
+    pub fn keymaps(&self, provider_id: &str) -> HashMap<&str, ProviderAction> {
+        let mut keymaps: HashMap<_, _> = self
+            .keymaps
+            .iter()
+            .map(|(key, action)| (key.as_str(), *action))
+            .collect();
+        if let Some(provider_keymaps) = self.provider_keymaps.get(provider_id) {
+            keymaps.extend(
+                provider_keymaps
+                    .iter()
+                    .map(|(key, action)| (key.as_str(), *action)),
+            );
+        }
+        keymaps
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Size budget of the cache directory in bytes.
+    ///
+    /// The least recently used cache files are evicted once the budget is exceeded, only the
+    /// cached command outputs and the project indexes are counted.
+    pub max_size: u64,
+
+    /// Whether to compress the cache files with zstd.
+    ///
+    /// The compressed cache files are decompressed on the fly when being read.
+    pub compress: bool,
+
+    /// How long the cache stays valid, e.g., `"10m"`, `"7d"`.
+    ///
+    /// `default` applies to all the providers, which can be overridden per provider, e.g.,
+    /// `files = "10m"`. The key is the provider id or the program of source command.
+    pub ttl: HashMap<String, HumanDuration>,
+
+    /// Pre-build the caches of some projects in the background.
+    pub warm_up: WarmUpConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct IndexerConfig {
+    /// Whether to maintain a persistent index of the project files, which is updated by
+    /// watching the file system.
+    ///
+    /// The files and grep providers use the index instead of walking the project once it's ready.
+    pub enable: bool,
+}
+
+/// Backend of the directory traversal.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum WalkerBackend {
+    /// Walker of the `ignore` crate, same as ripgrep.
+    #[default]
+    Ignore,
+    /// Batched directory reading using the platform specific syscalls, falls back to `ignore`
+    /// on the unsupported platforms.
+    Native,
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct WalkerConfig {
+    /// Backend used to traverse the directories in the files and grep providers.
+    ///
+    /// `"native"` reads the directory entries in batches and is faster on huge repos, it's
+    /// only available on Linux for now.
+    pub backend: WalkerBackend,
+
+    /// Whether to skip the hidden files and directories.
+    pub skip_hidden: bool,
+
+    /// Whether to follow the symbolic links.
+    pub follow_symlinks: bool,
+
+    /// Whether to respect the ignore files, i.e., `.ignore`, `.gitignore`, the global gitignore
+    /// and `.git/info/exclude`.
+    pub respect_ignore_files: bool,
+
+    /// Extra gitignore-style globs to ignore on top of the ignore files, e.g.,
+    /// `["node_modules/", "*.min.js"]`.
+    pub ignore_globs: Vec<String>,
+
+    /// Maximum depth of the directories to walk into, unlimited by default.
+    pub max_depth: Option<usize>,
+
+    /// Only walk the files of these types if not empty, e.g., `["rust", "py"]`, the types are
+    /// the same with `rg --type-list`.
+    pub file_types: Vec<String>,
+
+    /// Whether to descend into the git submodules, can be toggled at runtime by the
+    /// `toggle-submodules` action.
+    pub submodules: bool,
+
+    /// Options per provider overriding the ones above, e.g., `[walker.provider.grep]`.
+    ///
+    /// The options are used by the files, grep, igrep, todos and filer providers and the project
+    /// indexer (`indexer`). The filer only applies `ignore-globs` and `file-types`.
+    pub provider: HashMap<String, WalkerOverrides>,
+}
+
+impl Default for WalkerConfig {
+    fn default() -> Self {
+        Self {
+            backend: WalkerBackend::default(),
+            skip_hidden: true,
+            follow_symlinks: true,
+            respect_ignore_files: true,
+            ignore_globs: Vec::new(),
+            max_depth: None,
+            file_types: Vec::new(),
+            submodules: true,
+            provider: HashMap::new(),
+        }
+    }
+}
+
+impl WalkerConfig {
+    /// Returns the walk options of `provider_id`, the global ones if `provider_id` is `None`.
+    pub fn walk_config(&self, provider_id: Option<&str>) -> crate::searcher::WalkConfig {
+        let overrides = provider_id.and_then(|provider_id| self.provider.get(provider_id));
+        let get = |global: bool, f: fn(&WalkerOverrides) -> Option<bool>| {
+            overrides.and_then(f).unwrap_or(global)
+        };
+
+        let respect_ignore_files = get(self.respect_ignore_files, |o| o.respect_ignore_files);
+
+        crate::searcher::WalkConfig {
+            hidden: get(self.skip_hidden, |o| o.skip_hidden),
+            follow_symlinks: get(self.follow_symlinks, |o| o.follow_symlinks),
+            parents: respect_ignore_files,
+            ignore: respect_ignore_files,
+            git_ignore: respect_ignore_files,
+            git_global: respect_ignore_files,
+            git_exclude: respect_ignore_files,
+            max_depth: overrides.and_then(|o| o.max_depth).or(self.max_depth),
+            ignore_globs: overrides
+                .and_then(|o| o.ignore_globs.clone())
+                .unwrap_or_else(|| self.ignore_globs.clone()),
+            file_types: overrides
+                .and_then(|o| o.file_types.clone())
+                .unwrap_or_else(|| self.file_types.clone()),
+            submodules: get(self.submodules, |o| o.submodules),
+        }
+    }
+}
+
+/// Walker options of a provider, the unspecified ones fall back to the global options.
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct WalkerOverrides {
+    pub skip_hidden: Option<bool>,
+    pub follow_symlinks: Option<bool>,
+    pub respect_ignore_files: Option<bool>,
+    pub ignore_globs: Option<Vec<String>>,
+    pub max_depth: Option<usize>,
+    pub file_types: Option<Vec<String>>,
+    pub submodules: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct GrepConfig {
+    /// Stop scanning once enough matches are found instead of searching all the files.
+    pub early_stop: bool,
+
+    /// Number of the extra matches collected beyond the displayed ones before stopping, the
+    /// larger the margin, the more likely the best matches are found.
+    pub early_stop_margin: usize,
+
+    /// Stop scanning a file once this many matches are found in it, so that a file with lots of
+    /// matches, e.g., a minified bundle, does not drown out the other files. Unlimited by default.
+    ///
+    /// The last match of a truncated file is marked with `[more matches in this file]`.
+    pub max_matches_per_file: Option<usize>,
+
+    /// Search the decompressed content of the `.gz`, `.xz` and `.zst` files like `rg -z`, the
+    /// matches are marked with `[decompressed]`.
+    ///
+    /// gzip and xz are decompressed by the `gzip` and `xz` executables, which must be in `$PATH`.
+    pub search_compressed: bool,
+}
+
+impl Default for GrepConfig {
+    fn default() -> Self {
+        Self {
+            early_stop: true,
+            early_stop_margin: 5000,
+            max_matches_per_file: None,
+            search_compressed: false,
+        }
+    }
+}
+
+impl GrepConfig {
+    /// Returns the maximum number of matches to collect given the number of displayed items.
+    pub fn match_limit(&self, display_limit: usize) -> Option<usize> {
+        self.early_stop
+            .then(|| display_limit + self.early_stop_margin)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct WarmUpConfig {
+    /// Root directories of the projects whose grep and project tags caches are built on startup.
+    pub projects: Vec<AbsPathBuf>,
+
+    /// Rebuild the caches periodically at this interval, e.g., `"12h"`.
+    ///
+    /// The caches are only built on startup if not set.
+    pub interval: Option<HumanDuration>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            // 1GiB
+            max_size: 1024 * 1024 * 1024,
+            compress: false,
+            ttl: HashMap::new(),
+            warm_up: Default::default(),
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Returns the time to live of the cache created by `provider`.
+    pub fn ttl(&self, provider: &str) -> Duration {
+        const DEFAULT_TTL: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+        self.ttl
+            .get(provider)
+            .or_else(|| self.ttl.get("default"))
+            .map(|ttl| ttl.0)
+            .unwrap_or(DEFAULT_TTL)
+    }
+}
+
+/// Duration in the form of a number followed by a unit, e.g., `30s`, `10m`, `12h`, `7d`, `2w`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HumanDuration(pub Duration);
+
+impl std::str::FromStr for HumanDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (number, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+        let number: u64 = number
+            .parse()
+            .map_err(|_| format!("invalid duration `{s}`, expected e.g. `10m`"))?;
+        let secs = match unit {
+            "s" => 1,
+            "m" => 60,
+            "h" => 60 * 60,
+            "d" => 24 * 60 * 60,
+            "w" => 7 * 24 * 60 * 60,
+            _ => {
+                return Err(format!(
+                    "invalid unit of duration `{s}`, expected one of s, m, h, d, w"
+                ))
+            }
+        };
+        number
+            .checked_mul(secs)
+            .map(|secs| Self(Duration::from_secs(secs)))
+            .ok_or_else(|| format!("duration `{s}` is too large"))
+    }
+}
+
+impl Serialize for HumanDuration {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&format!("{}s", self.0.as_secs()))
+    }
+}
+
+impl<'de> Deserialize<'de> for HumanDuration {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl JsonSchema for HumanDuration {
+    fn schema_name() -> String {
+        "HumanDuration".into()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
+/// Attributes of a highlight group, same as the arguments of `:highlight`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct HighlightAttrs {
+    pub guifg: Option<String>,
+    pub guibg: Option<String>,
+    pub guisp: Option<String>,
+    pub gui: Option<String>,
+    pub ctermfg: Option<String>,
+    pub ctermbg: Option<String>,
+    pub cterm: Option<String>,
+    /// Link to an existing highlight group, the other attributes are ignored if specified.
+    pub link: Option<String>,
+}
+
+impl HighlightAttrs {
+    /// Returns the `:highlight` command defining `group` with these attributes.
+    fn highlight_command(&self, group: &str) -> String {
+        if let Some(link) = &self.link {
+            return format!("hi! link {group} {link}");
+        }
+
+        let attrs = [
+            ("guifg", &self.guifg),
+            ("guibg", &self.guibg),
+            ("guisp", &self.guisp),
+            ("gui", &self.gui),
+            ("ctermfg", &self.ctermfg),
+            ("ctermbg", &self.ctermbg),
+            ("cterm", &self.cterm),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.as_ref().map(|value| format!("{key}={value}")))
+        .collect::<Vec<_>>();
+
+        format!("hi {group} {}", attrs.join(" "))
+    }
+}
+
+/// Theme palette, applied on startup and on changing the colorscheme.
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct PaletteConfig {
+    /// Matched characters in the results.
+    pub matches: Option<HighlightAttrs>,
+    /// Target line in the preview window, e.g., the line of a grep result.
+    pub preview_target_line: Option<HighlightAttrs>,
+    /// Icons in the results.
+    pub icon: Option<HighlightAttrs>,
+    /// Added lines and files in the git diff.
+    pub diff_added: Option<HighlightAttrs>,
+    /// Removed lines and files in the git diff.
+    pub diff_removed: Option<HighlightAttrs>,
+}
+
+impl PaletteConfig {
+    /// Returns the `:highlight` commands defining the highlight groups in the palette.
+    ///
+    /// `fuzzy_matches_groups` is the number of `ClapFuzzyMatches{N}` groups defined in Vim.
+    pub fn highlight_commands(&self, fuzzy_matches_groups: usize) -> Vec<String> {
+        let mut commands = Vec::new();
+
+        if let Some(matches) = &self.matches {
+            commands.push(matches.highlight_command("ClapMatches"));
+            commands.extend(
+                (1..=fuzzy_matches_groups)
+                    .map(|idx| matches.highlight_command(&format!("ClapFuzzyMatches{idx}"))),
+            );
+        }
+
+        let groups = [
+            ("ClapPreviewTargetLine", &self.preview_target_line),
+            ("ClapIcon", &self.icon),
+            ("ClapDiffAdded", &self.diff_added),
+            ("ClapDiffRemoved", &self.diff_removed),
+        ];
+
+        commands.extend(groups.into_iter().filter_map(|(group, maybe_attrs)| {
+            maybe_attrs
+                .as_ref()
+                .map(|attrs| attrs.highlight_command(group))
+        }));
+
+        commands
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub palette: PaletteConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
 #[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
 pub struct Config {
     /// Log configuration.
@@ -153,7 +1245,8 @@ pub struct Config {
 
     /// Ignore configuration per project.
     ///
-    /// The project path must be specified as absolute path or a path relative to the home directory.
+    /// The project path must be specified as absolute path or a path relative to the home directory,
+    /// environment variables like `$VAR`/`${VAR}` are expanded.
     pub project_ignore: HashMap<AbsPathBuf, IgnoreConfig>,
 
     /// Ignore configuration per provider.
@@ -164,6 +1257,33 @@ pub struct Config {
 
     /// Input history configuration
     pub input_history: InputHistoryConfig,
+
+    /// Neovim configuration.
+    pub neovim: NeovimConfig,
+
+    /// Project-local config file.
+    pub project_config: ProjectConfig,
+
+    /// Provider configuration.
+    pub provider: ProviderConfig,
+
+    /// Theme configuration.
+    pub theme: ThemeConfig,
+
+    /// Cache configuration.
+    pub cache: CacheConfig,
+
+    /// Indexer configuration.
+    pub indexer: IndexerConfig,
+
+    /// Directory walker configuration.
+    pub walker: WalkerConfig,
+
+    /// Builtin grep configuration.
+    pub grep: GrepConfig,
+
+    /// Data, cache and log directories.
+    pub dirs: DirsConfig,
 }
 
 impl Config {
@@ -180,6 +1300,23 @@ impl Config {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_expand_path() {
+        std::env::set_var("CLAP_TEST_EXPAND_PATH", "foo");
+        assert_eq!(
+            expand_path("/tmp/$CLAP_TEST_EXPAND_PATH/${CLAP_TEST_EXPAND_PATH}.log"),
+            PathBuf::from("/tmp/foo/foo.log")
+        );
+        assert_eq!(
+            expand_path("/tmp/$CLAP_TEST_UNDEFINED_VAR/${CLAP_TEST_UNDEFINED_VAR}/$"),
+            PathBuf::from("/tmp/$CLAP_TEST_UNDEFINED_VAR/${CLAP_TEST_UNDEFINED_VAR}/$")
+        );
+        assert_eq!(
+            expand_path("~/$CLAP_TEST_EXPAND_PATH"),
+            crate::dirs::BASE_DIRS.home_dir().join("foo")
+        );
+    }
+
     #[test]
     fn test_load_config() {
         let toml_content = r#"
@@ -201,9 +1338,198 @@ mod tests {
 
           [plugin.highlight-cursor-word]
           enable = true
+          ignore-filetypes = ["markdown", "text"]
+          ignore-syntax-groups = ["Comment", "String"]
+          min-word-len = 3
 "#;
         let user_config: Config = toml::from_str(toml_content).unwrap();
         println!("{user_config:?}");
         println!("{}", toml::to_string(&user_config).unwrap());
     }
+
+    #[test]
+    fn test_provider_keymaps() {
+        let toml_content = r#"
+          [provider.keymaps]
+          ctrl-n = "toggle-preview"
+          shift-up = "scroll-preview-up"
+
+          [provider.provider-keymaps.files]
+          ctrl-n = "cycle-case-matching"
+          "<C-o>" = "open-in-tabs"
+          "<C-x><C-o>" = "open-in-splits"
+
+          [provider.aliases]
+          rust_files = { provider = "files", args = ["--ext", "rs"] }
+"#;
+        let config: Config = toml::from_str(toml_content).unwrap();
+
+        let keymaps = config.provider.keymaps("grep");
+        assert_eq!(keymaps.get("ctrl-n"), Some(&ProviderAction::TogglePreview));
+
+        let keymaps = config.provider.keymaps("files");
+        assert_eq!(
+            keymaps.get("ctrl-n"),
+            Some(&ProviderAction::CycleCaseMatching)
+        );
+        assert_eq!(
+            keymaps.get("shift-up"),
+            Some(&ProviderAction::ScrollPreviewUp)
+        );
+        assert_eq!(keymaps.get("<C-o>"), Some(&ProviderAction::OpenInTabs));
+        assert_eq!(
+            keymaps.get("<C-x><C-o>"),
+            Some(&ProviderAction::OpenInSplits)
+        );
+
+        let alias = &config.provider.aliases["rust_files"];
+        assert_eq!(alias.provider, "files");
+        assert_eq!(alias.args, vec!["--ext", "rs"]);
+
+        // A bare key can not be mapped in Vim.
+        let toml_content = r#"
+          [provider.provider-keymaps.files]
+          o = "open-in-tabs"
+"#;
+        assert!(toml::from_str::<Config>(toml_content).is_err());
+    }
+
+    #[test]
+    fn test_walker_config() {
+        let toml_content = r#"
+          [walker]
+          ignore-globs = ["node_modules/"]
+          max-depth = 8
+
+          [walker.provider.grep]
+          skip-hidden = false
+          file-types = ["rust"]
+"#;
+        let config: Config = toml::from_str(toml_content).unwrap();
+
+        let walk_config = config.walker.walk_config(None);
+        assert!(walk_config.hidden);
+        assert_eq!(walk_config.max_depth, Some(8));
+        assert_eq!(walk_config.ignore_globs, vec!["node_modules/"]);
+
+        let walk_config = config.walker.walk_config(Some("grep"));
+        assert!(!walk_config.hidden);
+        assert_eq!(walk_config.max_depth, Some(8));
+        assert_eq!(walk_config.file_types, vec!["rust"]);
+
+        let walk_filter = walk_config.filter(Path::new("/tmp/project"));
+        assert!(walk_filter.is_excluded(Path::new("/tmp/project/node_modules"), true));
+        assert!(walk_filter.is_excluded(Path::new("/tmp/project/README.md"), false));
+        assert!(!walk_filter.is_excluded(Path::new("/tmp/project/src/lib.rs"), false));
+    }
+
+    #[test]
+    fn test_find_unknown_keys() {
+        let toml_content = r#"
+          [matcher]
+          tiebraek = "score"
+
+          [plugin.highlight-cursor-word]
+          ignore_files = "*.toml"
+          enable = true
+
+          [plugin.markdown-toc]
+          ignore-comment-line = true
+
+          [provider-ignore.dumb_jump]
+          comment-lines = true
+"#;
+        let value: toml::Value = toml::from_str(toml_content).unwrap();
+        let diagnostics = find_unknown_keys(&value);
+
+        let suggestions = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.key.as_str(), diagnostic.suggestion.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            suggestions,
+            vec![
+                ("matcher.tiebraek", Some("matcher.tiebreak")),
+                (
+                    "plugin.highlight-cursor-word.ignore_files",
+                    Some("plugin.highlight-cursor-word.ignore-files")
+                ),
+                (
+                    "plugin.markdown-toc.ignore-comment-line",
+                    Some("plugin.highlight-cursor-word.ignore-comment-line")
+                ),
+                (
+                    "provider-ignore.dumb_jump.comment-lines",
+                    Some("provider-ignore.dumb_jump.comment-line")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_palette_highlight_commands() {
+        let toml_content = r##"
+          [theme.palette]
+          matches = { guifg = "#87ff00", ctermfg = "118", gui = "bold" }
+          icon = { link = "Type" }
+"##;
+        let config: Config = toml::from_str(toml_content).unwrap();
+        assert_eq!(
+            config.theme.palette.highlight_commands(2),
+            vec![
+                "hi ClapMatches guifg=#87ff00 gui=bold ctermfg=118",
+                "hi ClapFuzzyMatches1 guifg=#87ff00 gui=bold ctermfg=118",
+                "hi ClapFuzzyMatches2 guifg=#87ff00 gui=bold ctermfg=118",
+                "hi! link ClapIcon Type",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cache_ttl() {
+        let toml_content = r#"
+          [cache.ttl]
+          default = "1d"
+          files = "10m"
+"#;
+        let config: Config = toml::from_str(toml_content).unwrap();
+        assert_eq!(config.cache.ttl("files"), Duration::from_secs(600));
+        assert_eq!(config.cache.ttl("grep"), Duration::from_secs(86400));
+        assert!("10x".parse::<HumanDuration>().is_err());
+        assert!("m".parse::<HumanDuration>().is_err());
+        assert!("18446744073709551615w".parse::<HumanDuration>().is_err());
+    }
+
+    #[test]
+    fn test_merge_project_config() {
+        let mut config_value: toml::Value = toml::from_str(
+            r#"
+          [global-ignore]
+          comment-line = true
+          file-path-pattern = ["test"]
+
+          [matcher]
+          tiebreak = "score,-begin,-end,-length"
+"#,
+        )
+        .unwrap();
+
+        let project_config_value: toml::Value = toml::from_str(
+            r#"
+          [global-ignore]
+          file-path-pattern = ["build", "vendor"]
+"#,
+        )
+        .unwrap();
+
+        merge_toml(&mut config_value, project_config_value);
+
+        let config: Config = config_value.try_into().unwrap();
+        assert!(config.global_ignore.comment_line);
+        assert_eq!(
+            config.global_ignore.file_path_pattern,
+            vec!["build".to_string(), "vendor".to_string()]
+        );
+        assert_eq!(config.matcher.tiebreak, "score,-begin,-end,-length");
+    }
 }
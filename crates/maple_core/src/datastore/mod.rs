@@ -1,9 +1,8 @@
 //! This module provides the feature of persistent data store via file system.
 
 use crate::cache::{CacheInfo, MAX_DIGESTS};
-use crate::dirs::PROJECT_DIRS;
 use crate::recent_files::SortedRecentFiles;
-use crate::stdio_server::InputHistory;
+use crate::stdio_server::{InputHistory, SessionSnapshot, YankRing};
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use serde::de::DeserializeOwned;
@@ -21,6 +20,9 @@ static CACHE_METADATA_PATH: Lazy<Option<PathBuf>> =
 pub static CACHE_INFO_IN_MEMORY: Lazy<Arc<Mutex<CacheInfo>>> = Lazy::new(|| {
     let mut maybe_persistent = load_json::<CacheInfo, _>(CACHE_METADATA_PATH.as_deref())
         .unwrap_or_else(|| CacheInfo::with_capacity(MAX_DIGESTS));
+    if let Some(legacy_cache_dir) = crate::dirs::legacy_cache_dir() {
+        maybe_persistent.relocate(legacy_cache_dir, crate::dirs::cache_dir());
+    }
     maybe_persistent.remove_invalid_and_old_entries();
     Arc::new(Mutex::new(maybe_persistent))
 });
@@ -33,16 +35,38 @@ static RECENT_FILES_JSON_PATH: Lazy<Option<PathBuf>> =
 
 pub static RECENT_FILES_IN_MEMORY: Lazy<Mutex<SortedRecentFiles>> = Lazy::new(|| {
     let maybe_persistent = load_json(RECENT_FILES_JSON_PATH.as_deref())
-        .map(|f: SortedRecentFiles| f.remove_invalid_entries())
+        .map(|f: SortedRecentFiles| f.remove_invalid_entries().migrate())
         .unwrap_or_default();
     Mutex::new(maybe_persistent)
 });
 
+/// Linux: ~/.local/share/vimclap/yank_history.json
+const YANK_HISTORY_FILENAME: &str = "yank_history.json";
+
+static YANK_HISTORY_JSON_PATH: Lazy<Option<PathBuf>> =
+    Lazy::new(|| generate_data_file_path(YANK_HISTORY_FILENAME).ok());
+
+pub static YANK_HISTORY_IN_MEMORY: Lazy<Mutex<YankRing>> =
+    Lazy::new(|| Mutex::new(load_json(YANK_HISTORY_JSON_PATH.as_deref()).unwrap_or_default()));
+
+/// Linux: ~/.local/share/vimclap/input_history.json
+const INPUT_HISTORY_FILENAME: &str = "input_history.json";
+
+static INPUT_HISTORY_JSON_PATH: Lazy<Option<PathBuf>> =
+    Lazy::new(|| generate_data_file_path(INPUT_HISTORY_FILENAME).ok());
+
 pub static INPUT_HISTORY_IN_MEMORY: Lazy<Arc<Mutex<InputHistory>>> = Lazy::new(|| {
-    // TODO: make input history persistent?
-    Arc::new(Mutex::new(InputHistory::new()))
+    Arc::new(Mutex::new(
+        load_json(INPUT_HISTORY_JSON_PATH.as_deref()).unwrap_or_default(),
+    ))
 });
 
+/// Linux: ~/.local/share/vimclap/last_session.json
+const LAST_SESSION_FILENAME: &str = "last_session.json";
+
+static LAST_SESSION_JSON_PATH: Lazy<Option<PathBuf>> =
+    Lazy::new(|| generate_data_file_path(LAST_SESSION_FILENAME).ok());
+
 pub fn store_cache_info(cache_info: &CacheInfo) -> std::io::Result<()> {
     write_json(cache_info, CACHE_METADATA_PATH.as_ref())
 }
@@ -51,20 +75,36 @@ pub fn store_recent_files(recent_files: &SortedRecentFiles) -> std::io::Result<(
     write_json(recent_files, RECENT_FILES_JSON_PATH.as_ref())
 }
 
+pub fn store_yank_history(yank_ring: &YankRing) -> std::io::Result<()> {
+    write_json(yank_ring, YANK_HISTORY_JSON_PATH.as_ref())
+}
+
+pub fn store_input_history(input_history: &InputHistory) -> std::io::Result<()> {
+    write_json(input_history, INPUT_HISTORY_JSON_PATH.as_ref())
+}
+
+pub fn store_last_session(snapshot: &SessionSnapshot) -> std::io::Result<()> {
+    write_json(snapshot, LAST_SESSION_JSON_PATH.as_ref())
+}
+
+pub fn load_last_session() -> Option<SessionSnapshot> {
+    load_json(LAST_SESSION_JSON_PATH.as_deref())
+}
+
 pub fn cache_metadata_path() -> Option<&'static PathBuf> {
     CACHE_METADATA_PATH.as_ref()
 }
 
 /// Returns a `PathBuf` using given file name under the project data directory.
 pub fn generate_data_file_path(filename: &str) -> std::io::Result<PathBuf> {
-    let data_dir = PROJECT_DIRS.data_dir();
+    let data_dir = crate::dirs::data_dir();
     std::fs::create_dir_all(data_dir)?;
     Ok(data_dir.join(filename))
 }
 
 /// Returns a `PathBuf` using given file name under the project cache directory.
 pub fn generate_cache_file_path(filename: impl AsRef<Path>) -> std::io::Result<PathBuf> {
-    let cache_dir = PROJECT_DIRS.cache_dir();
+    let cache_dir = crate::dirs::cache_dir();
     std::fs::create_dir_all(cache_dir)?;
     Ok(cache_dir.join(filename))
 }
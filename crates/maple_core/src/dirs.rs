@@ -1,10 +1,12 @@
 use directories::{BaseDirs, ProjectDirs};
 use once_cell::sync::Lazy;
-use std::path::PathBuf;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 
 /// Project directory for Vim Clap.
 ///
-/// All the files generated by vim-clap are stored there.
+/// The config file is stored there, the data and cache directories default to it unless
+/// overridden, see [`data_dir`] and [`cache_dir`].
 pub static PROJECT_DIRS: Lazy<ProjectDirs> = Lazy::new(|| {
     ProjectDirs::from("org", "vim", "Vim Clap")
         .expect("Couldn't create project directory for vim-clap")
@@ -15,8 +17,221 @@ pub static BASE_DIRS: Lazy<BaseDirs> =
 
 pub static HOME: Lazy<PathBuf> = Lazy::new(|| BASE_DIRS.home_dir().to_path_buf());
 
+/// Name of the vim-clap directory under the base directories given by the user or XDG, which
+/// may be shared with the other programs.
+const DIR_NAME: &str = "vimclap";
+
+static DATA_DIR: Lazy<PathBuf> = Lazy::new(|| {
+    let configured = crate::config::try_config().and_then(|c| c.dirs.data_dir.as_deref());
+    let dir = select_dir(
+        std::env::var_os("VIM_CLAP_DATA_DIR"),
+        configured,
+        std::env::var_os("XDG_DATA_HOME"),
+        PROJECT_DIRS.data_dir(),
+    );
+    migrate_legacy_dir(PROJECT_DIRS.data_dir(), &dir);
+    dir
+});
+
+static CACHE_DIR: Lazy<PathBuf> = Lazy::new(|| {
+    let configured = crate::config::try_config().and_then(|c| c.dirs.cache_dir.as_deref());
+    let dir = select_dir(
+        std::env::var_os("VIM_CLAP_CACHE_DIR"),
+        configured,
+        std::env::var_os("XDG_CACHE_HOME"),
+        PROJECT_DIRS.cache_dir(),
+    );
+    migrate_legacy_dir(PROJECT_DIRS.cache_dir(), &dir);
+    dir
+});
+
+/// Returns the directory of the persistent data.
+///
+/// Priorities: `$VIM_CLAP_DATA_DIR/vimclap` > `dirs.data-dir/vimclap` >
+/// `$XDG_DATA_HOME/vimclap` > the platform data directory.
+pub fn data_dir() -> &'static Path {
+    &DATA_DIR
+}
+
+/// Returns the directory of the caches.
+///
+/// Priorities: `$VIM_CLAP_CACHE_DIR/vimclap` > `dirs.cache-dir/vimclap` >
+/// `$XDG_CACHE_HOME/vimclap` > the platform cache directory.
+pub fn cache_dir() -> &'static Path {
+    &CACHE_DIR
+}
+
+/// Returns the directory of the log file if any, `$VIM_CLAP_LOG_DIR` > `dirs.log-dir`.
+pub fn log_dir() -> Option<PathBuf> {
+    match std::env::var_os("VIM_CLAP_LOG_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => crate::config::try_config()
+            .and_then(|c| c.dirs.log_dir.as_deref())
+            .map(crate::config::expand_path),
+    }
+}
+
+/// Returns the legacy cache directory, i.e., the platform one, if the caches have been moved
+/// elsewhere.
+pub fn legacy_cache_dir() -> Option<&'static Path> {
+    let legacy = PROJECT_DIRS.cache_dir();
+    (legacy != cache_dir()).then_some(legacy)
+}
+
 pub fn clap_cache_dir() -> std::io::Result<PathBuf> {
-    let cache_dir = PROJECT_DIRS.cache_dir();
+    let cache_dir = cache_dir();
     std::fs::create_dir_all(cache_dir)?;
     Ok(cache_dir.to_path_buf())
 }
+
+fn select_dir(
+    env_dir: Option<OsString>,
+    configured: Option<&str>,
+    xdg_home: Option<OsString>,
+    platform_dir: &Path,
+) -> PathBuf {
+    if let Some(dir) = env_dir.filter(|dir| !dir.is_empty()) {
+        return PathBuf::from(dir).join(DIR_NAME);
+    }
+
+    if let Some(dir) = configured {
+        return crate::config::expand_path(dir).join(DIR_NAME);
+    }
+
+    // The relative paths in the XDG variables are invalid per the spec and ignored.
+    if cfg!(unix) {
+        if let Some(xdg_home) = xdg_home.map(PathBuf::from).filter(|p| p.is_absolute()) {
+            return xdg_home.join(DIR_NAME);
+        }
+    }
+
+    platform_dir.to_path_buf()
+}
+
+/// Moves the files in the legacy directory to `dir` on the first run with `dir`, i.e., when
+/// `dir` doesn't exist yet. `dir` inside the legacy directory is left as is, it can not be moved
+/// into itself.
+///
+/// The config file is left in place as the config dir may be the same as the legacy one, e.g.,
+/// on macOS.
+fn migrate_legacy_dir(legacy: &Path, dir: &Path) {
+    if dir.starts_with(legacy) || dir.exists() || !legacy.is_dir() {
+        return;
+    }
+
+    let config_dir = PROJECT_DIRS.config_dir();
+
+    let migrate = || -> std::io::Result<usize> {
+        std::fs::create_dir_all(dir)?;
+        let mut moved = 0;
+        for entry in std::fs::read_dir(legacy)? {
+            let from = entry?.path();
+            if config_dir.starts_with(&from) || from == config_dir.join("config.toml") {
+                continue;
+            }
+            let to = dir.join(from.file_name().expect("Entry must have a file name"));
+            move_path(&from, &to)?;
+            moved += 1;
+        }
+        Ok(moved)
+    };
+
+    match migrate() {
+        Ok(moved) => tracing::info!(?legacy, ?dir, moved, "Migrated the legacy directory"),
+        Err(err) => tracing::error!(
+            ?err,
+            ?legacy,
+            ?dir,
+            "Failed to migrate the legacy directory"
+        ),
+    }
+}
+
+/// Moves `from` to `to`, falling back to copy and remove across the filesystems.
+fn move_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    if from.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            move_path(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        std::fs::remove_dir(from)
+    } else {
+        std::fs::copy(from, to)?;
+        std::fs::remove_file(from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_dir() {
+        let platform_dir = Path::new("/home/xlc/.local/share/vimclap");
+
+        assert_eq!(
+            select_dir(
+                Some("/env/clap".into()),
+                Some("/config/clap"),
+                Some("/xdg".into()),
+                platform_dir
+            ),
+            PathBuf::from("/env/clap/vimclap")
+        );
+        assert_eq!(
+            select_dir(
+                Some("".into()),
+                Some("/config/clap"),
+                Some("/xdg".into()),
+                platform_dir
+            ),
+            PathBuf::from("/config/clap/vimclap")
+        );
+        if cfg!(unix) {
+            assert_eq!(
+                select_dir(None, None, Some("/xdg".into()), platform_dir),
+                PathBuf::from("/xdg/vimclap")
+            );
+        }
+        assert_eq!(
+            select_dir(None, None, Some("relative/xdg".into()), platform_dir),
+            platform_dir
+        );
+        assert_eq!(select_dir(None, None, None, platform_dir), platform_dir);
+    }
+
+    #[test]
+    fn test_migrate_legacy_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let legacy = root.path().join("legacy");
+        std::fs::create_dir_all(legacy.join("tags")).unwrap();
+        std::fs::write(legacy.join("recent_files.json"), "{}").unwrap();
+        std::fs::write(legacy.join("tags").join("foo"), "bar").unwrap();
+
+        let dir = root.path().join("new");
+        migrate_legacy_dir(&legacy, &dir);
+
+        assert!(dir.join("recent_files.json").is_file());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("tags").join("foo")).unwrap(),
+            "bar"
+        );
+        assert!(!legacy.join("recent_files.json").exists());
+
+        // Only migrated once.
+        std::fs::write(legacy.join("input_history.json"), "{}").unwrap();
+        migrate_legacy_dir(&legacy, &dir);
+        assert!(!dir.join("input_history.json").exists());
+
+        // The new dir inside the legacy one is not migrated.
+        let inner = legacy.join("data");
+        migrate_legacy_dir(&legacy, &inner);
+        assert!(!inner.exists());
+        assert!(legacy.join("input_history.json").is_file());
+    }
+}
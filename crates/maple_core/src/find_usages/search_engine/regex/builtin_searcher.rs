@@ -0,0 +1,169 @@
+use crate::tools::rg::{Data, Match, SubMatch};
+use ignore::overrides::OverrideBuilder;
+use ignore::types::TypesBuilder;
+use ignore::WalkBuilder;
+use rayon::prelude::*;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+
+/// Files to search, in the sense of `rg -g` or `rg --type`.
+#[derive(Debug, Clone)]
+pub enum FileFilter {
+    /// File extension.
+    Extension(String),
+    /// Language type defined by ripgrep.
+    Type(String),
+}
+
+/// Fallback of the rg searches when rg is unavailable, it produces the same matches as
+/// `rg --json` but the regexp is limited to the syntax of the `regex` crate.
+#[derive(Debug, Clone)]
+pub struct BuiltinSearcher {
+    pub dir: Option<PathBuf>,
+    pub regexp: String,
+    /// `rg --word-regexp`.
+    pub word_regexp: bool,
+    /// `rg --trim`.
+    pub trim: bool,
+    pub file_filter: FileFilter,
+}
+
+fn invalid_input(err: impl ToString) -> Error {
+    Error::new(ErrorKind::InvalidInput, err.to_string())
+}
+
+impl BuiltinSearcher {
+    pub fn search(&self) -> Result<Vec<Match>> {
+        let regexp = if self.word_regexp {
+            format!(r"\b(?:{})\b", self.regexp)
+        } else {
+            self.regexp.clone()
+        };
+        let re = regex::Regex::new(&regexp).map_err(invalid_input)?;
+
+        let root = self.dir.clone().unwrap_or_else(|| PathBuf::from("."));
+
+        let mut walk_builder = WalkBuilder::new(&root);
+        match &self.file_filter {
+            FileFilter::Extension(ext) => {
+                let mut override_builder = OverrideBuilder::new(&root);
+                override_builder
+                    .add(&format!("*.{ext}"))
+                    .map_err(invalid_input)?;
+                walk_builder.overrides(override_builder.build().map_err(invalid_input)?);
+            }
+            FileFilter::Type(lang) => {
+                let mut types_builder = TypesBuilder::new();
+                types_builder.add_defaults();
+                types_builder.select(lang);
+                walk_builder.types(types_builder.build().map_err(invalid_input)?);
+            }
+        }
+
+        let files = walk_builder
+            .build()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().map_or(false, |ft| ft.is_file()))
+            .map(|entry| entry.into_path())
+            .collect::<Vec<_>>();
+
+        Ok(files
+            .par_iter()
+            .flat_map_iter(|path| {
+                // Same as the paths printed by rg, relative to the search directory.
+                let display_path = path.strip_prefix(&root).unwrap_or(path);
+                search_file(path, display_path, &re, self.trim)
+            })
+            .collect())
+    }
+}
+
+fn search_file(path: &Path, display_path: &Path, re: &regex::Regex, trim: bool) -> Vec<Match> {
+    // The binary files and the files not in UTF-8 are skipped.
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    let display_path = display_path.to_string_lossy();
+
+    let mut matches = Vec::new();
+    let mut absolute_offset = 0;
+
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let line_offset = absolute_offset;
+        absolute_offset += line.len();
+
+        let text = line.trim_end_matches(['\n', '\r']);
+        let trimmed = if trim {
+            text.len() - text.trim_start().len()
+        } else {
+            0
+        };
+
+        let submatches = re
+            .find_iter(text)
+            .map(|m| SubMatch {
+                m: Data::Text {
+                    text: m.as_str().to_string(),
+                },
+                start: m.start().saturating_sub(trimmed),
+                end: m.end().saturating_sub(trimmed),
+            })
+            .collect::<Vec<_>>();
+
+        if submatches.is_empty() {
+            continue;
+        }
+
+        matches.push(Match {
+            path: Data::Text {
+                text: display_path.to_string(),
+            },
+            lines: Data::Text {
+                text: line[trimmed..].to_string(),
+            },
+            line_number: Some(index as u64 + 1),
+            absolute_offset: line_offset as u64,
+            submatches,
+        });
+    }
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_searcher() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = tempdir.path();
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(
+            dir.join("src").join("lib.rs"),
+            "fn foo() {}\n    let foobar = foo();\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("notes.txt"), "foo\n").unwrap();
+
+        let searcher = BuiltinSearcher {
+            dir: Some(dir.to_path_buf()),
+            regexp: "foo".into(),
+            word_regexp: true,
+            trim: true,
+            file_filter: FileFilter::Extension("rs".into()),
+        };
+        let matches = searcher.search().unwrap();
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(
+            matches[0].path(),
+            Path::new("src").join("lib.rs").to_string_lossy()
+        );
+        assert_eq!(matches[0].column(), 3);
+        assert_eq!(matches[1].line_number(), 2);
+        assert_eq!(matches[1].pattern(), "let foobar = foo();\n");
+        assert_eq!(matches[1].column(), 13);
+    }
+}
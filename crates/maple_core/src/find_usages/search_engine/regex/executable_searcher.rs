@@ -1,7 +1,9 @@
+use super::builtin_searcher::{BuiltinSearcher, FileFilter};
 use super::definition::{
     build_full_regexp, get_definition_rules, is_comment, DefinitionKind, DefinitionSearchResult,
     Definitions, Occurrences,
 };
+use crate::tooling::Tool;
 use crate::tools::rg::{Match, Word};
 use dumb_analyzer::get_comment_syntax;
 use rayon::prelude::*;
@@ -11,25 +13,46 @@ use std::path::PathBuf;
 use std::process::Command;
 
 /// Searches a directory for pattern matches using ripgrep.
+///
+/// The builtin searcher is used instead if rg is unavailable or lacks PCRE2 required by
+/// `command`.
 #[derive(Debug)]
 pub struct ExecutableSearcher {
     command: Command,
+    fallback: BuiltinSearcher,
+    requires_pcre2: bool,
 }
 
 impl ExecutableSearcher {
-    fn new(command: Command) -> Self {
-        Self { command }
+    fn new(command: Command, fallback: BuiltinSearcher) -> Self {
+        Self {
+            command,
+            fallback,
+            requires_pcre2: false,
+        }
+    }
+
+    fn requires_pcre2(mut self) -> Self {
+        self.requires_pcre2 = true;
+        self
     }
 
     /// Executes `command` as a child process.
     ///
     /// Convert the entire output into a stream of ripgrep `Match`.
     fn search(self, maybe_comments: Option<&[&str]>) -> Result<Vec<Match>> {
-        if !*crate::tools::rg::RG_EXISTS {
-            return Err(Error::new(
-                ErrorKind::NotFound,
-                String::from("rg executable not found"),
-            ));
+        let is_wanted = |matched: &Match| {
+            maybe_comments
+                .map(|comments| !is_comment(matched, comments))
+                .unwrap_or(true)
+        };
+
+        if !crate::tooling::is_available(Tool::Rg)
+            || (self.requires_pcre2 && !crate::tooling::has_feature(Tool::Rg, "pcre2"))
+        {
+            let mut matches = self.fallback.search()?;
+            matches.retain(is_wanted);
+            return Ok(matches);
         }
 
         let mut cmd = self.command;
@@ -46,34 +69,40 @@ impl ExecutableSearcher {
         Ok(cmd_output
             .stdout
             .par_split(|x| x == &b'\n')
-            .filter_map(|s| {
-                Match::try_from(s).ok().filter(|matched| {
-                    maybe_comments
-                        .map(|comments| !is_comment(matched, comments))
-                        .unwrap_or(true)
-                })
-            })
+            .filter_map(|s| Match::try_from(s).ok().filter(is_wanted))
             .collect())
     }
 }
 
+/// Builds the rg command running in `maybe_dir` or the current directory.
+fn rg_command_in(maybe_dir: Option<&PathBuf>) -> Command {
+    match maybe_dir {
+        Some(dir) => crate::process::wsl::command_in("rg", dir),
+        None => Command::new("rg"),
+    }
+}
+
 pub(super) fn word_regex_search_with_extension(
     search_pattern: &str,
     ignore_comment: bool,
     file_extension: &str,
     maybe_dir: Option<&PathBuf>,
 ) -> Result<Vec<Match>> {
-    let mut command = Command::new("rg");
+    let mut command = rg_command_in(maybe_dir);
     command
         .arg("--json")
         .arg("--word-regexp")
         .arg(search_pattern)
         .arg("-g")
         .arg(format!("*.{file_extension}"));
-    if let Some(ref dir) = maybe_dir {
-        command.current_dir(dir);
-    }
-    ExecutableSearcher::new(command).search(if ignore_comment {
+    let fallback = BuiltinSearcher {
+        dir: maybe_dir.cloned(),
+        regexp: search_pattern.to_string(),
+        word_regexp: true,
+        trim: false,
+        file_filter: FileFilter::Extension(file_extension.to_string()),
+    };
+    ExecutableSearcher::new(command, fallback).search(if ignore_comment {
         Some(get_comment_syntax(file_extension))
     } else {
         None
@@ -96,6 +125,16 @@ impl LanguageRegexSearcher {
         Self { dir, word, lang }
     }
 
+    fn fallback_searcher(&self, regexp: String, word_regexp: bool, trim: bool) -> BuiltinSearcher {
+        BuiltinSearcher {
+            dir: self.dir.clone(),
+            regexp,
+            word_regexp,
+            trim,
+            file_filter: FileFilter::Type(self.lang.clone()),
+        }
+    }
+
     /// Finds the occurrences and all definitions concurrently.
     pub fn all(&self, comments: &[&str]) -> (Definitions, Occurrences) {
         let (definitions, occurrences) = (self.definitions(), self.occurrences(comments));
@@ -127,50 +166,47 @@ impl LanguageRegexSearcher {
     ///
     /// Basically the occurrences are composed of definitions and usages.
     fn occurrences(&self, comments: &[&str]) -> Result<Vec<Match>> {
-        let mut command = Command::new("rg");
+        let mut command = rg_command_in(self.dir.as_ref());
         command
             .arg("--json")
             .arg("--word-regexp")
             .arg(&self.word.raw)
             .arg("--type")
             .arg(&self.lang);
-        if let Some(ref dir) = self.dir {
-            command.current_dir(dir);
-        }
-        ExecutableSearcher::new(command).search(Some(comments))
+        let fallback = self.fallback_searcher(self.word.raw.clone(), true, false);
+        ExecutableSearcher::new(command, fallback).search(Some(comments))
     }
 
     pub(super) fn regexp_search(&self, comments: &[&str]) -> Result<Vec<Match>> {
-        let mut command = Command::new("rg");
+        let regexp = self.word.raw.replace(char::is_whitespace, ".*");
+        let mut command = rg_command_in(self.dir.as_ref());
         command
             .arg("--json")
             .arg("--regexp")
-            .arg(self.word.raw.replace(char::is_whitespace, ".*"))
+            .arg(&regexp)
             .arg("--type")
             .arg(&self.lang);
-        if let Some(ref dir) = self.dir {
-            command.current_dir(dir);
-        }
-        ExecutableSearcher::new(command).search(Some(comments))
+        let fallback = self.fallback_searcher(regexp, false, false);
+        ExecutableSearcher::new(command, fallback).search(Some(comments))
     }
 
     /// Returns a tuple of (definition_kind, ripgrep_matches) by searching given language `lang`.
     fn find_definitions(&self, kind: &DefinitionKind) -> Result<(DefinitionKind, Vec<Match>)> {
         let regexp = build_full_regexp(&self.lang, kind, &self.word)
             .ok_or_else(|| Error::new(ErrorKind::Other, "Can not find the definition rule"))?;
-        let mut command = Command::new("rg");
+        let mut command = rg_command_in(self.dir.as_ref());
         command
             .arg("--trim")
             .arg("--json")
             .arg("--pcre2")
             .arg("--regexp")
-            .arg(regexp)
+            .arg(&regexp)
             .arg("--type")
             .arg(&self.lang);
-        if let Some(ref dir) = self.dir {
-            command.current_dir(dir);
-        }
-        ExecutableSearcher::new(command)
+        // The definition rules not supported by the `regex` crate are skipped by the fallback.
+        let fallback = self.fallback_searcher(regexp, false, true);
+        ExecutableSearcher::new(command, fallback)
+            .requires_pcre2()
             .search(None)
             .map(|defs| (kind.clone(), defs))
     }
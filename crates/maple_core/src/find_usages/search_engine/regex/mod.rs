@@ -6,8 +6,10 @@
 //!
 //! # Dependency
 //!
-//! The executable rg with `--json` and `--pcre2` is required to be installed on the system.
+//! The executable rg with `--json` and `--pcre2` is preferred, the builtin searcher is used
+//! as a slower fallback without it.
 
+mod builtin_searcher;
 mod definition;
 mod executable_searcher;
 
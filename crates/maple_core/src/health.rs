@@ -0,0 +1,253 @@
+//! Health checks of the maple setup, used by `maple check` and the `health` provider.
+//!
+//! Each check reports what is wrong along with an actionable fix, covering the config file,
+//! the external tools and the directories maple writes to.
+
+use crate::tooling::Tool;
+use std::path::Path;
+
+/// Minimum versions of the tools, the older ones lack the options maple relies on.
+const MIN_VERSIONS: &[(Tool, &str)] = &[
+    // `--json` was introduced in ripgrep 0.10.0.
+    (Tool::Rg, "0.10.0"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    Ok,
+    Warn,
+    Error,
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            Self::Ok => "OK",
+            Self::Warn => "WARN",
+            Self::Error => "ERROR",
+        };
+        write!(f, "{status}")
+    }
+}
+
+/// Result of a health check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub status: Status,
+    /// What is checked, e.g., `config`, `rg`, `cache-dir`.
+    pub subject: String,
+    pub message: String,
+    /// How to fix the problem, always `None` for [`Status::Ok`].
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(subject: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            status: Status::Ok,
+            subject: subject.into(),
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn problem(
+        status: Status,
+        subject: impl Into<String>,
+        message: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            status,
+            subject: subject.into(),
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    /// Returns the line of the check, e.g., `[WARN ] fd: not found, fix: install fd`.
+    pub fn display_line(&self) -> String {
+        match &self.fix {
+            Some(fix) => format!(
+                "[{:<5}] {}: {}, fix: {fix}",
+                self.status, self.subject, self.message
+            ),
+            None => format!("[{:<5}] {}: {}", self.status, self.subject, self.message),
+        }
+    }
+}
+
+/// Runs all the health checks.
+///
+/// The config file must have been initialized.
+pub fn run_checks() -> Vec<Check> {
+    let mut checks = check_config();
+    checks.extend(Tool::ALL.into_iter().map(check_tool));
+    checks.push(check_dir(
+        "data-dir",
+        crate::dirs::data_dir(),
+        "VIM_CLAP_DATA_DIR",
+    ));
+    checks.push(check_dir(
+        "cache-dir",
+        crate::dirs::cache_dir(),
+        "VIM_CLAP_CACHE_DIR",
+    ));
+    if let Some(log_dir) = crate::dirs::log_dir() {
+        checks.push(check_dir("log-dir", &log_dir, "VIM_CLAP_LOG_DIR"));
+    }
+    checks
+}
+
+/// Returns the lines of all the health checks, the problems come first.
+pub fn check_lines() -> Vec<String> {
+    let mut checks = run_checks();
+    checks.sort_by_key(|check| std::cmp::Reverse(check.status));
+    checks.iter().map(Check::display_line).collect()
+}
+
+fn check_config() -> Vec<Check> {
+    let config_file = crate::config::config_file();
+
+    if !config_file.is_file() {
+        return vec![Check::ok(
+            "config",
+            format!("{} not found, the defaults are used", config_file.display()),
+        )];
+    }
+
+    let diagnostics = crate::config::config_diagnostics();
+
+    if diagnostics.is_empty() {
+        return vec![Check::ok("config", config_file.display().to_string())];
+    }
+
+    diagnostics
+        .into_iter()
+        .map(|diagnostic| {
+            let fix = match diagnostic.suggestion {
+                Some(suggestion) => format!("replace `{}` with `{suggestion}`", diagnostic.key),
+                None if diagnostic.key.is_empty() => {
+                    format!("correct {}", config_file.display())
+                }
+                None => format!("remove `{}`", diagnostic.key),
+            };
+            Check::problem(Status::Error, "config", diagnostic.message, fix)
+        })
+        .collect()
+}
+
+fn parse_version_numbers(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map(|n| n.parse().unwrap_or_default())
+        .collect()
+}
+
+fn check_tool(tool: Tool) -> Check {
+    let info = crate::tooling::tool_info(tool);
+    let executable = tool.executable();
+
+    if !info.available {
+        return match tool {
+            Tool::Fd | Tool::Gtags => Check::ok(executable, "not found, optional"),
+            Tool::Ctags => Check::problem(
+                Status::Warn,
+                executable,
+                "Universal Ctags not found",
+                "install Universal Ctags, Exuberant Ctags is unsupported",
+            ),
+            Tool::Rg | Tool::Git => Check::problem(
+                Status::Warn,
+                executable,
+                "not found",
+                format!("install {executable} and make sure it's in $PATH"),
+            ),
+        };
+    }
+
+    let version = info.version.as_deref().unwrap_or("unknown version");
+
+    if let Some((_, min_version)) = MIN_VERSIONS.iter().find(|(t, _)| *t == tool) {
+        if let Some(version) = &info.version {
+            if parse_version_numbers(version) < parse_version_numbers(min_version) {
+                return Check::problem(
+                    Status::Error,
+                    executable,
+                    format!("{version} is too old"),
+                    format!("upgrade {executable} to {min_version} or newer"),
+                );
+            }
+        }
+    }
+
+    match tool {
+        Tool::Ctags if !crate::tooling::has_feature(tool, "json") => Check::problem(
+            Status::Warn,
+            executable,
+            format!("{version} without the json feature, proj_tags is unavailable"),
+            "install a ctags build with the json feature (libjansson)",
+        ),
+        Tool::Rg if !crate::tooling::has_feature(tool, "pcre2") => Check::problem(
+            Status::Warn,
+            executable,
+            format!("{version} without PCRE2, dumb_jump falls back to the builtin search"),
+            "install a ripgrep build with the pcre2 feature",
+        ),
+        _ => Check::ok(executable, version),
+    }
+}
+
+/// Checks whether `dir` can be created and written.
+fn check_dir(subject: &str, dir: &Path, env_var: &str) -> Check {
+    let probe = || -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        let probe_file = dir.join(".maple_check");
+        std::fs::write(&probe_file, b"")?;
+        std::fs::remove_file(probe_file)
+    };
+
+    match probe() {
+        Ok(()) => Check::ok(subject, dir.display().to_string()),
+        Err(err) => Check::problem(
+            Status::Error,
+            subject,
+            format!("{} is not writable: {err}", dir.display()),
+            format!(
+                "fix the permissions or set ${env_var} or `dirs.{subject}` to a writable directory"
+            ),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_dir() {
+        let tempdir = tempfile::tempdir().unwrap();
+        // The missing directory is created.
+        let dir = tempdir.path().join("cache");
+
+        let check = check_dir("cache-dir", &dir, "VIM_CLAP_CACHE_DIR");
+        assert_eq!(check.status, Status::Ok);
+        assert!(dir.is_dir());
+        assert!(!dir.join(".maple_check").exists());
+
+        // A file in the way of the directory.
+        let file = dir.join("file");
+        std::fs::write(&file, "").unwrap();
+        let check = check_dir("cache-dir", &file.join("cache"), "VIM_CLAP_CACHE_DIR");
+        assert_eq!(check.status, Status::Error);
+        assert!(check
+            .display_line()
+            .ends_with("set $VIM_CLAP_CACHE_DIR or `dirs.cache-dir` to a writable directory"));
+    }
+
+    #[test]
+    fn test_parse_version_numbers() {
+        assert!(parse_version_numbers("0.9.0") < parse_version_numbers("0.10.0"));
+        assert!(parse_version_numbers("13.0.0") > parse_version_numbers("0.10.0"));
+    }
+}
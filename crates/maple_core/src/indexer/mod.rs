@@ -0,0 +1,277 @@
+//! Persistent index of the project files, updated incrementally by watching the file system.
+//!
+//! The index of a project is created on the first use and the searchers fall back to walking
+//! the directory until it's ready. The index is persisted in the cache directory so that it's
+//! available immediately in the next run, refreshed by a walk in the background.
+
+use crate::searcher::WalkConfig;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::Duration;
+
+static PROJECT_INDEXES: Lazy<Mutex<HashMap<PathBuf, Arc<ProjectIndex>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Returns the directory of the persisted indexes, which only contains the index files.
+pub(crate) fn index_dir() -> PathBuf {
+    crate::dirs::cache_dir().join("indexes")
+}
+
+/// Index of the files in a project.
+#[derive(Debug)]
+struct ProjectIndex {
+    root: PathBuf,
+    /// Options of walking the project, `[walker.provider.indexer]` in the config file.
+    walk_config: WalkConfig,
+    /// Paths relative to the project root.
+    files: RwLock<BTreeSet<String>>,
+    /// Whether the index is usable.
+    ready: AtomicBool,
+    /// The index is no longer updated once the watcher is dropped.
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+impl ProjectIndex {
+    fn new(root: PathBuf, walk_config: WalkConfig) -> Self {
+        Self {
+            root,
+            walk_config,
+            files: RwLock::new(BTreeSet::new()),
+            ready: AtomicBool::new(false),
+            watcher: Mutex::new(None),
+        }
+    }
+
+    fn index_file(&self) -> PathBuf {
+        index_dir().join(format!("{}.json", utils::calculate_hash(&self.root)))
+    }
+
+    fn load(&self) -> bool {
+        let Ok(content) = std::fs::read(self.index_file()) else {
+            return false;
+        };
+        match serde_json::from_slice::<BTreeSet<String>>(&content) {
+            Ok(files) => {
+                *self.files.write() = files;
+                self.ready.store(true, Ordering::SeqCst);
+                true
+            }
+            Err(err) => {
+                tracing::debug!(?err, root = ?self.root, "Failed to load the project index");
+                false
+            }
+        }
+    }
+
+    fn persist(&self) -> std::io::Result<()> {
+        let index_file = self.index_file();
+        if let Some(dir) = index_file.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_vec(&*self.files.read())?;
+        utils::create_or_overwrite(index_file, &content)
+    }
+
+    fn relative_path(&self, path: &Path) -> Option<String> {
+        path.strip_prefix(&self.root)
+            .ok()
+            .map(|p| p.to_string_lossy().to_string())
+    }
+
+    /// Rebuilds the index by walking the project.
+    fn rebuild(&self) {
+        let files = crate::searcher::walk(vec![self.root.clone()], self.walk_config.clone())
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                if entry.file_type()?.is_file() {
+                    self.relative_path(entry.path())
+                } else {
+                    None
+                }
+            })
+            .collect();
+        *self.files.write() = files;
+        self.ready.store(true, Ordering::SeqCst);
+    }
+
+    /// Applies the change of `path` to the index, returns true if the index is changed.
+    ///
+    /// A file is added only if it would be found by walking the project, the same rules as
+    /// [`Self::rebuild`].
+    fn update_path(&self, path: &Path) -> bool {
+        let Some(relative_path) = self.relative_path(path) else {
+            return false;
+        };
+        if path.is_file() {
+            self.walk_config.is_walked(&self.root, path) && self.files.write().insert(relative_path)
+        } else if !path.exists() {
+            // The files under the removed directory are removed as well.
+            let dir_prefix = format!("{relative_path}{}", std::path::MAIN_SEPARATOR);
+            let mut files = self.files.write();
+            let len = files.len();
+            files.retain(|f| f != &relative_path && !f.starts_with(&dir_prefix));
+            files.len() != len
+        } else {
+            false
+        }
+    }
+
+    /// Applies the file system events to the index, the index is persisted once there is no
+    /// event for a while.
+    fn handle_events(&self, events: Receiver<notify::Result<notify::Event>>) {
+        const PERSIST_DELAY: Duration = Duration::from_secs(5);
+
+        let mut dirty = false;
+
+        loop {
+            match events.recv_timeout(PERSIST_DELAY) {
+                Ok(Ok(event)) => {
+                    for path in event.paths {
+                        dirty |= self.update_path(&path);
+                    }
+                }
+                Ok(Err(err)) => tracing::debug!(?err, root = ?self.root, "Watch error"),
+                Err(RecvTimeoutError::Timeout) => {
+                    if dirty {
+                        if let Err(err) = self.persist() {
+                            tracing::debug!(?err, "Failed to persist the project index");
+                        }
+                        dirty = false;
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+}
+
+/// Starts indexing the project at `root` in the background.
+fn start_indexing(index: Arc<ProjectIndex>) {
+    std::thread::Builder::new()
+        .name("indexer".into())
+        .spawn(move || {
+            index.load();
+
+            let (sender, receiver) = channel();
+            match notify::recommended_watcher(sender) {
+                Ok(mut watcher) => {
+                    if let Err(err) = watcher.watch(&index.root, RecursiveMode::Recursive) {
+                        tracing::debug!(?err, root = ?index.root, "Failed to watch the project");
+                        index.ready.store(false, Ordering::SeqCst);
+                        return;
+                    }
+                    index.watcher.lock().replace(watcher);
+                }
+                Err(err) => {
+                    tracing::debug!(?err, "File system watcher is unavailable");
+                    index.ready.store(false, Ordering::SeqCst);
+                    return;
+                }
+            }
+
+            // The project could have been changed since the index was persisted.
+            index.rebuild();
+            if let Err(err) = index.persist() {
+                tracing::debug!(?err, "Failed to persist the project index");
+            }
+
+            index.handle_events(receiver);
+        })
+        .expect("Failed to spawn indexer thread");
+}
+
+/// Returns the indexed files of project `root`, the paths are relative to `root`.
+///
+/// Returns `None` if the indexer is disabled, the config file is not initialized, the index is
+/// not ready yet or the index is built with the walk options other than `walk_config`, in which
+/// case the caller should walk the directory instead. The indexing is started on the first call.
+pub fn project_files(root: &Path, walk_config: &WalkConfig) -> Option<Vec<String>> {
+    let config = crate::config::try_config()?;
+    if !config.indexer.enable {
+        return None;
+    }
+
+    let index_walk_config = config.walker.walk_config(Some("indexer"));
+    if *walk_config != index_walk_config {
+        return None;
+    }
+
+    let index = PROJECT_INDEXES
+        .lock()
+        .entry(root.to_path_buf())
+        .or_insert_with(|| {
+            let index = Arc::new(ProjectIndex::new(root.to_path_buf(), index_walk_config));
+            start_indexing(index.clone());
+            index
+        })
+        .clone();
+
+    if index.ready.load(Ordering::SeqCst) {
+        Some(index.files.read().iter().cloned().collect())
+    } else {
+        None
+    }
+}
+
+/// Builds the index of project `root` in the foreground and persists it, so that it's ready
+/// once the project is opened in the editor.
+///
+/// `walk_config` must be the walk options of the indexer in the config for the index to be used.
+/// Returns the number of the indexed files.
+pub fn build_project_index(root: &Path, walk_config: WalkConfig) -> std::io::Result<usize> {
+    let index = ProjectIndex::new(root.to_path_buf(), walk_config);
+    index.rebuild();
+    index.persist()?;
+    let total = index.files.read().len();
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_and_load_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src").join("lib.rs"), "").unwrap();
+        std::fs::write(root.join(".hidden"), "").unwrap();
+        std::fs::write(root.join("ignored.log"), "").unwrap();
+        std::fs::write(root.join(".ignore"), "*.log\n").unwrap();
+
+        let index = ProjectIndex::new(root.clone(), WalkConfig::default());
+        index.rebuild();
+        index.persist().unwrap();
+        let lib_rs = format!("src{}lib.rs", std::path::MAIN_SEPARATOR);
+        assert_eq!(index.files.read().iter().collect::<Vec<_>>(), vec![&lib_rs]);
+
+        let loaded = ProjectIndex::new(root.clone(), WalkConfig::default());
+        assert!(loaded.load());
+        assert!(loaded.ready.load(Ordering::SeqCst));
+        assert_eq!(*loaded.files.read(), *index.files.read());
+
+        // The new files follow the same rules as the walking.
+        std::fs::write(root.join("main.rs"), "").unwrap();
+        std::fs::write(root.join("new.log"), "").unwrap();
+        assert!(loaded.update_path(&root.join("main.rs")));
+        assert!(!loaded.update_path(&root.join("new.log")));
+        assert!(!loaded.update_path(&root.join(".hidden")));
+
+        // The files under the removed directory are dropped.
+        std::fs::remove_dir_all(root.join("src")).unwrap();
+        assert!(loaded.update_path(&root.join("src")));
+        assert_eq!(
+            loaded.files.read().iter().collect::<Vec<_>>(),
+            vec!["main.rs"]
+        );
+
+        std::fs::remove_file(index.index_file()).unwrap();
+    }
+}
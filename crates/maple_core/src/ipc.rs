@@ -0,0 +1,221 @@
+//! Sockets of the daemons, only the current user is allowed to connect.
+//!
+//! The unix domain socket is only accessible to its owner. The TCP socket must be on the
+//! loopback interface and the clients must send the token of the daemon as the first line, the
+//! token is written to `daemon-<port>.token` under the cache directory, readable by the owner only.
+
+use std::io::{BufRead, Error, ErrorKind, Result, Write};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+
+/// Address of a daemon.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Address {
+    /// Path of the unix domain socket.
+    #[cfg(unix)]
+    Unix(PathBuf),
+    /// TCP socket on the loopback interface.
+    Tcp(SocketAddr),
+}
+
+impl Address {
+    /// Parses `address`, either a path of the unix domain socket or `host:port` of a TCP socket
+    /// on the loopback interface.
+    pub fn parse(address: &str) -> Result<Self> {
+        #[cfg(unix)]
+        if !address.contains(':') {
+            return Ok(Self::Unix(PathBuf::from(address)));
+        }
+
+        let socket_addr = address.to_socket_addrs()?.next().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid address {address}"),
+            )
+        })?;
+
+        if !socket_addr.ip().is_loopback() {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                format!("{address} is not on the loopback interface"),
+            ));
+        }
+
+        Ok(Self::Tcp(socket_addr))
+    }
+}
+
+/// Removes the stale socket left by the previous daemon.
+///
+/// Fails if `path` is not a socket or another daemon is still listening on it.
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path) -> Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    if !metadata.file_type().is_socket() {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path.display()),
+        ));
+    }
+
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        return Err(Error::new(
+            ErrorKind::AddrInUse,
+            format!("Another daemon is listening on {}", path.display()),
+        ));
+    }
+
+    std::fs::remove_file(path)
+}
+
+/// Binds the unix domain socket at `path`, which is only accessible to the owner.
+#[cfg(unix)]
+pub fn bind_unix(path: &Path) -> Result<std::os::unix::net::UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    remove_stale_socket(path)?;
+
+    // The socket is bound in a private directory and moved into place once its permissions are
+    // restricted, so that no one else can connect in between.
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let private_dir = parent.join(format!(".maple-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&private_dir);
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)?;
+
+    let bind = || {
+        let tmp_path = private_dir.join("socket");
+        let listener = std::os::unix::net::UnixListener::bind(&tmp_path)?;
+        std::fs::set_permissions(&tmp_path, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(listener)
+    };
+    let result = bind();
+
+    let _ = std::fs::remove_dir_all(&private_dir);
+
+    result
+}
+
+/// Returns the path of the token file of the daemon listening on `socket_addr`.
+pub fn token_path(socket_addr: &SocketAddr) -> Result<PathBuf> {
+    Ok(crate::dirs::clap_cache_dir()?.join(format!("daemon-{}.token", socket_addr.port())))
+}
+
+/// Generates a new token of the daemon listening on `socket_addr` and writes it to the token
+/// file.
+pub fn create_token(socket_addr: &SocketAddr) -> Result<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).map_err(|err| Error::new(ErrorKind::Other, err))?;
+    let token = bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(token_path(socket_addr)?)?;
+    // The mode only applies to a new file.
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(token.as_bytes())?;
+
+    Ok(token)
+}
+
+/// Reads the token of the daemon listening on `socket_addr`.
+pub fn read_token(socket_addr: &SocketAddr) -> Result<String> {
+    let token = std::fs::read_to_string(token_path(socket_addr)?)?;
+    Ok(token.trim().to_string())
+}
+
+/// Checks the first line sent by the client against the token of the daemon.
+pub fn authenticate(reader: &mut impl BufRead, token: &str) -> Result<()> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    let sent = line.trim_end().as_bytes();
+    // Compare in constant time.
+    let matched = sent.len() == token.len()
+        && sent
+            .iter()
+            .zip(token.as_bytes())
+            .fold(0, |acc, (x, y)| acc | (x ^ y))
+            == 0;
+
+    if matched {
+        Ok(())
+    } else {
+        Err(Error::new(ErrorKind::PermissionDenied, "Invalid token"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_address() {
+        assert!(matches!(
+            Address::parse("127.0.0.1:43117"),
+            Ok(Address::Tcp(_))
+        ));
+        assert_eq!(
+            Address::parse("0.0.0.0:43117").unwrap_err().kind(),
+            ErrorKind::PermissionDenied
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            Address::parse("/tmp/maple.sock").unwrap(),
+            Address::Unix(PathBuf::from("/tmp/maple.sock"))
+        );
+    }
+
+    #[test]
+    fn test_authenticate() {
+        let token = "0123456789abcdef";
+        assert!(authenticate(&mut "0123456789abcdef\n{}".as_bytes(), token).is_ok());
+        assert!(authenticate(&mut "0123456789abcdee\n".as_bytes(), token).is_err());
+        assert!(authenticate(&mut "\n".as_bytes(), token).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bind_unix() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+
+        // A regular file is never removed.
+        let file = dir.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        assert_eq!(
+            bind_unix(&file).unwrap_err().kind(),
+            ErrorKind::AlreadyExists
+        );
+        assert!(file.is_file());
+
+        let socket = dir.path().join("maple.sock");
+        let listener = bind_unix(&socket).unwrap();
+        let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(std::os::unix::net::UnixStream::connect(&socket).is_ok());
+
+        // The socket is in use.
+        assert_eq!(bind_unix(&socket).unwrap_err().kind(), ErrorKind::AddrInUse);
+
+        // The stale socket is replaced.
+        drop(listener);
+        assert!(bind_unix(&socket).is_ok());
+    }
+}
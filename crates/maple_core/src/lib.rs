@@ -3,13 +3,20 @@ pub mod config;
 pub mod datastore;
 pub mod dirs;
 pub mod find_usages;
+pub mod health;
 pub mod helptags;
+pub mod indexer;
+pub mod ipc;
+pub mod log_filter;
 pub mod paths;
+pub mod picker_daemon;
 mod previewer;
 pub mod process;
 mod recent_files;
 pub mod searcher;
+pub mod source_service;
 pub mod stdio_server;
+pub mod tooling;
 pub mod tools;
 
 /// For benchmarks.
@@ -0,0 +1,101 @@
+//! Runtime control of the log filter, e.g., `maple_core::searcher=trace` to trace the searcher
+//! temporarily without restarting maple.
+//!
+//! The filter itself is owned by the binary which sets up the logging, the reloader registered
+//! by it is invoked to apply the new directives.
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+
+type Reloader = Box<dyn Fn(&str) -> Result<()> + Send + Sync>;
+
+/// Reloader of the log filter and the filter in effect.
+#[derive(Default)]
+struct LogFilter {
+    reloader: OnceCell<Reloader>,
+    current: Mutex<String>,
+}
+
+impl LogFilter {
+    fn set_reloader(
+        &self,
+        initial: String,
+        reloader: impl Fn(&str) -> Result<()> + Send + Sync + 'static,
+    ) {
+        if self.reloader.set(Box::new(reloader)).is_ok() {
+            *self.current.lock() = initial;
+        }
+    }
+
+    fn current(&self) -> String {
+        self.current.lock().clone()
+    }
+
+    fn set_filter(&self, directives: &str) -> Result<()> {
+        let reloader = self
+            .reloader
+            .get()
+            .ok_or_else(|| anyhow!("logging is disabled, no log file is specified"))?;
+        let directives = directives.trim();
+        reloader(directives)?;
+        tracing::info!(directives, "Log filter updated");
+        *self.current.lock() = directives.to_string();
+        Ok(())
+    }
+}
+
+static LOG_FILTER: Lazy<LogFilter> = Lazy::new(Default::default);
+
+/// Registers the function applying the filter directives, only the first call takes effect.
+///
+/// `initial` is the filter in effect when the logging is set up.
+pub fn set_reloader(
+    initial: String,
+    reloader: impl Fn(&str) -> Result<()> + Send + Sync + 'static,
+) {
+    LOG_FILTER.set_reloader(initial, reloader);
+}
+
+/// Returns the filter in effect, empty if the logging is disabled.
+pub fn current() -> String {
+    LOG_FILTER.current()
+}
+
+/// Applies the filter directives, e.g., `debug,maple_core::searcher=trace`.
+pub fn set_log_filter(directives: &str) -> Result<()> {
+    LOG_FILTER.set_filter(directives)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_log_filter() {
+        let log_filter = LogFilter::default();
+        assert!(log_filter.current().is_empty());
+        assert!(log_filter.set_filter("debug").is_err());
+
+        log_filter.set_reloader("debug".to_string(), |directives| {
+            if directives.contains(' ') {
+                Err(anyhow!("invalid directives: {directives}"))
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(log_filter.current(), "debug");
+
+        // Only the first reloader takes effect.
+        log_filter.set_reloader("info".to_string(), |_| Ok(()));
+        assert_eq!(log_filter.current(), "debug");
+
+        log_filter
+            .set_filter(" debug,maple_core::searcher=trace ")
+            .unwrap();
+        assert_eq!(log_filter.current(), "debug,maple_core::searcher=trace");
+
+        assert!(log_filter.set_filter("maple_core = trace").is_err());
+        assert_eq!(log_filter.current(), "debug,maple_core::searcher=trace");
+    }
+}
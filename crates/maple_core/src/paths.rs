@@ -1,12 +1,12 @@
 use crate::dirs::BASE_DIRS;
 use itertools::Itertools;
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use serde::de::Error as DeserializeError;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::borrow::Cow;
 use std::convert::TryFrom;
 use std::fs::canonicalize;
-use std::path::{Display, Path, PathBuf, MAIN_SEPARATOR};
+use std::path::{Display, Path, PathBuf, MAIN_SEPARATOR, MAIN_SEPARATOR_STR};
 
 /// Unit type wrapper of [`PathBuf`] that is absolute path.
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize)]
@@ -17,17 +17,18 @@ impl<'de> Deserialize<'de> for AbsPathBuf {
     where
         D: Deserializer<'de>,
     {
-        let path = PathBuf::deserialize(deserializer)?;
+        let path = String::deserialize(deserializer)?;
+        let path = crate::config::expand_path(&normalize_path(&path));
         if path.is_absolute() {
             Ok(Self(path))
         } else if let Ok(stripped) = path.strip_prefix("~") {
             let path = BASE_DIRS.home_dir().join(stripped);
             // Resolve the symlink.
             let path =
-                canonicalize(path).map_err(|err| DeserializeError::custom(err.to_string()))?;
+                canonicalize_path(path).map_err(|err| DeserializeError::custom(err.to_string()))?;
             Ok(Self(path))
         } else {
-            let path = canonicalize(&path).map_err(|err| {
+            let path = canonicalize_path(&path).map_err(|err| {
                 DeserializeError::custom(format!("Can not canonicalize {}: {err}", path.display()))
             })?;
             if path.is_absolute() {
@@ -42,6 +43,16 @@ impl<'de> Deserialize<'de> for AbsPathBuf {
     }
 }
 
+impl schemars::JsonSchema for AbsPathBuf {
+    fn schema_name() -> String {
+        "AbsPathBuf".into()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        PathBuf::json_schema(gen)
+    }
+}
+
 impl AbsPathBuf {
     pub fn display(&self) -> Display<'_> {
         self.0.display()
@@ -66,6 +77,7 @@ impl From<AbsPathBuf> for PathBuf {
 impl TryFrom<PathBuf> for AbsPathBuf {
     type Error = PathBuf;
     fn try_from(path_buf: PathBuf) -> Result<AbsPathBuf, PathBuf> {
+        let path_buf = normalize_path_buf(path_buf);
         if !path_buf.is_absolute() {
             return Err(path_buf);
         }
@@ -107,6 +119,142 @@ impl std::fmt::Display for AbsPathBuf {
     }
 }
 
+/// Mapping of a path prefix in the editor to the one seen by maple, e.g., the local mount point
+/// of a project to the project on the remote host maple runs on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathMap {
+    pub editor: String,
+    pub maple: String,
+}
+
+impl std::str::FromStr for PathMap {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (editor, maple) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected EDITOR_PATH=MAPLE_PATH, got {s}"))?;
+        Ok(Self {
+            editor: editor.trim_end_matches(['/', '\\']).to_string(),
+            maple: maple.trim_end_matches(['/', '\\']).to_string(),
+        })
+    }
+}
+
+static PATH_MAPS: OnceCell<Vec<PathMap>> = OnceCell::new();
+
+/// Sets the path maps applied to the paths from the editor, only the first call takes effect.
+///
+/// The path maps are set by `maple rpc` serving a single editor, the shared daemon never sets
+/// them as the editors in the remote mode do not connect to it.
+pub fn set_path_maps(path_maps: Vec<PathMap>) {
+    let _ = PATH_MAPS.set(path_maps);
+}
+
+fn map_editor_path(path: &str) -> Option<String> {
+    PATH_MAPS.get()?.iter().find_map(|path_map| {
+        let rest = path.strip_prefix(&path_map.editor)?;
+        if !rest.is_empty() && !rest.starts_with(['/', '\\']) {
+            return None;
+        }
+        let rest = rest.replace(['/', '\\'], MAIN_SEPARATOR_STR);
+        Some(format!("{}{rest}", path_map.maple))
+    })
+}
+
+/// Returns the path seen by the editor of `path` under a [`PathMap`], along with the number of
+/// chars of the maple prefix replaced and of the editor prefix replacing it.
+pub fn map_maple_path(path: &str) -> Option<(String, usize, usize)> {
+    PATH_MAPS.get()?.iter().find_map(|path_map| {
+        let rest = path.strip_prefix(&path_map.maple)?;
+        if !rest.is_empty() && !rest.starts_with(['/', '\\']) {
+            return None;
+        }
+        Some((
+            format!("{}{rest}", path_map.editor),
+            path_map.maple.chars().count(),
+            path_map.editor.chars().count(),
+        ))
+    })
+}
+
+/// Returns the path seen by the editor, the reverse of [`normalize_path`] for the paths under a
+/// [`PathMap`].
+pub fn to_editor_path(path: &str) -> Cow<'_, str> {
+    map_maple_path(path).map_or(path.into(), |(editor_path, _, _)| editor_path.into())
+}
+
+/// Returns the path in the form Vim uses on Windows, no-op on the other platforms except WSL,
+/// where the Windows path is translated to the mount point, e.g., `C:\src` to `/mnt/c/src`.
+///
+/// The path under a [`PathMap`] is translated to the path seen by maple instead.
+///
+/// - The verbatim prefix added by [`canonicalize`] is stripped, `\\?\C:\src` becomes
+///   `C:\src` and `\\?\UNC\server\share` becomes `\\server\share`.
+/// - The slashes are converted to backslashes, e.g., the paths of Vim with `shellslash`.
+/// - The drive letter is uppercased so that `c:\src` and `C:\src` are the same prefix.
+pub fn normalize_path(path: &str) -> Cow<'_, str> {
+    if let Some(mapped) = map_editor_path(path) {
+        mapped.into()
+    } else if cfg!(windows) {
+        normalize_windows_path(path).into()
+    } else if *crate::process::wsl::IS_WSL {
+        crate::process::wsl::windows_to_mount_path(path).map_or(path.into(), Into::into)
+    } else {
+        path.into()
+    }
+}
+
+fn normalize_windows_path(path: &str) -> String {
+    let path = path.replace('/', "\\");
+
+    let path = if let Some(unc) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{unc}")
+    } else if let Some(local) = path
+        .strip_prefix(r"\\?\")
+        .filter(|local| has_drive_letter(local))
+    {
+        local.to_string()
+    } else {
+        // Other verbatim paths, e.g., `\\?\Volume{GUID}\`, have no equivalent form.
+        path
+    };
+
+    if has_drive_letter(&path) {
+        let mut path = path;
+        path[..1].make_ascii_uppercase();
+        path
+    } else {
+        path
+    }
+}
+
+fn has_drive_letter(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+}
+
+fn normalize_path_buf(path: PathBuf) -> PathBuf {
+    if let Some(Cow::Owned(normalized)) = path.to_str().map(normalize_path) {
+        return normalized.into();
+    }
+    path
+}
+
+/// [`canonicalize`] without the verbatim prefix on Windows.
+pub fn canonicalize_path(path: impl AsRef<Path>) -> std::io::Result<PathBuf> {
+    canonicalize(path).map(normalize_path_buf)
+}
+
+/// Converts the slashes in the query for matching the file paths to the main separator so that
+/// `src/lib` matches `src\lib.rs` on Windows.
+pub fn query_with_main_separator(query: &str) -> Cow<'_, str> {
+    if MAIN_SEPARATOR == '/' {
+        query.into()
+    } else {
+        query.replace('/', MAIN_SEPARATOR_STR).into()
+    }
+}
+
 pub fn expand_tilde(path: impl AsRef<str>) -> PathBuf {
     static HOME_PREFIX: Lazy<String> = Lazy::new(|| format!("~{MAIN_SEPARATOR}"));
 
@@ -119,10 +267,24 @@ pub fn expand_tilde(path: impl AsRef<str>) -> PathBuf {
 
 // /home/xlc/.rustup/toolchains/stable-x86_64-unknown-linux-gnu/lib/rustlib/src/rust/library/alloc/src/string.rs
 pub fn truncate_absolute_path(abs_path: &str, max_len: usize) -> Cow<'_, str> {
-    if abs_path.len() > max_len {
-        let gap = abs_path.len() - max_len;
+    // `c:/Users/xlc` is under the home directory `C:\Users\xlc` on Windows.
+    match normalize_path(abs_path) {
+        Cow::Borrowed(abs_path) => truncate_normalized_path(abs_path, max_len),
+        Cow::Owned(abs_path) => truncate_normalized_path(&abs_path, max_len)
+            .into_owned()
+            .into(),
+    }
+}
+
+fn truncate_normalized_path(abs_path: &str, max_len: usize) -> Cow<'_, str> {
+    // `max_len` is in chars.
+    let abs_path_len = abs_path.chars().count();
+    if abs_path_len > max_len {
+        let gap = abs_path_len - max_len;
 
         if let Some(home_dir) = BASE_DIRS.home_dir().to_str() {
+            let home_dir = normalize_path(home_dir);
+            let home_dir = home_dir.as_ref();
             if abs_path.starts_with(home_dir) {
                 // ~/.rustup/toolchains/stable-x86_64-unknown-linux-gnu/lib/rustlib/src/rust/library/alloc/src/string.rs
                 if home_dir.len() > gap {
@@ -167,7 +329,7 @@ pub fn truncate_absolute_path(abs_path: &str, max_len: usize) -> Cow<'_, str> {
         } else {
             // Truncate the left of absolute path string.
             // ../stable-x86_64-unknown-linux-gnu/lib/rustlib/src/rust/library/alloc/src/string.rs
-            if let Some((offset, _)) = abs_path.char_indices().nth(abs_path.len() - max_len + 2) {
+            if let Some((offset, _)) = abs_path.char_indices().nth(gap + 2) {
                 let mut abs_path = abs_path.to_string();
                 abs_path.replace_range(..offset, "..");
                 return abs_path.into();
@@ -182,6 +344,64 @@ pub fn truncate_absolute_path(abs_path: &str, max_len: usize) -> Cow<'_, str> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_map_editor_path() {
+        set_path_maps(vec!["/Users/xlc/mnt/devbox/src/=/home/xlc/src"
+            .parse()
+            .unwrap()]);
+        assert_eq!(
+            map_editor_path("/Users/xlc/mnt/devbox/src/vim-clap/README.md"),
+            Some(format!(
+                "/home/xlc/src{MAIN_SEPARATOR}vim-clap{MAIN_SEPARATOR}README.md"
+            ))
+        );
+        assert_eq!(
+            map_editor_path("/Users/xlc/mnt/devbox/src"),
+            Some("/home/xlc/src".into())
+        );
+        assert_eq!(map_editor_path("/Users/xlc/mnt/devbox/src2"), None);
+
+        assert_eq!(
+            map_maple_path("/home/xlc/src/vim-clap/README.md"),
+            Some((
+                "/Users/xlc/mnt/devbox/src/vim-clap/README.md".into(),
+                "/home/xlc/src".len(),
+                "/Users/xlc/mnt/devbox/src".len()
+            ))
+        );
+        assert_eq!(to_editor_path("/home/xlc/src2"), "/home/xlc/src2");
+    }
+
+    #[test]
+    fn test_normalize_windows_path() {
+        assert_eq!(
+            normalize_windows_path(r"\\?\C:\src\lib.rs"),
+            r"C:\src\lib.rs"
+        );
+        assert_eq!(
+            normalize_windows_path(r"\\?\UNC\server\share\lib.rs"),
+            r"\\server\share\lib.rs"
+        );
+        assert_eq!(
+            normalize_windows_path(r"\\server\share\lib.rs"),
+            r"\\server\share\lib.rs"
+        );
+        assert_eq!(normalize_windows_path("c:/src/lib.rs"), r"C:\src\lib.rs");
+        assert_eq!(
+            normalize_windows_path(r"\\?\Volume{1234}\lib.rs"),
+            r"\\?\Volume{1234}\lib.rs"
+        );
+        assert_eq!(normalize_windows_path("src/lib.rs"), r"src\lib.rs");
+    }
+
+    #[test]
+    fn test_truncate_non_ascii_path() {
+        let abs_path = "/数据/xlc/Data/src/github.com/paritytech/substrate/bin/node/cli/src/lib.rs";
+        // The path has 72 chars and more bytes.
+        assert_eq!(truncate_absolute_path(abs_path, 72), abs_path);
+        assert!(truncate_absolute_path(abs_path, 60).contains("..."));
+    }
+
     #[test]
     #[ignore = "Not sure why the behavior is differnt in CI"]
     fn test_truncate_absolute_path() {
@@ -0,0 +1,381 @@
+//! Picker daemon serving the picker sessions over a unix domain socket or TCP.
+//!
+//! External tools like tmux popups and shell scripts can reuse the matcher and previewer of
+//! maple outside Vim. Each connection owns one picker session, the messages are JSON lines:
+//!
+//! - Request: `{"id": 1, "method": "start", "params": {...}}`
+//! - Response: `{"id": 1, "result": ...}` or `{"id": 1, "error": "..."}`
+//!
+//! Over TCP, the first line must be the token of the daemon, see [`crate::ipc`].
+//!
+//! Methods:
+//!
+//! - `start`: `{"source": "files" | "git_files" | "recent_files" | "grep" | {"lines": [...]},
+//!   "cwd": "...", "provider": "..."}`, starts a new session and returns `{"total": N}`. The
+//!   entries are ranked and located the same way as the provider of the builtin source, or of
+//!   the optional `provider` for the lines.
+//! - `query`: `{"query": "...", "number": 50, "winwidth": 80}`, returns the top matched items
+//!   `{"total": N, "lines": [...], "indices": [...]}`.
+//! - `preview`: `{"line": "...", "height": 30}`, returns the preview lines of an entry of the
+//!   session.
+//! - `accept`: `{"line": "..."}`, ends the session and returns the selection
+//!   `{"line": "...", "path": "...", "lnum": N}`, `path` and `lnum` are available if the line
+//!   can be parsed as a file location.
+//!
+//! The stateless `source/list` and `source/filter` in [`crate::source_service`] are available too.
+
+use crate::config::MatcherConfig;
+use crate::ipc::Address;
+use crate::source_service::{Source, SourceCache, SourceParams};
+use crate::stdio_server::{parse_quickfix_item, ProviderId, QuickfixItem};
+use anyhow::{anyhow, Result};
+use matcher::MatcherBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use types::{CaseMatching, ClapItem, Query};
+
+#[derive(Debug, Deserialize)]
+struct StartParams {
+    #[serde(flatten)]
+    source: SourceParams,
+    /// Provider whose ranking and entry format the lines follow, e.g., `grep`.
+    #[serde(default)]
+    provider: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QueryParams {
+    query: String,
+    #[serde(default = "default_number")]
+    number: usize,
+    #[serde(default = "default_winwidth")]
+    winwidth: usize,
+    /// `smart`, `ignore` or `respect`, defaults to `smart`.
+    #[serde(default)]
+    case_matching: Option<String>,
+}
+
+fn default_number() -> usize {
+    100
+}
+
+fn default_winwidth() -> usize {
+    80
+}
+
+#[derive(Debug, Deserialize)]
+struct PreviewParams {
+    line: String,
+    #[serde(default = "default_preview_height")]
+    height: usize,
+}
+
+fn default_preview_height() -> usize {
+    30
+}
+
+#[derive(Debug, Deserialize)]
+struct AcceptParams {
+    line: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    id: u64,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct Selection {
+    line: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lnum: Option<usize>,
+}
+
+/// Picker session of a connection.
+#[derive(Debug)]
+struct Session {
+    provider_id: ProviderId,
+    cwd: PathBuf,
+    items: Vec<Arc<dyn ClapItem>>,
+    /// Matcher of the provider with the rank criteria of the project.
+    matcher_builder: MatcherBuilder,
+}
+
+impl Session {
+    fn start(StartParams { source, provider }: StartParams) -> Result<Self> {
+        let cwd = match source.cwd {
+            Some(cwd) => cwd,
+            None => std::env::current_dir()?,
+        };
+
+        let provider_id = match (&source.source, provider) {
+            (Source::Builtin(builtin_source), _) => ProviderId::from(builtin_source.provider_id()),
+            (Source::Lines { .. }, Some(provider)) => ProviderId::from(provider),
+            (Source::Lines { .. }, None) => ProviderId::from("picker"),
+        };
+
+        // The default config is in use if the config file is not initialized.
+        let rank_criteria = match crate::config::try_config() {
+            Some(config) => crate::config::load_project_config(&cwd)
+                .as_ref()
+                .unwrap_or(config)
+                .matcher
+                .rank_criteria(),
+            None => MatcherConfig::default().rank_criteria(),
+        };
+        let matcher_builder = provider_id.matcher_builder().rank_criteria(rank_criteria);
+
+        let items = source
+            .source
+            .collect_lines(&cwd)?
+            .into_iter()
+            .map(|line| Arc::new(line) as Arc<dyn ClapItem>)
+            .collect();
+
+        Ok(Self {
+            provider_id,
+            cwd,
+            items,
+            matcher_builder,
+        })
+    }
+
+    fn query(
+        &self,
+        QueryParams {
+            query,
+            number,
+            winwidth,
+            case_matching,
+        }: QueryParams,
+    ) -> Value {
+        let matcher = self
+            .matcher_builder
+            .clone()
+            .case_matching(case_matching.map(CaseMatching::from).unwrap_or_default())
+            .build(Query::from(&query));
+        crate::source_service::filter_items(&self.items, &matcher, number, winwidth)
+    }
+
+    /// Returns the file location of an entry of the session.
+    fn locate(&self, line: &str) -> Result<Option<QuickfixItem>> {
+        if !self.items.iter().any(|item| item.raw_text() == line) {
+            return Err(anyhow!("{line} is not an entry of the session"));
+        }
+
+        Ok(
+            parse_quickfix_item(self.provider_id.as_str(), line, &self.cwd, &self.cwd)
+                .filter(|quickfix_item| Path::new(&quickfix_item.filename).is_file()),
+        )
+    }
+
+    fn preview(&self, PreviewParams { line, height }: PreviewParams) -> Result<Vec<String>> {
+        let Some(QuickfixItem { filename, lnum, .. }) = self.locate(&line)? else {
+            return Ok(vec![line]);
+        };
+
+        // Only the recent files may live outside of the session directory.
+        let path = Path::new(&filename).canonicalize()?;
+        if self.provider_id.as_str() != "recent_files"
+            && !path.starts_with(self.cwd.canonicalize()?)
+        {
+            return Err(anyhow!("{filename} is outside of {}", self.cwd.display()));
+        }
+
+        let max_width = 200;
+        if lnum > 1 {
+            let (lines, _highlight_lnum) =
+                crate::previewer::preview_file_at(path, height, max_width, lnum)?;
+            Ok(lines)
+        } else {
+            let (lines, _abs_path) = crate::previewer::preview_file(path, height, max_width)?;
+            Ok(lines)
+        }
+    }
+
+    fn accept(&self, AcceptParams { line }: AcceptParams) -> Result<Selection> {
+        let (path, lnum) = match self.locate(&line)? {
+            Some(QuickfixItem { filename, lnum, .. }) => {
+                (Some(PathBuf::from(filename)), Some(lnum))
+            }
+            None => (None, None),
+        };
+        Ok(Selection { line, path, lnum })
+    }
+}
+
+fn handle_request(
+    session: &mut Option<Session>,
+    source_cache: &SourceCache,
+    method: &str,
+    params: Value,
+) -> Result<Value> {
+    match method {
+        "source/list" => {
+            return crate::source_service::list_source(
+                serde_json::from_value(params)?,
+                source_cache,
+            )
+        }
+        "source/filter" => {
+            return crate::source_service::filter_source(
+                serde_json::from_value(params)?,
+                source_cache,
+            )
+        }
+        _ => {}
+    }
+
+    if method == "start" {
+        let new_session = Session::start(serde_json::from_value(params)?)?;
+        let total = new_session.items.len();
+        session.replace(new_session);
+        return Ok(json!({ "total": total }));
+    }
+
+    let current_session = session
+        .as_ref()
+        .ok_or_else(|| anyhow!("No picker session, `start` must be called first"))?;
+
+    match method {
+        "query" => Ok(current_session.query(serde_json::from_value(params)?)),
+        "preview" => Ok(json!(
+            current_session.preview(serde_json::from_value(params)?)?
+        )),
+        "accept" => {
+            let selection = current_session.accept(serde_json::from_value(params)?)?;
+            session.take();
+            Ok(serde_json::to_value(selection)?)
+        }
+        _ => Err(anyhow!("Unknown method: {method}")),
+    }
+}
+
+/// Serves one connection until it's closed.
+fn handle_connection(reader: impl BufRead, mut writer: impl Write) -> Result<()> {
+    let mut session = None;
+    let source_cache = SourceCache::default();
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(Request { id, method, params }) => {
+                match handle_request(&mut session, &source_cache, &method, params) {
+                    Ok(result) => json!({ "id": id, "result": result }),
+                    Err(err) => json!({ "id": id, "error": err.to_string() }),
+                }
+            }
+            Err(err) => json!({ "id": null, "error": format!("Invalid request: {err}") }),
+        };
+
+        serde_json::to_writer(&mut writer, &response)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Starts the picker daemon listening on `address`, which is either a path of the unix domain
+/// socket or `host:port` of a TCP socket on the loopback interface.
+pub fn run(address: &str) -> Result<()> {
+    match Address::parse(address)? {
+        #[cfg(unix)]
+        Address::Unix(path) => {
+            let listener = crate::ipc::bind_unix(&path)?;
+            tracing::debug!(address, "Picker daemon is listening");
+            for stream in listener.incoming() {
+                let stream = stream?;
+                std::thread::spawn(move || {
+                    let reader = BufReader::new(stream.try_clone()?);
+                    handle_connection(reader, stream)
+                });
+            }
+        }
+        Address::Tcp(socket_addr) => {
+            let listener = std::net::TcpListener::bind(socket_addr)?;
+            let token = Arc::new(crate::ipc::create_token(&listener.local_addr()?)?);
+            tracing::debug!(address, "Picker daemon is listening");
+            for stream in listener.incoming() {
+                let stream = stream?;
+                let token = token.clone();
+                std::thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone()?);
+                    crate::ipc::authenticate(&mut reader, &token)?;
+                    handle_connection(reader, stream)
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn responses(requests: &[&str]) -> Vec<Value> {
+        let mut output = Vec::new();
+        handle_connection(requests.join("\n").as_bytes(), &mut output).unwrap();
+
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_picker_session() {
+        let responses = responses(&[
+            r#"{"id": 1, "method": "query", "params": {"query": "foo"}}"#,
+            r#"{"id": 2, "method": "start", "params": {"source": {"lines": ["foo", "bar", "fob"]}}}"#,
+            r#"{"id": 3, "method": "query", "params": {"query": "fo", "number": 1}}"#,
+            r#"{"id": 4, "method": "accept", "params": {"line": "foo"}}"#,
+        ]);
+
+        assert!(responses[0]["error"].is_string());
+        assert_eq!(responses[1]["result"], json!({ "total": 3 }));
+        assert_eq!(responses[2]["result"]["total"], json!(2));
+        assert_eq!(responses[2]["result"]["lines"].as_array().unwrap().len(), 1);
+        assert_eq!(responses[3]["result"], json!({ "line": "foo" }));
+    }
+
+    #[test]
+    fn test_preview_only_session_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("foo.txt"), "hello").unwrap();
+
+        let start = json!({
+            "id": 1,
+            "method": "start",
+            "params": { "source": { "lines": ["foo.txt"] }, "cwd": dir.path() },
+        })
+        .to_string();
+        let responses = responses(&[
+            &start,
+            r#"{"id": 2, "method": "preview", "params": {"line": "foo.txt"}}"#,
+            r#"{"id": 3, "method": "preview", "params": {"line": "/etc/passwd"}}"#,
+            r#"{"id": 4, "method": "start", "params": {"source": {"cmd": "true"}}}"#,
+        ]);
+
+        // The first line is the path of the file.
+        assert_eq!(responses[1]["result"][1], json!("hello"));
+        assert!(responses[2]["error"].is_string());
+        // The raw shell command is not a source.
+        assert!(responses[3]["error"].is_string());
+    }
+}
@@ -0,0 +1,189 @@
+//! Previewer of the documentation downloaded from [devdocs](https://devdocs.io).
+//!
+//! Each doc is stored under `{docs_dir}/{slug}/` with the same layout as devdocs serves it:
+//!
+//! - `index.json`: `{ "entries": [{ "name": "...", "path": "...", "type": "..." }] }`
+//! - `db.json`: map of the page path to the HTML of the page.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DocEntry {
+    pub name: String,
+    pub path: String,
+    #[serde(rename = "type", default)]
+    pub kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DocIndex {
+    entries: Vec<DocEntry>,
+}
+
+/// Returns the directory of the downloaded docs.
+pub fn docs_dir() -> PathBuf {
+    match &crate::config::config().provider.docs.dir {
+        Some(dir) => crate::config::expand_path(dir),
+        None => crate::dirs::data_dir().join("devdocs"),
+    }
+}
+
+/// Returns the slugs of the downloaded docs for `filetype`.
+///
+/// The slugs configured in `[provider.docs.filetypes]` are used if any, otherwise the docs whose
+/// slug is `filetype` or starts with `{filetype}~`, e.g., `python~3.11`.
+pub fn find_slugs(docs_dir: &Path, filetype: &str) -> Vec<String> {
+    if let Some(slugs) = crate::config::config()
+        .provider
+        .docs
+        .filetypes
+        .get(filetype)
+    {
+        return slugs.clone();
+    }
+
+    let Ok(entries) = std::fs::read_dir(docs_dir) else {
+        return Vec::new();
+    };
+
+    let mut slugs = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|slug| {
+            filetype.is_empty()
+                || slug == filetype
+                || slug
+                    .strip_prefix(filetype)
+                    .map(|rest| rest.starts_with('~'))
+                    .unwrap_or(false)
+        })
+        .collect::<Vec<_>>();
+    slugs.sort();
+    slugs
+}
+
+/// Returns the lines of the doc entries in the format of `name\t[type]\tslug/path`.
+pub fn entry_lines(docs_dir: &Path, slugs: &[String]) -> Vec<String> {
+    slugs
+        .iter()
+        .filter_map(|slug| {
+            let index = std::fs::read(docs_dir.join(slug).join("index.json")).ok()?;
+            let DocIndex { entries } = serde_json::from_slice(&index).ok()?;
+            Some(
+                entries
+                    .into_iter()
+                    .map(move |DocEntry { name, path, kind }| {
+                        format!("{name}\t[{kind}]\t{slug}/{path}")
+                    }),
+            )
+        })
+        .flatten()
+        .collect()
+}
+
+/// Map of the page path to the HTML of the page.
+type DocDb = Arc<HashMap<String, String>>;
+
+/// The db of the last previewed doc, which can be large and is reused across the entries.
+static LAST_DB: Lazy<Mutex<Option<(PathBuf, DocDb)>>> = Lazy::new(|| Mutex::new(None));
+
+fn load_db(db_path: &Path) -> std::io::Result<DocDb> {
+    if let Some((path, db)) = LAST_DB.lock().as_ref() {
+        if path == db_path {
+            return Ok(db.clone());
+        }
+    }
+
+    let db: HashMap<String, String> = serde_json::from_slice(&std::fs::read(db_path)?)?;
+    let db = Arc::new(db);
+    LAST_DB.lock().replace((db_path.to_path_buf(), db.clone()));
+
+    Ok(db)
+}
+
+/// Returns the doc page of `slug/path` as plain text.
+pub fn preview_lines(docs_dir: &Path, slug: &str, path: &str) -> std::io::Result<Vec<String>> {
+    let db = load_db(&docs_dir.join(slug).join("db.json"))?;
+
+    let page = path.split('#').next().unwrap_or(path);
+    let html = db.get(page).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("{page} not found in the doc {slug}"),
+        )
+    })?;
+
+    Ok(html_to_text(html).lines().map(Into::into).collect())
+}
+
+/// Converts the HTML to plain text roughly, the block elements are separated by line breaks.
+pub fn html_to_text(html: &str) -> String {
+    static INVISIBLE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?is)<(script|style)[^>]*>.*?</(script|style)>").unwrap());
+    static LINE_BREAK: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?i)<br\s*/?>|</(p|div|h[1-6]|pre|tr|table|ul|ol|dl|dt|dd|blockquote)>")
+            .unwrap()
+    });
+    static LIST_ITEM: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<li[^>]*>").unwrap());
+    static TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").unwrap());
+    static BLANK_LINES: Lazy<Regex> = Lazy::new(|| Regex::new(r"\n\s*\n(\s*\n)+").unwrap());
+
+    let text = INVISIBLE.replace_all(html, "");
+    let text = LINE_BREAK.replace_all(&text, "\n");
+    let text = LIST_ITEM.replace_all(&text, "\n- ");
+    let text = TAG.replace_all(&text, "");
+    let text = decode_entities(&text);
+    let text = BLANK_LINES.replace_all(&text, "\n\n");
+
+    text.trim().to_string()
+}
+
+fn decode_entities(text: &str) -> String {
+    static ENTITY: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"&(#x[0-9a-fA-F]+|#[0-9]+|\w+);").unwrap());
+
+    ENTITY
+        .replace_all(text, |caps: &regex::Captures| {
+            let entity = &caps[1];
+            let decoded = if let Some(hex) = entity.strip_prefix("#x") {
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+            } else if let Some(dec) = entity.strip_prefix('#') {
+                dec.parse::<u32>().ok().and_then(char::from_u32)
+            } else {
+                match entity {
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "amp" => Some('&'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some(' '),
+                    _ => None,
+                }
+            };
+            decoded
+                .map(String::from)
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_text() {
+        let html = r#"<h1>Vec::push</h1><style>h1 {}</style><p>Appends an element &amp; returns.</p>
+<pre>let mut v = vec![1];
+v.push(2);</pre><ul><li>one</li><li>&lt;two&gt;&#x21;</li></ul>"#;
+        assert_eq!(
+            html_to_text(html),
+            "Vec::push\nAppends an element & returns.\n\nlet mut v = vec![1];\nv.push(2);\n\n- one\n- <two>!"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+pub mod devdocs;
 pub mod vim_help;
 
 use crate::paths::truncate_absolute_path;
@@ -28,6 +29,7 @@ pub fn get_file_preview<P: AsRef<Path>>(
     path: P,
     target_line_number: usize,
     winheight: usize,
+    search_compressed: bool,
 ) -> std::io::Result<FilePreview> {
     let mid = winheight / 2;
     let (start, end, highlight_lnum) = if target_line_number > mid {
@@ -36,7 +38,8 @@ pub fn get_file_preview<P: AsRef<Path>>(
         (0, winheight, target_line_number)
     };
 
-    let lines = read_preview_lines(path, start, end)?;
+    let decompress = is_decompressed(path.as_ref(), search_compressed);
+    let lines = read_preview_lines(path, start, end, decompress)?;
 
     Ok(FilePreview {
         start,
@@ -55,37 +58,59 @@ fn initial_buffer_size(file: &File) -> usize {
     file.metadata().map(|m| m.len() as usize + 1).unwrap_or(0)
 }
 
+/// Returns true if the compressed file at `path` is previewed decompressed, i.e., the grep
+/// results found by `grep.search-compressed`. The tar archives are never decompressed.
+fn is_decompressed(path: &Path, search_compressed: bool) -> bool {
+    search_compressed
+        && utils::is_compressed_path(path)
+        && !matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("tgz" | "txz")
+        )
+}
+
 fn read_preview_lines<P: AsRef<Path>>(
     path: P,
     start: usize,
     end: usize,
+    decompress: bool,
 ) -> std::io::Result<Vec<String>> {
     let mut filebuf: Vec<u8> = Vec::new();
 
-    File::open(path)
-        .and_then(|mut file| {
-            // XXX: is megabyte enough for any text file?
-            const MEGABYTE: usize = 32 * 1_048_576;
-
-            let filesize = initial_buffer_size(&file);
-            if filesize > MEGABYTE {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    "maximum preview file buffer size reached",
-                ));
-            }
-
-            filebuf.reserve_exact(filesize);
-            file.read_to_end(&mut filebuf)
-        })
-        .map(|_| {
-            ByteLines::new(&filebuf)
-                .skip(start)
-                .take(end - start)
-                // trim_end() to get rid of ^M on Windows.
-                .map(|l| l.trim_end().to_string())
-                .collect()
-        })
+    // XXX: is megabyte enough for any text file?
+    const MEGABYTE: usize = 32 * 1_048_576;
+
+    let maybe_reader = if decompress {
+        utils::open_decompressed(path.as_ref())?
+    } else {
+        None
+    };
+
+    if let Some(reader) = maybe_reader {
+        reader.take(MEGABYTE as u64).read_to_end(&mut filebuf)?;
+    } else {
+        let mut file = File::open(path)?;
+        let filesize = initial_buffer_size(&file);
+        if filesize > MEGABYTE {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "maximum preview file buffer size reached",
+            ));
+        }
+
+        filebuf.reserve_exact(filesize);
+        file.read_to_end(&mut filebuf)?;
+    }
+
+    // The legacy encoded files are displayed as UTF-8 instead of the replacement characters.
+    let filebuf = utils::transcode_to_utf8(filebuf);
+
+    Ok(ByteLines::new(&filebuf)
+        .skip(start)
+        .take(end - start)
+        // trim_end() to get rid of ^M on Windows.
+        .map(|l| l.trim_end().to_string())
+        .collect())
 }
 
 #[inline]
@@ -93,10 +118,10 @@ fn as_absolute_path<P: AsRef<Path>>(path: P) -> std::io::Result<String> {
     if path.as_ref().is_absolute() {
         Ok(path.as_ref().to_string_lossy().into())
     } else {
-        // Somehow the absolute path on Windows is problematic using `canonicalize`:
-        // C:\Users\liuchengxu\AppData\Local\nvim\init.vim
+        // `canonicalize` returns the verbatim path on Windows, which is stripped:
         // \\?\C:\Users\liuchengxu\AppData\Local\nvim\init.vim
-        Ok(std::fs::canonicalize(path.as_ref())?
+        // C:\Users\liuchengxu\AppData\Local\nvim\init.vim
+        Ok(crate::paths::canonicalize_path(path.as_ref())?
             .into_os_string()
             .to_string_lossy()
             .into())
@@ -174,7 +199,12 @@ pub fn preview_file_at<P: AsRef<Path>>(
         lines,
         highlight_lnum,
         ..
-    } = get_file_preview(path.as_ref(), lnum, winheight)?;
+    } = get_file_preview(
+        path.as_ref(),
+        lnum,
+        winheight,
+        crate::config::config().grep.search_compressed,
+    )?;
 
     let lines = std::iter::once(format!("{}:{lnum}", path.as_ref().display()))
         .chain(truncate_lines(lines.into_iter(), max_width))
@@ -198,7 +228,7 @@ mod tests {
             .join("test")
             .join("testdata")
             .join("test_673.txt");
-        let FilePreview { lines, .. } = get_file_preview(test_txt, 2, 10).unwrap();
+        let FilePreview { lines, .. } = get_file_preview(test_txt, 2, 10, false).unwrap();
         assert_eq!(
             lines,
             [
@@ -210,4 +240,24 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_read_compressed_preview_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log");
+        std::fs::write(&path, "foo\nbar\nbaz\n").unwrap();
+        let path = utils::compress_file(&path).unwrap();
+
+        assert!(is_decompressed(&path, true));
+        assert!(!is_decompressed(&path, false));
+        assert!(!is_decompressed(Path::new("src.tgz"), true));
+        assert!(!is_decompressed(Path::new("src.txz"), true));
+        assert!(!is_decompressed(Path::new("src.rs"), true));
+
+        assert_eq!(
+            read_preview_lines(&path, 1, 3, true).unwrap(),
+            ["bar", "baz"]
+        );
+        assert_ne!(read_preview_lines(&path, 0, 1, false).unwrap(), ["foo"]);
+    }
 }
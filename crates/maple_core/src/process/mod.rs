@@ -1,5 +1,6 @@
 pub mod subprocess;
 pub mod tokio;
+pub mod wsl;
 
 use crate::cache::{push_cache_digest, Digest};
 use crate::datastore::{generate_cache_file_path, CACHE_INFO_IN_MEMORY};
@@ -28,22 +29,36 @@ pub fn shell_command(shell_cmd: &str) -> Command {
     }
 }
 
+/// Returns the path of temporary file for writing `output_file`.
+fn temp_file_path(output_file: &Path) -> PathBuf {
+    let mut temp_file = output_file.as_os_str().to_owned();
+    temp_file.push(".tmp");
+    temp_file.into()
+}
+
 /// Executes the command and redirects the output to a file.
+///
+/// The output is written to a temporary file which then replaces `output_file`, the existing
+/// `output_file` is never modified in place as it might be memory-mapped.
 pub fn write_stdout_to_file<P: AsRef<Path>>(
     cmd: &mut Command,
     output_file: P,
 ) -> std::io::Result<()> {
+    let output_file = output_file.as_ref();
+    let temp_file = temp_file_path(output_file);
+
     let file = std::fs::OpenOptions::new()
         .write(true)
         .create(true)
         .truncate(true)
-        .open(output_file)?;
+        .open(&temp_file)?;
 
     let exit_status = cmd.stdout(file).spawn()?.wait()?;
 
     if exit_status.success() {
-        Ok(())
+        std::fs::rename(temp_file, output_file)
     } else {
+        let _ = std::fs::remove_file(temp_file);
         Err(std::io::Error::new(
             std::io::ErrorKind::Other,
             format!(
@@ -119,18 +134,37 @@ impl ShellCommand {
     // TODO: remove this.
     /// Caches the output into a tempfile and also writes the cache digest to the disk.
     pub fn write_cache(self, total: usize, cmd_stdout: &[u8]) -> std::io::Result<PathBuf> {
+        let digest = self.write_cache_file(total, cmd_stdout)?;
+        let cache_file = digest.cached_path.clone();
+
+        push_cache_digest(digest);
+
+        Ok(cache_file)
+    }
+
+    /// Same as [`Self::write_cache`] but the cache digest has been stored once it returns.
+    pub fn write_cache_blocking(self, total: usize, cmd_stdout: &[u8]) -> std::io::Result<PathBuf> {
+        let digest = self.write_cache_file(total, cmd_stdout)?;
+        let cache_file = digest.cached_path.clone();
+
+        CACHE_INFO_IN_MEMORY.lock().limited_push(digest)?;
+
+        Ok(cache_file)
+    }
+
+    fn write_cache_file(self, total: usize, cmd_stdout: &[u8]) -> std::io::Result<Digest> {
         use std::io::Write;
 
         let cache_filename = utils::calculate_hash(&self);
         let cache_file = generate_cache_file_path(cache_filename.to_string())?;
 
-        std::fs::File::create(&cache_file)?.write_all(cmd_stdout)?;
+        let temp_file = temp_file_path(&cache_file);
+        std::fs::File::create(&temp_file)?.write_all(cmd_stdout)?;
+        std::fs::rename(temp_file, &cache_file)?;
 
-        let digest = Digest::new(self, total, cache_file.clone());
+        let cache_file = crate::cache::compress_cache_file(cache_file)?;
 
-        push_cache_digest(digest);
-
-        Ok(cache_file)
+        Ok(Digest::new(self, total, cache_file))
     }
 }
 
@@ -265,7 +299,9 @@ impl<'a> CacheableCommand<'a> {
 
         // Store the cache file if the total number of items exceeds the threshold, so that the
         // cache can be reused if the identical command is executed again.
-        if total > self.output_threshold {
+        let cache_file_path = if total > self.output_threshold {
+            let cache_file_path = crate::cache::compress_cache_file(cache_file_path)?;
+
             let digest = Digest::new(self.shell_cmd.clone(), total, cache_file_path.clone());
 
             {
@@ -273,7 +309,11 @@ impl<'a> CacheableCommand<'a> {
                 let mut cache_info = cache_info.lock();
                 cache_info.limited_push(digest)?;
             }
-        }
+
+            cache_file_path
+        } else {
+            cache_file_path
+        };
 
         Ok(ExecInfo {
             using_cache: false,
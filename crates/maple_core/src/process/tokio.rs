@@ -3,21 +3,42 @@
 use std::path::Path;
 use tokio::process::Command;
 
+/// Removes the temporary file on drop unless it has been renamed.
+struct TempFileGuard<'a>(&'a Path);
+
+impl Drop for TempFileGuard<'_> {
+    fn drop(&mut self) {
+        if self.0.exists() {
+            let _ = std::fs::remove_file(self.0);
+        }
+    }
+}
+
 /// Executes the command and redirects the output to a file.
+///
+/// The output is written to a temporary file which then replaces `output_file`, the existing
+/// `output_file` is never modified in place as it might be memory-mapped.
+///
+/// The command is killed and the temporary file is removed if the returned future is dropped
+/// before the command exits, e.g., on timeout.
 pub async fn write_stdout_to_file<P: AsRef<Path>>(
     cmd: &mut Command,
     output_file: P,
 ) -> std::io::Result<()> {
+    let output_file = output_file.as_ref();
+    let temp_file = super::temp_file_path(output_file);
+
     let file = std::fs::OpenOptions::new()
         .write(true)
         .create(true)
         .truncate(true)
-        .open(output_file)?;
+        .open(&temp_file)?;
+    let _temp_file_guard = TempFileGuard(&temp_file);
 
-    let exit_status = cmd.stdout(file).spawn()?.wait().await?;
+    let exit_status = cmd.stdout(file).kill_on_drop(true).spawn()?.wait().await?;
 
     if exit_status.success() {
-        Ok(())
+        std::fs::rename(&temp_file, output_file)
     } else {
         Err(std::io::Error::new(
             std::io::ErrorKind::Other,
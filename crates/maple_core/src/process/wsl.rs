@@ -0,0 +1,194 @@
+//! Translation of the paths between Windows and WSL.
+//!
+//! The tools walking a project on the WSL filesystem from Windows, i.e., under
+//! `\\wsl$\<distro>` or `\\wsl.localhost\<distro>`, are slow over the share and git may refuse
+//! the repo owned by another user, hence they are run inside the distro via `wsl.exe`. The tool
+//! is run in the project directory so that the paths in its output are relative and need no
+//! mapping, the path arguments are translated to the Linux form via [`tool_arg`]. The tool of
+//! the host is used instead if it's not installed in the distro.
+//!
+//! Conversely, the Windows paths received by maple running inside WSL are translated to the
+//! mount points, e.g., `C:\src` to `/mnt/c/src`.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Hosts of the WSL filesystem share on Windows.
+const SHARE_HOSTS: &[&str] = &["wsl$", "wsl.localhost"];
+
+/// Whether maple is running inside WSL.
+pub static IS_WSL: Lazy<bool> = Lazy::new(|| {
+    cfg!(target_os = "linux")
+        && (std::env::var_os("WSL_DISTRO_NAME").is_some()
+            || Path::new("/proc/sys/fs/binfmt_misc/WSLInterop").exists())
+});
+
+/// Path on the WSL filesystem seen from Windows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharePath {
+    pub distro: String,
+    /// Absolute path inside the distro.
+    pub linux_path: String,
+}
+
+impl SharePath {
+    /// Parses `\\wsl$\Ubuntu\home\xlc` or `//wsl.localhost/Ubuntu/home/xlc`.
+    pub fn parse(path: &str) -> Option<Self> {
+        let rest = path
+            .strip_prefix(r"\\")
+            .or_else(|| path.strip_prefix("//"))?;
+
+        let mut components = rest.split(['\\', '/']);
+
+        let host = components.next()?;
+        if !SHARE_HOSTS
+            .iter()
+            .any(|share_host| share_host.eq_ignore_ascii_case(host))
+        {
+            return None;
+        }
+
+        let distro = components.next().filter(|distro| !distro.is_empty())?;
+
+        let mut linux_path = String::new();
+        for component in components.filter(|component| !component.is_empty()) {
+            linux_path.push('/');
+            linux_path.push_str(component);
+        }
+        if linux_path.is_empty() {
+            linux_path.push('/');
+        }
+
+        Some(Self {
+            distro: distro.to_string(),
+            linux_path,
+        })
+    }
+}
+
+/// Converts `C:\src` to `/mnt/c/src`.
+pub fn windows_to_mount_path(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    if bytes.len() < 2 || !bytes[0].is_ascii_alphabetic() || bytes[1] != b':' {
+        return None;
+    }
+    let drive = bytes[0].to_ascii_lowercase() as char;
+    let rest = path[2..].replace('\\', "/");
+    let rest = rest.trim_end_matches('/');
+    Some(format!("/mnt/{drive}{rest}"))
+}
+
+/// Returns the WSL share `dir` is under if maple is running on Windows.
+fn share_of(dir: &Path) -> Option<SharePath> {
+    if cfg!(windows) {
+        dir.to_str().and_then(SharePath::parse)
+    } else {
+        None
+    }
+}
+
+/// Whether `program` can be found by `wsl.exe --exec` in `distro`, probed only once.
+fn is_installed_in(distro: &str, program: &str) -> bool {
+    static INSTALLED: Lazy<Mutex<HashMap<(String, String), bool>>> = Lazy::new(Default::default);
+
+    *INSTALLED
+        .lock()
+        .entry((distro.to_string(), program.to_string()))
+        .or_insert_with(|| {
+            let installed = Command::new("wsl.exe")
+                .args(["--distribution", distro])
+                .args(["--exec", "sh", "-c", r#"command -v "$1""#, "sh", program])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .map(|status| status.success())
+                .unwrap_or(false);
+            if !installed {
+                tracing::debug!(distro, program, "Not installed in WSL, using the host one");
+            }
+            installed
+        })
+}
+
+/// Returns the WSL share to run `program` in, `None` if `dir` is not on the WSL filesystem or
+/// `program` is not installed in the distro.
+fn share_for(program: &str, dir: &Path) -> Option<SharePath> {
+    share_of(dir).filter(|share| is_installed_in(&share.distro, program))
+}
+
+/// Builds the command running `program` in `dir`.
+///
+/// On Windows, the program is run inside the distro via `wsl.exe` if `dir` is on the WSL
+/// filesystem and the program is installed there.
+pub fn command_in(program: &str, dir: &Path) -> Command {
+    match share_for(program, dir) {
+        Some(share) => {
+            let mut cmd = Command::new("wsl.exe");
+            cmd.args(["--distribution", share.distro.as_str()])
+                .args(["--cd", share.linux_path.as_str()])
+                .args(["--exec", program]);
+            cmd
+        }
+        None => {
+            let mut cmd = Command::new(program);
+            cmd.current_dir(dir);
+            cmd
+        }
+    }
+}
+
+/// Returns the path argument of the command built by [`command_in`] in the form the tool
+/// expects, i.e., the Linux form if it's run inside the distro.
+pub fn tool_arg<'a>(program: &str, path: &'a Path, dir: &Path) -> Cow<'a, OsStr> {
+    if share_for(program, dir).is_none() {
+        return path.as_os_str().into();
+    }
+
+    match path.to_str() {
+        Some(path_str) => match SharePath::parse(path_str) {
+            Some(share) => Cow::Owned(share.linux_path.into()),
+            None => Cow::Owned(path_str.replace('\\', "/").into()),
+        },
+        None => path.as_os_str().into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_share_path() {
+        assert_eq!(
+            SharePath::parse(r"\\wsl$\Ubuntu\home\xlc\src"),
+            Some(SharePath {
+                distro: "Ubuntu".into(),
+                linux_path: "/home/xlc/src".into(),
+            })
+        );
+        assert_eq!(
+            SharePath::parse("//wsl.localhost/Debian"),
+            Some(SharePath {
+                distro: "Debian".into(),
+                linux_path: "/".into(),
+            })
+        );
+        assert_eq!(SharePath::parse(r"\\server\share\src"), None);
+        assert_eq!(SharePath::parse(r"C:\src"), None);
+    }
+
+    #[test]
+    fn test_windows_to_mount_path() {
+        assert_eq!(
+            windows_to_mount_path(r"C:\Users\xlc\src"),
+            Some("/mnt/c/Users/xlc/src".into())
+        );
+        assert_eq!(windows_to_mount_path(r"D:\"), Some("/mnt/d".into()));
+        assert_eq!(windows_to_mount_path("/home/xlc"), None);
+    }
+}
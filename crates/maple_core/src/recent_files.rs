@@ -1,3 +1,4 @@
+use crate::config::RecentFilesConfig;
 use crate::UtcTime;
 use chrono::prelude::*;
 use filter::SourceItem;
@@ -6,15 +7,45 @@ use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::path::Path;
 
-// 3600 seconds
-const HOUR: i64 = 3600;
-const DAY: i64 = HOUR * 24;
-const WEEK: i64 = DAY * 7;
-const MONTH: i64 = DAY * 30;
-
 /// Maximum number of recent files.
 const MAX_ENTRIES: u64 = 10_000;
 
+/// Number of the latest visits sampled for the frecent score.
+const MAX_SAMPLED_VISITS: usize = 10;
+
+/// Visits of a file within this number of seconds are merged into one.
+const VISIT_BUCKET_SECS: i64 = 60;
+
+/// How a file is accessed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AccessKind {
+    /// Opened in the editor, e.g., on `BufAdd`.
+    #[default]
+    Open,
+    /// Accepted in a picker.
+    Picker,
+    /// Written in the editor.
+    Edit,
+}
+
+impl AccessKind {
+    fn weight(self, config: &RecentFilesConfig) -> f64 {
+        match self {
+            Self::Open => config.open_weight,
+            Self::Picker => config.picker_weight,
+            Self::Edit => config.edit_weight,
+        }
+    }
+}
+
+/// A visit of the file.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Visit {
+    pub at: UtcTime,
+    pub kind: AccessKind,
+}
+
 /// Preference for sorting the recent files.
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub enum SortPreference {
@@ -35,8 +66,13 @@ pub struct FrecentEntry {
     pub last_visit: UtcTime,
     /// Number of total visits.
     pub visits: u64,
+    /// Latest visits sampled for the frecent score, in chronological order.
+    ///
+    /// Absent in the store written before the frecency is decayed, migrated on loading.
+    #[serde(default)]
+    pub recent_visits: Vec<Visit>,
     /// Score based on https://en.wikipedia.org/wiki/Frecency
-    pub frecent_score: u64,
+    pub frecent_score: f64,
 }
 
 impl PartialEq for FrecentEntry {
@@ -49,11 +85,13 @@ impl Eq for FrecentEntry {}
 
 impl PartialOrd for FrecentEntry {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some((self.frecent_score, self.visits, self.last_visit).cmp(&(
-            other.frecent_score,
-            other.visits,
-            other.last_visit,
-        )))
+        Some(
+            self.frecent_score
+                .total_cmp(&other.frecent_score)
+                .then_with(|| {
+                    (self.visits, self.last_visit).cmp(&(other.visits, other.last_visit))
+                }),
+        )
     }
 }
 
@@ -65,46 +103,91 @@ impl Ord for FrecentEntry {
 
 impl FrecentEntry {
     /// Creates a new instance of [`FrecentEntry`].
-    pub fn new(fpath: String) -> Self {
-        Self {
+    pub fn new(fpath: String, kind: AccessKind, config: &RecentFilesConfig) -> Self {
+        let visit = Visit {
+            at: Utc::now(),
+            kind,
+        };
+        Self::with_visit(fpath, visit, config)
+    }
+
+    /// Creates a new instance of [`FrecentEntry`] visited once at `visit.at`.
+    fn with_visit(fpath: String, visit: Visit, config: &RecentFilesConfig) -> Self {
+        let mut entry = Self {
             fpath,
-            last_visit: Utc::now(),
+            last_visit: visit.at,
             visits: 1u64,
-            frecent_score: 1u64,
-        }
+            recent_visits: vec![visit],
+            frecent_score: 0.0,
+        };
+        entry.update_frecent(Utc::now(), config);
+        entry
     }
 
     /// Updates an existing entry.
-    pub fn refresh_now(&mut self) {
+    pub fn refresh_now(&mut self, kind: AccessKind, config: &RecentFilesConfig) {
         let now = Utc::now();
+
+        match self.recent_visits.last_mut() {
+            // The file opened from a picker is noted as both `Open` and `Picker`, only the one
+            // weighs the most counts.
+            Some(last) if now.signed_duration_since(last.at).num_seconds() < VISIT_BUCKET_SECS => {
+                if kind.weight(config) > last.kind.weight(config) {
+                    last.kind = kind;
+                }
+            }
+            _ => {
+                self.visits += 1;
+                self.recent_visits.push(Visit { at: now, kind });
+                if self.recent_visits.len() > MAX_SAMPLED_VISITS {
+                    self.recent_visits.remove(0);
+                }
+            }
+        }
+
         self.last_visit = now;
-        self.visits += 1;
-        self.update_frecent(Some(now));
+        self.update_frecent(now, config);
     }
 
-    /// Updates the frecent score.
-    pub fn update_frecent(&mut self, at: Option<UtcTime>) {
-        let now = at.unwrap_or_else(Utc::now);
+    /// Updates the frecent score at the time `now`.
+    ///
+    /// Similar to the frecency of Firefox, the score is the number of total visits multiplied by
+    /// the average weight of the latest visits, each of which is halved every `half-life`.
+    pub fn update_frecent(&mut self, now: UtcTime, config: &RecentFilesConfig) {
+        if self.recent_visits.is_empty() {
+            self.frecent_score = 0.0;
+            return;
+        }
 
-        let duration = now.signed_duration_since(self.last_visit).num_seconds();
+        let half_life = config.half_life.0.as_secs_f64().max(1.0);
 
-        self.frecent_score = if duration < HOUR {
-            self.visits * 4
-        } else if duration < DAY {
-            self.visits * 2
-        } else if duration < WEEK {
-            self.visits * 3 / 2
-        } else if duration < MONTH {
-            self.visits / 2
-        } else {
-            self.visits / 4
-        };
+        let total_weight: f64 = self
+            .recent_visits
+            .iter()
+            .map(|visit| {
+                let age = now.signed_duration_since(visit.at).num_seconds().max(0) as f64;
+                visit.kind.weight(config) * (-age / half_life).exp2()
+            })
+            .sum();
+
+        self.frecent_score = self.visits as f64 * total_weight / self.recent_visits.len() as f64;
+    }
+
+    /// Migrates the entry written before the visits are recorded, the last visit is assumed
+    /// to be an [`AccessKind::Open`].
+    fn migrate(&mut self) {
+        if self.recent_visits.is_empty() {
+            self.recent_visits.push(Visit {
+                at: self.last_visit,
+                kind: AccessKind::Open,
+            });
+        }
     }
 
     /// Add a bonus score based on cwd.
-    pub fn cwd_preferred_score(&self, cwd: &str) -> u64 {
+    pub fn cwd_preferred_score(&self, cwd: &str) -> f64 {
         if self.fpath.starts_with(cwd) {
-            self.frecent_score * 2
+            self.frecent_score * 2.0
         } else {
             self.frecent_score
         }
@@ -120,6 +203,9 @@ pub struct SortedRecentFiles {
     pub sort_preference: SortPreference,
     /// An ordered list of [`FrecentEntry`].
     pub entries: Vec<FrecentEntry>,
+    /// Whether `v:oldfiles` has been imported, which is done once on the first run.
+    #[serde(default)]
+    pub oldfiles_imported: bool,
 }
 
 impl Default for SortedRecentFiles {
@@ -128,6 +214,7 @@ impl Default for SortedRecentFiles {
             max_entries: MAX_ENTRIES,
             sort_preference: Default::default(),
             entries: Default::default(),
+            oldfiles_imported: false,
         }
     }
 }
@@ -150,6 +237,77 @@ impl SortedRecentFiles {
         }
     }
 
+    /// Removes the entries whose files no longer exist and writes back to the disk.
+    ///
+    /// Returns the number of the removed entries.
+    pub fn purge_missing(&mut self) -> std::io::Result<usize> {
+        let total = self.entries.len();
+        self.entries
+            .retain(|entry| Path::new(&entry.fpath).is_file());
+        let purged = total - self.entries.len();
+        if purged > 0 {
+            crate::datastore::store_recent_files(self)?;
+        }
+        Ok(purged)
+    }
+
+    /// Seeds the entries from the oldfiles of Vim or Neovim, i.e., `v:oldfiles` read from the
+    /// viminfo or shada file, and writes back to the disk.
+    ///
+    /// `oldfiles` is ordered from the most recent one, each is assumed to be visited once a
+    /// second earlier than the previous one to keep the order. The files already noted or no
+    /// longer existing are skipped. Returns the number of the imported entries.
+    pub fn import_oldfiles(&mut self, oldfiles: Vec<String>) -> std::io::Result<usize> {
+        let config = &crate::config::config().provider.recent_files;
+        let now = Utc::now();
+
+        let total = self.entries.len();
+        for (i, fpath) in oldfiles.into_iter().enumerate() {
+            if self.entries.iter().any(|entry| entry.fpath == fpath) || !Path::new(&fpath).is_file()
+            {
+                continue;
+            }
+            let visit = Visit {
+                at: now - chrono::Duration::seconds(i as i64 + 1),
+                kind: AccessKind::Open,
+            };
+            let entry = FrecentEntry::with_visit(fpath, visit, config);
+            self.entries.push(entry);
+        }
+        let imported = self.entries.len() - total;
+        let first_import = !std::mem::replace(&mut self.oldfiles_imported, true);
+
+        if imported > 0 {
+            self.rescore(config);
+            self.entries.truncate(self.max_entries as usize);
+        }
+
+        if imported > 0 || first_import {
+            crate::datastore::store_recent_files(self)?;
+        }
+
+        Ok(imported)
+    }
+
+    /// Migrates the entries of the older store and rescores them at present.
+    ///
+    /// Used when loading from the disk.
+    pub fn migrate(mut self) -> Self {
+        self.entries.iter_mut().for_each(FrecentEntry::migrate);
+        self.rescore(&crate::config::config().provider.recent_files);
+        self
+    }
+
+    /// Recomputes the decayed scores of all the entries and sorts them.
+    fn rescore(&mut self, config: &RecentFilesConfig) {
+        let now = Utc::now();
+        self.entries
+            .iter_mut()
+            .for_each(|entry| entry.update_frecent(now, config));
+        self.entries
+            .sort_unstable_by(|a, b| b.partial_cmp(a).unwrap());
+    }
+
     /// Returns the size of entries.
     pub fn len(&self) -> usize {
         self.entries.len()
@@ -172,41 +330,54 @@ impl SortedRecentFiles {
             .collect()
     }
 
-    pub fn filter_on_query(&self, query: &str, cwd: String) -> Vec<filter::MatchedItem> {
-        let mut cwd = cwd;
-        cwd.push(std::path::MAIN_SEPARATOR);
+    /// Returns the entries under `cwd` if `project_scoped` is true, otherwise all the entries.
+    pub fn scoped_entries<'a>(
+        &'a self,
+        cwd: &'a str,
+        project_scoped: bool,
+    ) -> impl Iterator<Item = &'a FrecentEntry> {
+        // Compare the components so that all the files are under the cwd of `/`.
+        self.entries
+            .iter()
+            .filter(move |entry| !project_scoped || Path::new(&entry.fpath).starts_with(cwd))
+    }
+
+    pub fn filter_on_query(
+        &self,
+        query: &str,
+        cwd: String,
+        project_scoped: bool,
+    ) -> Vec<filter::MatchedItem> {
+        let cwd_prefix = format!("{cwd}{}", std::path::MAIN_SEPARATOR);
 
         let source_items: Vec<SourceItem> = self
-            .entries
-            .iter()
-            .map(|entry| entry.fpath.replacen(&cwd, "", 1).into())
+            .scoped_entries(&cwd, project_scoped)
+            .map(|entry| entry.fpath.replacen(&cwd_prefix, "", 1).into())
             .collect();
 
-        cwd.pop();
-
         let matcher = MatcherBuilder::new()
             .bonuses(vec![Bonus::Cwd(cwd.into()), Bonus::FileName])
-            .build(query.into());
+            .build(crate::paths::query_with_main_separator(query).into());
 
         filter::par_filter(source_items, &matcher)
     }
 
     /// Updates or inserts a new entry in a sorted way.
-    pub fn upsert(&mut self, file: String) {
+    pub fn upsert(&mut self, file: String, kind: AccessKind, config: &RecentFilesConfig) {
         match self
             .entries
             .iter()
             .position(|entry| entry.fpath.as_str() == file.as_str())
         {
-            Some(pos) => FrecentEntry::refresh_now(&mut self.entries[pos]),
+            Some(pos) => self.entries[pos].refresh_now(kind, config),
             None => {
-                let entry = FrecentEntry::new(file);
+                let entry = FrecentEntry::new(file, kind, config);
                 self.entries.push(entry);
             }
         }
 
-        self.entries
-            .sort_unstable_by(|a, b| b.partial_cmp(a).unwrap());
+        // The scores of the other entries have decayed since they were computed.
+        self.rescore(config);
 
         if self.entries.len() > self.max_entries as usize {
             self.entries.truncate(self.max_entries as usize);
@@ -225,6 +396,7 @@ mod tests {
 
     #[test]
     fn test_sort_by_cwd() {
+        let config = RecentFilesConfig::default();
         let mut sorted_recent_files = SortedRecentFiles::default();
 
         let entries = vec![
@@ -234,7 +406,7 @@ mod tests {
         ];
 
         for entry in entries.iter() {
-            sorted_recent_files.upsert(entry.to_string());
+            sorted_recent_files.upsert(entry.to_string(), AccessKind::Open, &config);
         }
 
         sorted_recent_files.sort_by_cwd("/usr/local/share");
@@ -252,4 +424,76 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_frecent_score_decay() {
+        let config = RecentFilesConfig::default();
+        let half_life = chrono::Duration::from_std(config.half_life.0).unwrap();
+        let now = Utc::now();
+
+        let mut entry = FrecentEntry::new("/tmp/foo.txt".into(), AccessKind::Open, &config);
+        entry.recent_visits = vec![Visit {
+            at: now - half_life,
+            kind: AccessKind::Open,
+        }];
+        entry.update_frecent(now, &config);
+        assert!((entry.frecent_score - 0.5).abs() < 1e-6);
+
+        entry.recent_visits[0].kind = AccessKind::Edit;
+        entry.update_frecent(now, &config);
+        assert!((entry.frecent_score - config.edit_weight / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_visits_in_the_same_bucket_are_merged() {
+        let config = RecentFilesConfig::default();
+
+        let mut entry = FrecentEntry::new("/tmp/foo.txt".into(), AccessKind::Open, &config);
+        entry.refresh_now(AccessKind::Picker, &config);
+        entry.refresh_now(AccessKind::Open, &config);
+
+        assert_eq!(entry.visits, 1);
+        assert_eq!(entry.recent_visits.len(), 1);
+        assert_eq!(entry.recent_visits[0].kind, AccessKind::Picker);
+    }
+
+    #[test]
+    fn test_migrate_entry() {
+        let mut entry: FrecentEntry = serde_json::from_str(
+            r#"{"fpath":"/tmp/foo.txt","last_visit":"2023-01-01T00:00:00Z","visits":3,"frecent_score":6}"#,
+        )
+        .unwrap();
+        entry.migrate();
+        assert_eq!(entry.recent_visits.len(), 1);
+        assert_eq!(entry.recent_visits[0].at, entry.last_visit);
+    }
+
+    #[test]
+    fn test_scoped_entries() {
+        let config = RecentFilesConfig::default();
+        let sorted_recent_files = SortedRecentFiles {
+            entries: [
+                "/home/xlc/src/foo.rs",
+                "/home/xlc/src-bar/baz.rs",
+                "/tmp/qux.txt",
+            ]
+            .into_iter()
+            .map(|entry| FrecentEntry::new(entry.to_string(), AccessKind::Open, &config))
+            .collect(),
+            ..Default::default()
+        };
+
+        let scoped = sorted_recent_files
+            .scoped_entries("/home/xlc/src", true)
+            .map(|entry| entry.fpath.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(scoped, vec!["/home/xlc/src/foo.rs"]);
+        assert_eq!(
+            sorted_recent_files
+                .scoped_entries("/home/xlc/src", false)
+                .count(),
+            3
+        );
+        assert_eq!(sorted_recent_files.scoped_entries("/", true).count(), 3);
+    }
 }
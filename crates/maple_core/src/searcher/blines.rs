@@ -8,7 +8,6 @@ use std::io::{BufRead, Result};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
 use types::{ClapItem, ProgressUpdate};
 
@@ -96,13 +95,18 @@ pub async fn search(
         vim,
         stop_signal,
         item_pool_size,
+        walk_config: _,
+        priority_paths: _,
+        path_display: _,
+        grep_config: _,
     } = search_context;
 
     let printer = Printer::new(line_width, icon);
     let number = item_pool_size;
     let progressor = VimProgressor::new(vim, stop_signal.clone());
 
-    let mut best_items = BestItems::new(printer, number, progressor, Duration::from_millis(200));
+    let update_interval = progressor.update_interval();
+    let mut best_items = BestItems::new(printer, number, progressor, update_interval);
 
     let (sender, mut receiver) = unbounded_channel();
 
@@ -1,56 +1,91 @@
-use super::{walk_parallel, WalkConfig};
+use super::walker::walker;
+use super::WalkConfig;
 use crate::searcher::SearchContext;
 use crate::stdio_server::VimProgressor;
 use filter::{BestItems, MatchedItem};
-use ignore::{DirEntry, WalkState};
 use matcher::Matcher;
 use printer::Printer;
-use std::path::PathBuf;
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
 use types::ProgressUpdate;
 
+/// Returns the priority paths not found by the walk in the form they are listed, i.e., the
+/// hidden or ignored open buffers relative to the search root and the files outside of the
+/// search paths in the absolute form.
+fn unwalked_priority_paths(
+    paths: &[PathBuf],
+    walk_config: &WalkConfig,
+    priority_paths: Vec<PathBuf>,
+) -> Vec<String> {
+    let search_root = &paths[0];
+    priority_paths
+        .into_iter()
+        .filter(|path| {
+            path.is_file()
+                && !paths
+                    .iter()
+                    .any(|search_path| walk_config.is_walked(search_path, path))
+        })
+        .map(|path| {
+            path.strip_prefix(search_root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect()
+}
+
 fn search_files(
     paths: Vec<PathBuf>,
-    hidden: bool,
+    walk_config: WalkConfig,
+    priority_paths: Vec<PathBuf>,
     matcher: Matcher,
     stop_signal: Arc<AtomicBool>,
     sender: UnboundedSender<Option<MatchedItem>>,
 ) {
-    let walk_config = WalkConfig {
-        hidden,
-        ..Default::default()
-    };
-
     let search_root = paths[0].clone();
 
-    walk_parallel(paths, walk_config).run(|| {
+    // The priority paths found by the walk are matched in it, the others are matched ahead of it.
+    for path in unwalked_priority_paths(&paths, &walk_config, priority_paths) {
+        if sender.send(matcher.match_item(Arc::new(path))).is_err() {
+            return;
+        }
+    }
+
+    if paths.len() == 1 {
+        if let Some(files) = crate::indexer::project_files(&search_root, &walk_config) {
+            // Stop once the search is cancelled or the sender is dropped.
+            let _ = files
+                .into_par_iter()
+                .try_for_each_with(sender, |sender, path| {
+                    if stop_signal.load(Ordering::SeqCst) {
+                        return Err(());
+                    }
+                    sender
+                        .send(matcher.match_item(Arc::new(path)))
+                        .map_err(|err| tracing::debug!("Sender is dropped: {err:?}"))
+                });
+            return;
+        }
+    }
+
+    walker().walk_files(paths, walk_config, &stop_signal, &|| {
         let matcher = matcher.clone();
         let sender = sender.clone();
         let stop_signal = stop_signal.clone();
         let search_root = search_root.clone();
-        Box::new(move |entry: Result<DirEntry, ignore::Error>| -> WalkState {
+        Box::new(move |file: &Path| -> bool {
             if stop_signal.load(Ordering::SeqCst) {
-                return WalkState::Quit;
+                return false;
             }
 
-            let entry = match entry {
-                Ok(entry) => entry,
-                Err(_) => return WalkState::Continue,
-            };
-
-            // Only search file and skip everything else.
-            match entry.file_type() {
-                Some(entry) if entry.is_file() => {}
-                _ => return WalkState::Continue,
-            };
-
-            let path = if let Ok(p) = entry.path().strip_prefix(&search_root) {
+            let path = if let Ok(p) = file.strip_prefix(&search_root) {
                 p.to_string_lossy().to_string()
             } else {
-                entry.path().to_string_lossy().to_string()
+                file.to_string_lossy().to_string()
             };
 
             // TODO: Add match_file_path() in matcher to avoid allocation each time.
@@ -58,15 +93,15 @@ fn search_files(
 
             if let Err(err) = sender.send(maybe_matched_item) {
                 tracing::debug!("Sender is dropped: {err:?}");
-                WalkState::Quit
+                false
             } else {
-                WalkState::Continue
+                true
             }
         })
     });
 }
 
-pub async fn search(query: String, hidden: bool, matcher: Matcher, search_context: SearchContext) {
+pub async fn search(query: String, matcher: Matcher, search_context: SearchContext) {
     let SearchContext {
         paths,
         vim,
@@ -74,6 +109,10 @@ pub async fn search(query: String, hidden: bool, matcher: Matcher, search_contex
         line_width,
         stop_signal,
         item_pool_size,
+        walk_config,
+        priority_paths,
+        path_display,
+        grep_config: _,
     } = search_context;
 
     let number = item_pool_size;
@@ -85,15 +124,25 @@ pub async fn search(query: String, hidden: bool, matcher: Matcher, search_contex
         .name("files-worker".into())
         .spawn({
             let stop_signal = stop_signal.clone();
-            move || search_files(paths, hidden, matcher, stop_signal, sender)
+            move || {
+                search_files(
+                    paths,
+                    walk_config,
+                    priority_paths,
+                    matcher,
+                    stop_signal,
+                    sender,
+                )
+            }
         })
         .expect("Failed to spawn blines worker thread");
 
     let mut total_matched = 0usize;
     let mut total_processed = 0usize;
 
-    let printer = Printer::new(line_width, icon);
-    let mut best_items = BestItems::new(printer, number, progressor, Duration::from_millis(200));
+    let printer = Printer::new(line_width, icon).with_path_display(path_display);
+    let update_interval = progressor.update_interval();
+    let mut best_items = BestItems::new(printer, number, progressor, update_interval);
 
     let now = std::time::Instant::now();
 
@@ -135,3 +184,38 @@ pub async fn search(query: String, hidden: bool, matcher: Matcher, search_contex
         "Searching is complete in {elapsed:?}ms"
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unwalked_priority_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        std::fs::create_dir(&root).unwrap();
+        for name in ["a.txt", ".hidden.txt", "ignored.txt"] {
+            std::fs::write(root.join(name), "").unwrap();
+        }
+        std::fs::write(root.join(".ignore"), "ignored.txt\n").unwrap();
+        let outside = dir.path().join("outside.txt");
+        std::fs::write(&outside, "").unwrap();
+
+        let priority_paths = vec![
+            root.join("a.txt"),
+            root.join(".hidden.txt"),
+            root.join("ignored.txt"),
+            root.join("missing.txt"),
+            outside.clone(),
+        ];
+
+        assert_eq!(
+            unwalked_priority_paths(&[root], &WalkConfig::default(), priority_paths),
+            vec![
+                ".hidden.txt".to_string(),
+                "ignored.txt".to_string(),
+                outside.to_string_lossy().to_string(),
+            ]
+        );
+    }
+}
@@ -1,7 +1,9 @@
 mod stoppable_searcher;
 
-pub use self::stoppable_searcher::search;
-use self::stoppable_searcher::{FileResult, SearcherMessage, StoppableSearchImpl, UPDATE_INTERVAL};
+pub use self::stoppable_searcher::{search, FileResult};
+use self::stoppable_searcher::{SearcherMessage, StoppableSearchImpl, UPDATE_INTERVAL};
+use crate::config::GrepConfig;
+use crate::searcher::WalkConfig;
 use matcher::Matcher;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
@@ -59,3 +61,44 @@ pub async fn cli_search(paths: Vec<PathBuf>, matcher: Matcher) -> SearchResult {
         total_processed,
     }
 }
+
+/// Searches `paths` outside of the picker with the same grep options and ranking as the `grep`
+/// provider, `grep_config` is the grep options in effect for the project of `paths`.
+///
+/// Returns the best `limit` matches if any, in descending order of rank.
+pub async fn headless_search(
+    paths: Vec<PathBuf>,
+    matcher: Matcher,
+    walk_config: WalkConfig,
+    grep_config: &GrepConfig,
+    limit: Option<usize>,
+) -> Vec<FileResult> {
+    let (sender, mut receiver) = unbounded_channel();
+
+    let stop_signal = Arc::new(AtomicBool::new(false));
+
+    let searcher = StoppableSearchImpl::new(paths, matcher, sender, stop_signal)
+        .match_limit(limit.and_then(|limit| grep_config.match_limit(limit)))
+        .max_matches_per_file(grep_config.max_matches_per_file)
+        .search_compressed(grep_config.search_compressed)
+        .walk_config(walk_config);
+
+    std::thread::Builder::new()
+        .name("grep-worker".into())
+        .spawn(move || searcher.run())
+        .expect("Failed to spawn grep-worker thread");
+
+    let mut matches = Vec::new();
+    while let Some(searcher_message) = receiver.recv().await {
+        if let SearcherMessage::Match(file_result) = searcher_message {
+            matches.push(file_result);
+        }
+    }
+
+    matches.sort_by(|a, b| b.rank.cmp(&a.rank));
+    if let Some(limit) = limit {
+        matches.truncate(limit);
+    }
+
+    matches
+}
@@ -1,12 +1,16 @@
-use crate::searcher::{walk_parallel, SearchContext, WalkConfig};
+use crate::searcher::walker::{walker, Walker};
+use crate::searcher::{SearchContext, WalkConfig};
 use crate::stdio_server::VimProgressor;
 use filter::MatchedItem;
-use grep_searcher::{sinks, BinaryDetection, SearcherBuilder};
+use grep_searcher::{sinks, BinaryDetection, Searcher, SearcherBuilder};
 use icon::Icon;
-use ignore::{DirEntry, WalkState};
 use matcher::Matcher;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
@@ -46,6 +50,95 @@ pub struct FileResult {
     pub rank: Rank,
     pub indices_in_path: Vec<usize>,
     pub indices_in_line: Vec<usize>,
+    /// Whether the scanning of the file is stopped after this match due to the per-file limit.
+    pub truncated: bool,
+    /// Whether the match is found in the decompressed content of the file.
+    pub decompressed: bool,
+}
+
+impl FileResult {
+    /// Converts to the grep line `path:line:column:text` with the paths under `search_root`
+    /// displayed relatively, `None` if there are no matched indices.
+    pub fn to_grep_result(&self, search_root: &Path) -> Option<printer::GrepResult> {
+        let Self {
+            path,
+            line_number,
+            line,
+            rank,
+            indices_in_path,
+            indices_in_line,
+            truncated,
+            decompressed,
+        } = self;
+
+        let mut column = indices_in_path
+            .first()
+            .or_else(|| indices_in_line.first())
+            .copied()?;
+        column += 1;
+
+        let path = path.strip_prefix(search_root).unwrap_or(path);
+        let mut fmt_line = format!("{}:{line_number}:{column}:", path.display());
+        let offset = fmt_line.len();
+        fmt_line.push_str(line);
+        if *decompressed {
+            fmt_line.push_str(DECOMPRESSED_MARKER);
+        }
+        if *truncated {
+            fmt_line.push_str(TRUNCATED_MARKER);
+        }
+
+        let mut indices = indices_in_path.clone();
+        indices.extend(indices_in_line.iter().map(|x| *x + offset));
+
+        let matched_item = MatchedItem::new(Arc::new(fmt_line), *rank, indices);
+
+        Some(printer::GrepResult {
+            matched_item,
+            path: path.to_path_buf(),
+            line_number: *line_number as usize,
+            column,
+            column_end: offset,
+        })
+    }
+}
+
+/// Appended to the last displayed match of a file truncated by the per-file limit.
+const TRUNCATED_MARKER: &str = "  [more matches in this file]";
+
+/// Appended to the displayed matches found in the compressed files.
+const DECOMPRESSED_MARKER: &str = "  [decompressed]";
+
+/// Number of the indexed files searched as a unit, the idle threads steal the remaining shards
+/// from the busy ones.
+const SHARD_SIZE: usize = 64;
+
+/// Stops the search once enough matches have been found.
+#[derive(Debug, Default)]
+struct MatchBudget {
+    limit: Option<usize>,
+    matched: AtomicUsize,
+}
+
+impl MatchBudget {
+    fn new(limit: Option<usize>) -> Self {
+        Self {
+            limit,
+            matched: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns `false` if the search should stop after this match.
+    fn on_match(&self) -> bool {
+        let matched = self.matched.fetch_add(1, Ordering::Relaxed) + 1;
+        self.limit.map(|limit| matched < limit).unwrap_or(true)
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.limit
+            .map(|limit| self.matched.load(Ordering::Relaxed) >= limit)
+            .unwrap_or(false)
+    }
 }
 
 #[derive(Debug)]
@@ -54,6 +147,13 @@ pub(super) struct StoppableSearchImpl {
     matcher: Matcher,
     sender: UnboundedSender<SearcherMessage>,
     stop_signal: Arc<AtomicBool>,
+    match_limit: Option<usize>,
+    max_matches_per_file: Option<usize>,
+    search_compressed: bool,
+    walk_config: WalkConfig,
+    priority_paths: Vec<PathBuf>,
+    /// Walker of the directories, the one specified in the config if not set.
+    walker: Option<Box<dyn Walker>>,
 }
 
 impl StoppableSearchImpl {
@@ -68,91 +168,268 @@ impl StoppableSearchImpl {
             matcher,
             sender,
             stop_signal,
+            match_limit: None,
+            max_matches_per_file: None,
+            search_compressed: false,
+            walk_config: WalkConfig::default(),
+            priority_paths: Vec::new(),
+            walker: None,
         }
     }
 
+    /// Stops scanning a file once `max_matches_per_file` matches have been found in it.
+    pub(super) fn max_matches_per_file(mut self, max_matches_per_file: Option<usize>) -> Self {
+        self.max_matches_per_file = max_matches_per_file;
+        self
+    }
+
+    /// Searches the decompressed content of the `.gz`, `.xz` and `.zst` files.
+    pub(super) fn search_compressed(mut self, search_compressed: bool) -> Self {
+        self.search_compressed = search_compressed;
+        self
+    }
+
+    /// Walks the directories with `walk_config` when the project index is unavailable.
+    pub(super) fn walk_config(mut self, walk_config: WalkConfig) -> Self {
+        self.walk_config = walk_config;
+        self
+    }
+
+    /// Walks the directories with `walker` instead of the one specified in the config.
+    #[cfg(test)]
+    fn walker(mut self, walker: Box<dyn Walker>) -> Self {
+        self.walker = Some(walker);
+        self
+    }
+
+    /// Searches `priority_paths` ahead of the rest of the files, the paths which would not be
+    /// found by walking the search paths with the walk config are ignored.
+    pub(super) fn priority_paths(mut self, priority_paths: Vec<PathBuf>) -> Self {
+        self.priority_paths = priority_paths;
+        self
+    }
+
+    /// Stops scanning once `match_limit` matches have been found.
+    pub(super) fn match_limit(mut self, match_limit: Option<usize>) -> Self {
+        self.match_limit = match_limit;
+        self
+    }
+
     pub(super) fn run(self) {
         let Self {
             paths,
             matcher,
             sender,
             stop_signal,
+            match_limit,
+            max_matches_per_file,
+            search_compressed,
+            walk_config,
+            priority_paths,
+            walker: maybe_walker,
         } = self;
 
-        let searcher = SearcherBuilder::new()
-            .binary_detection(BinaryDetection::quit(b'\x00'))
-            .build();
-
         let search_root = paths[0].clone();
 
-        walk_parallel(paths, WalkConfig::default()).run(|| {
-            let mut searcher = searcher.clone();
-            let matcher = matcher.clone();
-            let sender = sender.clone();
-            let stop_signal = stop_signal.clone();
-            let search_root = search_root.clone();
-            Box::new(move |entry: Result<DirEntry, ignore::Error>| -> WalkState {
-                if stop_signal.load(Ordering::SeqCst) {
-                    return WalkState::Quit;
+        let budget = MatchBudget::new(match_limit);
+        let file_searcher = FileSearcher {
+            matcher: &matcher,
+            search_root: &search_root,
+            sender: &sender,
+            budget: &budget,
+            stop_signal: &stop_signal,
+            max_matches_per_file,
+            search_compressed,
+        };
+        let should_stop = || stop_signal.load(Ordering::SeqCst) || budget.is_exhausted();
+
+        let new_searcher = || {
+            SearcherBuilder::new()
+                .binary_detection(BinaryDetection::quit(b'\x00'))
+                .build()
+        };
+
+        let priority_paths = priority_paths
+            .into_iter()
+            .filter(|path| {
+                path.is_file() && paths.iter().any(|root| walk_config.is_walked(root, path))
+            })
+            .collect::<Vec<_>>();
+        let prioritized = priority_paths
+            .iter()
+            .map(PathBuf::as_path)
+            .collect::<HashSet<_>>();
+
+        // The prioritized files are searched first so that their matches show up earliest.
+        priority_paths
+            .par_iter()
+            .for_each_init(new_searcher, |searcher, path| {
+                if !should_stop() {
+                    file_searcher.search(searcher, path);
                 }
+            });
+        if should_stop() {
+            return;
+        }
+
+        if paths.len() == 1 {
+            if let Some(files) = crate::indexer::project_files(&search_root, &walk_config) {
+                files
+                    .par_chunks(SHARD_SIZE)
+                    .for_each_init(new_searcher, |searcher, shard| {
+                        for path in shard {
+                            if should_stop() {
+                                return;
+                            }
+                            let path = search_root.join(path);
+                            if !prioritized.contains(path.as_path()) {
+                                file_searcher.search(searcher, &path);
+                            }
+                        }
+                    });
+                return;
+            }
+        }
 
-                let entry = match entry {
-                    Ok(entry) => entry,
-                    Err(_) => return WalkState::Continue,
-                };
+        let walker = maybe_walker.unwrap_or_else(walker);
+        walker.walk_files(paths, walk_config, &stop_signal, &|| {
+            let mut searcher = new_searcher();
+            let file_searcher = &file_searcher;
+            let should_stop = &should_stop;
+            let prioritized = &prioritized;
+            Box::new(move |path: &Path| -> bool {
+                if should_stop() {
+                    return false;
+                }
 
                 // TODO: Add search syntax for filtering path
 
-                // Only search file and skip everything else.
-                match entry.file_type() {
-                    Some(entry) if entry.is_file() => {}
-                    _ => return WalkState::Continue,
-                };
-
-                let result = searcher.search_path(
-                    &MatchEverything,
-                    entry.path(),
-                    sinks::Lossy(|line_number, line| {
-                        if line.is_empty() {
-                            // Discontinue if the sender has been dropped.
-                            return Ok(sender.send(SearcherMessage::ProcessedOne).is_ok());
-                        }
-
-                        let path = entry
-                            .path()
-                            .strip_prefix(&search_root)
-                            .unwrap_or_else(|_| entry.path());
-                        let line = line.trim();
-                        let maybe_file_result =
-                            matcher
-                                .match_file_result(path, line)
-                                .map(|matched| FileResult {
-                                    path: entry.path().to_path_buf(),
-                                    line_number,
-                                    line: line.to_string(),
-                                    rank: matched.rank,
-                                    indices_in_path: matched.exact_indices,
-                                    indices_in_line: matched.fuzzy_indices,
-                                });
-
-                        let searcher_message = if let Some(file_result) = maybe_file_result {
-                            SearcherMessage::Match(file_result)
-                        } else {
-                            SearcherMessage::ProcessedOne
-                        };
-
-                        // Discontinue if the sender has been dropped.
-                        Ok(sender.send(searcher_message).is_ok())
-                    }),
-                );
-
-                if let Err(err) = result {
-                    tracing::error!("Global search error: {}, {}", entry.path().display(), err);
+                if !prioritized.contains(path) {
+                    file_searcher.search(&mut searcher, path);
                 }
 
-                WalkState::Continue
+                true
             })
         });
+
+        if budget.is_exhausted() {
+            tracing::debug!(
+                ?match_limit,
+                "Grep stopped early as enough matches are found"
+            );
+        }
+    }
+}
+
+/// Searches the lines of a file and sends the matches.
+struct FileSearcher<'a> {
+    matcher: &'a Matcher,
+    search_root: &'a Path,
+    sender: &'a UnboundedSender<SearcherMessage>,
+    budget: &'a MatchBudget,
+    stop_signal: &'a AtomicBool,
+    max_matches_per_file: Option<usize>,
+    search_compressed: bool,
+}
+
+impl FileSearcher<'_> {
+    fn search(&self, searcher: &mut Searcher, path: &Path) {
+        let Self {
+            matcher,
+            search_root,
+            sender,
+            budget,
+            stop_signal,
+            max_matches_per_file,
+            search_compressed,
+        } = self;
+
+        let decompressed = if *search_compressed && utils::is_compressed_path(path) {
+            match utils::open_decompressed(path) {
+                Ok(reader) => reader,
+                Err(err) => {
+                    tracing::debug!(?err, ?path, "Failed to decompress the file");
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+        let is_decompressed = decompressed.is_some();
+
+        let relative_path = path.strip_prefix(search_root).unwrap_or(path);
+
+        let mut matches_in_file = 0usize;
+        // The last match is held back until the next one is found, so that it can be marked as
+        // truncated once the per-file limit is exceeded.
+        let mut pending_match: Option<FileResult> = None;
+        let mut sender_dropped = false;
+
+        let sink = sinks::Lossy(|line_number, line| {
+            // Abort in the middle of a large file once the query is superseded.
+            if stop_signal.load(Ordering::Relaxed) {
+                return Ok(false);
+            }
+
+            if line.is_empty() {
+                // Discontinue if the sender has been dropped.
+                sender_dropped = sender.send(SearcherMessage::ProcessedOne).is_err();
+                return Ok(!sender_dropped);
+            }
+
+            let line = line.trim();
+            let Some(matched) = matcher.match_file_result(relative_path, line) else {
+                sender_dropped = sender.send(SearcherMessage::ProcessedOne).is_err();
+                return Ok(!sender_dropped);
+            };
+
+            if max_matches_per_file.is_some_and(|limit| matches_in_file >= limit) {
+                if let Some(last_match) = pending_match.as_mut() {
+                    last_match.truncated = true;
+                }
+                return Ok(false);
+            }
+            matches_in_file += 1;
+
+            let file_result = FileResult {
+                path: path.to_path_buf(),
+                line_number,
+                line: line.to_string(),
+                rank: matched.rank,
+                indices_in_path: matched.exact_indices,
+                indices_in_line: matched.fuzzy_indices,
+                truncated: false,
+                decompressed: is_decompressed,
+            };
+
+            let keep_searching = budget.on_match();
+
+            if let Some(last_match) = pending_match.replace(file_result) {
+                sender_dropped = sender.send(SearcherMessage::Match(last_match)).is_err();
+            }
+
+            // Discontinue if the sender has been dropped.
+            Ok(!sender_dropped && keep_searching)
+        });
+
+        // The legacy encoded files are transcoded to UTF-8 instead of being skipped as binary
+        // or matched against the replacement characters.
+        let result = match decompressed {
+            Some(reader) => Ok(reader),
+            None => File::open(path).map(|file| Box::new(file) as Box<dyn Read + Send>),
+        }
+        .and_then(utils::transcoding_reader)
+        .and_then(|reader| searcher.search_reader(&MatchEverything, reader, sink));
+
+        if let Some(last_match) = pending_match {
+            if !sender_dropped {
+                let _ = sender.send(SearcherMessage::Match(last_match));
+            }
+        }
+
+        if let Err(err) = result {
+            tracing::error!("Global search error: {}, {}", path.display(), err);
+        }
     }
 }
 
@@ -160,6 +437,8 @@ impl StoppableSearchImpl {
 struct BestFileResults {
     /// Time of last notification.
     past: Instant,
+    /// Interval between the notifications, adapted to the render latency of Vim.
+    update_interval: Duration,
     results: Vec<FileResult>,
     last_lines: Vec<String>,
     last_visible_highlights: Vec<Vec<usize>>,
@@ -167,9 +446,10 @@ struct BestFileResults {
 }
 
 impl BestFileResults {
-    fn new(max_capacity: usize) -> Self {
+    fn new(max_capacity: usize, update_interval: Duration) -> Self {
         Self {
-            past: Instant::now(),
+            past: filter::initial_update_time(update_interval),
+            update_interval,
             results: Vec::with_capacity(max_capacity),
             last_lines: Vec::with_capacity(max_capacity),
             last_visible_highlights: Vec::with_capacity(max_capacity),
@@ -190,13 +470,17 @@ pub async fn search(query: String, matcher: Matcher, search_context: SearchConte
         paths,
         stop_signal,
         item_pool_size,
+        walk_config,
+        priority_paths,
+        path_display,
+        grep_config,
     } = search_context;
 
     let progressor = VimProgressor::new(vim, stop_signal.clone());
     let number = item_pool_size;
     let search_root = paths[0].clone();
 
-    let mut best_results = BestFileResults::new(number);
+    let mut best_results = BestFileResults::new(number, progressor.update_interval());
 
     let (sender, mut receiver) = unbounded_channel();
 
@@ -204,7 +488,15 @@ pub async fn search(query: String, matcher: Matcher, search_context: SearchConte
         .name("grep-worker".into())
         .spawn({
             let stop_signal = stop_signal.clone();
-            move || StoppableSearchImpl::new(paths, matcher, sender, stop_signal).run()
+            move || {
+                StoppableSearchImpl::new(paths, matcher, sender, stop_signal)
+                    .match_limit(grep_config.match_limit(number))
+                    .max_matches_per_file(grep_config.max_matches_per_file)
+                    .search_compressed(grep_config.search_compressed)
+                    .walk_config(walk_config)
+                    .priority_paths(priority_paths)
+                    .run()
+            }
         })
         .expect("Failed to spawn grep-worker thread");
 
@@ -214,50 +506,14 @@ pub async fn search(query: String, matcher: Matcher, search_context: SearchConte
     let to_display_lines = |best_results: &[FileResult], icon: Icon| {
         let grep_results = best_results
             .iter()
-            .filter_map(|file_result| {
-                let FileResult {
-                    path,
-                    line_number,
-                    line,
-                    rank,
-                    indices_in_path,
-                    indices_in_line,
-                } = file_result;
-
-                let maybe_column = indices_in_path.first().or_else(|| indices_in_line.first());
-
-                if let Some(mut column) = maybe_column.copied() {
-                    column += 1;
-                    let mut fmt_line = if let Ok(relative_path) = path.strip_prefix(&search_root) {
-                        format!("{}:{line_number}:{column}:", relative_path.display())
-                    } else {
-                        format!("{}:{line_number}:{column}:", path.display())
-                    };
-                    let offset = fmt_line.len();
-                    fmt_line.push_str(line);
-
-                    let mut indices = indices_in_path.clone();
-                    indices.extend(indices_in_line.iter().map(|x| *x + offset));
-
-                    let matched_item = MatchedItem::new(Arc::new(fmt_line), *rank, indices);
-
-                    let line_number = *line_number as usize;
-                    Some(printer::GrepResult {
-                        matched_item,
-                        path: path
-                            .strip_prefix(&search_root)
-                            .unwrap_or(path)
-                            .to_path_buf(),
-                        line_number,
-                        column,
-                        column_end: offset,
-                    })
-                } else {
-                    None
-                }
-            })
+            .filter_map(|file_result| file_result.to_grep_result(&search_root))
             .collect();
-        printer::grep_results_to_display_lines(grep_results, line_width, icon)
+        printer::grep_results_to_display_lines(
+            grep_results,
+            line_width,
+            icon,
+            path_display.as_ref(),
+        )
     };
 
     let now = std::time::Instant::now();
@@ -276,7 +532,7 @@ pub async fn search(query: String, matcher: Matcher, search_context: SearchConte
                     best_results.sort();
 
                     let now = Instant::now();
-                    if now > best_results.past + UPDATE_INTERVAL {
+                    if now > best_results.past + best_results.update_interval {
                         let display_lines = to_display_lines(&best_results.results, icon);
                         progressor.update_all(&display_lines, total_matched, total_processed);
                         best_results.last_lines = display_lines.lines;
@@ -296,7 +552,7 @@ pub async fn search(query: String, matcher: Matcher, search_context: SearchConte
 
                     if total_matched % 16 == 0 || total_processed % 16 == 0 {
                         let now = Instant::now();
-                        if now > best_results.past + UPDATE_INTERVAL {
+                        if now > best_results.past + best_results.update_interval {
                             let display_lines = to_display_lines(&best_results.results, icon);
 
                             let visible_highlights = display_lines
@@ -349,3 +605,168 @@ pub async fn search(query: String, matcher: Matcher, search_context: SearchConte
         "Searching is complete in {elapsed:?}ms"
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::searcher::walker::IgnoreWalker;
+    use matcher::MatcherBuilder;
+
+    #[test]
+    fn test_max_matches_per_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let path = root.join("bundle.js");
+        std::fs::write(&path, "foo 1\nbar\nfoo 2\nfoo 3\nfoo 4\n").unwrap();
+
+        let matcher = MatcherBuilder::new().build("foo".into());
+        let (sender, mut receiver) = unbounded_channel();
+        let file_searcher = FileSearcher {
+            matcher: &matcher,
+            search_root: root,
+            sender: &sender,
+            budget: &MatchBudget::default(),
+            stop_signal: &AtomicBool::new(false),
+            max_matches_per_file: Some(2),
+            search_compressed: false,
+        };
+        file_searcher.search(&mut SearcherBuilder::new().build(), &path);
+        drop(sender);
+
+        let mut matches = Vec::new();
+        while let Ok(message) = receiver.try_recv() {
+            if let SearcherMessage::Match(file_result) = message {
+                matches.push((file_result.line, file_result.truncated));
+            }
+        }
+        assert_eq!(
+            matches,
+            vec![("foo 1".to_string(), false), ("foo 2".to_string(), true)]
+        );
+    }
+
+    #[test]
+    fn test_priority_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::create_dir_all(root.join("deep").join("er")).unwrap();
+        for name in [
+            "a.txt",
+            "b.txt",
+            "c.txt",
+            ".hidden.txt",
+            "ignored.txt",
+            "deep/er/d.txt",
+        ] {
+            std::fs::write(root.join(name), "foo\n").unwrap();
+        }
+        std::fs::write(root.join(".ignore"), "ignored.txt\n").unwrap();
+        let outside_dir = tempfile::tempdir().unwrap();
+        let outside = outside_dir.path().join("outside.txt");
+        std::fs::write(&outside, "foo\n").unwrap();
+
+        let matcher = MatcherBuilder::new().build("foo".into());
+        let (sender, mut receiver) = unbounded_channel();
+        StoppableSearchImpl::new(
+            vec![root.clone()],
+            matcher,
+            sender,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .walk_config(WalkConfig {
+            max_depth: Some(2),
+            ..Default::default()
+        })
+        .priority_paths(vec![
+            root.join("c.txt"),
+            root.join(".hidden.txt"),
+            root.join("ignored.txt"),
+            root.join("deep/er/d.txt"),
+            outside,
+        ])
+        .run();
+
+        let mut matched_paths = Vec::new();
+        while let Ok(message) = receiver.try_recv() {
+            if let SearcherMessage::Match(file_result) = message {
+                matched_paths.push(file_result.path);
+            }
+        }
+        // The priority paths excluded by the walk config are not searched.
+        assert_eq!(matched_paths.len(), 3);
+        assert_eq!(matched_paths[0], root.join("c.txt"));
+    }
+
+    #[test]
+    fn test_search_legacy_encoded_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let latin1 = root.join("latin1.txt");
+        std::fs::write(&latin1, b"caf\xe9 latin\n").unwrap();
+        let utf16 = root.join("utf16.txt");
+        let mut bytes = vec![0xff, 0xfe];
+        bytes.extend(
+            "caf\u{e9} utf16\n"
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes),
+        );
+        std::fs::write(&utf16, bytes).unwrap();
+
+        let matcher = MatcherBuilder::new().build("caf\u{e9}".into());
+        let (sender, mut receiver) = unbounded_channel();
+        let file_searcher = FileSearcher {
+            matcher: &matcher,
+            search_root: root,
+            sender: &sender,
+            budget: &MatchBudget::default(),
+            stop_signal: &AtomicBool::new(false),
+            max_matches_per_file: None,
+            search_compressed: false,
+        };
+        let mut searcher = SearcherBuilder::new()
+            .binary_detection(BinaryDetection::quit(b'\x00'))
+            .build();
+        file_searcher.search(&mut searcher, &latin1);
+        file_searcher.search(&mut searcher, &utf16);
+        drop(sender);
+
+        let mut lines = Vec::new();
+        while let Ok(message) = receiver.try_recv() {
+            if let SearcherMessage::Match(file_result) = message {
+                lines.push(file_result.line);
+            }
+        }
+        assert_eq!(lines, vec!["caf\u{e9} latin", "caf\u{e9} utf16"]);
+    }
+
+    #[test]
+    fn test_to_grep_result() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::write(root.join("lib.rs"), "let foo = 1;\n").unwrap();
+
+        let matcher = MatcherBuilder::new().build("foo".into());
+        let (sender, mut receiver) = unbounded_channel();
+        StoppableSearchImpl::new(
+            vec![root.clone()],
+            matcher,
+            sender,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .walker(Box::new(IgnoreWalker))
+        .run();
+
+        let Ok(SearcherMessage::Match(file_result)) = receiver.try_recv() else {
+            panic!("Expected a match");
+        };
+        let grep_result = file_result.to_grep_result(&root).unwrap();
+        assert_eq!(
+            grep_result.matched_item.item.raw_text(),
+            "lib.rs:1:5:let foo = 1;"
+        );
+        assert_eq!(grep_result.path, PathBuf::from("lib.rs"));
+        assert_eq!(grep_result.column, 5);
+        assert_eq!(grep_result.column_end, "lib.rs:1:5:".len());
+        assert_eq!(grep_result.matched_item.indices, vec![15, 16, 17]);
+    }
+}
@@ -2,12 +2,17 @@ pub mod blines;
 pub mod files;
 pub mod grep;
 pub mod tagfiles;
+pub mod walker;
 
+use crate::config::GrepConfig;
 use crate::stdio_server::Vim;
 use icon::Icon;
-use ignore::{WalkBuilder, WalkParallel};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::types::{Types, TypesBuilder};
+use ignore::{Walk, WalkBuilder, WalkParallel};
+use printer::PathDisplay;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use types::MatchedItem;
@@ -26,6 +31,13 @@ pub struct SearchContext {
     pub vim: Vim,
     pub stop_signal: Arc<AtomicBool>,
     pub item_pool_size: usize,
+    pub walk_config: WalkConfig,
+    /// Files searched ahead of the rest of the project, e.g., the open buffers.
+    pub priority_paths: Vec<PathBuf>,
+    /// Style of displaying the file paths.
+    pub path_display: Option<PathDisplay>,
+    /// Grep options in effect for the project.
+    pub grep_config: GrepConfig,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -54,6 +66,12 @@ pub struct WalkConfig {
     /// WalkBuilder options
     /// Maximum Depth to recurse directories in file picker and global search. Defaults to `None`.
     pub max_depth: Option<usize>,
+    /// Extra gitignore-style globs to ignore, relative to the first path being walked.
+    pub ignore_globs: Vec<String>,
+    /// Only the files of these types are walked if not empty, e.g., `rust`, `py`.
+    pub file_types: Vec<String>,
+    /// Whether to descend into the git submodules. Defaults to true.
+    pub submodules: bool,
 }
 
 impl Default for WalkConfig {
@@ -67,11 +85,195 @@ impl Default for WalkConfig {
             git_global: true,
             git_exclude: true,
             max_depth: None,
+            ignore_globs: Vec::new(),
+            file_types: Vec::new(),
+            submodules: true,
         }
     }
 }
 
-fn walk_parallel(paths: Vec<PathBuf>, walk_config: WalkConfig) -> WalkParallel {
+/// Filter of the entries by the ignore globs, file types and submodules of [`WalkConfig`].
+#[derive(Debug, Clone, Default)]
+pub struct WalkFilter {
+    ignore_globs: Option<Gitignore>,
+    file_types: Option<Types>,
+    /// Root of the walking if the git submodules under it are skipped.
+    skip_submodules_under: Option<PathBuf>,
+}
+
+/// Returns true if `dir` is a git submodule, whose `.git` is a file pointing to the git
+/// directory of the parent repo.
+fn is_git_submodule(dir: &Path) -> bool {
+    dir.join(".git").is_file()
+}
+
+impl WalkFilter {
+    /// Returns true if the entry at `path` should be skipped.
+    ///
+    /// The directories are never excluded by the file types.
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        if let Some(ignore_globs) = &self.ignore_globs {
+            if ignore_globs.matched(path, is_dir).is_ignore() {
+                return true;
+            }
+        }
+        if let Some(file_types) = &self.file_types {
+            if file_types.matched(path, is_dir).is_ignore() {
+                return true;
+            }
+        }
+        if let Some(root) = &self.skip_submodules_under {
+            if is_dir && path != root && is_git_submodule(path) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl WalkConfig {
+    /// Returns the filter of the entries walked from `root`.
+    ///
+    /// The invalid globs and unknown file types are logged and ignored.
+    pub fn filter(&self, root: &Path) -> WalkFilter {
+        let ignore_globs = if self.ignore_globs.is_empty() {
+            None
+        } else {
+            let mut builder = GitignoreBuilder::new(root);
+            for glob in &self.ignore_globs {
+                if let Err(err) = builder.add_line(None, glob) {
+                    tracing::warn!(?err, glob, "Invalid ignore glob");
+                }
+            }
+            builder
+                .build()
+                .map_err(|err| tracing::warn!(?err, "Failed to build the ignore globs"))
+                .ok()
+        };
+
+        let file_types = if self.file_types.is_empty() {
+            None
+        } else {
+            let mut builder = TypesBuilder::new();
+            builder.add_defaults();
+            for file_type in &self.file_types {
+                builder.select(file_type);
+            }
+            builder
+                .build()
+                .map_err(|err| tracing::warn!(?err, "Invalid file types"))
+                .ok()
+        };
+
+        WalkFilter {
+            ignore_globs,
+            file_types,
+            skip_submodules_under: (!self.submodules).then(|| root.to_path_buf()),
+        }
+    }
+}
+
+impl WalkConfig {
+    /// Returns true if the file at `path` is found by walking from `root`, i.e., it's under
+    /// `root` and not excluded by the hidden, ignore files, max depth or [`WalkFilter`] rules.
+    ///
+    /// This applies the rules of the walking to the files searched without walking, e.g., the
+    /// open buffers, the ignore files are read on each call and hence it's only meant for a few
+    /// paths.
+    pub fn is_walked(&self, root: &Path, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(root) else {
+            return false;
+        };
+        let components = relative.iter().collect::<Vec<_>>();
+        if components.is_empty() {
+            return false;
+        }
+
+        if self
+            .max_depth
+            .is_some_and(|max_depth| components.len() > max_depth)
+        {
+            return false;
+        }
+
+        let is_hidden = |name: &std::ffi::OsStr| name.to_string_lossy().starts_with('.');
+        if components
+            .iter()
+            .any(|name| *name == ".git" || (self.hidden && is_hidden(name)))
+        {
+            return false;
+        }
+
+        let walk_filter = self.filter(root);
+        let mut entry = root.to_path_buf();
+        for (i, name) in components.iter().enumerate() {
+            entry.push(name);
+            if walk_filter.is_excluded(&entry, i + 1 < components.len()) {
+                return false;
+            }
+        }
+
+        !self.is_ignored_by_files(root, path)
+    }
+
+    /// Returns true if `path` is ignored by the ignore files in its parent directories, the
+    /// deeper ignore files take precedence.
+    fn is_ignored_by_files(&self, root: &Path, path: &Path) -> bool {
+        let git_root = path
+            .ancestors()
+            .skip(1)
+            .find(|dir| dir.join(".git").exists());
+        let in_git_repo = git_root.is_some();
+
+        let dirs = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| self.parents || dir.starts_with(root));
+
+        for dir in dirs {
+            let mut builder = GitignoreBuilder::new(dir);
+            let mut ignore_files = Vec::new();
+            if in_git_repo && self.git_exclude {
+                ignore_files.push(dir.join(".git").join("info").join("exclude"));
+            }
+            if in_git_repo && self.git_ignore {
+                ignore_files.push(dir.join(".gitignore"));
+            }
+            if self.ignore {
+                ignore_files.push(dir.join(".ignore"));
+            }
+            // The later added ignore files take precedence.
+            for ignore_file in ignore_files.iter().filter(|file| file.is_file()) {
+                if let Some(err) = builder.add(ignore_file) {
+                    tracing::debug!(?err, ?ignore_file, "Failed to read the ignore file");
+                }
+            }
+            let Ok(gitignore) = builder.build() else {
+                continue;
+            };
+
+            let matched = gitignore.matched_path_or_any_parents(path, false);
+            if matched.is_ignore() {
+                return true;
+            }
+            if matched.is_whitelist() {
+                return false;
+            }
+        }
+
+        // The global gitignore is rooted at the cwd, match the path relative to the repo.
+        match git_root.and_then(|git_root| path.strip_prefix(git_root).ok()) {
+            Some(relative_path) if self.git_global => Gitignore::global()
+                .0
+                .matched_path_or_any_parents(relative_path, false)
+                .is_ignore(),
+            _ => false,
+        }
+    }
+}
+
+fn walk_builder(paths: Vec<PathBuf>, walk_config: WalkConfig) -> WalkBuilder {
+    let walk_filter = walk_config.filter(&paths[0]);
     let mut builder = WalkBuilder::new(&paths[0]);
     for path in &paths[1..] {
         builder.add(path);
@@ -88,6 +290,63 @@ fn walk_parallel(paths: Vec<PathBuf>, walk_config: WalkConfig) -> WalkParallel {
         // We always want to ignore the .git directory, otherwise if
         // `ignore` is turned off above, we end up with a lot of noise
         // in our picker.
-        .filter_entry(|entry| entry.file_name() != ".git")
-        .build_parallel()
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            entry.file_name() != ".git" && !walk_filter.is_excluded(entry.path(), is_dir)
+        });
+    builder
+}
+
+fn walk_parallel(paths: Vec<PathBuf>, walk_config: WalkConfig) -> WalkParallel {
+    walk_builder(paths, walk_config).build_parallel()
+}
+
+pub(crate) fn walk(paths: Vec<PathBuf>, walk_config: WalkConfig) -> Walk {
+    walk_builder(paths, walk_config).build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skip_submodules() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir(root.join(".git")).unwrap();
+        std::fs::write(root.join("main.rs"), "").unwrap();
+        // The `.git` of a submodule is a file pointing to the git directory of the parent repo.
+        let submodule = root.join("vendor");
+        std::fs::create_dir(&submodule).unwrap();
+        std::fs::write(submodule.join(".git"), "gitdir: ../.git/modules/vendor").unwrap();
+        std::fs::write(submodule.join("lib.rs"), "").unwrap();
+
+        let walked_files = |walk_config: WalkConfig| {
+            let mut files = walk(vec![root.to_path_buf()], walk_config)
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+                .map(|entry| entry.path().strip_prefix(root).unwrap().to_path_buf())
+                .collect::<Vec<_>>();
+            files.sort();
+            files
+        };
+
+        let walk_config = WalkConfig {
+            submodules: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            walked_files(walk_config.clone()),
+            [PathBuf::from("main.rs")]
+        );
+        assert!(!walk_config.is_walked(root, &submodule.join("lib.rs")));
+        assert!(walk_config.is_walked(root, &root.join("main.rs")));
+
+        let walk_config = WalkConfig::default();
+        assert_eq!(
+            walked_files(walk_config.clone()),
+            [PathBuf::from("main.rs"), PathBuf::from("vendor/lib.rs")]
+        );
+        assert!(walk_config.is_walked(root, &submodule.join("lib.rs")));
+    }
 }
@@ -10,7 +10,6 @@ use std::io::{BufRead, BufReader, Result};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
 use types::{ClapItem, ProgressUpdate};
 
@@ -291,17 +290,23 @@ pub async fn search(query: String, cwd: PathBuf, matcher: Matcher, search_contex
         vim,
         stop_signal,
         item_pool_size,
+        walk_config: _,
+        priority_paths: _,
+        path_display: _,
+        grep_config: _,
     } = search_context;
 
     let printer = Printer {
         line_width,
         icon,
         truncate_text: false,
+        path_display: None,
     };
     let number = item_pool_size;
     let progressor = VimProgressor::new(vim, stop_signal.clone());
 
-    let mut best_items = BestItems::new(printer, number, progressor, Duration::from_millis(200));
+    let update_interval = progressor.update_interval();
+    let mut best_items = BestItems::new(printer, number, progressor, update_interval);
 
     let (sender, mut receiver) = unbounded_channel();
 
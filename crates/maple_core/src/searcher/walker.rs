@@ -0,0 +1,465 @@
+//! Directory traversal behind the [`Walker`] trait.
+//!
+//! [`IgnoreWalker`] backed by the `ignore` crate is used by default, [`NativeWalker`] reads the
+//! directory entries in batches with `getdents64` on Linux, which avoids a `stat` call per entry
+//! and is faster on the huge repos with a cold file system cache.
+
+use super::{walk_parallel, WalkConfig};
+use crate::config::WalkerBackend;
+use ignore::WalkState;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Visitor of the files found by a [`Walker`], returns `false` to stop the walking.
+pub type FileVisitor<'s> = Box<dyn FnMut(&Path) -> bool + Send + 's>;
+
+/// Walks the files under the given paths in parallel.
+pub trait Walker: std::fmt::Debug + Send + Sync {
+    /// Calls the visitor created by `mkf` on each file, a visitor is never shared between threads.
+    ///
+    /// `stop_signal` is checked on every entry including the directories, the walking is
+    /// aborted as soon as it's set.
+    fn walk_files<'s>(
+        &self,
+        paths: Vec<PathBuf>,
+        walk_config: WalkConfig,
+        stop_signal: &AtomicBool,
+        mkf: &(dyn Fn() -> FileVisitor<'s> + Sync),
+    );
+}
+
+/// Returns the walker specified in the config, the default one if the config file is not
+/// initialized.
+pub fn walker() -> Box<dyn Walker> {
+    let backend = crate::config::try_config()
+        .map(|config| config.walker.backend)
+        .unwrap_or_default();
+    match backend {
+        WalkerBackend::Ignore => Box::new(IgnoreWalker),
+        #[cfg(target_os = "linux")]
+        WalkerBackend::Native => Box::new(NativeWalker),
+        #[cfg(not(target_os = "linux"))]
+        WalkerBackend::Native => Box::new(IgnoreWalker),
+    }
+}
+
+/// Walker of the `ignore` crate.
+#[derive(Debug)]
+pub struct IgnoreWalker;
+
+impl Walker for IgnoreWalker {
+    fn walk_files<'s>(
+        &self,
+        paths: Vec<PathBuf>,
+        walk_config: WalkConfig,
+        stop_signal: &AtomicBool,
+        mkf: &(dyn Fn() -> FileVisitor<'s> + Sync),
+    ) {
+        walk_parallel(paths, walk_config).run(|| {
+            let mut visit = mkf();
+            Box::new(move |entry| {
+                if stop_signal.load(Ordering::Relaxed) {
+                    return WalkState::Quit;
+                }
+
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(_) => return WalkState::Continue,
+                };
+
+                // Only search file and skip everything else.
+                match entry.file_type() {
+                    Some(file_type) if file_type.is_file() => {}
+                    _ => return WalkState::Continue,
+                };
+
+                if visit(entry.path()) {
+                    WalkState::Continue
+                } else {
+                    WalkState::Quit
+                }
+            })
+        });
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use self::linux::NativeWalker;
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{FileVisitor, WalkConfig, Walker};
+    use crate::searcher::WalkFilter;
+    use ignore::gitignore::Gitignore;
+    use parking_lot::Mutex;
+    use std::collections::HashSet;
+    use std::ffi::{CStr, OsStr};
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::MetadataExt;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    const DT_UNKNOWN: u8 = 0;
+    const DT_DIR: u8 = 4;
+    const DT_REG: u8 = 8;
+    const DT_LNK: u8 = 10;
+
+    /// Size of the buffer for reading the directory entries, one syscall reads hundreds
+    /// of entries.
+    const DIRENT_BUFFER_SIZE: usize = 64 * 1024;
+
+    /// Reads the entries of `dir` as (file name, file type) using `getdents64`.
+    fn read_dir_batched(dir: &Path) -> std::io::Result<Vec<(PathBuf, u8)>> {
+        let c_path = std::ffi::CString::new(dir.as_os_str().as_bytes())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+
+        // SAFETY: `c_path` is a valid nul-terminated string.
+        let fd = unsafe {
+            libc::open(
+                c_path.as_ptr(),
+                libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        let mut buffer = vec![0u8; DIRENT_BUFFER_SIZE];
+        let mut entries = Vec::new();
+
+        let result = loop {
+            // SAFETY: `buffer` is valid for writes of `buffer.len()` bytes.
+            let read = unsafe {
+                libc::syscall(libc::SYS_getdents64, fd, buffer.as_mut_ptr(), buffer.len())
+            };
+
+            if read < 0 {
+                break Err(std::io::Error::last_os_error());
+            }
+            if read == 0 {
+                break Ok(());
+            }
+
+            // struct linux_dirent64 {
+            //     ino64_t        d_ino;
+            //     off64_t        d_off;
+            //     unsigned short d_reclen;
+            //     unsigned char  d_type;
+            //     char           d_name[];
+            // };
+            let mut offset = 0usize;
+            while offset < read as usize {
+                let record = &buffer[offset..];
+                let reclen = u16::from_ne_bytes([record[16], record[17]]) as usize;
+                let d_type = record[18];
+                // SAFETY: `d_name` is nul-terminated within the record.
+                let name = unsafe { CStr::from_ptr(record[19..].as_ptr().cast()) };
+                let name = OsStr::from_bytes(name.to_bytes());
+                if name != "." && name != ".." {
+                    entries.push((dir.join(name), d_type));
+                }
+                offset += reclen;
+            }
+        };
+
+        // SAFETY: `fd` is opened above and not used afterwards.
+        unsafe { libc::close(fd) };
+
+        result.map(|()| entries)
+    }
+
+    /// Stack of the ignore files from the root to the current directory.
+    #[derive(Debug)]
+    struct IgnoreStack {
+        parent: Option<Arc<IgnoreStack>>,
+        matcher: Gitignore,
+    }
+
+    fn push_ignore(
+        stack: Option<Arc<IgnoreStack>>,
+        ignore_file: &Path,
+    ) -> Option<Arc<IgnoreStack>> {
+        if !ignore_file.is_file() {
+            return stack;
+        }
+        let (matcher, _) = Gitignore::new(ignore_file);
+        Some(Arc::new(IgnoreStack {
+            parent: stack,
+            matcher,
+        }))
+    }
+
+    fn is_ignored(mut stack: Option<&Arc<IgnoreStack>>, path: &Path, is_dir: bool) -> bool {
+        while let Some(ignore) = stack {
+            let matched = ignore.matcher.matched(path, is_dir);
+            if matched.is_ignore() {
+                return true;
+            }
+            if matched.is_whitelist() {
+                return false;
+            }
+            stack = ignore.parent.as_ref();
+        }
+        false
+    }
+
+    struct WalkContext<'a, 's> {
+        walk_config: &'a WalkConfig,
+        walk_filter: WalkFilter,
+        stop_signal: &'a AtomicBool,
+        mkf: &'a (dyn Fn() -> FileVisitor<'s> + Sync),
+        /// Reuses the visitors instead of creating one per directory.
+        visitors: Mutex<Vec<FileVisitor<'s>>>,
+        /// Directories visited via symlinks, for detecting the loop.
+        visited_links: Mutex<HashSet<(u64, u64)>>,
+        quit: AtomicBool,
+    }
+
+    impl<'a, 's> WalkContext<'a, 's> {
+        fn should_quit(&self) -> bool {
+            self.quit.load(Ordering::SeqCst) || self.stop_signal.load(Ordering::Relaxed)
+        }
+
+        fn visit_files(&self, files: Vec<PathBuf>) {
+            let mut visit = self.visitors.lock().pop().unwrap_or_else(|| (self.mkf)());
+            for file in files {
+                if self.should_quit() || !visit(&file) {
+                    self.quit.store(true, Ordering::SeqCst);
+                    break;
+                }
+            }
+            self.visitors.lock().push(visit);
+        }
+
+        fn ignore_stack(
+            &self,
+            stack: Option<Arc<IgnoreStack>>,
+            dir: &Path,
+        ) -> Option<Arc<IgnoreStack>> {
+            let mut stack = stack;
+            if self.walk_config.git_ignore {
+                stack = push_ignore(stack, &dir.join(".gitignore"));
+            }
+            if self.walk_config.ignore {
+                stack = push_ignore(stack, &dir.join(".ignore"));
+            }
+            if self.walk_config.git_exclude {
+                stack = push_ignore(stack, &dir.join(".git").join("info").join("exclude"));
+            }
+            stack
+        }
+
+        fn walk_dir<'scope>(
+            &'scope self,
+            scope: &rayon::Scope<'scope>,
+            dir: PathBuf,
+            depth: usize,
+            stack: Option<Arc<IgnoreStack>>,
+        ) {
+            if self.should_quit() {
+                return;
+            }
+
+            let entries = match read_dir_batched(&dir) {
+                Ok(entries) => entries,
+                Err(_) => return,
+            };
+
+            let stack = self.ignore_stack(stack, &dir);
+
+            let mut files = Vec::new();
+
+            for (path, d_type) in entries {
+                if self.should_quit() {
+                    return;
+                }
+
+                let is_hidden = path
+                    .file_name()
+                    .map(|name| name.as_bytes().starts_with(b"."))
+                    .unwrap_or(false);
+                if is_hidden && (self.walk_config.hidden || path.ends_with(".git")) {
+                    continue;
+                }
+
+                let is_dir = match d_type {
+                    DT_DIR => true,
+                    DT_REG => false,
+                    DT_LNK if self.walk_config.follow_symlinks => match path.metadata() {
+                        Ok(metadata) if metadata.is_dir() => {
+                            if !self
+                                .visited_links
+                                .lock()
+                                .insert((metadata.dev(), metadata.ino()))
+                            {
+                                continue;
+                            }
+                            true
+                        }
+                        Ok(metadata) if metadata.is_file() => false,
+                        _ => continue,
+                    },
+                    DT_UNKNOWN => match path.symlink_metadata() {
+                        Ok(metadata) if metadata.is_dir() => true,
+                        Ok(metadata) if metadata.is_file() => false,
+                        _ => continue,
+                    },
+                    _ => continue,
+                };
+
+                if is_ignored(stack.as_ref(), &path, is_dir)
+                    || self.walk_filter.is_excluded(&path, is_dir)
+                {
+                    continue;
+                }
+
+                if is_dir {
+                    if self
+                        .walk_config
+                        .max_depth
+                        .map(|max_depth| depth + 1 < max_depth)
+                        .unwrap_or(true)
+                    {
+                        let stack = stack.clone();
+                        scope.spawn(move |scope| self.walk_dir(scope, path, depth + 1, stack));
+                    }
+                } else {
+                    files.push(path);
+                }
+            }
+
+            if !files.is_empty() {
+                self.visit_files(files);
+            }
+        }
+    }
+
+    /// Walker reading the directory entries in batches via `getdents64`.
+    ///
+    /// The ignore files are respected as the `ignore` crate does, except that `.gitignore` is
+    /// also applied outside of a git repo.
+    #[derive(Debug)]
+    pub struct NativeWalker;
+
+    impl Walker for NativeWalker {
+        fn walk_files<'s>(
+            &self,
+            paths: Vec<PathBuf>,
+            walk_config: WalkConfig,
+            stop_signal: &AtomicBool,
+            mkf: &(dyn Fn() -> FileVisitor<'s> + Sync),
+        ) {
+            let Some(root) = paths.first() else {
+                return;
+            };
+            let context = WalkContext {
+                walk_config: &walk_config,
+                walk_filter: walk_config.filter(root),
+                stop_signal,
+                mkf,
+                visitors: Mutex::new(Vec::new()),
+                visited_links: Mutex::new(HashSet::new()),
+                quit: AtomicBool::new(false),
+            };
+
+            let global_ignore = if walk_config.git_global {
+                let (matcher, _) = Gitignore::global();
+                Some(Arc::new(IgnoreStack {
+                    parent: None,
+                    matcher,
+                }))
+            } else {
+                None
+            };
+
+            rayon::scope(|scope| {
+                for path in paths {
+                    if path.is_file() {
+                        context.visit_files(vec![path]);
+                        continue;
+                    }
+
+                    let mut stack = global_ignore.clone();
+                    if walk_config.parents {
+                        let mut ancestors = path.ancestors().skip(1).collect::<Vec<_>>();
+                        ancestors.reverse();
+                        for ancestor in ancestors {
+                            stack = context.ignore_stack(stack, ancestor);
+                        }
+                    }
+
+                    let context = &context;
+                    scope.spawn(move |scope| context.walk_dir(scope, path, 0, stack));
+                }
+            });
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_native_walker() {
+            let dir = tempfile::tempdir().unwrap();
+            let root = dir.path().to_path_buf();
+            std::fs::create_dir_all(root.join("src").join("nested")).unwrap();
+            std::fs::create_dir_all(root.join("target")).unwrap();
+            std::fs::create_dir_all(root.join(".hidden")).unwrap();
+            for file in [
+                "src/lib.rs",
+                "src/nested/mod.rs",
+                "target/build.rs",
+                ".hidden/secret",
+                "README.md",
+            ] {
+                std::fs::write(root.join(file), "").unwrap();
+            }
+            std::fs::write(root.join(".ignore"), "target/\n").unwrap();
+
+            let found = Mutex::new(Vec::new());
+            let mkf = || -> FileVisitor {
+                Box::new(|path: &Path| {
+                    found
+                        .lock()
+                        .push(path.strip_prefix(&root).unwrap().to_path_buf());
+                    true
+                })
+            };
+            NativeWalker.walk_files(
+                vec![root.clone()],
+                WalkConfig::default(),
+                &AtomicBool::new(false),
+                &mkf,
+            );
+
+            let mut found = found.into_inner();
+            found.sort();
+            assert_eq!(
+                found,
+                vec![
+                    PathBuf::from("README.md"),
+                    PathBuf::from("src/lib.rs"),
+                    PathBuf::from("src/nested/mod.rs"),
+                ]
+            );
+
+            // Nothing is visited once the walking is cancelled.
+            let found = Mutex::new(Vec::new());
+            let mkf = || -> FileVisitor {
+                Box::new(|path: &Path| {
+                    found.lock().push(path.to_path_buf());
+                    true
+                })
+            };
+            NativeWalker.walk_files(
+                vec![root.clone()],
+                WalkConfig::default(),
+                &AtomicBool::new(true),
+                &mkf,
+            );
+            assert!(found.into_inner().is_empty());
+        }
+    }
+}
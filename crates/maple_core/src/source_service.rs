@@ -0,0 +1,246 @@
+//! Candidate sources and rankings served to the frontends other than the Clap UI.
+//!
+//! The requests `source/list` and `source/filter` are accepted by both the stdio server and the
+//! picker daemon, so that a thin extension of Telescope or another picker can reuse the backends
+//! of maple.
+
+use crate::config::WalkerConfig;
+use anyhow::Result;
+use icon::Icon;
+use matcher::{Matcher, MatcherBuilder};
+use parking_lot::Mutex;
+use printer::Printer;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use types::{CaseMatching, ClapItem, Query};
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltinSource {
+    Files,
+    GitFiles,
+    RecentFiles,
+    Grep,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Source {
+    Builtin(BuiltinSource),
+    Lines { lines: Vec<String> },
+}
+
+/// Returns the stdout lines of the shell command `cmd` executed in `cwd`.
+fn command_lines(cmd: &str, cwd: &Path) -> Result<Vec<String>> {
+    let output = crate::process::shell_command(cmd)
+        .current_dir(cwd)
+        .output()?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(Into::into)
+        .collect())
+}
+
+impl Source {
+    /// Returns the lines of the source collected in `cwd`.
+    pub fn collect_lines(&self, cwd: &Path) -> Result<Vec<String>> {
+        let lines = match self {
+            Self::Builtin(BuiltinSource::Files) => {
+                let walk_config = match crate::config::try_config() {
+                    Some(config) => config.walker.walk_config(Some("files")),
+                    None => WalkerConfig::default().walk_config(Some("files")),
+                };
+                crate::searcher::walk(vec![cwd.to_path_buf()], walk_config)
+                    .filter_map(|entry| {
+                        let entry = entry.ok()?;
+                        if !entry.file_type()?.is_file() {
+                            return None;
+                        }
+                        let path = entry.path().strip_prefix(cwd).unwrap_or(entry.path());
+                        Some(path.display().to_string())
+                    })
+                    .collect()
+            }
+            Self::Builtin(BuiltinSource::GitFiles) => command_lines("git ls-files", cwd)?,
+            Self::Builtin(BuiltinSource::Grep) => {
+                command_lines(crate::tools::rg::RG_EXEC_CMD, cwd)?
+            }
+            Self::Builtin(BuiltinSource::RecentFiles) => {
+                let mut recent_files = crate::datastore::RECENT_FILES_IN_MEMORY.lock();
+                recent_files.sort_by_cwd(&cwd.display().to_string());
+                recent_files.recent_n_files(recent_files.len())
+            }
+            Self::Lines { lines } => lines.clone(),
+        };
+
+        Ok(lines)
+    }
+}
+
+impl BuiltinSource {
+    /// Returns the builtin source of provider `provider_id`, if any.
+    pub fn from_provider_id(provider_id: &str) -> Option<Self> {
+        match provider_id {
+            "files" => Some(Self::Files),
+            "git_files" => Some(Self::GitFiles),
+            "recent_files" => Some(Self::RecentFiles),
+            "grep" | "live_grep" => Some(Self::Grep),
+            _ => None,
+        }
+    }
+
+    /// Returns the id of the provider serving this source.
+    pub fn provider_id(&self) -> &'static str {
+        match self {
+            Self::Files => "files",
+            Self::GitFiles => "git_files",
+            Self::RecentFiles => "recent_files",
+            Self::Grep => "grep",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SourceParams {
+    pub source: Source,
+    pub cwd: Option<PathBuf>,
+}
+
+impl SourceParams {
+    fn cwd(&self) -> Result<PathBuf> {
+        match &self.cwd {
+            Some(cwd) => Ok(cwd.clone()),
+            None => Ok(std::env::current_dir()?),
+        }
+    }
+}
+
+type SourceItems = Arc<[Arc<dyn ClapItem>]>;
+
+/// Items of the last listed source of a connection, reused by the consecutive `source/filter`
+/// requests.
+#[derive(Clone, Default)]
+pub struct SourceCache(Arc<Mutex<Option<(Source, PathBuf, SourceItems)>>>);
+
+fn source_items(
+    params: &SourceParams,
+    refresh: bool,
+    source_cache: &SourceCache,
+) -> Result<SourceItems> {
+    let cwd = params.cwd()?;
+
+    if !refresh {
+        if let Some((source, source_cwd, items)) = source_cache.0.lock().as_ref() {
+            if *source == params.source && *source_cwd == cwd {
+                return Ok(items.clone());
+            }
+        }
+    }
+
+    let items: SourceItems = params
+        .source
+        .collect_lines(&cwd)?
+        .into_iter()
+        .map(|line| Arc::new(line) as Arc<dyn ClapItem>)
+        .collect();
+
+    source_cache
+        .0
+        .lock()
+        .replace((params.source.clone(), cwd, items.clone()));
+
+    Ok(items)
+}
+
+/// Returns the lines of the source, `source/list`.
+pub fn list_source(params: SourceParams, source_cache: &SourceCache) -> Result<Value> {
+    let items = source_items(&params, true, source_cache)?;
+    let lines = items.iter().map(|item| item.raw_text()).collect::<Vec<_>>();
+    Ok(json!({ "total": lines.len(), "lines": lines }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FilterParams {
+    #[serde(flatten)]
+    pub source: SourceParams,
+    pub query: String,
+    #[serde(default = "default_number")]
+    pub number: usize,
+    #[serde(default = "default_winwidth")]
+    pub winwidth: usize,
+    /// `smart`, `ignore` or `respect`, defaults to `smart`.
+    #[serde(default)]
+    pub case_matching: Option<String>,
+}
+
+fn default_number() -> usize {
+    100
+}
+
+fn default_winwidth() -> usize {
+    80
+}
+
+/// Returns the top `number` ranked items matched by `matcher`.
+pub fn filter_items(
+    items: &[Arc<dyn ClapItem>],
+    matcher: &Matcher,
+    number: usize,
+    winwidth: usize,
+) -> Value {
+    let mut matched_items = filter::par_filter_items(items, matcher);
+    let total = matched_items.len();
+    matched_items.truncate(number);
+    let ranks = matched_items
+        .iter()
+        .map(|matched_item| matched_item.rank)
+        .collect::<Vec<_>>();
+    let display_lines = Printer::new(winwidth, Icon::Null).to_display_lines(matched_items);
+    json!({
+        "total": total,
+        "lines": display_lines.lines,
+        "indices": display_lines.indices,
+        "ranks": ranks,
+    })
+}
+
+/// Returns the ranked items of the source matching the query, `source/filter`.
+///
+/// The source is only collected again if it differs from the last one of the connection.
+pub fn filter_source(params: FilterParams, source_cache: &SourceCache) -> Result<Value> {
+    let items = source_items(&params.source, false, source_cache)?;
+    let matcher = MatcherBuilder::new()
+        .case_matching(
+            params
+                .case_matching
+                .map(CaseMatching::from)
+                .unwrap_or_default(),
+        )
+        .build(Query::from(&params.query));
+    Ok(filter_items(
+        &items,
+        &matcher,
+        params.number,
+        params.winwidth,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_source() {
+        let params: FilterParams = serde_json::from_value(json!({
+            "source": { "lines": ["foo", "bar", "fob"] },
+            "query": "fo",
+            "number": 1,
+        }))
+        .unwrap();
+        let result = filter_source(params, &SourceCache::default()).unwrap();
+        assert_eq!(result["total"], json!(2));
+        assert_eq!(result["lines"], json!(["foo"]));
+    }
+}
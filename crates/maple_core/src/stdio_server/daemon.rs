@@ -0,0 +1,396 @@
+//! Daemon shared by the editors of the user, see `maple serve`.
+//!
+//! Each client sends a [`DaemonHello`] first, after the token over TCP, and is then served the
+//! same way as [`super::start`] does over stdio. The file indexes, caches, etc, are shared by
+//! all the clients, the state of the editor connection is kept per client.
+
+use crate::ipc::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Environment variables affecting maple, `PATH` decides the external tools in use.
+const DAEMON_ENV_VARS: &[&str] = &[
+    "PATH",
+    "RAYON_NUM_THREADS",
+    "VIM_CLAP_CACHE_DIR",
+    "VIM_CLAP_DATA_DIR",
+    "VIM_CLAP_LOG_DIR",
+    "VIM_CLAP_LOG_PATH",
+    "XDG_CACHE_HOME",
+    "XDG_DATA_HOME",
+];
+
+/// First message of a client of the daemon.
+///
+/// The daemon only serves the clients with the same config file and environment as its own,
+/// the other clients are refused and serve the editor by themselves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DaemonHello {
+    pub config_file: PathBuf,
+    /// Values of [`DAEMON_ENV_VARS`].
+    pub env: BTreeMap<String, String>,
+    /// Working directory of the client, the default cwd of its requests.
+    pub cwd: PathBuf,
+}
+
+impl DaemonHello {
+    /// Returns the hello of the current process, the config file must have been initialized.
+    pub fn current() -> std::io::Result<Self> {
+        let config_file = crate::config::config_file();
+        Ok(Self {
+            config_file: config_file
+                .canonicalize()
+                .unwrap_or_else(|_| config_file.clone()),
+            env: DAEMON_ENV_VARS
+                .iter()
+                .filter_map(|name| Some((name.to_string(), std::env::var(name).ok()?)))
+                .collect(),
+            cwd: std::env::current_dir()?,
+        })
+    }
+
+    /// Returns why the client can not be served by the daemon of `self`, if any.
+    fn mismatch(&self, client: &Self) -> Option<String> {
+        if self.config_file != client.config_file {
+            return Some(format!(
+                "the daemon uses config file {}",
+                self.config_file.display()
+            ));
+        }
+
+        DAEMON_ENV_VARS
+            .iter()
+            .find(|name| self.env.get(**name) != client.env.get(**name))
+            .map(|name| format!("${name} differs from the one of the daemon"))
+    }
+}
+
+/// Reply of the daemon to [`DaemonHello`], the client is served if `error` is `None`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DaemonReply {
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Authenticates the client and checks its hello, returns the cwd of the client if it's
+/// accepted.
+fn accept_client(
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+    token: Option<&str>,
+    daemon_hello: &DaemonHello,
+) -> std::io::Result<Option<PathBuf>> {
+    if let Some(token) = token {
+        crate::ipc::authenticate(reader, token)?;
+    }
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let client_hello: DaemonHello = serde_json::from_str(&line)?;
+
+    let error = daemon_hello.mismatch(&client_hello);
+    serde_json::to_writer(
+        &mut *writer,
+        &DaemonReply {
+            error: error.clone(),
+        },
+    )?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+
+    Ok(error.is_none().then_some(client_hello.cwd))
+}
+
+type ClientReader = BufReader<Box<dyn std::io::Read + Send>>;
+type ClientWriter = Box<dyn Write + Send>;
+
+/// Connection to a client, not yet accepted.
+struct PendingClient {
+    reader: ClientReader,
+    writer: ClientWriter,
+}
+
+enum DaemonListener {
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, PathBuf),
+    Tcp(tokio::net::TcpListener, Arc<String>),
+}
+
+impl DaemonListener {
+    fn bind(address: &str) -> std::io::Result<Self> {
+        match Address::parse(address)? {
+            #[cfg(unix)]
+            Address::Unix(path) => {
+                let listener = crate::ipc::bind_unix(&path)?;
+                listener.set_nonblocking(true)?;
+                Ok(Self::Unix(
+                    tokio::net::UnixListener::from_std(listener)?,
+                    path,
+                ))
+            }
+            Address::Tcp(socket_addr) => {
+                let listener = std::net::TcpListener::bind(socket_addr)?;
+                let token = crate::ipc::create_token(&listener.local_addr()?)?;
+                listener.set_nonblocking(true)?;
+                Ok(Self::Tcp(
+                    tokio::net::TcpListener::from_std(listener)?,
+                    Arc::new(token),
+                ))
+            }
+        }
+    }
+
+    fn token(&self) -> Option<Arc<String>> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(..) => None,
+            Self::Tcp(_, token) => Some(token.clone()),
+        }
+    }
+
+    async fn accept(&self) -> std::io::Result<PendingClient> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(listener, _) => {
+                let (stream, _) = listener.accept().await?;
+                let stream = stream.into_std()?;
+                stream.set_nonblocking(false)?;
+                Ok(PendingClient {
+                    reader: BufReader::new(Box::new(stream.try_clone()?)),
+                    writer: Box::new(stream),
+                })
+            }
+            Self::Tcp(listener, _) => {
+                let (stream, _) = listener.accept().await?;
+                let stream = stream.into_std()?;
+                stream.set_nonblocking(false)?;
+                Ok(PendingClient {
+                    reader: BufReader::new(Box::new(stream.try_clone()?)),
+                    writer: Box::new(stream),
+                })
+            }
+        }
+    }
+
+    /// Removes the socket or the token file on exit.
+    fn cleanup(&self) {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(_, path) => {
+                let _ = std::fs::remove_file(path);
+            }
+            Self::Tcp(listener, _) => {
+                if let Ok(token_path) = listener
+                    .local_addr()
+                    .and_then(|addr| crate::ipc::token_path(&addr))
+                {
+                    let _ = std::fs::remove_file(token_path);
+                }
+            }
+        }
+    }
+}
+
+/// Starts the daemon shared by the editors connected to `address`, which is either a path of
+/// the unix domain socket or `host:port` of a TCP socket on the loopback interface.
+///
+/// The daemon exits once it has had no client for `idle_timeout`.
+pub async fn start_daemon(address: &str, idle_timeout: Duration) -> std::io::Result<()> {
+    let listener = DaemonListener::bind(address)?;
+    let daemon_hello = DaemonHello::current()?;
+
+    super::spawn_background_tasks();
+
+    tracing::debug!(address, "Daemon is listening");
+
+    run_daemon(
+        listener,
+        daemon_hello,
+        idle_timeout,
+        |reader, writer, cwd| super::serve(reader, BufWriter::new(writer), false, Some(cwd)),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Accepts the clients on `listener` and serves each accepted client with `serve_client` until
+/// the daemon has been idle for `idle_timeout`.
+async fn run_daemon<F, Fut>(
+    listener: DaemonListener,
+    daemon_hello: DaemonHello,
+    idle_timeout: Duration,
+    serve_client: F,
+) where
+    F: Fn(ClientReader, ClientWriter, PathBuf) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let daemon_hello = Arc::new(daemon_hello);
+    let active_clients = Arc::new(AtomicUsize::new(0));
+    let (disconnected_tx, mut disconnected_rx) = tokio::sync::mpsc::unbounded_channel();
+
+    loop {
+        let idle = active_clients.load(Ordering::SeqCst) == 0;
+
+        tokio::select! {
+            accepted = listener.accept() => {
+                let PendingClient { mut reader, mut writer } = match accepted {
+                    Ok(pending_client) => pending_client,
+                    Err(err) => {
+                        tracing::debug!(?err, "Failed to accept the client");
+                        continue;
+                    }
+                };
+
+                active_clients.fetch_add(1, Ordering::SeqCst);
+
+                let token = listener.token();
+                let daemon_hello = daemon_hello.clone();
+                let active_clients = active_clients.clone();
+                let disconnected_tx = disconnected_tx.clone();
+                let serve_client = serve_client.clone();
+
+                tokio::spawn(async move {
+                    let accepted = tokio::task::spawn_blocking(move || {
+                        let token = token.as_ref().map(|token| token.as_str());
+                        accept_client(&mut reader, &mut writer, token, &daemon_hello)
+                            .map(|maybe_cwd| maybe_cwd.map(|cwd| (reader, writer, cwd)))
+                    })
+                    .await;
+
+                    match accepted {
+                        Ok(Ok(Some((reader, writer, cwd)))) => serve_client(reader, writer, cwd).await,
+                        Ok(Ok(None)) => tracing::debug!("Refused the client of a different setup"),
+                        Ok(Err(err)) => tracing::debug!(?err, "Failed to accept the client"),
+                        Err(err) => tracing::debug!(?err, "Failed to accept the client"),
+                    }
+
+                    active_clients.fetch_sub(1, Ordering::SeqCst);
+                    let _ = disconnected_tx.send(());
+                });
+            }
+            // Check whether the daemon becomes idle.
+            _ = disconnected_rx.recv() => {}
+            _ = tokio::time::sleep(idle_timeout), if idle => {
+                tracing::debug!(?idle_timeout, "Daemon exits due to being idle");
+                listener.cleanup();
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::os::unix::net::UnixStream;
+
+    /// Serves the client by answering each request with the cwd of the client, in place of the
+    /// editor session which is out of the scope of the daemon.
+    async fn serve_echo(mut reader: ClientReader, mut writer: ClientWriter, cwd: PathBuf) {
+        tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+            let mut line = String::new();
+            while reader.read_line(&mut line)? > 0 {
+                let request: Value = serde_json::from_str(&line)?;
+                let response = json!({ "id": request["id"], "result": cwd });
+                writeln!(writer, "{response}")?;
+                writer.flush()?;
+                line.clear();
+            }
+            Ok(())
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    }
+
+    /// Connects to the daemon at `socket`, returns the reply to `hello` and the connection.
+    fn connect(
+        socket: &std::path::Path,
+        hello: &DaemonHello,
+    ) -> (DaemonReply, BufReader<UnixStream>) {
+        let mut stream = UnixStream::connect(socket).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        serde_json::to_writer(&mut stream, hello).unwrap();
+        stream.write_all(b"\n").unwrap();
+
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        (serde_json::from_str(&line).unwrap(), reader)
+    }
+
+    /// Sends a request and returns the response.
+    fn request(reader: &mut BufReader<UnixStream>, id: u64, method: &str) -> Value {
+        let request = json!({ "id": id, "method": method, "params": [] });
+        writeln!(reader.get_mut(), "{request}").unwrap();
+
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_multiple_clients() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("maple.sock");
+        let hello = DaemonHello {
+            config_file: dir.path().join("config.toml"),
+            env: BTreeMap::from([("PATH".to_string(), "/usr/bin".to_string())]),
+            cwd: dir.path().to_path_buf(),
+        };
+
+        let listener = DaemonListener::bind(&socket.display().to_string()).unwrap();
+        let daemon = tokio::spawn(run_daemon(
+            listener,
+            hello.clone(),
+            Duration::from_millis(500),
+            serve_echo,
+        ));
+
+        let socket_clone = socket.clone();
+        tokio::task::spawn_blocking(move || {
+            let socket = socket_clone;
+
+            let (reply1, mut client1) = connect(&socket, &hello);
+            let (reply2, mut client2) = connect(&socket, &hello);
+            assert!(reply1.error.is_none());
+            assert!(reply2.error.is_none());
+
+            // Both clients are served concurrently.
+            let cwd = json!(hello.cwd);
+            assert_eq!(request(&mut client2, 1, "session/last")["result"], cwd);
+            assert_eq!(request(&mut client1, 1, "session/last")["result"], cwd);
+
+            // A client of another config file is refused.
+            let mut other_hello = hello.clone();
+            other_hello.config_file = PathBuf::from("/other/config.toml");
+            let (reply, _) = connect(&socket, &other_hello);
+            assert!(reply.error.unwrap().contains("config file"));
+
+            // So is a client of another environment.
+            let mut other_hello = hello.clone();
+            other_hello.env.clear();
+            let (reply, _) = connect(&socket, &other_hello);
+            assert!(reply.error.unwrap().contains("$PATH"));
+        })
+        .await
+        .unwrap();
+
+        // The daemon exits once all the clients are gone.
+        tokio::time::timeout(Duration::from_secs(10), daemon)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!socket.exists());
+    }
+}
@@ -1,19 +1,29 @@
 use crate::datastore::RECENT_FILES_IN_MEMORY;
+use crate::recent_files::AccessKind;
+use crate::stdio_server::Vim;
 use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use rpc::RpcRequest;
 use serde::Deserialize;
 use serde_json::{json, Value};
 
-pub fn note_recent_file(file_path: String) -> Result<()> {
-    tracing::debug!(?file_path, "Received a recent file notification");
+pub fn note_recent_file(file_path: String, kind: AccessKind) -> Result<()> {
+    tracing::debug!(?file_path, ?kind, "Received a recent file notification");
 
-    let path = std::path::Path::new(&file_path);
-    if !path.exists() || !path.is_file() {
+    // The path is checked as maple sees it.
+    let file_path = crate::paths::normalize_path(&file_path).into_owned();
+
+    if !std::path::Path::new(&file_path).is_file() {
         return Ok(());
     }
 
     let mut recent_files = RECENT_FILES_IN_MEMORY.lock();
-    recent_files.upsert(file_path);
+    recent_files.upsert(
+        file_path,
+        kind,
+        &crate::config::config().provider.recent_files,
+    );
 
     Ok(())
 }
@@ -95,6 +105,30 @@ pub async fn preview_quickfix(msg: RpcRequest) -> Result<Value> {
     Ok(value)
 }
 
+/// Replaces the placeholders of the snippet body, e.g., `${1:default}` and `$1`, with the
+/// default text.
+fn expand_placeholders(body: &[String]) -> Vec<String> {
+    static PLACEHOLDER: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"\$\{\d+:([^}]*)\}|\$\{?\d+\}?").expect("Invalid regex"));
+
+    body.iter()
+        .map(|line| PLACEHOLDER.replace_all(line, "$1").into_owned())
+        .collect()
+}
+
+/// Expands the snippet chosen in the snippets provider at the cursor, `snippets.expand`.
+///
+/// The snippet is expanded by its engine if available, otherwise the body is inserted as the
+/// plain text.
+pub fn expand_snippet(vim: &Vim, snippet: Value) -> Result<()> {
+    let body: Vec<String> = serde_json::from_value(snippet["body"].clone())?;
+    let plain_body = expand_placeholders(&body);
+    vim.exec(
+        "clap#provider#snippets#expand",
+        json!([snippet, plain_body]),
+    )
+}
+
 fn parse_quickfix_entry(line: &str) -> Result<(&str, usize)> {
     let mut splitted = line.split('|');
     let fpath = splitted
@@ -127,4 +161,10 @@ mod tests {
             ("test/bench/python/test_fuzzy_filter.vim", 0usize)
         );
     }
+
+    #[test]
+    fn test_expand_placeholders() {
+        let body = ["for ${1:i} in ${2:iter} {", "    $0${3}", "}"].map(String::from);
+        assert_eq!(expand_placeholders(&body), ["for i in iter {", "    ", "}"]);
+    }
 }
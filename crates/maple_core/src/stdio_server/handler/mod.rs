@@ -2,5 +2,5 @@ pub mod messages;
 mod on_initialize;
 mod on_move;
 
-pub use self::on_initialize::initialize_provider;
+pub use self::on_initialize::{initialize_provider, initialize_provider_source};
 pub use self::on_move::{CachedPreviewImpl, Preview, PreviewTarget};
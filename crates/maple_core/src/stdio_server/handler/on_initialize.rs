@@ -6,7 +6,7 @@ use crate::tools::ctags::ProjectCtagsCommand;
 use crate::tools::rg::{RgTokioCommand, RG_EXEC_CMD};
 use anyhow::Result;
 use filter::SourceItem;
-use printer::{DisplayLines, Printer};
+use printer::DisplayLines;
 use serde_json::{json, Value};
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -33,7 +33,7 @@ async fn execute_and_write_cache(
 }
 
 /// Performs the initialization like collecting the source and total number of source items.
-async fn initialize_provider_source(ctx: &Context) -> Result<ProviderSource> {
+pub async fn initialize_provider_source(ctx: &Context) -> Result<ProviderSource> {
     let to_small_provider_source = |lines: Vec<String>| {
         let total = lines.len();
         let items = lines
@@ -75,6 +75,71 @@ async fn initialize_provider_source(ctx: &Context) -> Result<ProviderSource> {
             };
             return Ok(provider_source);
         }
+        "yanks" if ctx.config().plugin.yank_history.enable => {
+            let entries = crate::datastore::YANK_HISTORY_IN_MEMORY
+                .lock()
+                .entries()
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>();
+            let lines = entries.iter().map(|e| e.display_line()).collect();
+            // The full entries are used for the multi-line preview and the paste actions.
+            ctx.vim
+                .exec("clap#provider#yanks#set_entries", json!([entries]))?;
+            return Ok(to_small_provider_source(lines));
+        }
+        "env" => {
+            let to_lines = |prefix: &str, vars: Vec<(String, String)>| {
+                let mut lines = vars
+                    .into_iter()
+                    .map(|(name, value)| {
+                        // Same escaping as the env provider in Vim.
+                        let value = value.replace('\\', "\\\\").replace('\n', "\\n");
+                        format!("[{prefix}] {name}={value}")
+                    })
+                    .collect::<Vec<_>>();
+                lines.sort();
+                lines
+            };
+            let mut lines = to_lines(
+                "maple",
+                std::env::vars_os()
+                    .map(|(k, v)| (k.to_string_lossy().into(), v.to_string_lossy().into()))
+                    .collect(),
+            );
+            if ctx
+                .vim
+                .get_var_bool("clap_provider_env_include_vim")
+                .await?
+            {
+                let vim_environ: std::collections::HashMap<String, String> =
+                    ctx.vim.bare_call("environ").await?;
+                lines.extend(to_lines("vim", vim_environ.into_iter().collect()));
+            }
+            return Ok(to_small_provider_source(lines));
+        }
+        "docs" => {
+            let filetype: String = ctx
+                .vim
+                .call("getbufvar", json!([ctx.env.start.bufnr, "&filetype"]))
+                .await?;
+            let docs_dir = crate::previewer::devdocs::docs_dir();
+            let slugs = crate::previewer::devdocs::find_slugs(&docs_dir, &filetype);
+            let lines = crate::previewer::devdocs::entry_lines(&docs_dir, &slugs);
+            return Ok(to_small_provider_source(lines));
+        }
+        "unicode" => {
+            const UNICODE_TABLE: &str =
+                include_str!("../../../../../scripts/unicode/unicode_table.txt");
+            let lines = UNICODE_TABLE.lines().map(Into::into).collect();
+            return Ok(to_small_provider_source(lines));
+        }
+        "cache_info" => {
+            return Ok(to_small_provider_source(crate::cache::cache_entry_lines()));
+        }
+        "health" => {
+            return Ok(to_small_provider_source(crate::health::check_lines()));
+        }
         "help_tags" => {
             let helplang: String = ctx.vim.eval("&helplang").await?;
             let runtimepath: String = ctx.vim.eval("&runtimepath").await?;
@@ -158,8 +223,10 @@ pub async fn initialize_provider(ctx: &Context) -> Result<()> {
                 ctx.vim.set_var("g:clap.display.initial_size", total)?;
             }
 
-            if let Some(items) = provider_source.try_skim(ctx.provider_id(), 100) {
-                let printer = Printer::new(ctx.env.display_winwidth, ctx.env.icon);
+            if let Some(items) =
+                provider_source.try_skim(ctx.provider_id(), ctx.env.display_winheight)
+            {
+                let printer = ctx.printer();
                 let DisplayLines {
                     lines,
                     icon_added,
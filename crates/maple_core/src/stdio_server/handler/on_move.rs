@@ -4,7 +4,6 @@ use crate::previewer::vim_help::HelpTagPreview;
 use crate::previewer::{get_file_preview, FilePreview};
 use crate::stdio_server::job;
 use crate::stdio_server::provider::{read_dir_entries, Context, ProviderSource};
-use crate::stdio_server::vim::preview_syntax;
 use crate::tools::ctags::{current_context_tag_async, BufferTag};
 use pattern::*;
 use serde::{Deserialize, Serialize};
@@ -51,6 +50,8 @@ pub enum PreviewTarget {
         doc_filename: String,
         runtimepath: String,
     },
+    /// For the provider `docs`.
+    Devdocs { slug: String, path: String },
 }
 
 impl PreviewTarget {
@@ -82,7 +83,7 @@ fn parse_preview_target(curline: String, ctx: &Context) -> Result<(PreviewTarget
     let mut line_content = None;
 
     let preview_target = match ctx.provider_id() {
-        "files" | "git_files" => PreviewTarget::File(ctx.cwd.join(&curline)),
+        "files" | "git_files" | "smart_open" => PreviewTarget::File(ctx.cwd.join(&curline)),
         "recent_files" => PreviewTarget::File(PathBuf::from(&curline)),
         "history" => {
             let path = if curline.starts_with('~') {
@@ -92,7 +93,7 @@ fn parse_preview_target(curline: String, ctx: &Context) -> Result<(PreviewTarget
             };
             PreviewTarget::File(path)
         }
-        "coc_location" | "grep" | "live_grep" | "igrep" => {
+        "coc_location" | "grep" | "live_grep" | "igrep" | "todos" => {
             let mut try_extract_file_path = |line: &str| {
                 let (fpath, lnum, _col, cache_line) =
                     extract_grep_position(line).ok_or_else(err)?;
@@ -105,7 +106,14 @@ fn parse_preview_target(curline: String, ctx: &Context) -> Result<(PreviewTarget
                 Ok::<_, Error>((path, lnum))
             };
 
-            let (path, line_number) = try_extract_file_path(&curline)?;
+            // The todos line is `[TAG] [severity] [@author age] ` followed by the grep line.
+            let grep_line = if ctx.provider_id() == "todos" {
+                curline.splitn(4, "] ").nth(3).unwrap_or(&curline)
+            } else {
+                &curline
+            };
+
+            let (path, line_number) = try_extract_file_path(grep_line)?;
 
             PreviewTarget::LineInFile { path, line_number }
         }
@@ -223,6 +231,7 @@ impl<'a> CachedPreviewImpl<'a> {
                 doc_filename,
                 runtimepath,
             } => self.preview_help_subject(subject, doc_filename, runtimepath),
+            PreviewTarget::Devdocs { slug, path } => self.preview_devdocs(slug, path),
         };
 
         self.ctx
@@ -268,6 +277,25 @@ impl<'a> CachedPreviewImpl<'a> {
         }
     }
 
+    fn preview_devdocs(&self, slug: &str, path: &str) -> Preview {
+        let docs_dir = previewer::devdocs::docs_dir();
+        match previewer::devdocs::preview_lines(&docs_dir, slug, path) {
+            Ok(lines) => {
+                let header = format!("{slug}/{path}");
+                let lines = std::iter::once(header.clone())
+                    .chain(lines.into_iter().take(self.preview_height * 2))
+                    .collect::<Vec<_>>();
+                Preview {
+                    lines,
+                    hi_lnum: Some(1),
+                    fname: Some(header),
+                    syntax: None,
+                }
+            }
+            Err(err) => Preview::new(vec![format!("Failed to preview {slug}/{path}: {err}")]),
+        }
+    }
+
     fn preview_directory<P: AsRef<Path>>(&self, path: P) -> Result<Preview> {
         let enable_icon = self.ctx.env.icon.enabled();
         let lines = read_dir_entries(&path, enable_icon, Some(self.preview_height))?;
@@ -344,7 +372,7 @@ impl<'a> CachedPreviewImpl<'a> {
                 fname: Some(fname),
                 ..Default::default()
             })
-        } else if let Some(syntax) = preview_syntax(path) {
+        } else if let Some(syntax) = self.ctx.vim.preview_syntax(path) {
             Ok(Preview {
                 lines,
                 syntax: Some(syntax.into()),
@@ -377,7 +405,8 @@ impl<'a> CachedPreviewImpl<'a> {
             }
         };
 
-        match get_file_preview(path, lnum, self.preview_height) {
+        let search_compressed = self.ctx.config().grep.search_compressed;
+        match get_file_preview(path, lnum, self.preview_height, search_compressed) {
             Ok(FilePreview {
                 lines,
                 highlight_lnum,
@@ -444,7 +473,7 @@ impl<'a> CachedPreviewImpl<'a> {
                     .chain(self.truncate_preview_lines(lines.into_iter()))
                     .collect::<Vec<_>>();
 
-                if let Some(syntax) = preview_syntax(path) {
+                if let Some(syntax) = self.ctx.vim.preview_syntax(path) {
                     Preview {
                         lines,
                         syntax: Some(syntax.into()),
@@ -1,5 +1,7 @@
-use crate::stdio_server::provider::ProviderId;
+use crate::stdio_server::provider::{EntryAction, ProviderId};
 use crate::stdio_server::service::ProviderSessionId;
+use rpc::Params;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -8,23 +10,48 @@ pub enum Event {
     Provider(ProviderEvent),
     Autocmd(Autocmd),
     Key(KeyEvent),
+    /// Key notation bound in the keymaps config, which is parsed from the params.
+    CustomKey,
+    /// Mouse event, which is parsed from the params.
+    Mouse,
+    /// Entry (un)selected by `<Tab>`, which is parsed from the params.
+    Selection,
     /// Various uncategoried actions.
     Action(String),
 }
 
 #[derive(Debug, Clone)]
 pub enum PluginEvent {
-    Autocmd(Autocmd),
+    Autocmd(AutocmdEvent),
 }
 
+/// Autocmd along with the params sent from Vim.
+pub type AutocmdEvent = (Autocmd, Params);
+
 /// Provider specific events.
 #[derive(Debug)]
 pub enum ProviderEvent {
     NewSession,
     OnMove,
     OnTyped,
+    /// Load the next page of the results on scrolling to the bottom.
+    LoadMore,
     Exit,
     Key(KeyEvent),
+    /// Key notation bound in the keymaps config, e.g., `<C-x><C-o>`.
+    CustomKey(String),
+    Mouse(MouseEvent),
+    /// Entry multi-selected or unselected by `<Tab>`.
+    Selection {
+        line: String,
+        selected: bool,
+    },
+    /// The multi-selected entries are dropped on the query change.
+    ClearSelection,
+    /// Source changed by the actions of the provider in Vim.
+    ReloadSource,
+    /// Shows the action menu of the current entry if `None`, otherwise runs the chosen action.
+    Action(Option<EntryAction>),
     /// Signal fired internally.
     Internal(InternalProviderEvent),
 }
@@ -33,6 +60,8 @@ pub enum ProviderEvent {
 pub enum InternalProviderEvent {
     OnInitialize,
     Terminate,
+    /// The entries accepted by the user, sent right before exiting.
+    EntriesAccepted(Vec<String>),
 }
 
 /// Represents a key event.
@@ -52,6 +81,33 @@ pub enum KeyEvent {
     CtrlN,
     // <C-P>
     CtrlP,
+    // <C-Q>
+    CtrlQ,
+    // <C-R>
+    CtrlR,
+}
+
+/// Represents a mouse event in the window `winid`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MouseEvent {
+    Click { winid: usize, lnum: usize },
+    DoubleClick { winid: usize, lnum: usize },
+    WheelUp { winid: usize },
+    WheelDown { winid: usize },
+}
+
+impl MouseEvent {
+    /// Parses the mouse event from the kind and position sent by Vim.
+    pub fn from_kind(kind: &str, winid: usize, lnum: usize) -> Option<Self> {
+        let mouse_event = match kind {
+            "click" => Self::Click { winid, lnum },
+            "double-click" => Self::DoubleClick { winid, lnum },
+            "wheel-up" => Self::WheelUp { winid },
+            "wheel-down" => Self::WheelDown { winid },
+            _ => return None,
+        };
+        Some(mouse_event)
+    }
 }
 
 /// Represents a key event.
@@ -59,6 +115,8 @@ pub enum KeyEvent {
 pub enum Autocmd {
     CursorMoved,
     InsertEnter,
+    WinScrolled,
+    TextYankPost,
 }
 
 impl Event {
@@ -68,20 +126,45 @@ impl Event {
             "on_move" => Self::Provider(ProviderEvent::OnMove),
             "on_typed" => Self::Provider(ProviderEvent::OnTyped),
             "new_session" => Self::Provider(ProviderEvent::NewSession),
-            "cr" => Self::Key(KeyEvent::CarriageReturn),
-            "tab" => Self::Key(KeyEvent::Tab),
-            "ctrl-n" => Self::Key(KeyEvent::CtrlN),
-            "ctrl-p" => Self::Key(KeyEvent::CtrlP),
-            "shift-up" => Self::Key(KeyEvent::ShiftUp),
-            "shift-down" => Self::Key(KeyEvent::ShiftDown),
-            "backspace" => Self::Key(KeyEvent::Backspace),
+            "load_more" => Self::Provider(ProviderEvent::LoadMore),
+            // The chosen action, if any, is parsed from the params.
+            "entry_action" => Self::Provider(ProviderEvent::Action(None)),
             "CursorMoved" => Self::Autocmd(Autocmd::CursorMoved),
             "InsertEnter" => Self::Autocmd(Autocmd::InsertEnter),
-            action => Self::Action(action.to_string()),
+            "WinScrolled" => Self::Autocmd(Autocmd::WinScrolled),
+            "TextYankPost" => Self::Autocmd(Autocmd::TextYankPost),
+            "custom_key" => Self::CustomKey,
+            "mouse" => Self::Mouse,
+            "selection" => Self::Selection,
+            "clear_selection" => Self::Provider(ProviderEvent::ClearSelection),
+            "reload_source" => Self::Provider(ProviderEvent::ReloadSource),
+            other => match KeyEvent::from_name(other) {
+                Some(key_event) => Self::Key(key_event),
+                None => Self::Action(other.to_string()),
+            },
         }
     }
 }
 
+impl KeyEvent {
+    /// Parses the key name used in the RPC method and the keymaps config.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let key_event = match name {
+            "cr" => Self::CarriageReturn,
+            "tab" => Self::Tab,
+            "ctrl-n" => Self::CtrlN,
+            "ctrl-p" => Self::CtrlP,
+            "ctrl-q" => Self::CtrlQ,
+            "ctrl-r" => Self::CtrlR,
+            "shift-up" => Self::ShiftUp,
+            "shift-down" => Self::ShiftDown,
+            "backspace" => Self::Backspace,
+            _ => return None,
+        };
+        Some(key_event)
+    }
+}
+
 /// A small wrapper of `UnboundedSender<ProviderEvent>` for logging on sending error.
 #[derive(Debug)]
 pub struct ProviderEventSender {
@@ -109,7 +192,8 @@ impl ProviderEventSender {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+/// Inputs of each provider, persisted across the sessions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct InputHistory(HashMap<ProviderId, VecDeque<String>>);
 
 impl InputHistory {
@@ -223,3 +307,42 @@ impl InputRecorder {
         self.inputs.get(self.current_index).map(AsRef::as_ref)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mouse_event_from_kind() {
+        assert_eq!(
+            MouseEvent::from_kind("double-click", 1000, 3),
+            Some(MouseEvent::DoubleClick {
+                winid: 1000,
+                lnum: 3
+            })
+        );
+        assert_eq!(
+            MouseEvent::from_kind("wheel-down", 1001, 0),
+            Some(MouseEvent::WheelDown { winid: 1001 })
+        );
+        assert_eq!(MouseEvent::from_kind("triple-click", 1000, 3), None);
+    }
+
+    #[test]
+    fn test_input_history_serde() {
+        let mut input_history = InputHistory::new();
+        input_history.insert(
+            ProviderId::from("files"),
+            VecDeque::from(vec!["main".to_string(), "lib.rs".to_string()]),
+        );
+
+        let json = serde_json::to_string(&input_history).unwrap();
+        assert_eq!(json, r#"{"files":["main","lib.rs"]}"#);
+
+        let input_history: InputHistory = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            input_history.inputs(&ProviderId::from("files")),
+            VecDeque::from(vec!["main".to_string(), "lib.rs".to_string()])
+        );
+    }
+}
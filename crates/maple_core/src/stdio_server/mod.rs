@@ -1,3 +1,4 @@
+mod daemon;
 mod handler;
 mod input;
 mod job;
@@ -6,20 +7,29 @@ mod provider;
 mod service;
 mod vim;
 
+pub use self::daemon::{start_daemon, DaemonHello, DaemonReply};
 pub use self::input::InputHistory;
-use self::input::{Event, PluginEvent, ProviderEvent};
-use self::plugin::{ClapPlugin, CursorWordHighlighter};
-use self::provider::{create_provider, Context};
+pub(crate) use self::input::KeyEvent;
+use self::input::{Event, InternalProviderEvent, MouseEvent, PluginEvent, ProviderEvent};
+pub use self::plugin::YankRing;
+use self::plugin::{ClapPlugin, CursorWordHighlighter, YankHistory};
+use self::provider::{create_provider, Context, EntryAction};
+pub(crate) use self::provider::{parse_quickfix_item, QuickfixItem};
+pub use self::provider::{ProviderId, SessionSnapshot};
 use self::service::ServiceManager;
-use self::vim::initialize_syntax_map;
 pub use self::vim::{Vim, VimProgressor};
+use crate::source_service::SourceCache;
 use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use rpc::{RpcClient, RpcNotification, RpcRequest, VimMessage};
 use serde_json::{json, Value};
-use std::io::{BufReader, BufWriter};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::broadcast;
 use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::time::Instant;
 
@@ -35,9 +45,27 @@ async fn initialize(vim: Vim) -> Result<()> {
     let output: String = vim
         .call("execute", json!(["autocmd filetypedetect"]))
         .await?;
-    let ext_map = initialize_syntax_map(&output);
+    let ext_map = vim.initialize_syntax_map(&output);
     vim.exec("clap#ext#set", json![ext_map])?;
 
+    apply_palette(&vim).await?;
+
+    if let Some(notice) = tokio::task::spawn_blocking(crate::tooling::missing_tools_notice).await? {
+        vim.echo_warn(notice)?;
+    }
+
+    // Seed the recent files on the first run so that they are useful right away.
+    let first_run = {
+        let recent_files = crate::datastore::RECENT_FILES_IN_MEMORY.lock();
+        !recent_files.oldfiles_imported && recent_files.len() == 0
+    };
+    if first_run {
+        match import_oldfiles(&vim).await {
+            Ok(imported) => tracing::debug!("Imported {imported} recent files from v:oldfiles"),
+            Err(err) => tracing::error!(?err, "Failed to import v:oldfiles"),
+        }
+    }
+
     const ACTIONS: &[&str] = &["open-config", "generate-toc", "update-toc", "delete-toc"];
     vim.set_var("g:clap_actions", json![ACTIONS])?;
 
@@ -46,18 +74,140 @@ async fn initialize(vim: Vim) -> Result<()> {
     Ok(())
 }
 
-/// Starts and keep running the server on top of stdio.
-pub async fn start() {
-    // TODO: setup test framework using vim_message_sender.
+/// Imports `v:oldfiles`, which is read from the viminfo or shada file, into the recent files.
+async fn import_oldfiles(vim: &Vim) -> Result<usize> {
+    let oldfiles: Vec<String> = vim
+        .eval("map(copy(v:oldfiles), 'fnamemodify(v:val, \":p\")')")
+        .await?;
+    let imported = crate::datastore::RECENT_FILES_IN_MEMORY
+        .lock()
+        .import_oldfiles(oldfiles)?;
+    Ok(imported)
+}
+
+/// Defines the highlight groups in the theme palette of config.
+async fn apply_palette(vim: &Vim) -> Result<()> {
+    let palette = &crate::config::config().theme.palette;
+    let fuzzy_matches_groups: usize = vim
+        .eval("get(g:, '__clap_fuzzy_matches_hl_group_cnt', 9)")
+        .await?;
+    let commands = palette.highlight_commands(fuzzy_matches_groups);
+    if !commands.is_empty() {
+        vim.exec("execute", json!([commands]))?;
+    }
+    Ok(())
+}
+
+/// Attaches to the msgpack-rpc server of Neovim if enabled, `$NVIM` is set by Neovim
+/// for the jobs started by `jobstart()`.
+fn attach_nvim(vim: Vim) -> Vim {
+    if !crate::config::config().neovim.attach_rpc {
+        return vim;
+    }
+
+    let Ok(address) = std::env::var("NVIM") else {
+        tracing::debug!("$NVIM is not set, skip attaching to Neovim");
+        return vim;
+    };
+
+    match rpc::NvimClient::connect(&address) {
+        Ok(nvim_client) => {
+            tracing::debug!(address, "Attached to Neovim");
+            vim.with_nvim_client(nvim_client)
+        }
+        Err(error) => {
+            tracing::error!(?error, address, "Failed to attach to Neovim");
+            vim
+        }
+    }
+}
+
+/// Result of reloading the modified config file, the error message if the config file is
+/// invalid.
+type ConfigReload = std::result::Result<(), String>;
+
+/// Notifies all the clients of the process of the config reloads.
+static CONFIG_RELOADS: Lazy<broadcast::Sender<ConfigReload>> =
+    Lazy::new(|| broadcast::channel(16).0);
+
+/// Reloads the config file whenever it's modified, the clients are notified via
+/// [`CONFIG_RELOADS`].
+///
+/// Only one watcher runs in a process, the daemon reloads the config file once for all its
+/// clients.
+async fn watch_config_file() {
+    const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+    let config_file = crate::config::config_file();
+    let modified_time = || {
+        std::fs::metadata(config_file)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    };
+
+    let mut last_modified = modified_time();
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let modified = modified_time();
+        if modified.is_none() || modified == last_modified {
+            continue;
+        }
+        last_modified = modified;
+
+        let reload = match crate::config::reload_config() {
+            Ok(()) => {
+                tracing::debug!(?config_file, "Config reloaded");
+                Ok(())
+            }
+            Err(err) => {
+                tracing::debug!(?err, "Failed to reload config");
+                let diagnostics = crate::config::config_diagnostics();
+                if diagnostics.is_empty() {
+                    Err(err.to_string())
+                } else {
+                    Err(diagnostics
+                        .iter()
+                        .map(|diagnostic| diagnostic.message.as_str())
+                        .collect::<Vec<_>>()
+                        .join("; "))
+                }
+            }
+        };
+
+        // No client is connected otherwise.
+        let _ = CONFIG_RELOADS.send(reload);
+    }
+}
+
+/// Starts the tasks shared by all the clients of the process.
+fn spawn_background_tasks() {
+    // Probe the external tools in the background before they are needed.
+    tokio::task::spawn_blocking(crate::tooling::capabilities);
+
+    tokio::spawn(watch_config_file());
+
+    tokio::spawn(crate::cache::run_cache_janitor());
+    tokio::spawn(crate::cache::run_cache_warm_up());
+}
+
+/// Serves a client connected via `reader` and `writer` until it's disconnected.
+///
+/// `cwd` is the working directory of the client if it differs from the one of the process.
+async fn serve(
+    reader: impl BufRead + Send + 'static,
+    writer: impl Write + Send + 'static,
+    attach: bool,
+    cwd: Option<PathBuf>,
+) {
     let (vim_message_sender, vim_message_receiver) = tokio::sync::mpsc::unbounded_channel();
 
-    let rpc_client = Arc::new(RpcClient::new(
-        BufReader::new(std::io::stdin()),
-        BufWriter::new(std::io::stdout()),
-        vim_message_sender.clone(),
-    ));
+    let rpc_client = Arc::new(RpcClient::new(reader, writer, vim_message_sender));
 
     let vim = Vim::new(rpc_client);
+    let vim = if attach { attach_nvim(vim) } else { vim };
 
     tokio::spawn({
         let vim = vim.clone();
@@ -68,27 +218,121 @@ pub async fn start() {
         }
     });
 
-    Client::new(vim).run(vim_message_receiver).await;
+    let client = Client::new(vim, cwd);
+
+    let config_reloads = tokio::spawn(client.clone().handle_config_reloads());
+
+    client.run(vim_message_receiver).await;
+
+    config_reloads.abort();
+}
+
+/// Starts and keep running the server on top of stdio.
+pub async fn start() {
+    spawn_background_tasks();
+
+    serve(
+        BufReader::new(std::io::stdin()),
+        BufWriter::new(std::io::stdout()),
+        true,
+        None,
+    )
+    .await;
 }
 
 #[derive(Clone)]
 struct Client {
     vim: Vim,
     service_manager_mutex: Arc<Mutex<ServiceManager>>,
+    /// Working directory of the client connected to the shared daemon.
+    cwd: Option<PathBuf>,
+    source_cache: SourceCache,
 }
 
 impl Client {
     /// Creates a new instnace of [`Client`].
-    fn new(vim: Vim) -> Self {
-        let mut service_manager = ServiceManager::default();
+    fn new(vim: Vim, cwd: Option<PathBuf>) -> Self {
+        let client = Self {
+            vim,
+            service_manager_mutex: Arc::new(Mutex::new(ServiceManager::default())),
+            cwd,
+            source_cache: SourceCache::default(),
+        };
+        client.update_plugins();
+        client.update_provider_aliases();
+        client
+    }
+
+    /// Sends the provider aliases in the config to Vim, which resolves the alias on invoking.
+    fn update_provider_aliases(&self) {
+        let aliases = crate::config::config()
+            .provider
+            .aliases
+            .iter()
+            .map(|(alias, provider_alias)| (alias, &provider_alias.provider))
+            .collect::<HashMap<_, _>>();
+        if let Err(err) = self
+            .vim
+            .exec("clap#set_config_provider_alias", json!([aliases]))
+        {
+            tracing::debug!(?err, "Failed to send the provider aliases");
+        }
+    }
+
+    /// Starts or stops the plugins according to the current config.
+    fn update_plugins(&self) {
+        let mut service_manager = self.service_manager_mutex.lock();
         if crate::config::config().plugin.highlight_cursor_word.enable {
-            service_manager.new_plugin(
-                Box::new(CursorWordHighlighter::new(vim.clone())) as Box<dyn ClapPlugin>
-            );
+            if !service_manager.has_plugin(CursorWordHighlighter::ID) {
+                let vim = self.vim.clone();
+                service_manager.register_lazy_plugin(
+                    CursorWordHighlighter::ID,
+                    CursorWordHighlighter::INIT_AUTOCMDS,
+                    move || Box::new(CursorWordHighlighter::new(vim)) as Box<dyn ClapPlugin>,
+                );
+            }
+        } else {
+            service_manager.remove_plugin(CursorWordHighlighter::ID);
         }
-        Self {
-            vim,
-            service_manager_mutex: Arc::new(Mutex::new(service_manager)),
+
+        if crate::config::config().plugin.yank_history.enable {
+            if !service_manager.has_plugin(YankHistory::ID) {
+                service_manager.register_lazy_plugin(
+                    YankHistory::ID,
+                    YankHistory::INIT_AUTOCMDS,
+                    || Box::new(YankHistory) as Box<dyn ClapPlugin>,
+                );
+            }
+        } else {
+            service_manager.remove_plugin(YankHistory::ID);
+        }
+    }
+
+    /// Applies the reloaded config, the plugins are started or stopped accordingly.
+    ///
+    /// The ignore rules, matcher config, etc are read from the latest config on use.
+    async fn handle_config_reloads(self) {
+        let mut config_reloads = CONFIG_RELOADS.subscribe();
+
+        loop {
+            match config_reloads.recv().await {
+                Ok(Ok(())) => {
+                    self.update_plugins();
+                    self.update_provider_aliases();
+                    if let Err(err) = apply_palette(&self.vim).await {
+                        tracing::debug!(?err, "Failed to apply the palette");
+                    }
+                    let _ = self.vim.echo_info("config.toml reloaded");
+                }
+                Ok(Err(msg)) => {
+                    let _ = self.vim.echo_warn(format!(
+                        "Invalid config.toml, the old config is still in use. {msg}"
+                    ));
+                }
+                // Only the latest config matters.
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
         }
     }
 
@@ -184,18 +428,69 @@ impl Client {
                         .session_id()
                         .ok_or_else(|| anyhow!("`session_id` not found in Params"))?;
                     let ctx = Context::new(notification.params, self.vim.clone()).await?;
+                    ctx.define_custom_keys()?;
                     let provider = create_provider(&provider_id, &ctx).await?;
                     self.service_manager_mutex
                         .lock()
                         .new_provider(session_id, provider, ctx);
                 }
                 ProviderEvent::Exit => {
+                    #[derive(serde::Deserialize, Default)]
+                    struct ExitParams {
+                        #[serde(default)]
+                        accepted_entries: Vec<String>,
+                        #[serde(default)]
+                        snapshot: Option<SessionSnapshot>,
+                    }
+
                     let session_id = notification
                         .session_id()
                         .ok_or_else(|| anyhow!("`session_id` not found in Params"))?;
+                    let ExitParams {
+                        accepted_entries,
+                        snapshot,
+                    } = notification
+                        .params
+                        .parse::<ExitParams>()
+                        .unwrap_or_default();
+
+                    if let Some(snapshot) = snapshot {
+                        if let Err(err) = crate::datastore::store_last_session(&snapshot) {
+                            tracing::error!(?err, "Failed to store the snapshot of the session");
+                        }
+                    }
+
+                    let mut service_manager = self.service_manager_mutex.lock();
+                    if !accepted_entries.is_empty() {
+                        service_manager.notify_provider(
+                            session_id,
+                            ProviderEvent::Internal(InternalProviderEvent::EntriesAccepted(
+                                accepted_entries,
+                            )),
+                        );
+                    }
+                    service_manager.notify_provider_exit(session_id);
+                }
+                ProviderEvent::Action(_) => {
+                    #[derive(serde::Deserialize)]
+                    struct ActionParams {
+                        #[serde(default)]
+                        action: Option<String>,
+                    }
+
+                    let session_id = notification
+                        .session_id()
+                        .ok_or_else(|| anyhow!("`session_id` not found in Params"))?;
+                    let action = match notification.params.parse::<ActionParams>()?.action {
+                        Some(name) => Some(
+                            EntryAction::from_name(&name)
+                                .ok_or_else(|| anyhow!("Unknown entry action: {name}"))?,
+                        ),
+                        None => None,
+                    };
                     self.service_manager_mutex
                         .lock()
-                        .notify_provider_exit(session_id);
+                        .notify_provider(session_id, ProviderEvent::Action(action));
                 }
                 to_send => {
                     let session_id = notification
@@ -214,10 +509,70 @@ impl Client {
                     .lock()
                     .notify_provider(session_id, ProviderEvent::Key(key_event));
             }
+            Event::CustomKey => {
+                #[derive(serde::Deserialize)]
+                struct CustomKeyParams {
+                    key: String,
+                }
+
+                let session_id = notification
+                    .session_id()
+                    .ok_or_else(|| anyhow!("`session_id` not found in Params"))?;
+                let CustomKeyParams { key } = notification.params.parse::<CustomKeyParams>()?;
+                self.service_manager_mutex
+                    .lock()
+                    .notify_provider(session_id, ProviderEvent::CustomKey(key));
+            }
+            Event::Mouse => {
+                #[derive(serde::Deserialize)]
+                struct MouseParams {
+                    kind: String,
+                    winid: usize,
+                    #[serde(default)]
+                    lnum: usize,
+                }
+
+                let session_id = notification
+                    .session_id()
+                    .ok_or_else(|| anyhow!("`session_id` not found in Params"))?;
+                let MouseParams { kind, winid, lnum } =
+                    notification.params.parse::<MouseParams>()?;
+                let mouse_event = MouseEvent::from_kind(&kind, winid, lnum)
+                    .ok_or_else(|| anyhow!("Unknown mouse event: {kind}"))?;
+                self.service_manager_mutex
+                    .lock()
+                    .notify_provider(session_id, ProviderEvent::Mouse(mouse_event));
+            }
+            Event::Selection => {
+                #[derive(serde::Deserialize)]
+                struct SelectionParams {
+                    line: String,
+                    selected: bool,
+                    #[serde(default)]
+                    icon_added: bool,
+                }
+
+                let session_id = notification
+                    .session_id()
+                    .ok_or_else(|| anyhow!("`session_id` not found in Params"))?;
+                let SelectionParams {
+                    line,
+                    selected,
+                    icon_added,
+                } = notification.params.parse::<SelectionParams>()?;
+                let line = if icon_added {
+                    line.chars().skip(2).collect()
+                } else {
+                    line
+                };
+                self.service_manager_mutex
+                    .lock()
+                    .notify_provider(session_id, ProviderEvent::Selection { line, selected });
+            }
             Event::Autocmd(autocmd) => {
                 self.service_manager_mutex
                     .lock()
-                    .notify_plugins(PluginEvent::Autocmd(autocmd));
+                    .notify_plugins(PluginEvent::Autocmd((autocmd, notification.params)));
             }
             Event::Action(action) => self.handle_action(notification, action).await?,
         }
@@ -228,12 +583,56 @@ impl Client {
     async fn handle_action(&self, notification: RpcNotification, action: String) -> Result<()> {
         match action.as_str() {
             "note_recent_files" => {
-                let bufnr: Vec<usize> = notification.params.parse()?;
-                let bufnr = bufnr
+                // [bufnr] or [bufnr, access_kind]
+                let params: Vec<Value> = notification.params.parse()?;
+                let bufnr = params
                     .first()
+                    .and_then(Value::as_u64)
                     .ok_or(anyhow!("bufnr not found in `note_recent_file`"))?;
+                let kind = params
+                    .get(1)
+                    .and_then(|kind| serde_json::from_value(kind.clone()).ok())
+                    .unwrap_or_default();
                 let file_path: String = self.vim.expand(format!("#{bufnr}:p")).await?;
-                handler::messages::note_recent_file(file_path)?
+                handler::messages::note_recent_file(file_path, kind)?
+            }
+            "picker-rendered" => {
+                let render_id: Vec<u64> = notification.params.parse()?;
+                if let Some(render_id) = render_id.first() {
+                    self.vim.on_picker_rendered(*render_id);
+                }
+            }
+            "recent_files.purge-missing" => {
+                let purged = crate::datastore::RECENT_FILES_IN_MEMORY
+                    .lock()
+                    .purge_missing()?;
+                self.vim
+                    .echo_info(format!("Purged {purged} missing recent files"))?;
+            }
+            "recent_files.import-oldfiles" => {
+                let imported = import_oldfiles(&self.vim).await?;
+                self.vim
+                    .echo_info(format!("Imported {imported} recent files from v:oldfiles"))?;
+            }
+            "apply-palette" => apply_palette(&self.vim).await?,
+            "snippets.expand" => {
+                let params: Vec<Value> = notification.params.parse()?;
+                let snippet = params
+                    .into_iter()
+                    .next()
+                    .ok_or(anyhow!("snippet not found in `{action}`"))?;
+                handler::messages::expand_snippet(&self.vim, snippet)?;
+            }
+            "purge-cache-entry" | "purge-project-cache" => {
+                let cache_key: Vec<String> = notification.params.parse()?;
+                let cache_key = cache_key
+                    .first()
+                    .ok_or(anyhow!("cache key not found in `{action}`"))?;
+                if action == "purge-cache-entry" {
+                    crate::cache::purge_cache_entry(cache_key)?;
+                } else {
+                    crate::cache::purge_project_cache(cache_key)?;
+                }
             }
             "open-config" => {
                 let config_file = crate::config::config_file();
@@ -312,6 +711,50 @@ impl Client {
         let value = match msg.method.as_str() {
             "preview/file" => Some(handler::messages::preview_file(msg).await?),
             "quickfix" => Some(handler::messages::preview_quickfix(msg).await?),
+            "config/diagnostics" => Some(json!(crate::config::config_diagnostics())),
+            "session/last" => Some(json!(crate::datastore::load_last_session())),
+            "system/set-log-level" => {
+                #[derive(serde::Deserialize)]
+                struct LogLevelParams {
+                    filter: String,
+                }
+                let LogLevelParams { filter } = msg.params.parse()?;
+                // The empty filter only queries the current one.
+                let result = if filter.trim().is_empty() {
+                    Ok(())
+                } else {
+                    crate::log_filter::set_log_filter(&filter)
+                };
+                match result {
+                    Ok(()) => Some(json!({ "filter": crate::log_filter::current() })),
+                    Err(err) => Some(json!({ "error": err.to_string() })),
+                }
+            }
+            "capabilities" => Some(json!(
+                tokio::task::spawn_blocking(crate::tooling::capabilities).await?
+            )),
+            "source/list" => {
+                let mut params: crate::source_service::SourceParams = msg.params.parse()?;
+                params.cwd = params.cwd.or_else(|| self.cwd.clone());
+                let source_cache = self.source_cache.clone();
+                Some(
+                    tokio::task::spawn_blocking(move || {
+                        crate::source_service::list_source(params, &source_cache)
+                    })
+                    .await??,
+                )
+            }
+            "source/filter" => {
+                let mut params: crate::source_service::FilterParams = msg.params.parse()?;
+                params.source.cwd = params.source.cwd.or_else(|| self.cwd.clone());
+                let source_cache = self.source_cache.clone();
+                Some(
+                    tokio::task::spawn_blocking(move || {
+                        crate::source_service::filter_source(params, &source_cache)
+                    })
+                    .await??,
+                )
+            }
             _ => Some(json!({
                 "error": format!("Unknown request: {}", msg.method)
             })),
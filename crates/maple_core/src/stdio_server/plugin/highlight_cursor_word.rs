@@ -1,11 +1,13 @@
-use crate::stdio_server::input::Autocmd;
+use crate::stdio_server::input::{Autocmd, AutocmdEvent};
 use crate::stdio_server::plugin::ClapPlugin;
 use crate::stdio_server::vim::Vim;
 use anyhow::Result;
 use matcher::WordMatcher;
+use rpc::{NvimClient, Params};
+use serde::Deserialize;
+use serde_json::{json, Value};
 use std::fmt::Debug;
 use std::path::Path;
-use utils::read_lines_from;
 
 #[derive(Debug, serde::Serialize)]
 struct WordHighlights {
@@ -16,6 +18,29 @@ struct WordHighlights {
     cword_len: usize,
 }
 
+impl WordHighlights {
+    /// Returns the `nvim_buf_set_extmark` calls in the form accepted by `nvim_call_atomic`.
+    fn extmark_calls(&self, bufnr: usize, namespace: i64) -> impl Iterator<Item = Value> + '_ {
+        let extmark = move |group: &str, (lnum, col): (usize, usize)| {
+            json!([
+                "nvim_buf_set_extmark",
+                [
+                    bufnr,
+                    namespace,
+                    lnum - 1,
+                    col,
+                    { "end_col": col + self.cword_len, "hl_group": group }
+                ]
+            ])
+        };
+        std::iter::once(extmark("ClapCurrentWord", self.cword_highlight)).chain(
+            self.other_words_highlight
+                .iter()
+                .map(move |pos| extmark("ClapCurrentWordTwins", *pos)),
+        )
+    }
+}
+
 /// Returns the char at given byte index in a line.
 fn char_at(byte_idx: usize, line: &str) -> Option<char> {
     line.char_indices().enumerate().find_map(
@@ -29,26 +54,51 @@ fn char_at(byte_idx: usize, line: &str) -> Option<char> {
     )
 }
 
-fn find_word_highlights(
-    source_file: &Path,
+/// Visible line range of a window, reported by Vim along with the autocmd.
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct Viewport {
+    bufnr: usize,
+    winid: usize,
+    /// First visible line, 1-based.
     line_start: usize,
+    /// Last visible line, 1-based.
     line_end: usize,
+}
+
+impl Viewport {
+    async fn from_params(params: Params, vim: &Vim) -> Result<Self> {
+        match params {
+            Params::None => Ok(Self {
+                bufnr: vim.current_bufnr().await?,
+                winid: vim.current_winid().await?,
+                line_start: vim.line("w0").await?,
+                line_end: vim.line("w$").await?,
+            }),
+            params => Ok(params.parse()?),
+        }
+    }
+}
+
+/// Finds the highlights of the cursor word in `lines`, which are the lines in view.
+///
+/// `line_start` is the line number of the first line in `lines`, 1-based.
+fn find_word_highlights(
+    lines: &[String],
+    line_start: usize,
     curlnum: usize,
     col: usize,
     cword: String,
-) -> std::io::Result<Option<WordHighlights>> {
+) -> Option<WordHighlights> {
     let cword_len = cword.len();
     let word_matcher = WordMatcher::new(vec![cword.into()]);
-    // line_start and line_end is 1-based.
-    let line_start = line_start - 1;
-    let line_end = line_end - 1;
     let mut cursor_word_highlight = None;
-    let other_words_highlight = read_lines_from(source_file, line_start, line_end - line_start)?
+    let other_words_highlight = lines
+        .iter()
         .enumerate()
         .flat_map(|(idx, line)| {
-            let matches_range = word_matcher.find_all_matches_range(&line);
+            let matches_range = word_matcher.find_all_matches_range(line);
 
-            let line_number = idx + line_start + 1;
+            let line_number = idx + line_start;
 
             if line_number == curlnum {
                 let cursor_word_start = matches_range.iter().find_map(|word_range| {
@@ -73,45 +123,79 @@ fn find_word_highlights(
             })
         })
         .collect();
-    if let Some(cword_highlight) = cursor_word_highlight {
-        Ok(Some(WordHighlights {
-            other_words_highlight,
-            cword_highlight,
-            cword_len,
-        }))
-    } else {
-        Ok(None)
-    }
+    cursor_word_highlight.map(|cword_highlight| WordHighlights {
+        other_words_highlight,
+        cword_highlight,
+        cword_len,
+    })
 }
 
 #[derive(Debug)]
-struct WinHighlights {
-    winid: usize,
-    // Use `i32` as matchaddpos() returns -1 on error.
-    match_ids: Vec<i32>,
+enum CursorHighlights {
+    /// Highlights added by `matchaddpos()`.
+    Matches {
+        winid: usize,
+        // Use `i32` as matchaddpos() returns -1 on error.
+        match_ids: Vec<i32>,
+    },
+    /// Highlights added as the extmarks when attached to Neovim.
+    Extmarks { bufnr: usize },
 }
 
 #[derive(Debug)]
 pub struct CursorWordHighlighter {
     vim: Vim,
-    cursor_highlights: Option<WinHighlights>,
+    cursor_highlights: Option<CursorHighlights>,
+    /// Namespace of the extmarks, created on the first use.
+    extmark_namespace: Option<i64>,
 }
 
 impl CursorWordHighlighter {
+    pub const ID: &'static str = "highlight-cursor-word";
+
+    /// Autocmds on which the plugin is initialized, nothing to clear on `InsertEnter` before
+    /// any highlights are added.
+    pub const INIT_AUTOCMDS: &'static [Autocmd] = &[Autocmd::CursorMoved, Autocmd::WinScrolled];
+
     pub fn new(vim: Vim) -> Self {
         Self {
             vim,
             cursor_highlights: None,
+            extmark_namespace: None,
+        }
+    }
+
+    async fn extmark_namespace(&mut self, nvim_client: &NvimClient) -> Result<i64> {
+        if let Some(namespace) = self.extmark_namespace {
+            return Ok(namespace);
         }
+        let namespace = nvim_client
+            .request(
+                "nvim_create_namespace",
+                json!(["clap_highlight_cursor_word"]),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!("RpcError: {e:?}"))?;
+        self.extmark_namespace.replace(namespace);
+        Ok(namespace)
     }
 
-    async fn create_new_highlights(&mut self) -> Result<Option<WinHighlights>> {
+    async fn create_new_highlights(
+        &mut self,
+        viewport: Viewport,
+    ) -> Result<Option<CursorHighlights>> {
         let cword = self.vim.expand("<cword>").await?;
 
         if cword.is_empty() {
             return Ok(None);
         }
 
+        let config = &crate::config::config().plugin.highlight_cursor_word;
+
+        if cword.chars().count() < config.min_word_len {
+            return Ok(None);
+        }
+
         let source_file = self.vim.current_buffer_path().await?;
         let source_file = Path::new(&source_file);
 
@@ -127,9 +211,7 @@ impl CursorWordHighlighter {
             return Ok(None)
         };
 
-        let (ignore_extensions, ignore_file_names): (Vec<_>, Vec<_>) = crate::config::config()
-            .plugin
-            .highlight_cursor_word
+        let (ignore_extensions, ignore_file_names): (Vec<_>, Vec<_>) = config
             .ignore_files
             .split(',')
             .partition(|s| s.starts_with("*."));
@@ -140,15 +222,34 @@ impl CursorWordHighlighter {
             return Ok(None);
         }
 
+        if !config.ignore_filetypes.is_empty() {
+            let filetype: String = self.vim.getbufvar("", "&filetype").await?;
+            if config.ignore_filetypes.contains(&filetype) {
+                return Ok(None);
+            }
+        }
+
+        let Viewport {
+            bufnr,
+            winid,
+            line_start,
+            line_end,
+        } = viewport;
+
+        // Only the lines in view are fetched, the size of buffer does not matter.
+        let lines = self.vim.getbufline(bufnr, line_start, line_end).await?;
+
         // TODO: filter the false positive results, using a blocklist of filetypes?
         let [_bufnum, curlnum, col, _off] = self.vim.getpos(".").await?;
-        let curline = self.vim.getcurbufline(curlnum).await?;
-
-        if crate::config::config()
-            .plugin
-            .highlight_cursor_word
-            .ignore_comment_line
+        let curline = match curlnum
+            .checked_sub(line_start)
+            .and_then(|idx| lines.get(idx))
         {
+            Some(line) => line.clone(),
+            None => return Ok(None),
+        };
+
+        if config.ignore_comment_line {
             if let Some(ext) = source_file.extension().and_then(|s| s.to_str()) {
                 if dumb_analyzer::is_comment(curline.as_str(), ext) {
                     return Ok(None);
@@ -166,15 +267,41 @@ impl CursorWordHighlighter {
             return Ok(None);
         }
 
-        let winid = self.vim.current_winid().await?;
-
-        // Lines in view.
-        let line_start = self.vim.line("w0").await?;
-        let line_end = self.vim.line("w$").await?;
+        if !config.ignore_syntax_groups.is_empty() {
+            let syntax_groups = self.vim.syntax_groups_at(curlnum, col).await?;
+            if syntax_groups
+                .iter()
+                .any(|group| config.ignore_syntax_groups.contains(group))
+            {
+                return Ok(None);
+            }
+        }
 
-        if let Ok(Some(word_highlights)) =
-            find_word_highlights(source_file, line_start, line_end, curlnum, col, cword)
+        if let Some(word_highlights) = find_word_highlights(&lines, line_start, curlnum, col, cword)
         {
+            if let Some(nvim_client) = self.vim.nvim_client().cloned() {
+                let namespace = self.extmark_namespace(&nvim_client).await?;
+                // Clear the old extmarks in the same batch so that no flicker occurs.
+                let clear_old_extmarks = match self.cursor_highlights {
+                    Some(CursorHighlights::Extmarks { bufnr }) => {
+                        self.cursor_highlights.take();
+                        Some(json!([
+                            "nvim_buf_clear_namespace",
+                            [bufnr, namespace, 0, -1]
+                        ]))
+                    }
+                    _ => None,
+                };
+                let calls = clear_old_extmarks
+                    .into_iter()
+                    .chain(word_highlights.extmark_calls(bufnr, namespace))
+                    .collect::<Vec<_>>();
+                nvim_client
+                    .notify("nvim_call_atomic", json!([calls]))
+                    .map_err(|e| anyhow::anyhow!("RpcError: {e:?}"))?;
+                return Ok(Some(CursorHighlights::Extmarks { bufnr }));
+            }
+
             let match_ids: Vec<i32> = self
                 .vim
                 .call(
@@ -182,40 +309,89 @@ impl CursorWordHighlighter {
                     word_highlights,
                 )
                 .await?;
-            return Ok(Some(WinHighlights { match_ids, winid }));
+            return Ok(Some(CursorHighlights::Matches { match_ids, winid }));
         }
 
         Ok(None)
     }
 
     /// Highlight the cursor word and all the occurrences.
-    async fn highlight_symbol_under_cursor(&mut self) -> Result<()> {
-        let maybe_new_highlights = self.create_new_highlights().await?;
+    async fn highlight_symbol_under_cursor(&mut self, viewport: Viewport) -> Result<()> {
+        let maybe_new_highlights = self.create_new_highlights(viewport).await?;
         let old_highlights = match maybe_new_highlights {
             Some(new_highlights) => self.cursor_highlights.replace(new_highlights),
             None => self.cursor_highlights.take(),
         };
 
         // Clear the old highlights after the new added ones so that no flicker occurs.
-        if let Some(WinHighlights { winid, match_ids }) = old_highlights {
-            self.vim.matchdelete_batch(match_ids, winid).await?;
+        if let Some(old_highlights) = old_highlights {
+            self.clear_highlights(old_highlights).await?;
         }
 
         Ok(())
     }
+
+    async fn clear_highlights(&self, highlights: CursorHighlights) -> Result<()> {
+        match highlights {
+            CursorHighlights::Matches { winid, match_ids } => {
+                self.vim.matchdelete_batch(match_ids, winid).await?;
+            }
+            CursorHighlights::Extmarks { bufnr } => {
+                if let (Some(nvim_client), Some(namespace)) =
+                    (self.vim.nvim_client(), self.extmark_namespace)
+                {
+                    nvim_client
+                        .notify("nvim_buf_clear_namespace", json!([bufnr, namespace, 0, -1]))
+                        .map_err(|e| anyhow::anyhow!("RpcError: {e:?}"))?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
 impl ClapPlugin for CursorWordHighlighter {
-    async fn on_autocmd(&mut self, autocmd: Autocmd) -> Result<()> {
+    fn id(&self) -> &'static str {
+        Self::ID
+    }
+
+    async fn on_autocmd(&mut self, autocmd_event: AutocmdEvent) -> Result<()> {
+        let (autocmd, params) = autocmd_event;
         match autocmd {
-            Autocmd::CursorMoved => self.highlight_symbol_under_cursor().await,
+            Autocmd::CursorMoved | Autocmd::WinScrolled => {
+                let viewport = Viewport::from_params(params, &self.vim).await?;
+                self.highlight_symbol_under_cursor(viewport).await
+            }
             Autocmd::InsertEnter => {
-                if let Some(WinHighlights { winid, match_ids }) = self.cursor_highlights.take() {
-                    self.vim.matchdelete_batch(match_ids, winid).await?;
+                if let Some(cursor_highlights) = self.cursor_highlights.take() {
+                    self.clear_highlights(cursor_highlights).await?;
                 }
                 Ok(())
             }
+            Autocmd::TextYankPost => Ok(()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_word_highlights_in_viewport() {
+        let lines = vec![
+            "let foo = 1;".to_string(),
+            "let bar = foo + 1;".to_string(),
+            "foo(bar);".to_string(),
+        ];
+        // The viewport starts from line 10, cursor is at `foo` in line 11.
+        let word_highlights = find_word_highlights(&lines, 10, 11, 12, "foo".into()).unwrap();
+        assert_eq!(word_highlights.cword_highlight, (11, 10));
+        assert_eq!(
+            word_highlights.other_words_highlight,
+            vec![(10, 4), (12, 0)]
+        );
+        assert_eq!(word_highlights.cword_len, 3);
+    }
+}
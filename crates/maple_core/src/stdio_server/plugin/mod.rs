@@ -1,15 +1,20 @@
 mod highlight_cursor_word;
 mod markdown_toc;
+mod yank_history;
 
-use crate::stdio_server::input::Autocmd;
+use crate::stdio_server::input::AutocmdEvent;
 use anyhow::Result;
 use std::fmt::Debug;
 
 pub use highlight_cursor_word::CursorWordHighlighter;
 pub use markdown_toc::{find_toc_range, generate_toc};
+pub use yank_history::{YankHistory, YankRing};
 
 /// A trait each Clap plugin must implement.
 #[async_trait::async_trait]
 pub trait ClapPlugin: Debug + Send + Sync + 'static {
-    async fn on_autocmd(&mut self, autocmd: Autocmd) -> Result<()>;
+    /// Unique identifier of the plugin.
+    fn id(&self) -> &'static str;
+
+    async fn on_autocmd(&mut self, autocmd_event: AutocmdEvent) -> Result<()>;
 }
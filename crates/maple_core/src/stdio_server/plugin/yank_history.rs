@@ -0,0 +1,132 @@
+use crate::stdio_server::input::{Autocmd, AutocmdEvent};
+use crate::stdio_server::plugin::ClapPlugin;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Yanked text sent from Vim on `TextYankPost`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct YankEntry {
+    /// Yanked lines, i.e., `v:event.regcontents`.
+    pub regcontents: Vec<String>,
+    /// Type of the register, i.e., `v:event.regtype`.
+    pub regtype: String,
+    /// Filetype of the buffer where the text is yanked, used for highlighting the preview.
+    #[serde(default)]
+    pub filetype: String,
+    /// Unix timestamp of the yank.
+    #[serde(default)]
+    pub time: u64,
+}
+
+impl YankEntry {
+    /// Returns the line displayed in the yanks provider, the line breaks are joined.
+    pub fn display_line(&self) -> String {
+        self.regcontents.join("↵")
+    }
+
+    fn text_len(&self) -> usize {
+        self.regcontents
+            .iter()
+            .map(|line| line.chars().count())
+            .sum()
+    }
+}
+
+/// Yank history in the most recent first order.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct YankRing {
+    entries: VecDeque<YankEntry>,
+}
+
+impl YankRing {
+    /// Adds a new entry to the front of the ring, the same text yanked before is moved to the
+    /// front and the oldest entries beyond `max_entries` are dropped.
+    pub fn push(&mut self, entry: YankEntry, max_entries: usize) {
+        self.entries.retain(|e| e.regcontents != entry.regcontents);
+        self.entries.push_front(entry);
+        self.entries.truncate(max_entries);
+    }
+
+    pub fn entries(&self) -> &VecDeque<YankEntry> {
+        &self.entries
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct YankHistory;
+
+impl YankHistory {
+    pub const ID: &'static str = "yank-history";
+
+    pub const INIT_AUTOCMDS: &'static [Autocmd] = &[Autocmd::TextYankPost];
+
+    fn on_text_yank_post(&self, mut entry: YankEntry) -> Result<()> {
+        let config = &crate::config::config().plugin.yank_history;
+
+        if entry.text_len() < config.min_len {
+            return Ok(());
+        }
+
+        entry.time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        let mut yank_ring = crate::datastore::YANK_HISTORY_IN_MEMORY.lock();
+        yank_ring.push(entry, config.max_entries);
+        crate::datastore::store_yank_history(&yank_ring)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ClapPlugin for YankHistory {
+    fn id(&self) -> &'static str {
+        Self::ID
+    }
+
+    async fn on_autocmd(&mut self, autocmd_event: AutocmdEvent) -> Result<()> {
+        let (autocmd, params) = autocmd_event;
+        match autocmd {
+            Autocmd::TextYankPost => {
+                let [entry]: [YankEntry; 1] = params.parse()?;
+                self.on_text_yank_post(entry)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yank_ring_push() {
+        let entry = |text: &str| YankEntry {
+            regcontents: text.split('\n').map(Into::into).collect(),
+            regtype: "v".into(),
+            filetype: String::new(),
+            time: 0,
+        };
+
+        let mut yank_ring = YankRing::default();
+        yank_ring.push(entry("foo"), 3);
+        yank_ring.push(entry("bar\nbaz"), 3);
+        yank_ring.push(entry("foo"), 3);
+        yank_ring.push(entry("qux"), 3);
+        yank_ring.push(entry("quux"), 3);
+
+        assert_eq!(
+            yank_ring
+                .entries()
+                .iter()
+                .map(|e| e.display_line())
+                .collect::<Vec<_>>(),
+            vec!["quux", "qux", "foo"]
+        );
+    }
+}
@@ -6,8 +6,8 @@ use crate::paths::AbsPathBuf;
 use crate::stdio_server::handler::CachedPreviewImpl;
 use crate::stdio_server::job;
 use crate::stdio_server::provider::{ClapProvider, Context};
-use crate::tools::ctags::{get_language, TagsGenerator, CTAGS_EXISTS};
-use crate::tools::gtags::GTAGS_EXISTS;
+use crate::tooling::Tool;
+use crate::tools::ctags::{get_language, TagsGenerator};
 use anyhow::Result;
 use filter::Query;
 use futures::Future;
@@ -209,7 +209,10 @@ impl DumbJumpProvider {
                 });
             }
 
-            match (*CTAGS_EXISTS, *GTAGS_EXISTS) {
+            match (
+                crate::tooling::is_available(Tool::Ctags),
+                crate::tooling::is_available(Tool::Gtags),
+            ) {
                 (true, true) => run(
                     async move {
                         futures::future::join(ctags_future, gtags_future).await;
@@ -299,6 +302,7 @@ impl ClapProvider for DumbJumpProvider {
         if !query.is_empty() {
             let query_info = parse_query_info(&query);
             let search_worker = SearchWorker {
+                ignore_config: ctx.config().ignore_config("dumb_jump", &cwd).clone(),
                 cwd,
                 query_info: query_info.clone(),
                 source_file_extension,
@@ -380,6 +384,7 @@ impl ClapProvider for DumbJumpProvider {
 
         let cwd: AbsPathBuf = ctx.vim.working_dir().await?;
         let search_worker = SearchWorker {
+            ignore_config: ctx.config().ignore_config("dumb_jump", &cwd).clone(),
             cwd,
             query_info: query_info.clone(),
             source_file_extension: ctx.start_buffer_extension()?.to_string(),
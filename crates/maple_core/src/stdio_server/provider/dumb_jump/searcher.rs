@@ -7,7 +7,7 @@ use rayon::prelude::*;
 use std::collections::HashSet;
 use std::io::Result;
 use std::path::Path;
-use std::process::{Command, Stdio};
+use std::process::Stdio;
 
 /// `dumb_jump` search worker.
 #[derive(Debug, Clone)]
@@ -15,6 +15,7 @@ pub(super) struct SearchWorker {
     pub cwd: AbsPathBuf,
     pub query_info: QueryInfo,
     pub source_file_extension: String,
+    pub ignore_config: IgnoreConfig,
 }
 
 impl SearchWorker {
@@ -115,6 +116,7 @@ pub(super) enum SearchEngine {
 impl SearchEngine {
     pub async fn run(&self, search_worker: SearchWorker) -> Result<Usages> {
         let cwd = search_worker.cwd.clone();
+        let ignore_config = search_worker.ignore_config.clone();
 
         let ctags_future = {
             let search_worker = search_worker.clone();
@@ -166,7 +168,7 @@ impl SearchEngine {
             }
         };
 
-        let addressable_usages = filter_usages(&cwd, addressable_usages);
+        let addressable_usages = filter_usages(&cwd, &ignore_config, addressable_usages);
 
         Ok(addressable_usages.into())
     }
@@ -174,13 +176,14 @@ impl SearchEngine {
 
 fn filter_usages(
     cwd: &AbsPathBuf,
+    ignore_config: &IgnoreConfig,
     addressable_usages: Vec<AddressableUsage>,
 ) -> Vec<AddressableUsage> {
     let IgnoreConfig {
         git_tracked_only,
         file_path_pattern,
         ..
-    } = crate::config::config().ignore_config("dumb_jump", cwd);
+    } = ignore_config;
 
     let mut addressable_usages = addressable_usages;
 
@@ -211,11 +214,14 @@ fn filter_usages(
 
 fn is_git_tracked(file_path: &str, git_dir: &Path) -> bool {
     // Only the exit status matters.
-    Command::new("git")
+    crate::process::wsl::command_in("git", git_dir)
         .arg("ls-files")
         .arg("--error-unmatch")
-        .arg(file_path)
-        .current_dir(git_dir)
+        .arg(crate::process::wsl::tool_arg(
+            "git",
+            Path::new(file_path),
+            git_dir,
+        ))
         .stderr(Stdio::null())
         .stdout(Stdio::null())
         .status()
@@ -0,0 +1,171 @@
+//! Actions applicable to the entry under the cursor, listed in the action menu.
+
+use super::quickfix::QuickfixItem;
+use super::Context;
+use anyhow::Result;
+use serde_json::json;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryAction {
+    OpenSplit,
+    OpenVsplit,
+    OpenTab,
+    CopyPath,
+    RevealInFiler,
+    YankEntry,
+}
+
+impl EntryAction {
+    const FILE_ACTIONS: [Self; 5] = [
+        Self::OpenSplit,
+        Self::OpenVsplit,
+        Self::OpenTab,
+        Self::CopyPath,
+        Self::RevealInFiler,
+    ];
+
+    /// Name of the action in the RPC message.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::OpenSplit => "open-split",
+            Self::OpenVsplit => "open-vsplit",
+            Self::OpenTab => "open-tab",
+            Self::CopyPath => "copy-path",
+            Self::RevealInFiler => "reveal-in-filer",
+            Self::YankEntry => "yank-entry",
+        }
+    }
+
+    /// Title in the action menu, the character after `&` is the shortcut.
+    fn title(&self) -> &'static str {
+        match self {
+            Self::OpenSplit => "Open in &split",
+            Self::OpenVsplit => "Open in &vsplit",
+            Self::OpenTab => "Open in &tab",
+            Self::CopyPath => "&Copy path",
+            Self::RevealInFiler => "&Reveal in filer",
+            Self::YankEntry => "&Yank entry",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::FILE_ACTIONS
+            .into_iter()
+            .chain(std::iter::once(Self::YankEntry))
+            .find(|action| action.name() == name)
+    }
+}
+
+/// Returns the actions applicable to an entry, the file actions are only applicable to the
+/// entries pointing to a file location.
+fn applicable_actions(file_location: Option<&QuickfixItem>) -> Vec<EntryAction> {
+    let mut actions = if file_location.is_some() {
+        EntryAction::FILE_ACTIONS.to_vec()
+    } else {
+        Vec::new()
+    };
+    actions.push(EntryAction::YankEntry);
+    actions
+}
+
+impl Context {
+    /// Returns the current entry and its file location, if any.
+    async fn current_entry(&self) -> Result<(String, Option<QuickfixItem>)> {
+        let curline = self.vim.display_getcurline().await?;
+        let file_location = super::quickfix::parse_quickfix_item(
+            self.provider_id(),
+            &curline,
+            &self.cwd,
+            &self.env.start_buffer_path,
+        )
+        .map(QuickfixItem::into_editor_item);
+        Ok((curline, file_location))
+    }
+
+    /// Shows the action menu of the current entry if `action` is `None`, otherwise runs the
+    /// chosen action.
+    pub async fn handle_entry_action(&self, action: Option<EntryAction>) -> Result<()> {
+        match action {
+            Some(action) => self.run_entry_action(action).await,
+            None => self.show_entry_actions().await,
+        }
+    }
+
+    /// Shows the menu of the actions applicable to the current entry.
+    async fn show_entry_actions(&self) -> Result<()> {
+        let (curline, file_location) = self.current_entry().await?;
+        if curline.is_empty() {
+            return Ok(());
+        }
+
+        let actions = applicable_actions(file_location.as_ref())
+            .into_iter()
+            .map(|action| json!([action.title(), action.name()]))
+            .collect::<Vec<_>>();
+
+        self.vim
+            .exec("clap#action#invoke_entry_actions", json!([actions]))
+    }
+
+    /// Runs the action chosen from the action menu on the current entry.
+    async fn run_entry_action(&self, action: EntryAction) -> Result<()> {
+        let (curline, file_location) = self.current_entry().await?;
+
+        let file_location = match file_location {
+            Some(file_location) => file_location,
+            None if action == EntryAction::YankEntry => {
+                return self.vim.exec("clap#action#set_clipboard", json!([curline]));
+            }
+            None => {
+                return self.vim.echo_warn(format!(
+                    "{} is not applicable to an entry without file location",
+                    action.name()
+                ));
+            }
+        };
+
+        let open_cmd = match action {
+            EntryAction::OpenSplit => "split",
+            EntryAction::OpenVsplit => "vsplit",
+            EntryAction::OpenTab => "tab split",
+            EntryAction::CopyPath => {
+                return self
+                    .vim
+                    .exec("clap#action#set_clipboard", json!([file_location.filename]));
+            }
+            EntryAction::RevealInFiler => {
+                let dir = Path::new(&file_location.filename)
+                    .parent()
+                    .unwrap_or(self.cwd.as_ref());
+                return self.vim.exec("clap#action#reveal_in_filer", json!([dir]));
+            }
+            EntryAction::YankEntry => {
+                return self.vim.exec("clap#action#set_clipboard", json!([curline]));
+            }
+        };
+
+        self.note_accepted_files(&[curline]);
+        self.vim
+            .exec("clap#sink#open_all", json!([[file_location], open_cmd]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_actions() {
+        assert_eq!(applicable_actions(None), vec![EntryAction::YankEntry]);
+        assert_eq!(
+            EntryAction::from_name("reveal-in-filer"),
+            Some(EntryAction::RevealInFiler)
+        );
+        assert_eq!(
+            EntryAction::from_name("yank-entry"),
+            Some(EntryAction::YankEntry)
+        );
+        assert_eq!(EntryAction::from_name("unknown"), None);
+    }
+}
@@ -1,7 +1,7 @@
+use crate::searcher::{WalkConfig, WalkFilter};
 use crate::stdio_server::handler::{CachedPreviewImpl, Preview, PreviewTarget};
 use crate::stdio_server::input::KeyEvent;
 use crate::stdio_server::provider::{ClapProvider, Context, Direction};
-use crate::stdio_server::vim::preview_syntax;
 use anyhow::Result;
 use icon::{icon_or_default, FOLDER_ICON};
 use printer::Printer;
@@ -10,7 +10,7 @@ use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf, MAIN_SEPARATOR};
 use std::sync::Arc;
-use types::{ClapItem, MatchResult};
+use types::{ClapItem, MatchResult, MatchedItem};
 
 #[inline]
 fn file_name(path: &Path) -> &str {
@@ -19,6 +19,16 @@ fn file_name(path: &Path) -> &str {
         .expect("Path terminates in `..`")
 }
 
+/// Adjusts the highlight positions of the lines whose icon is added by the provider instead of
+/// the printer.
+pub(super) fn strip_icon_offset(indices: &mut [Vec<usize>]) {
+    indices.iter_mut().for_each(|v| {
+        v.iter_mut().for_each(|x| {
+            *x -= 2;
+        })
+    });
+}
+
 fn to_string_nicer(path: PathBuf, enable_icon: bool) -> String {
     if path.is_dir() {
         let dir_name = file_name(&path);
@@ -39,8 +49,23 @@ pub fn read_dir_entries<P: AsRef<Path>>(
     enable_icon: bool,
     max: Option<usize>,
 ) -> std::io::Result<Vec<String>> {
-    let entries_iter =
-        std::fs::read_dir(dir)?.map(|res| res.map(|x| to_string_nicer(x.path(), enable_icon)));
+    read_filtered_dir_entries(dir, enable_icon, max, &WalkFilter::default())
+}
+
+/// Same with [`read_dir_entries`] but the entries excluded by `walk_filter` are skipped.
+pub fn read_filtered_dir_entries<P: AsRef<Path>>(
+    dir: P,
+    enable_icon: bool,
+    max: Option<usize>,
+    walk_filter: &WalkFilter,
+) -> std::io::Result<Vec<String>> {
+    let entries_iter = std::fs::read_dir(dir)?
+        .filter(|res| {
+            res.as_ref()
+                .map(|x| !walk_filter.is_excluded(&x.path(), x.path().is_dir()))
+                .unwrap_or(true)
+        })
+        .map(|res| res.map(|x| to_string_nicer(x.path(), enable_icon)));
 
     let mut entries = if let Some(m) = max {
         entries_iter.take(m).collect::<std::io::Result<Vec<_>>>()?
@@ -88,9 +113,15 @@ pub struct FilerProvider {
     current_dir: PathBuf,
     dir_entries: HashMap<PathBuf, Vec<Arc<dyn ClapItem>>>,
     current_lines: Vec<String>,
+    /// Items of the current directory matching the query, paged in on scrolling.
+    matched_items: Vec<MatchedItem>,
+    /// Number of the matched items sent to the display window.
+    displayed: usize,
     printer: Printer,
     icon_enabled: bool,
     winwidth: usize,
+    /// Only the ignore globs and file types are applied.
+    walk_config: WalkConfig,
 }
 
 impl FilerProvider {
@@ -104,9 +135,15 @@ impl FilerProvider {
             current_dir,
             dir_entries: HashMap::new(),
             current_lines: Vec::new(),
+            matched_items: Vec::new(),
+            displayed: 0,
             printer,
             winwidth,
             icon_enabled,
+            walk_config: WalkConfig {
+                submodules: true,
+                ..ctx.config().walker.walk_config(Some("filer"))
+            },
         })
     }
 
@@ -153,7 +190,7 @@ impl FilerProvider {
             )?;
         } else {
             input.pop();
-            ctx.vim.exec("input_set", [&input])?;
+            ctx.set_query(&input, None)?;
         }
 
         self.current_lines = self.on_query_change(&input, ctx)?;
@@ -193,7 +230,7 @@ impl FilerProvider {
         self.update_preview(preview_target, ctx).await
     }
 
-    fn on_query_change(&self, query: &str, ctx: &Context) -> Result<Vec<String>> {
+    fn on_query_change(&mut self, query: &str, ctx: &Context) -> Result<Vec<String>> {
         let current_items = self
             .dir_entries
             .get(&self.current_dir)
@@ -201,69 +238,44 @@ impl FilerProvider {
 
         let processed = current_items.len();
 
-        if query.is_empty() {
-            let printer::DisplayLines {
-                lines,
-                mut indices,
-                truncated_map: _,
-                icon_added,
-            } = self.printer.to_display_lines(
-                current_items
-                    .iter()
-                    .take(200)
-                    .cloned()
-                    .map(Into::into)
-                    .collect(),
-            );
-
-            if self.icon_enabled {
-                indices.iter_mut().for_each(|v| {
-                    v.iter_mut().for_each(|x| {
-                        *x -= 2;
-                    })
-                });
-            }
-
-            let result = json!({
-                "lines": &lines, "indices": indices, "matched": 0, "processed": processed, "icon_added": icon_added,
-            });
-
-            ctx.vim
-                .exec("clap#state#process_filter_message", json!([result, true]))?;
-
-            return Ok(lines);
-        }
-
-        let matcher = ctx.matcher_builder().build(query.into());
-        let mut matched_items = filter::par_filter_items(current_items, &matcher);
-        let matched = matched_items.len();
-
-        matched_items.truncate(200);
+        let (matched_items, matched) = if query.is_empty() {
+            let matched_items: Vec<MatchedItem> =
+                current_items.iter().cloned().map(Into::into).collect();
+            (matched_items, 0)
+        } else {
+            let matcher = ctx.matcher_builder().build(query.into());
+            let matched_items = filter::par_filter_items(current_items, &matcher);
+            let matched = matched_items.len();
+            (matched_items, matched)
+        };
 
         let printer::DisplayLines {
             lines,
             mut indices,
             truncated_map,
             icon_added,
-        } = self.printer.to_display_lines(matched_items);
+        } = self
+            .printer
+            .to_visible_display_lines(&matched_items, ctx.env.display_winheight);
 
         if self.icon_enabled {
-            indices.iter_mut().for_each(|v| {
-                v.iter_mut().for_each(|x| {
-                    *x -= 2;
-                })
-            });
+            strip_icon_offset(&mut indices);
         }
 
+        let has_more = matched_items.len() > lines.len();
+
         let result = if truncated_map.is_empty() {
-            json!({ "lines": &lines, "indices": indices, "matched": matched, "processed": processed, "icon_added": icon_added })
+            json!({ "lines": &lines, "indices": indices, "matched": matched, "processed": processed, "icon_added": icon_added, "has_more": has_more })
         } else {
-            json!({ "lines": &lines, "indices": indices, "matched": matched, "processed": processed, "icon_added": icon_added, "truncated_map": truncated_map })
+            json!({ "lines": &lines, "indices": indices, "matched": matched, "processed": processed, "icon_added": icon_added, "has_more": has_more, "truncated_map": truncated_map })
         };
 
         ctx.vim
             .exec("clap#state#process_filter_message", json!([result, true]))?;
 
+        self.displayed = lines.len();
+        self.matched_items = matched_items;
+
         Ok(lines)
     }
 
@@ -286,7 +298,7 @@ impl FilerProvider {
                     if path.is_dir() {
                         Some("clap_filer")
                     } else if path.is_file() {
-                        preview_syntax(path)
+                        ctx.vim.preview_syntax(path)
                     } else {
                         None
                     }
@@ -307,7 +319,7 @@ impl FilerProvider {
     fn goto_dir(&mut self, dir: PathBuf, ctx: &Context) -> Result<()> {
         self.current_dir = dir.clone();
         self.load_dir(dir, ctx)?;
-        ctx.vim.exec("input_set", [""])?;
+        ctx.set_query("", None)?;
         ctx.vim.exec(
             "clap#file_explorer#set_prompt",
             serde_json::json!([&self.current_dir, self.winwidth]),
@@ -328,7 +340,12 @@ impl FilerProvider {
 
     fn load_dir(&mut self, target_dir: PathBuf, ctx: &Context) -> Result<()> {
         if let Entry::Vacant(v) = self.dir_entries.entry(target_dir) {
-            let entries = match read_dir_entries(&self.current_dir, self.icon_enabled, None) {
+            let entries = match read_filtered_dir_entries(
+                &self.current_dir,
+                self.icon_enabled,
+                None,
+                &self.walk_config.filter(&self.current_dir),
+            ) {
                 Ok(entries) => entries,
                 Err(err) => {
                     ctx.vim
@@ -360,7 +377,12 @@ impl ClapProvider for FilerProvider {
     async fn on_initialize(&mut self, ctx: &mut Context) -> Result<()> {
         let cwd = &ctx.cwd;
 
-        let entries = match read_dir_entries(cwd, self.icon_enabled, None) {
+        let entries = match read_filtered_dir_entries(
+            cwd,
+            self.icon_enabled,
+            None,
+            &self.walk_config.filter(cwd),
+        ) {
             Ok(entries) => entries,
             Err(err) => {
                 tracing::error!(?cwd, "Failed to read directory entries");
@@ -389,12 +411,15 @@ impl ClapProvider for FilerProvider {
                 .collect(),
         );
         self.current_lines = entries;
+        // All the entries are displayed by `handle_on_initialize`.
+        self.matched_items.clear();
+        self.displayed = 0;
 
         Ok(())
     }
 
     async fn on_move(&mut self, ctx: &mut Context) -> Result<()> {
-        if !ctx.env.preview_enabled {
+        if !ctx.preview_enabled() {
             return Ok(());
         }
         self.preview_current_entry(ctx).await
@@ -419,6 +444,43 @@ impl ClapProvider for FilerProvider {
         Ok(())
     }
 
+    fn matched_lines(&self) -> Option<Vec<String>> {
+        Some(
+            self.matched_items
+                .iter()
+                .map(|matched_item| {
+                    let entry = matched_item.item.raw_text();
+                    let entry = if self.icon_enabled {
+                        entry.chars().skip(2).collect()
+                    } else {
+                        entry.to_string()
+                    };
+                    self.current_dir.join(entry).display().to_string()
+                })
+                .collect(),
+        )
+    }
+
+    async fn on_load_more(&mut self, ctx: &mut Context) -> Result<()> {
+        if self.displayed >= self.matched_items.len() {
+            return Ok(());
+        }
+
+        let mut display_lines = self.printer.to_page_display_lines(
+            &self.matched_items,
+            self.displayed,
+            ctx.env.display_winheight,
+        );
+
+        if self.icon_enabled {
+            strip_icon_offset(&mut display_lines.indices);
+        }
+
+        self.displayed += display_lines.lines.len();
+
+        ctx.append_page(display_lines, self.matched_items.len() > self.displayed)
+    }
+
     async fn on_key_event(&mut self, ctx: &mut Context, key_event: KeyEvent) -> Result<()> {
         match key_event {
             KeyEvent::Tab => self.on_tab(ctx).await,
@@ -428,6 +490,8 @@ impl ClapProvider for FilerProvider {
             KeyEvent::ShiftDown => ctx.scroll_preview(Direction::Down).await,
             KeyEvent::CtrlN => ctx.next_input().await,
             KeyEvent::CtrlP => ctx.previous_input().await,
+            // Bound to `send-to-quickfix` in `handle_key_event()`, no command to restart.
+            KeyEvent::CtrlQ | KeyEvent::CtrlR => Ok(()),
         }
     }
 }
@@ -1,3 +1,4 @@
+use crate::paths::query_with_main_separator;
 use crate::stdio_server::provider::{ClapProvider, Context, SearcherControl};
 use anyhow::Result;
 use clap::Parser;
@@ -73,7 +74,7 @@ impl FilesProvider {
                 MatchScope::Full
             })
             .bonuses(vec![self.recent_files_bonus.clone()])
-            .build(Query::from(&query));
+            .build(Query::from(query_with_main_separator(&query)));
 
         let new_control = {
             let stop_signal = Arc::new(AtomicBool::new(false));
@@ -85,11 +86,13 @@ impl FilesProvider {
                 } else {
                     search_context.paths.extend_from_slice(&self.args.paths);
                 }
+                if self.args.hidden {
+                    search_context.walk_config.hidden = false;
+                }
                 let vim = ctx.vim.clone();
-                let hidden = self.args.hidden;
                 tokio::spawn(async move {
                     let _ = vim.bare_exec("clap#spinner#set_busy");
-                    crate::searcher::files::search(query, hidden, matcher, search_context).await;
+                    crate::searcher::files::search(query, matcher, search_context).await;
                     let _ = vim.bare_exec("clap#spinner#set_idle");
                 })
             };
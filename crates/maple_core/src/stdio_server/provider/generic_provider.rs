@@ -1,17 +1,22 @@
-use crate::stdio_server::handler::{CachedPreviewImpl, PreviewTarget};
+use crate::process::ShellCommand;
+use crate::stdio_server::handler::{initialize_provider, CachedPreviewImpl, PreviewTarget};
+use crate::stdio_server::provider::retained_results::{RetainedResults, SpillCollector};
 use crate::stdio_server::provider::{ClapProvider, Context, ProviderSource};
 use crate::stdio_server::vim::VimProgressor;
 use anyhow::Result;
-use filter::{FilterContext, ParallelSource};
+use filter::{FilterContext, ParallelSource, SourceItem};
 use parking_lot::Mutex;
-use printer::{DisplayLines, Printer};
-use serde_json::json;
+use printer::DisplayLines;
+use serde_json::{json, Value};
 use std::path::PathBuf;
+use std::process::Stdio;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread::JoinHandle;
+use std::time::Duration;
 use subprocess::Exec;
-use types::MatchedItem;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use types::{ClapItem, MatchedItem};
 
 #[derive(Debug)]
 enum DataSource {
@@ -26,6 +31,11 @@ struct FilterControl {
 }
 
 impl FilterControl {
+    /// Signals the filter to stop without waiting for it.
+    fn stop(&self) {
+        self.stop_signal.store(true, Ordering::SeqCst);
+    }
+
     fn kill(self) {
         self.stop_signal.store(true, Ordering::SeqCst);
         let _ = self.join_handle.join();
@@ -33,11 +43,15 @@ impl FilterControl {
 }
 
 /// Start the parallel filter in a new thread.
+///
+/// All the matched items are spilled to the disk while filtering and retained in
+/// `current_results` for paging once the filtering is done.
 fn start_filter_parallel(
     query: String,
     number: usize,
     data_source: DataSource,
     ctx: &Context,
+    current_results: Arc<Mutex<FilteredResults>>,
 ) -> FilterControl {
     let stop_signal = Arc::new(AtomicBool::new(false));
 
@@ -52,21 +66,42 @@ fn start_filter_parallel(
         let cwd = ctx.cwd.clone();
         let vim = ctx.vim.clone();
         let stop_signal = stop_signal.clone();
+        let spill_collector = SpillCollector::new(ctx.matcher(&query).match_scope());
 
         std::thread::spawn(move || {
             if let Err(e) = filter::par_dyn_run_inprocess(
                 &query,
                 filter_context,
                 match data_source {
-                    DataSource::File(path) => ParallelSource::File(path),
+                    DataSource::File(path) => match crate::cache::shared_source(&path) {
+                        Some(bytes) => ParallelSource::Shared(bytes),
+                        None => ParallelSource::File(path),
+                    },
                     DataSource::Command(command) => {
                         ParallelSource::Exec(Box::new(Exec::shell(command).cwd(cwd)))
                     }
                 },
-                VimProgressor::new(vim, stop_signal.clone()),
-                stop_signal,
+                VimProgressor::new(vim.clone(), stop_signal.clone()),
+                stop_signal.clone(),
+                Some(&spill_collector),
             ) {
                 tracing::error!(error = ?e, "Error occured when filtering the cache source");
+                return;
+            }
+
+            let retained = spill_collector.finish().unwrap_or_default();
+            let displayed = retained.len().min(number);
+            let has_more = retained.len() > displayed;
+
+            // The stop signal is checked with the lock held, the results of a new query are
+            // never overwritten.
+            let mut current_results = current_results.lock();
+            if !stop_signal.load(Ordering::SeqCst) {
+                *current_results = FilteredResults {
+                    retained,
+                    displayed,
+                };
+                let _ = vim.set_var("g:__clap_has_more_results", has_more);
             }
         })
     };
@@ -77,12 +112,221 @@ fn start_filter_parallel(
     }
 }
 
+/// Results of the last filtering, shared with the task streaming the source command.
+#[derive(Debug, Default)]
+struct FilteredResults {
+    retained: RetainedResults,
+    /// Number of the results sent to the display window.
+    displayed: usize,
+}
+
+impl FilteredResults {
+    /// Retains the results of a new query, the items beyond the cap are spilled in a blocking
+    /// task.
+    async fn update(
+        current_results: &Mutex<Self>,
+        matched_items: Vec<MatchedItem>,
+        displayed: usize,
+        max_retained_items: usize,
+    ) -> Result<()> {
+        let retained = tokio::task::spawn_blocking(move || {
+            RetainedResults::new(matched_items, max_retained_items)
+        })
+        .await?;
+        *current_results.lock() = Self {
+            retained,
+            displayed,
+        };
+        Ok(())
+    }
+}
+
+/// Filters the small source against `query`, returns the message to the display window, the
+/// matched items and the number of the displayed ones.
+fn filter_small_items(
+    items: &[Arc<dyn ClapItem>],
+    query: &str,
+    ctx: &Context,
+) -> (Value, Vec<MatchedItem>, usize) {
+    let matched_items = filter::par_filter_items(items, &ctx.matcher(query));
+    let printer = ctx.printer();
+    // Only the visible entries are decorated.
+    let DisplayLines {
+        lines,
+        indices,
+        truncated_map,
+        icon_added,
+    } = printer.to_visible_display_lines(&matched_items, ctx.env.display_winheight);
+    let displayed = lines.len();
+    let msg = json!({
+        "total": matched_items.len(),
+        "lines": lines,
+        "indices": indices,
+        "icon_added": icon_added,
+        "truncated_map": truncated_map,
+        "has_more": matched_items.len() > displayed,
+    });
+    (msg, matched_items, displayed)
+}
+
+/// Interval of refreshing the display while the source command is still running.
+const STREAM_REFRESH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Refreshes the display with the lines received so far.
+async fn refresh_streamed_source(
+    ctx: &Context,
+    items: &[Arc<dyn ClapItem>],
+    current_results: &Mutex<FilteredResults>,
+) -> Result<()> {
+    ctx.set_provider_source(ProviderSource::Small {
+        total: items.len(),
+        items: items.to_vec(),
+    });
+    ctx.vim
+        .set_var("g:clap.display.initial_size", items.len())?;
+
+    let query = ctx.vim.input_get().await?;
+
+    if query.is_empty() {
+        let printer = ctx.printer();
+        let DisplayLines {
+            lines,
+            icon_added,
+            truncated_map,
+            ..
+        } = printer.to_display_lines(
+            items
+                .iter()
+                .take(ctx.env.display_winheight)
+                .map(|item| MatchedItem::from(item.clone()))
+                .collect(),
+        );
+        ctx.vim.exec(
+            "clap#state#init_display",
+            json!([lines, truncated_map, icon_added, false]),
+        )?;
+    } else {
+        // Filter the lines received so far against the current query, the query is unchanged
+        // and hence `on_typed` would be skipped.
+        let (msg, matched_items, displayed) = filter_small_items(items, &query, ctx);
+        ctx.vim
+            .exec("clap#state#process_filter_message", json!([msg, true]))?;
+        let max_retained_items = ctx.config().provider.max_retained_items;
+        FilteredResults::update(
+            current_results,
+            matched_items,
+            displayed,
+            max_retained_items,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Runs the source command and streams its stdout into the provider source.
+///
+/// The exit status and stderr of the command are shown in the prompt once it exits.
+async fn stream_command(
+    command: String,
+    ctx: Context,
+    current_results: Arc<Mutex<FilteredResults>>,
+) -> Result<()> {
+    let mut cmd = crate::process::tokio::shell_command(&command);
+    cmd.current_dir(&ctx.cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let mut child = cmd.spawn()?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+
+    // Drain stderr concurrently, otherwise the command can be blocked on a full pipe.
+    let stderr_task = tokio::spawn(async move {
+        let mut buf = String::new();
+        let _ = stderr.read_to_string(&mut buf).await;
+        buf
+    });
+
+    ctx.set_provider_source(ProviderSource::Small {
+        total: 0,
+        items: Vec::new(),
+    });
+    ctx.vim
+        .exec("clap#state#set_command_status", json!([true, ""]))?;
+
+    let mut lines = BufReader::new(stdout).lines();
+    let mut items: Vec<Arc<dyn ClapItem>> = Vec::new();
+    let mut interval = tokio::time::interval(STREAM_REFRESH_INTERVAL);
+    let mut has_new_lines = false;
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => match line? {
+                Some(line) => {
+                    items.push(Arc::new(SourceItem::from(line)));
+                    has_new_lines = true;
+                }
+                None => break,
+            },
+            _ = interval.tick() => {
+                if has_new_lines {
+                    refresh_streamed_source(&ctx, &items, &current_results).await?;
+                    has_new_lines = false;
+                }
+            }
+        }
+    }
+
+    let exit_status = child.wait().await?;
+    let stderr = stderr_task.await.unwrap_or_default();
+
+    refresh_streamed_source(&ctx, &items, &current_results).await?;
+
+    if !stderr.is_empty() {
+        tracing::debug!(?command, ?exit_status, %stderr, "Source command exited with stderr");
+    }
+
+    let last_stderr_line = stderr.lines().rev().find(|line| !line.trim().is_empty());
+    let status = match (exit_status.code(), last_stderr_line) {
+        (Some(0), None) => String::new(),
+        (Some(0), Some(line)) => format!("[{}]", line.trim()),
+        (code, line) => {
+            let code = code.map_or_else(|| "signal".to_string(), |code| code.to_string());
+            match line {
+                Some(line) => format!("[exit {code}: {}]", line.trim()),
+                None => format!("[exit {code}]"),
+            }
+        }
+    };
+    ctx.vim
+        .exec("clap#state#set_command_status", json!([false, status]))?;
+
+    if exit_status.success() {
+        let total = items.len();
+        let output = items
+            .iter()
+            .map(|item| item.raw_text())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let shell_cmd = ShellCommand::new(command, ctx.cwd.to_path_buf());
+        tokio::task::spawn_blocking(move || shell_cmd.write_cache(total, output.as_bytes()))
+            .await??;
+    }
+
+    Ok(())
+}
+
 /// Generic provider impl.
 #[derive(Debug)]
 pub struct GenericProvider {
     runtimepath: Option<String>,
     maybe_filter_control: Option<FilterControl>,
-    current_results: Arc<Mutex<Vec<MatchedItem>>>,
+    /// Task streaming the output of the source command, the command is killed on abort.
+    maybe_command_stream: Option<tokio::task::JoinHandle<()>>,
+    current_results: Arc<Mutex<FilteredResults>>,
     last_filter_control_killed: Arc<AtomicBool>,
 }
 
@@ -91,17 +335,36 @@ impl GenericProvider {
         Self {
             runtimepath: None,
             maybe_filter_control: None,
-            current_results: Arc::new(Mutex::new(Vec::new())),
+            maybe_command_stream: None,
+            current_results: Arc::new(Mutex::new(FilteredResults::default())),
             last_filter_control_killed: Arc::new(AtomicBool::new(true)),
         }
     }
 
+    /// Starts streaming the output of the source command, the last run is killed if any.
+    fn start_command_stream(&mut self, command: String, ctx: &Context) {
+        if let Some(command_stream) = self.maybe_command_stream.take() {
+            command_stream.abort();
+        }
+
+        let ctx = ctx.clone();
+        let current_results = self.current_results.clone();
+        let command_stream = tokio::spawn(async move {
+            if let Err(err) = stream_command(command, ctx, current_results).await {
+                tracing::error!(?err, "Failed to stream the source command");
+            }
+        });
+        self.maybe_command_stream.replace(command_stream);
+    }
+
     /// `lnum` is 1-based.
     #[allow(unused)]
     fn line_at(&self, lnum: usize) -> Option<String> {
         self.current_results
             .lock()
-            .get(lnum - 1)
+            .retained
+            .page(lnum - 1, 1)
+            .first()
             .map(|r| r.item.output_text().to_string())
     }
 
@@ -132,6 +395,20 @@ impl GenericProvider {
                     runtimepath,
                 })
             }
+            "docs" => {
+                // `name\t[type]\tslug/path`
+                let location = curline
+                    .rsplit('\t')
+                    .next()
+                    .and_then(|location| location.split_once('/'))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Couldn't extract slug and path from the line")
+                    })?;
+                Some(PreviewTarget::Devdocs {
+                    slug: location.0.to_string(),
+                    path: location.1.to_string(),
+                })
+            }
             "buffers" => {
                 let res: [String; 2] = ctx
                     .vim
@@ -151,8 +428,24 @@ impl GenericProvider {
 
 #[async_trait::async_trait]
 impl ClapProvider for GenericProvider {
+    async fn on_initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        initialize_provider(ctx).await?;
+
+        // The source command did not finish in time, stream its output instead of waiting for
+        // the exit.
+        let maybe_command = match *ctx.provider_source.read() {
+            ProviderSource::Command(ref cmd) => Some(cmd.clone()),
+            _ => None,
+        };
+        if let Some(command) = maybe_command {
+            self.start_command_stream(command, ctx);
+        }
+
+        Ok(())
+    }
+
     async fn on_move(&mut self, ctx: &mut Context) -> Result<()> {
-        if !ctx.env.preview_enabled {
+        if !ctx.preview_enabled() {
             return Ok(());
         }
 
@@ -196,34 +489,23 @@ impl ClapProvider for GenericProvider {
 
         let quick_response =
             if let ProviderSource::Small { ref items, .. } = *ctx.provider_source.read() {
-                let matched_items = filter::par_filter_items(items, &ctx.matcher(&query));
-                let printer = Printer::new(ctx.env.display_winwidth, ctx.env.icon);
-                // Take the first 200 entries and add an icon to each of them.
-                let DisplayLines {
-                    lines,
-                    indices,
-                    truncated_map,
-                    icon_added,
-                } = printer.to_display_lines(matched_items.iter().take(200).cloned().collect());
-                let msg = json!({
-                    "total": matched_items.len(),
-                    "lines": lines,
-                    "indices": indices,
-                    "icon_added": icon_added,
-                    "truncated_map": truncated_map,
-                });
-                Some((msg, matched_items))
+                Some(filter_small_items(items, &query, ctx))
             } else {
                 None
             };
 
-        if let Some((msg, matched_items)) = quick_response {
+        if let Some((msg, matched_items, displayed)) = quick_response {
             let new_query = ctx.vim.input_get().await?;
             if new_query == query {
                 ctx.vim
                     .exec("clap#state#process_filter_message", json!([msg, true]))?;
-                let mut current_results = self.current_results.lock();
-                *current_results = matched_items;
+                FilteredResults::update(
+                    &self.current_results,
+                    matched_items,
+                    displayed,
+                    ctx.config().provider.max_retained_items,
+                )
+                .await?;
             }
             return Ok(());
         }
@@ -249,6 +531,7 @@ impl ClapProvider for GenericProvider {
 
         // Kill the last par_dyn_run job if exists.
         if let Some(control) = self.maybe_filter_control.take() {
+            control.stop();
             self.last_filter_control_killed
                 .store(false, Ordering::SeqCst);
 
@@ -260,18 +543,108 @@ impl ClapProvider for GenericProvider {
         }
 
         let display_winheight = ctx.env.display_winheight;
-        let new_control = start_filter_parallel(query, display_winheight, data_source, ctx);
+        *self.current_results.lock() = FilteredResults::default();
+        let new_control = start_filter_parallel(
+            query,
+            display_winheight,
+            data_source,
+            ctx,
+            self.current_results.clone(),
+        );
 
         self.maybe_filter_control.replace(new_control);
 
         Ok(())
     }
 
+    async fn on_load_more(&mut self, ctx: &mut Context) -> Result<()> {
+        // Nothing is filtered yet, page in the source displayed on initialization.
+        if self.current_results.lock().retained.len() == 0 {
+            if !ctx.vim.input_get().await?.is_empty() {
+                return Ok(());
+            }
+            let start = ctx.vim.display_getcurlnum().await?;
+            let count = ctx.env.display_winheight;
+            let (maybe_items, maybe_total) = {
+                let provider_source = ctx.provider_source.read();
+                (
+                    provider_source.try_skim(ctx.provider_id(), start + count),
+                    provider_source.total(),
+                )
+            };
+            let (Some(items), Some(total)) = (maybe_items, maybe_total) else {
+                return Ok(());
+            };
+            let display_lines = ctx.printer().to_page_display_lines(&items, start, count);
+            let has_more = total > start + display_lines.lines.len();
+            return ctx.append_page(display_lines, has_more);
+        }
+
+        // The page may be read from the spill file.
+        let current_results = self.current_results.clone();
+        let count = ctx.env.display_winheight;
+        let (page, total, displayed) = tokio::task::spawn_blocking(move || {
+            let current_results = current_results.lock();
+            let displayed = current_results.displayed;
+            (
+                current_results.retained.page(displayed, count),
+                current_results.retained.len(),
+                displayed,
+            )
+        })
+        .await?;
+
+        if page.is_empty() {
+            return Ok(());
+        }
+
+        let printer = ctx.printer();
+        let mut display_lines = printer.to_display_lines(page);
+
+        // The line numbers in the truncated map are relative to the page.
+        display_lines.truncated_map = display_lines
+            .truncated_map
+            .into_iter()
+            .map(|(lnum, line)| (lnum + displayed, line))
+            .collect();
+
+        let displayed = displayed + display_lines.lines.len();
+        self.current_results.lock().displayed = displayed;
+
+        ctx.append_page(display_lines, total > displayed)
+    }
+
+    fn matched_lines(&self) -> Option<Vec<String>> {
+        let current_results = self.current_results.lock();
+        let retained = &current_results.retained;
+        if retained.len() == 0 {
+            return None;
+        }
+        Some(
+            retained
+                .page(0, retained.len())
+                .iter()
+                .map(|matched_item| matched_item.item.output_text().to_string())
+                .collect(),
+        )
+    }
+
+    async fn restart_command(&mut self, ctx: &mut Context) -> Result<()> {
+        let source_cmd: Vec<String> = ctx.vim.bare_call("provider_source_cmd").await?;
+        if let Some(command) = source_cmd.into_iter().next() {
+            self.start_command_stream(command, ctx);
+        }
+        Ok(())
+    }
+
     fn on_terminate(&mut self, ctx: &mut Context, session_id: u64) {
         if let Some(control) = self.maybe_filter_control.take() {
             // NOTE: The kill operation can not block current task.
             tokio::task::spawn_blocking(move || control.kill());
         }
+        if let Some(command_stream) = self.maybe_command_stream.take() {
+            command_stream.abort();
+        }
         ctx.signify_terminated(session_id);
     }
 }
@@ -24,6 +24,7 @@ struct GrepArgs {
 #[derive(Debug)]
 pub struct GrepProvider {
     args: GrepArgs,
+    priority_paths: Vec<PathBuf>,
     searcher_control: Option<SearcherControl>,
 }
 
@@ -35,6 +36,7 @@ impl GrepProvider {
                 base,
                 paths: ctx.expanded_paths(&paths).await?,
             },
+            priority_paths: ctx.priority_paths().await,
             searcher_control: None,
         })
     }
@@ -62,6 +64,7 @@ impl GrepProvider {
             } else {
                 search_context.paths.extend_from_slice(&self.args.paths);
             }
+            search_context.priority_paths = self.priority_paths.clone();
             let join_handle = tokio::spawn(async move {
                 let _ = vim.bare_exec("clap#spinner#set_busy");
                 crate::searcher::grep::search(query, matcher, search_context).await;
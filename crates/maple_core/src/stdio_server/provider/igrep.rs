@@ -1,9 +1,8 @@
-use super::filer::{read_dir_entries, FilerItem, FilerItemWithoutIcon};
+use super::filer::{read_dir_entries, strip_icon_offset, FilerItem, FilerItemWithoutIcon};
 use super::Direction;
 use crate::stdio_server::handler::{CachedPreviewImpl, Preview, PreviewTarget};
 use crate::stdio_server::input::KeyEvent;
 use crate::stdio_server::provider::{ClapProvider, Context, SearcherControl};
-use crate::stdio_server::vim::preview_syntax;
 use anyhow::Result;
 use matcher::MatchScope;
 use pattern::extract_grep_position;
@@ -14,7 +13,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
-use types::{ClapItem, Query};
+use types::{ClapItem, MatchedItem, Query};
 
 #[derive(Debug)]
 struct Grepper {
@@ -78,6 +77,10 @@ struct Explorer {
     current_dir: PathBuf,
     dir_entries_cache: HashMap<PathBuf, Vec<Arc<dyn ClapItem>>>,
     current_lines: Vec<String>,
+    /// Entries of the current directory, paged in on scrolling.
+    matched_items: Vec<MatchedItem>,
+    /// Number of the entries sent to the display window.
+    displayed: usize,
     icon_enabled: bool,
     winwidth: usize,
 }
@@ -93,6 +96,8 @@ impl Explorer {
             current_dir,
             dir_entries_cache: HashMap::new(),
             current_lines: Vec::new(),
+            matched_items: Vec::new(),
+            displayed: 0,
             icon_enabled,
             winwidth,
         })
@@ -193,7 +198,7 @@ impl Explorer {
     }
 
     /// Display the file explorer.
-    fn display_dir_entries(&self, ctx: &Context) -> Result<Vec<String>> {
+    fn display_dir_entries(&mut self, ctx: &Context) -> Result<Vec<String>> {
         let current_items = self
             .dir_entries_cache
             .get(&self.current_dir)
@@ -202,27 +207,20 @@ impl Explorer {
             })?;
 
         let processed = current_items.len();
+        let matched_items: Vec<MatchedItem> =
+            current_items.iter().cloned().map(Into::into).collect();
 
         let printer::DisplayLines {
             lines,
             mut indices,
             truncated_map: _,
             icon_added,
-        } = self.printer.to_display_lines(
-            current_items
-                .iter()
-                .take(200)
-                .cloned()
-                .map(Into::into)
-                .collect(),
-        );
+        } = self
+            .printer
+            .to_visible_display_lines(&matched_items, ctx.env.display_winheight);
 
         if ctx.env.icon.enabled() {
-            indices.iter_mut().for_each(|v| {
-                v.iter_mut().for_each(|x| {
-                    *x -= 2;
-                })
-            });
+            strip_icon_offset(&mut indices);
         }
 
         let result = json!({
@@ -232,14 +230,55 @@ impl Explorer {
             "processed": processed,
             "icon_added": icon_added,
             "display_syntax": "clap_filer",
+            "has_more": processed > lines.len(),
         });
 
         ctx.vim
             .exec("clap#state#process_filter_message", json!([result, true]))?;
 
+        self.displayed = lines.len();
+        self.matched_items = matched_items;
+
         Ok(lines)
     }
 
+    /// Returns the paths of all the entries of the current directory.
+    fn matched_lines(&self) -> Vec<String> {
+        self.matched_items
+            .iter()
+            .map(|matched_item| {
+                let entry = matched_item.item.raw_text();
+                let entry = if self.icon_enabled {
+                    entry.chars().skip(2).collect()
+                } else {
+                    entry.to_string()
+                };
+                self.current_dir.join(entry).display().to_string()
+            })
+            .collect()
+    }
+
+    /// Appends the next page of the directory entries.
+    fn load_more(&mut self, ctx: &Context) -> Result<()> {
+        if self.displayed >= self.matched_items.len() {
+            return Ok(());
+        }
+
+        let mut display_lines = self.printer.to_page_display_lines(
+            &self.matched_items,
+            self.displayed,
+            ctx.env.display_winheight,
+        );
+
+        if ctx.env.icon.enabled() {
+            strip_icon_offset(&mut display_lines.indices);
+        }
+
+        self.displayed += display_lines.lines.len();
+
+        ctx.append_page(display_lines, self.matched_items.len() > self.displayed)
+    }
+
     async fn preview_current_line(&self, ctx: &mut Context) -> Result<()> {
         let curline = self.current_line(ctx).await?;
         let target_dir = self.current_dir.join(curline);
@@ -274,7 +313,7 @@ impl Explorer {
                     if path.is_dir() {
                         Some("clap_filer")
                     } else if path.is_file() {
-                        preview_syntax(path)
+                        ctx.vim.preview_syntax(path)
                     } else {
                         None
                     }
@@ -296,7 +335,7 @@ impl Explorer {
         if let Err(err) = self.read_entries_if_not_in_cache(dir) {
             ctx.vim.exec("show_lines_in_preview", [err.to_string()])?;
         }
-        ctx.vim.exec("input_set", [""])?;
+        ctx.set_query("", None)?;
         ctx.vim.exec(
             "clap#file_explorer#set_prompt",
             serde_json::json!([&self.current_dir, self.winwidth]),
@@ -386,12 +425,14 @@ impl IgrepProvider {
         };
 
         if input.is_empty() {
+            self.mode = Mode::FileExplorer;
             self.explorer.goto_parent(ctx).await?;
         } else {
             input.pop();
-            ctx.vim.exec("input_set", [&input])?;
+            ctx.set_query(&input, None)?;
 
             if input.is_empty() {
+                self.mode = Mode::FileExplorer;
                 self.explorer.show_dir_entries(ctx)?;
             } else {
                 self.grepper
@@ -454,7 +495,7 @@ impl ClapProvider for IgrepProvider {
     }
 
     async fn on_move(&mut self, ctx: &mut Context) -> Result<()> {
-        if !ctx.env.preview_enabled {
+        if !ctx.preview_enabled() {
             return Ok(());
         }
         let query: String = ctx.vim.input_get().await?;
@@ -480,6 +521,19 @@ impl ClapProvider for IgrepProvider {
         Ok(())
     }
 
+    fn matched_lines(&self) -> Option<Vec<String>> {
+        // The grep results are not retained.
+        matches!(self.mode, Mode::FileExplorer).then(|| self.explorer.matched_lines())
+    }
+
+    async fn on_load_more(&mut self, ctx: &mut Context) -> Result<()> {
+        // The grep results are not paged.
+        if matches!(self.mode, Mode::FileExplorer) {
+            self.explorer.load_more(ctx)?;
+        }
+        Ok(())
+    }
+
     async fn on_key_event(&mut self, ctx: &mut Context, key_event: KeyEvent) -> Result<()> {
         match key_event {
             KeyEvent::CtrlN => ctx.next_input().await,
@@ -489,6 +543,8 @@ impl ClapProvider for IgrepProvider {
             KeyEvent::Tab => self.on_tab(ctx).await,
             KeyEvent::Backspace => self.on_backspace(ctx).await,
             KeyEvent::CarriageReturn => self.on_carriage_return(ctx).await,
+            // Bound to `send-to-quickfix` in `handle_key_event()`, no command to restart.
+            KeyEvent::CtrlQ | KeyEvent::CtrlR => Ok(()),
         }
     }
 }
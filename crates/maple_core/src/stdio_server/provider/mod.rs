@@ -1,28 +1,40 @@
 mod blines;
 mod dumb_jump;
+mod entry_action;
 mod filer;
 mod files;
 mod generic_provider;
 mod grep;
 mod igrep;
+mod quickfix;
 mod recent_files;
+mod resume;
+mod retained_results;
+mod smart_open;
 mod tagfiles;
+mod todos;
+mod undo;
 
+pub use self::entry_action::EntryAction;
 pub use self::filer::read_dir_entries;
+pub(crate) use self::quickfix::{parse_quickfix_item, QuickfixItem};
+pub use self::resume::SessionSnapshot;
+use crate::config::{Config, ProviderAction, ProviderHooks};
 use crate::paths::AbsPathBuf;
+use crate::recent_files::AccessKind;
 use crate::searcher::blines::BlinesItem;
-use crate::searcher::SearchContext;
+use crate::searcher::{SearchContext, WalkConfig};
 use crate::stdio_server::handler::{
-    initialize_provider, CachedPreviewImpl, Preview, PreviewTarget,
+    initialize_provider, initialize_provider_source, CachedPreviewImpl, Preview, PreviewTarget,
 };
-use crate::stdio_server::input::{InputRecorder, KeyEvent};
+use crate::stdio_server::input::{InputRecorder, KeyEvent, MouseEvent};
 use crate::stdio_server::vim::Vim;
 use anyhow::{anyhow, Result};
 use filter::Query;
 use icon::{Icon, IconKind};
 use matcher::{Bonus, MatchScope, Matcher, MatcherBuilder};
-use parking_lot::RwLock;
-use printer::Printer;
+use parking_lot::{Mutex, RwLock};
+use printer::{PathDisplay, Printer};
 use rpc::Params;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -31,7 +43,7 @@ use std::fmt::Debug;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use types::{ClapItem, MatchedItem};
+use types::{CaseMatching, ClapItem, MatchedItem};
 
 /// [`BaseArgs`] represents the arguments common to all the providers.
 #[derive(Debug, clap::Parser, PartialEq, Eq, Default)]
@@ -58,7 +70,10 @@ pub async fn create_provider(provider_id: &str, ctx: &Context) -> Result<Box<dyn
         "grep" => Box::new(grep::GrepProvider::new(ctx).await?),
         "igrep" => Box::new(igrep::IgrepProvider::new(ctx).await?),
         "recent_files" => Box::new(recent_files::RecentFilesProvider::new(ctx)),
+        "smart_open" => Box::new(smart_open::SmartOpenProvider::new(ctx).await?),
         "tagfiles" => Box::new(tagfiles::TagfilesProvider::new()),
+        "todos" => Box::new(todos::TodosProvider::new(ctx)),
+        "undo" => Box::new(undo::UndoProvider::new(ctx)),
         _ => Box::new(generic_provider::GenericProvider::new()),
     };
     Ok(provider)
@@ -106,6 +121,14 @@ pub struct ProviderEnvironment {
     /// winwidth.
     pub display_line_width: usize,
     pub start_buffer_path: PathBuf,
+    /// Key bindings from the config, parsed at invoking the provider.
+    pub keymaps: HashMap<KeyEvent, ProviderAction>,
+    /// Key bindings of the arbitrary key notations, e.g., `<C-x><C-o>`, mapped in Vim.
+    pub custom_keymaps: HashMap<String, ProviderAction>,
+    /// Preset arguments of the provider alias, if the provider is invoked via an alias.
+    pub preset_args: Vec<String>,
+    /// Whether the session is resumed by `:Clap ++resume`.
+    pub resume: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -240,6 +263,22 @@ pub struct Context {
     pub input_recorder: InputRecorder,
     pub preview_manager: PreviewManager,
     pub provider_source: Arc<RwLock<ProviderSource>>,
+    /// Global config with the project-local config merged, if any.
+    pub project_config: Option<Arc<Config>>,
+    /// Whether the preview has been hidden by the `toggle-preview` action.
+    pub preview_hidden: bool,
+    /// Case matching overridden by the `cycle-case-matching` action.
+    pub case_matching: Option<CaseMatching>,
+    /// Whether to walk into the git submodules, overridden by the `toggle-submodules` action.
+    pub submodules: Option<bool>,
+    /// Whether `recent_files` is scoped to the project, overridden by the
+    /// `toggle-project-scope` action.
+    pub project_scoped: Option<bool>,
+    /// Query set by the provider via [`Context::set_query`], the `OnTyped` event it causes
+    /// is skipped.
+    pub query_echo: Arc<Mutex<Option<String>>>,
+    /// Entries multi-selected by `<Tab>`, in the order of selection.
+    pub selected_lines: Vec<String>,
 }
 
 impl Context {
@@ -255,6 +294,10 @@ impl Context {
             debounce: bool,
             no_cache: bool,
             start_buffer_path: PathBuf,
+            #[serde(default)]
+            alias: Option<String>,
+            #[serde(default)]
+            resume: bool,
         }
 
         let InnerParams {
@@ -267,8 +310,15 @@ impl Context {
             no_cache,
             start_buffer_path,
             icon,
+            alias,
+            resume,
         } = params.parse()?;
 
+        let start_buffer_path = match start_buffer_path.to_str() {
+            Some(path) => PathBuf::from(crate::paths::normalize_path(path).into_owned()),
+            None => start_buffer_path,
+        };
+
         let icon = match icon.to_lowercase().as_str() {
             "file" => Icon::Enabled(IconKind::File),
             "grep" => Icon::Enabled(IconKind::Grep),
@@ -277,13 +327,14 @@ impl Context {
             _ => Icon::Null,
         };
 
-        let rank_criteria = crate::config::config()
-            .matcher
-            .tiebreak
-            .split(',')
-            .filter_map(|s| types::parse_criteria(s.trim()))
-            .collect();
-        let matcher_builder = provider_id.matcher_builder().rank_criteria(rank_criteria);
+        let project_config = crate::config::load_project_config(cwd.as_ref()).map(Arc::new);
+        let config = project_config
+            .as_deref()
+            .unwrap_or_else(|| crate::config::config());
+
+        let matcher_builder = provider_id
+            .matcher_builder()
+            .rank_criteria(config.matcher.rank_criteria());
         let display_winwidth = vim.winwidth(display.winid).await?;
         // Sign column occupies 2 spaces.
         let display_line_width = display_winwidth - 2;
@@ -297,13 +348,32 @@ impl Context {
         let preview_enabled: usize = vim.bare_call("clap#preview#is_enabled").await?;
 
         let input_history = crate::datastore::INPUT_HISTORY_IN_MEMORY.lock();
-        let inputs = if crate::config::config().input_history.share_all_inputs {
+        let inputs = if config.input_history.share_all_inputs {
             input_history.all_inputs()
         } else {
             input_history.inputs(&provider_id)
         };
         let input_recorder = InputRecorder::new(inputs);
 
+        let mut keymaps = HashMap::new();
+        let mut custom_keymaps = HashMap::new();
+        for (key, action) in config.provider.keymaps(provider_id.as_str()) {
+            // The keys have been checked on loading the config.
+            match KeyEvent::from_name(key) {
+                Some(key_event) => {
+                    keymaps.insert(key_event, action);
+                }
+                None => {
+                    custom_keymaps.insert(key.to_string(), action);
+                }
+            }
+        }
+
+        let preset_args = alias
+            .and_then(|alias| config.provider.aliases.get(&alias))
+            .map(|provider_alias| provider_alias.args.clone())
+            .unwrap_or_default();
+
         let env = ProviderEnvironment {
             is_nvim: is_nvim == 1,
             has_nvim_09: has_nvim_09 == 1,
@@ -320,6 +390,10 @@ impl Context {
             display_line_width,
             matcher_builder,
             icon,
+            keymaps,
+            custom_keymaps,
+            preset_args,
+            resume,
         };
 
         Ok(Self {
@@ -331,19 +405,125 @@ impl Context {
             input_recorder,
             preview_manager: PreviewManager::new(),
             provider_source: Arc::new(RwLock::new(ProviderSource::Unactionable)),
+            project_config,
+            preview_hidden: false,
+            case_matching: None,
+            submodules: None,
+            project_scoped: None,
+            query_echo: Arc::new(Mutex::new(None)),
+            selected_lines: Vec::new(),
         })
     }
 
+    /// Returns the config in effect for this provider session.
+    pub fn config(&self) -> &Config {
+        self.project_config
+            .as_deref()
+            .unwrap_or_else(|| crate::config::config())
+    }
+
     pub fn provider_id(&self) -> &str {
         self.env.provider_id.as_str()
     }
 
+    /// Returns the style of displaying the file paths configured for this provider.
+    pub fn path_display(&self) -> Option<PathDisplay> {
+        self.config()
+            .provider
+            .path_display
+            .get(self.provider_id())
+            .and_then(|style| style.path_display(&self.cwd))
+    }
+
+    /// Returns the printer of the display window.
+    pub fn printer(&self) -> Printer {
+        Printer::new(self.env.display_winwidth, self.env.icon)
+            .with_path_display(self.path_display())
+    }
+
+    /// Appends a page of the results to the display window, see [`ClapProvider::on_load_more`].
+    pub fn append_page(&self, display_lines: printer::DisplayLines, has_more: bool) -> Result<()> {
+        let printer::DisplayLines {
+            lines,
+            indices,
+            truncated_map,
+            ..
+        } = display_lines;
+        self.vim.exec(
+            "clap#state#append_page",
+            json!([{
+                "lines": lines,
+                "indices": indices,
+                "truncated_map": truncated_map,
+                "has_more": has_more,
+            }]),
+        )
+    }
+
     pub fn matcher_builder(&self) -> MatcherBuilder {
-        self.env.matcher_builder.clone()
+        match self.case_matching {
+            Some(case_matching) => self
+                .env
+                .matcher_builder
+                .clone()
+                .case_matching(case_matching),
+            None => self.env.matcher_builder.clone(),
+        }
     }
 
     pub fn matcher(&self, query: impl Into<Query>) -> Matcher {
-        self.env.matcher_builder.clone().build(query.into())
+        self.matcher_builder().build(query.into())
+    }
+
+    /// Invokes the hook callbacks selected by `hook` with the provider id and `extra` fields.
+    pub fn run_hooks(
+        &self,
+        hook: impl Fn(&ProviderHooks) -> &Vec<String>,
+        extra: serde_json::Value,
+    ) {
+        let callbacks = hook(&self.config().provider.hooks);
+
+        if callbacks.is_empty() {
+            return;
+        }
+
+        let mut payload = json!({ "provider_id": self.provider_id() });
+        if let (Some(payload), serde_json::Value::Object(extra)) = (payload.as_object_mut(), extra)
+        {
+            payload.extend(extra);
+        }
+
+        for callback in callbacks {
+            if let Err(err) = self.vim.exec(callback, json!([payload])) {
+                tracing::debug!(?err, callback, "Failed to invoke the hook callback");
+            }
+        }
+    }
+
+    /// Notes the files of the accepted `entries` as the recent files visited from the picker.
+    pub fn note_accepted_files(&self, entries: &[String]) {
+        for entry in entries {
+            let Some(item) = quickfix::parse_quickfix_item(
+                self.provider_id(),
+                entry,
+                &self.cwd,
+                &self.env.start_buffer_path,
+            ) else {
+                continue;
+            };
+            let file_path = self.cwd.join(item.filename).display().to_string();
+            if let Err(err) = crate::stdio_server::handler::messages::note_recent_file(
+                file_path,
+                AccessKind::Picker,
+            ) {
+                tracing::debug!(?err, "Failed to note the accepted file");
+            }
+        }
+    }
+
+    /// Returns `true` if the preview is enabled and not hidden by the user.
+    pub fn preview_enabled(&self) -> bool {
+        self.env.preview_enabled && !self.preview_hidden
     }
 
     pub fn search_context(&self, stop_signal: Arc<AtomicBool>) -> SearchContext {
@@ -354,7 +534,41 @@ impl Context {
             vim: self.vim.clone(),
             stop_signal,
             item_pool_size: self.env.display_winheight,
+            walk_config: self.walk_config(),
+            priority_paths: Vec::new(),
+            path_display: self.path_display(),
+            grep_config: self.config().grep.clone(),
+        }
+    }
+
+    /// Returns the paths of the open buffers followed by the recent files, which are likely
+    /// the most relevant to the search.
+    pub async fn priority_paths(&self) -> Vec<PathBuf> {
+        let buffer_paths = self.vim.listed_buffer_paths().await.unwrap_or_else(|err| {
+            tracing::debug!(?err, "Failed to get the paths of the listed buffers");
+            Vec::new()
+        });
+        let recent_files = crate::datastore::RECENT_FILES_IN_MEMORY
+            .lock()
+            .recent_n_files(100);
+
+        let mut priority_paths = Vec::with_capacity(buffer_paths.len() + recent_files.len());
+        for path in buffer_paths.into_iter().chain(recent_files) {
+            let path = PathBuf::from(path);
+            if !priority_paths.contains(&path) {
+                priority_paths.push(path);
+            }
+        }
+        priority_paths
+    }
+
+    /// Returns the walk options of the provider with the runtime overrides applied.
+    pub fn walk_config(&self) -> WalkConfig {
+        let mut walk_config = self.config().walker.walk_config(Some(self.provider_id()));
+        if let Some(submodules) = self.submodules {
+            walk_config.submodules = submodules;
         }
+        walk_config
     }
 
     /// Executes the command `cmd` and returns the raw bytes of stdout.
@@ -380,7 +594,13 @@ impl Context {
     }
 
     pub async fn parse_provider_args<T: clap::Parser + Default + Debug>(&self) -> Result<T> {
-        let args = self.vim.provider_args().await?;
+        let args = self
+            .env
+            .preset_args
+            .iter()
+            .cloned()
+            .chain(self.vim.provider_args().await?)
+            .collect::<Vec<_>>();
 
         let provider_args = if args.is_empty() {
             T::default()
@@ -441,16 +661,72 @@ impl Context {
             self.env.provider_id.clone(),
             self.input_recorder.clone().into_inputs(),
         );
+        if let Err(err) = crate::datastore::store_input_history(&input_history) {
+            tracing::error!(?err, "Failed to store the input history");
+        }
         tracing::debug!(
             "ProviderSession {session_id:?}-{} terminated",
             self.provider_id()
         );
     }
 
-    pub async fn record_input(&mut self) -> Result<()> {
+    /// Records the current input and returns it.
+    pub async fn record_input(&mut self) -> Result<String> {
         let input = self.vim.input_get().await?;
-        self.input_recorder.try_record(input);
-        Ok(())
+        self.input_recorder.try_record(input.clone());
+        Ok(input)
+    }
+
+    /// Sets the query in the input window and moves the cursor to the character index `cursor`,
+    /// to the end of the query if `None`.
+    ///
+    /// Unlike the query typed by the user, `on_typed` is not invoked for the new query, the
+    /// provider is expected to update the results on its own.
+    pub fn set_query(&self, query: &str, cursor: Option<usize>) -> Result<()> {
+        self.query_echo.lock().replace(query.to_string());
+        let cursor = cursor.map(|c| c as i64).unwrap_or(-1);
+        self.vim
+            .exec("clap#state#set_query", json!([query, cursor]))
+    }
+
+    /// Maps the key notations bound in the keymaps config in the input window, the keys of
+    /// the previous session are unmapped.
+    pub fn define_custom_keys(&self) -> Result<()> {
+        let keys = self.env.custom_keymaps.keys().collect::<Vec<_>>();
+        self.vim.exec("clap#keymap#define", json!([keys]))
+    }
+
+    /// Returns `true` if `query` is the echo of the last [`Context::set_query`].
+    ///
+    /// The pending echo is cleared anyway as the echo can be merged with the following
+    /// keystrokes by the debounce, and no echo is sent in Vim.
+    pub fn is_query_echo(&self, query: &str) -> bool {
+        self.query_echo.lock().take().as_deref() == Some(query)
+    }
+
+    /// Records the entry multi-selected or unselected in the display window.
+    pub fn set_selected(&mut self, line: String, selected: bool) {
+        let existing = self.selected_lines.iter().position(|l| *l == line);
+        match (existing, selected) {
+            (None, true) => self.selected_lines.push(line),
+            (Some(idx), false) => {
+                self.selected_lines.remove(idx);
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the multi-selected entries if any, otherwise the current entry.
+    async fn selected_or_current_lines(&self) -> Result<Vec<String>> {
+        if !self.selected_lines.is_empty() {
+            return Ok(self.selected_lines.clone());
+        }
+        let curline = self.vim.display_getcurline().await?;
+        Ok(if curline.is_empty() {
+            Vec::new()
+        } else {
+            vec![curline]
+        })
     }
 
     pub async fn next_input(&mut self) -> Result<()> {
@@ -549,13 +825,196 @@ impl Context {
         Ok(())
     }
 
+    async fn toggle_preview(&mut self) -> Result<()> {
+        if !self.env.preview_enabled {
+            return Ok(());
+        }
+        self.preview_hidden = !self.preview_hidden;
+        if self.preview_hidden {
+            self.vim.bare_exec("preview_hide")
+        } else {
+            self.preview_manager.reset_scroll();
+            self.update_preview(None).await
+        }
+    }
+
+    fn cycle_case_matching(&mut self) -> Result<()> {
+        let next = match self.case_matching.unwrap_or_default() {
+            CaseMatching::Smart => CaseMatching::Ignore,
+            CaseMatching::Ignore => CaseMatching::Respect,
+            CaseMatching::Respect => CaseMatching::Smart,
+        };
+        self.case_matching.replace(next);
+        self.vim.echo_info(format!(
+            "case matching: {}",
+            format!("{next:?}").to_lowercase()
+        ))
+    }
+
+    fn toggle_submodules(&mut self) -> Result<()> {
+        let submodules = !self.walk_config().submodules;
+        self.submodules.replace(submodules);
+        self.vim.echo_info(if submodules {
+            "submodules: included"
+        } else {
+            "submodules: skipped"
+        })
+    }
+
+    /// Returns whether `recent_files` only lists the files under the project root.
+    pub fn recent_files_project_scoped(&self) -> bool {
+        self.project_scoped
+            .unwrap_or(self.config().provider.recent_files.project_scoped)
+    }
+
+    fn toggle_project_scope(&mut self) -> Result<()> {
+        let project_scoped = !self.recent_files_project_scoped();
+        self.project_scoped.replace(project_scoped);
+        self.vim.echo_info(if project_scoped {
+            "recent files: project"
+        } else {
+            "recent files: global"
+        })
+    }
+
+    /// Sends the multi-selected entries or all the matched entries to the quickfix list.
+    async fn send_to_quickfix(&self, provider: &dyn ClapProvider) -> Result<()> {
+        let mut lines = self.selected_lines.clone();
+        if lines.is_empty() {
+            lines = match provider.matched_lines() {
+                Some(matched_lines) => matched_lines,
+                None => self.vim.display_matched_lines().await?,
+            };
+        }
+        let quickfix_items = lines
+            .iter()
+            .filter_map(|line| {
+                quickfix::parse_quickfix_item(
+                    self.provider_id(),
+                    line,
+                    &self.cwd,
+                    &self.env.start_buffer_path,
+                )
+                .map(QuickfixItem::into_editor_item)
+            })
+            .collect::<Vec<_>>();
+
+        if quickfix_items.is_empty() {
+            return self.vim.echo_warn(format!(
+                "No file locations found in the results of {}",
+                self.provider_id()
+            ));
+        }
+
+        let title = format!(
+            "clap {}: {}",
+            self.provider_id(),
+            self.vim.input_get().await?
+        );
+        self.vim
+            .exec("clap#sink#send_to_quickfix", json!([quickfix_items, title]))
+    }
+
+    /// Returns the file locations of the selected `lines`, warns if there is none.
+    fn file_locations(&self, lines: &[String]) -> Result<Vec<QuickfixItem>> {
+        let file_locations = lines
+            .iter()
+            .filter_map(|line| {
+                quickfix::parse_quickfix_item(
+                    self.provider_id(),
+                    line,
+                    &self.cwd,
+                    &self.env.start_buffer_path,
+                )
+                .map(QuickfixItem::into_editor_item)
+            })
+            .collect::<Vec<_>>();
+
+        if file_locations.is_empty() {
+            self.vim.echo_warn(format!(
+                "No file locations found in the selected entries of {}",
+                self.provider_id()
+            ))?;
+        }
+
+        Ok(file_locations)
+    }
+
+    /// Opens the multi-selected entries or the current entry with `open_cmd`, e.g., `split`.
+    async fn open_selected(&self, open_cmd: &str) -> Result<()> {
+        let lines = self.selected_or_current_lines().await?;
+        let file_locations = self.file_locations(&lines)?;
+        if file_locations.is_empty() {
+            return Ok(());
+        }
+        self.note_accepted_files(&lines);
+        self.vim
+            .exec("clap#sink#open_all", json!([file_locations, open_cmd]))
+    }
+
+    /// Adds the files of the multi-selected entries or the current entry to the arglist.
+    async fn add_selected_to_arglist(&self) -> Result<()> {
+        let lines = self.selected_or_current_lines().await?;
+        let mut files = Vec::new();
+        for file_location in self.file_locations(&lines)? {
+            if !files.contains(&file_location.filename) {
+                files.push(file_location.filename);
+            }
+        }
+        if files.is_empty() {
+            return Ok(());
+        }
+        self.vim.exec("clap#sink#add_to_arglist", json!([files]))
+    }
+
+    /// Overlays the quick-select labels on the entries in view of the display window, the
+    /// labels are read and the labeled entry is accepted in Vim.
+    async fn start_quick_select(&self) -> Result<()> {
+        let (topline, botline): (usize, usize) = self
+            .vim
+            .bare_call("clap#quick_select#visible_range")
+            .await?;
+        if botline < topline {
+            return Ok(());
+        }
+
+        let labels = printer::quick_select_labels(botline - topline + 1, printer::LABEL_CHARS)
+            .into_iter()
+            .zip(topline..)
+            .map(|(label, lnum)| json!([lnum, label]))
+            .collect::<Vec<_>>();
+
+        self.vim.exec("clap#quick_select#start", json!([labels]))
+    }
+
+    /// Prompts for the log filter, the current one is the default input.
+    async fn set_log_level(&self) -> Result<()> {
+        let filter: String = self
+            .vim
+            .call(
+                "input",
+                json!(["Log filter: ", crate::log_filter::current()]),
+            )
+            .await?;
+        if filter.trim().is_empty() {
+            return Ok(());
+        }
+
+        match crate::log_filter::set_log_filter(&filter) {
+            Ok(()) => self
+                .vim
+                .echo_info(format!("Log filter: {}", crate::log_filter::current())),
+            Err(err) => self.vim.echo_warn(err.to_string()),
+        }
+    }
+
     pub async fn update_on_empty_query(&self) -> Result<()> {
         if let Some(items) = self
             .provider_source
             .read()
-            .try_skim(self.provider_id(), 100)
+            .try_skim(self.provider_id(), self.env.display_winheight)
         {
-            let printer = Printer::new(self.env.display_winwidth, self.env.icon);
+            let printer = self.printer();
             let printer::DisplayLines {
                 lines,
                 icon_added,
@@ -694,6 +1153,108 @@ impl ProviderSource {
     }
 }
 
+/// Handles the key event, the action bound to the key in the keymaps config takes precedence
+/// over the provider's own handling.
+pub async fn handle_key_event(
+    provider: &mut dyn ClapProvider,
+    ctx: &mut Context,
+    key_event: KeyEvent,
+) -> Result<()> {
+    let action = match ctx.env.keymaps.get(&key_event).copied() {
+        Some(action) => action,
+        None if key_event == KeyEvent::CtrlQ => ProviderAction::SendToQuickfix,
+        None => return provider.on_key_event(ctx, key_event).await,
+    };
+
+    handle_provider_action(provider, ctx, action).await
+}
+
+/// Handles the key notation bound in the keymaps config, e.g., `<C-x><C-o>`.
+pub async fn handle_custom_key(
+    provider: &mut dyn ClapProvider,
+    ctx: &mut Context,
+    key: &str,
+) -> Result<()> {
+    match ctx.env.custom_keymaps.get(key).copied() {
+        Some(action) => handle_provider_action(provider, ctx, action).await,
+        None => Err(anyhow!("No action is bound to {key}")),
+    }
+}
+
+async fn handle_provider_action(
+    provider: &mut dyn ClapProvider,
+    ctx: &mut Context,
+    action: ProviderAction,
+) -> Result<()> {
+    match action {
+        ProviderAction::ScrollPreviewUp => ctx.scroll_preview(Direction::Up).await,
+        ProviderAction::ScrollPreviewDown => ctx.scroll_preview(Direction::Down).await,
+        ProviderAction::NextInput => ctx.next_input().await,
+        ProviderAction::PreviousInput => ctx.previous_input().await,
+        ProviderAction::TogglePreview => ctx.toggle_preview().await,
+        ProviderAction::CycleCaseMatching => {
+            ctx.cycle_case_matching()?;
+            provider.on_typed(ctx).await
+        }
+        ProviderAction::SendToQuickfix => ctx.send_to_quickfix(provider).await,
+        ProviderAction::RestartCommand => provider.restart_command(ctx).await,
+        ProviderAction::ToggleSubmodules => {
+            ctx.toggle_submodules()?;
+            provider.on_typed(ctx).await
+        }
+        ProviderAction::ToggleProjectScope => {
+            ctx.toggle_project_scope()?;
+            provider.on_typed(ctx).await
+        }
+        ProviderAction::OpenInSplits => ctx.open_selected("split").await,
+        ProviderAction::OpenInVsplits => ctx.open_selected("vsplit").await,
+        ProviderAction::OpenInTabs => ctx.open_selected("tab split").await,
+        ProviderAction::AddToArglist => ctx.add_selected_to_arglist().await,
+        ProviderAction::QuickSelect => ctx.start_quick_select().await,
+        ProviderAction::SetLogLevel => ctx.set_log_level().await,
+    }
+}
+
+/// Handles the mouse event, clicking an entry selects it, double-clicking accepts it and the
+/// wheel scrolls the preview or the results under the mouse.
+pub async fn handle_mouse_event(
+    provider: &mut dyn ClapProvider,
+    ctx: &mut Context,
+    mouse_event: MouseEvent,
+) -> Result<()> {
+    let display_winid = ctx.env.display.winid;
+
+    match mouse_event {
+        MouseEvent::Click { winid, lnum } if winid == display_winid => {
+            ctx.vim.exec("clap#mouse#select_line", json!([lnum]))?;
+            provider.on_move(ctx).await
+        }
+        MouseEvent::DoubleClick { winid, lnum } if winid == display_winid => {
+            ctx.vim.exec("clap#mouse#accept_line", json!([lnum]))
+        }
+        MouseEvent::WheelUp { winid } | MouseEvent::WheelDown { winid } => {
+            let (direction, linewise_direction) = match mouse_event {
+                MouseEvent::WheelUp { .. } => (Direction::Up, "up"),
+                _ => (Direction::Down, "down"),
+            };
+            // The preview window may not exist, e.g., the preview is disabled.
+            let in_preview = ctx.preview_enabled() && {
+                let preview_winid: i64 = ctx.vim.eval("get(g:clap.preview, 'winid', -1)").await?;
+                i64::try_from(winid).map_or(false, |winid| winid == preview_winid)
+            };
+            if in_preview {
+                ctx.scroll_preview(direction).await
+            } else {
+                ctx.vim.exec(
+                    "clap#navigation#linewise_scroll",
+                    json!([linewise_direction]),
+                )
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
 /// A trait each Clap provider must implement.
 #[async_trait::async_trait]
 pub trait ClapProvider: Debug + Send + Sync + 'static {
@@ -702,7 +1263,7 @@ pub trait ClapProvider: Debug + Send + Sync + 'static {
     }
 
     async fn on_move(&mut self, ctx: &mut Context) -> Result<()> {
-        if !ctx.env.preview_enabled {
+        if !ctx.preview_enabled() {
             return Ok(());
         }
         ctx.preview_manager.reset_scroll();
@@ -711,6 +1272,19 @@ pub trait ClapProvider: Debug + Send + Sync + 'static {
 
     async fn on_typed(&mut self, ctx: &mut Context) -> Result<()>;
 
+    /// Sends the next page of the results once the bottom of the display window is reached.
+    async fn on_load_more(&mut self, _ctx: &mut Context) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns all the matched entries of the session, including those not yet displayed.
+    ///
+    /// `None` if the provider does not retain the results, the lines in the display window are
+    /// used instead.
+    fn matched_lines(&self) -> Option<Vec<String>> {
+        None
+    }
+
     /// On receiving the Terminate event.
     ///
     /// Sets the running signal to false, in case of the forerunner thread is still working.
@@ -718,12 +1292,32 @@ pub trait ClapProvider: Debug + Send + Sync + 'static {
         ctx.signify_terminated(session_id);
     }
 
+    /// Reloads the source changed by the actions of the provider in Vim, e.g., deleting the
+    /// marks, the results are refreshed with the current query.
+    async fn reload_source(&mut self, ctx: &mut Context) -> Result<()> {
+        let provider_source = initialize_provider_source(ctx).await?;
+        if let Some(total) = provider_source.total() {
+            ctx.vim.set_var("g:clap.display.initial_size", total)?;
+        }
+        ctx.selected_lines.clear();
+        ctx.set_provider_source(provider_source);
+        self.on_typed(ctx).await
+    }
+
+    /// Kills the source command if it's still running and runs it again.
+    ///
+    /// Only the providers whose source is a shell command can be restarted.
+    async fn restart_command(&mut self, _ctx: &mut Context) -> Result<()> {
+        Ok(())
+    }
+
     async fn on_key_event(&mut self, ctx: &mut Context, key_event: KeyEvent) -> Result<()> {
         match key_event {
             KeyEvent::ShiftUp => ctx.scroll_preview(Direction::Up).await?,
             KeyEvent::ShiftDown => ctx.scroll_preview(Direction::Down).await?,
             KeyEvent::CtrlN => ctx.next_input().await?,
             KeyEvent::CtrlP => ctx.previous_input().await?,
+            KeyEvent::CtrlR => self.restart_command(ctx).await?,
             _ => {}
         }
         Ok(())
@@ -0,0 +1,130 @@
+//! Conversion of the provider results to the quickfix items.
+
+use crate::paths::expand_tilde;
+use pattern::*;
+use serde::Serialize;
+use std::path::Path;
+
+/// Item accepted by `setqflist()`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QuickfixItem {
+    pub filename: String,
+    pub lnum: usize,
+    pub col: usize,
+    pub text: String,
+}
+
+impl QuickfixItem {
+    fn new(path: &Path, lnum: usize, col: usize, text: impl Into<String>) -> Self {
+        Self {
+            filename: path.display().to_string(),
+            lnum,
+            col,
+            text: text.into(),
+        }
+    }
+
+    /// Returns the item with the file name as the editor sees it, see [`PathMap`].
+    ///
+    /// [`PathMap`]: crate::paths::PathMap
+    pub fn into_editor_item(self) -> Self {
+        let filename = crate::paths::to_editor_path(&self.filename).into_owned();
+        Self { filename, ..self }
+    }
+}
+
+/// Parses the quickfix item from a line of the results of provider `provider_id`.
+///
+/// Returns `None` if no file location can be found in the line.
+pub fn parse_quickfix_item(
+    provider_id: &str,
+    line: &str,
+    cwd: &Path,
+    start_buffer_path: &Path,
+) -> Option<QuickfixItem> {
+    let from_grep_line = |grep_line: &str| {
+        let (fpath, lnum, col, text) = extract_grep_position(grep_line)?;
+        let fpath = fpath.strip_prefix("./").unwrap_or(fpath);
+        Some(QuickfixItem::new(&cwd.join(fpath), lnum, col, text))
+    };
+
+    match provider_id {
+        "files" | "git_files" | "smart_open" => Some(QuickfixItem::new(&cwd.join(line), 1, 1, "")),
+        "recent_files" => Some(QuickfixItem::new(Path::new(line), 1, 1, "")),
+        "history" => {
+            let path = if line.starts_with('~') {
+                expand_tilde(line)
+            } else {
+                cwd.join(line)
+            };
+            Some(QuickfixItem::new(&path, 1, 1, ""))
+        }
+        // The todos line is `[TAG] [severity] [@author age] ` followed by the grep line.
+        "todos" => from_grep_line(line.splitn(4, "] ").nth(3)?),
+        "dumb_jump" => {
+            let (_def_kind, fpath, lnum, col) = extract_jump_line_info(line)?;
+            Some(QuickfixItem::new(&cwd.join(fpath), lnum, col, line))
+        }
+        "blines" => {
+            let lnum = extract_blines_lnum(line)?;
+            let text = line.trim_start().split_once(char::is_whitespace)?.1;
+            Some(QuickfixItem::new(start_buffer_path, lnum, 1, text))
+        }
+        "tags" => {
+            let lnum = extract_buf_tags_lnum(line)?;
+            Some(QuickfixItem::new(start_buffer_path, lnum, 1, line))
+        }
+        "proj_tags" => {
+            let (lnum, fpath) = extract_proj_tags(line)?;
+            Some(QuickfixItem::new(&cwd.join(fpath), lnum, 1, line))
+        }
+        _ => from_grep_line(line).or_else(|| {
+            // Any other provider listing the files.
+            let path = cwd.join(line);
+            path.is_file().then(|| QuickfixItem::new(&path, 1, 1, ""))
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_quickfix_item() {
+        let cwd = Path::new("/tmp/project");
+        let start_buffer_path = Path::new("/tmp/project/src/lib.rs");
+
+        assert_eq!(
+            parse_quickfix_item(
+                "grep",
+                "./src/main.rs:3:5:fn main() {",
+                cwd,
+                start_buffer_path
+            ),
+            Some(QuickfixItem::new(
+                Path::new("/tmp/project/src/main.rs"),
+                3,
+                5,
+                "fn main() {"
+            ))
+        );
+        assert_eq!(
+            parse_quickfix_item("blines", " 12 let foo = 1;", cwd, start_buffer_path),
+            Some(QuickfixItem::new(start_buffer_path, 12, 1, "let foo = 1;"))
+        );
+        assert_eq!(
+            parse_quickfix_item("files", "Cargo.toml", cwd, start_buffer_path),
+            Some(QuickfixItem::new(
+                Path::new("/tmp/project/Cargo.toml"),
+                1,
+                1,
+                ""
+            ))
+        );
+        assert_eq!(
+            parse_quickfix_item("commits", "abc1234 Fix typo", cwd, start_buffer_path),
+            None
+        );
+    }
+}
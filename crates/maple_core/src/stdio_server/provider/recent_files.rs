@@ -1,18 +1,82 @@
 use crate::datastore::RECENT_FILES_IN_MEMORY;
 use crate::paths::AbsPathBuf;
 use crate::stdio_server::handler::CachedPreviewImpl;
-use crate::stdio_server::provider::{ClapProvider, Context};
+use crate::stdio_server::input::KeyEvent;
+use crate::stdio_server::provider::{ClapProvider, Context, Direction};
 use anyhow::Result;
 use parking_lot::Mutex;
 use printer::Printer;
 use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use types::{ClapItem, MatchedItem, RankCalculator, Score};
 
+/// Removes the items whose files no longer exist until the `count` items from `start` all
+/// exist.
+///
+/// The items beyond are not checked to avoid a stat of every entry, they are pruned once
+/// paged into view. `cwd` is joined to the items relative to it.
+fn prune_missing_in_view(ranked: &mut Vec<MatchedItem>, start: usize, count: usize, cwd: &str) {
+    let cwd = Path::new(cwd);
+    let mut checked = start;
+    while checked < (start + count).min(ranked.len()) {
+        if cwd.join(ranked[checked].item.raw_text()).is_file() {
+            checked += 1;
+        } else {
+            ranked.remove(checked);
+        }
+    }
+}
+
+/// Strips the `cwd` prefix from the displayed paths, the paths outside of `cwd` are displayed
+/// as the editor sees them. The empty highlights of the empty query are dropped.
+fn relative_display_lines(
+    lines: Vec<String>,
+    mut indices: Vec<Vec<usize>>,
+    cwd: &str,
+) -> (Vec<String>, Vec<Vec<usize>>) {
+    let cwd_prefix = format!("{cwd}{}", std::path::MAIN_SEPARATOR);
+
+    let lines = lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, abs_path)| {
+            if let Some(relative_path) = abs_path.strip_prefix(&cwd_prefix) {
+                return relative_path.to_string();
+            }
+            match crate::paths::map_maple_path(&abs_path) {
+                Some((editor_path, maple_prefix_len, editor_prefix_len)) => {
+                    if let Some(line_indices) = indices.get_mut(i) {
+                        line_indices.retain(|&index| index >= maple_prefix_len);
+                        for index in line_indices.iter_mut() {
+                            *index = *index - maple_prefix_len + editor_prefix_len;
+                        }
+                    }
+                    editor_path
+                }
+                None => abs_path,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // The indices are empty on the empty query.
+    let indices = indices
+        .into_iter()
+        .filter(|i| !i.is_empty())
+        .collect::<Vec<_>>();
+
+    (lines, indices)
+}
+
 #[derive(Debug, Clone)]
 pub struct RecentFilesProvider {
     printer: Printer,
+    /// Number of the lines visible in the display window.
+    visible: usize,
     lines: Arc<Mutex<Vec<MatchedItem>>>,
+    /// Number of the lines sent to the display window.
+    displayed: Arc<AtomicUsize>,
 }
 
 impl RecentFilesProvider {
@@ -22,10 +86,13 @@ impl RecentFilesProvider {
         } else {
             icon::Icon::Null
         };
-        let printer = Printer::new(ctx.env.display_winwidth, icon);
+        let printer =
+            Printer::new(ctx.env.display_winwidth, icon).with_path_display(ctx.path_display());
         Self {
             printer,
+            visible: ctx.env.display_winheight,
             lines: Default::default(),
+            displayed: Default::default(),
         }
     }
 
@@ -35,24 +102,23 @@ impl RecentFilesProvider {
         query: String,
         preview_size: Option<usize>,
         lnum: usize,
+        project_scoped: bool,
     ) -> Result<Value> {
         let cwd = cwd.to_string();
 
         let mut recent_files = RECENT_FILES_IN_MEMORY.lock();
-        let ranked = if query.is_empty() {
+        let mut ranked = if query.is_empty() {
             // Sort the initial list according to the cwd.
             //
             // This changes the order of existing recent file entries.
             recent_files.sort_by_cwd(&cwd);
 
-            let mut cwd = cwd.clone();
-            cwd.push(std::path::MAIN_SEPARATOR);
+            let cwd_prefix = format!("{cwd}{}", std::path::MAIN_SEPARATOR);
 
             let rank_calculator = RankCalculator::default();
 
             recent_files
-                .entries
-                .iter()
+                .scoped_entries(&cwd, project_scoped)
                 .map(|entry| {
                     let item: Arc<dyn ClapItem> = Arc::new(entry.fpath.clone());
                     // frecent_score will not be larger than i32::MAX.
@@ -61,19 +127,21 @@ impl RecentFilesProvider {
                     let mut matched_item = MatchedItem::new(item, rank, Default::default());
                     matched_item
                         .output_text
-                        .replace(entry.fpath.replacen(&cwd, "", 1));
+                        .replace(entry.fpath.replacen(&cwd_prefix, "", 1));
                     matched_item
                 })
                 .collect::<Vec<_>>()
         } else {
-            recent_files.filter_on_query(&query, cwd.clone())
+            recent_files.filter_on_query(&query, cwd.clone(), project_scoped)
         };
 
-        let processed = recent_files.len();
-        let matched = ranked.len();
+        let processed = recent_files.scoped_entries(&cwd, project_scoped).count();
 
         drop(recent_files);
 
+        prune_missing_in_view(&mut ranked, 0, self.visible, &cwd);
+        let matched = ranked.len();
+
         // process the new preview
         let preview = match (preview_size, ranked.get(lnum - 1)) {
             (Some(size), Some(new_entry)) => {
@@ -94,23 +162,10 @@ impl RecentFilesProvider {
             indices,
             truncated_map,
             icon_added,
-        } = self
-            .printer
-            .to_display_lines(ranked.iter().take(200).cloned().collect());
+        } = self.printer.to_visible_display_lines(&ranked, self.visible);
 
-        let mut cwd = cwd;
-        cwd.push(std::path::MAIN_SEPARATOR);
-
-        let lines = lines
-            .into_iter()
-            .map(|abs_path| abs_path.replacen(&cwd, "", 1))
-            .collect::<Vec<_>>();
-
-        // The indices are empty on the empty query.
-        let indices = indices
-            .into_iter()
-            .filter(|i| !i.is_empty())
-            .collect::<Vec<_>>();
+        let (lines, indices) = relative_display_lines(lines, indices, &cwd);
+        let displayed = lines.len();
 
         let mut value = json!({
             "lines": lines,
@@ -119,6 +174,7 @@ impl RecentFilesProvider {
             "processed": processed,
             "icon_added": icon_added,
             "preview": preview,
+            "has_more": matched > displayed,
         });
 
         if !truncated_map.is_empty() {
@@ -130,6 +186,7 @@ impl RecentFilesProvider {
 
         let mut lines = self.lines.lock();
         *lines = ranked;
+        self.displayed.store(displayed, Ordering::SeqCst);
 
         Ok(value)
     }
@@ -141,13 +198,16 @@ impl ClapProvider for RecentFilesProvider {
         let query = ctx.vim.context_query_or_input().await?;
         let cwd = ctx.vim.working_dir().await?;
 
-        let preview_size = if ctx.env.preview_enabled {
+        let preview_size = if ctx.preview_enabled() {
             Some(ctx.preview_size().await?)
         } else {
             None
         };
 
-        let response = self.clone().process_query(cwd, query, preview_size, 1)?;
+        let project_scoped = ctx.recent_files_project_scoped();
+        let response = self
+            .clone()
+            .process_query(cwd, query, preview_size, 1, project_scoped)?;
 
         ctx.vim
             .exec("clap#state#process_response_on_typed", response)?;
@@ -185,14 +245,15 @@ impl ClapProvider for RecentFilesProvider {
             let recent_files = self.clone();
 
             let cwd = ctx.cwd.clone();
-            let preview_size = if ctx.env.preview_enabled {
+            let preview_size = if ctx.preview_enabled() {
                 Some(ctx.preview_size().await?)
             } else {
                 None
             };
             let lnum = ctx.vim.display_getcurlnum().await?;
+            let project_scoped = ctx.recent_files_project_scoped();
 
-            move || recent_files.process_query(cwd, query, preview_size, lnum)
+            move || recent_files.process_query(cwd, query, preview_size, lnum, project_scoped)
         })
         .await??;
 
@@ -204,4 +265,111 @@ impl ClapProvider for RecentFilesProvider {
 
         Ok(())
     }
+
+    fn matched_lines(&self) -> Option<Vec<String>> {
+        Some(
+            self.lines
+                .lock()
+                .iter()
+                .map(|matched_item| matched_item.item.raw_text().to_string())
+                .collect(),
+        )
+    }
+
+    async fn on_key_event(&mut self, ctx: &mut Context, key_event: KeyEvent) -> Result<()> {
+        match key_event {
+            KeyEvent::ShiftUp => ctx.scroll_preview(Direction::Up).await,
+            KeyEvent::ShiftDown => ctx.scroll_preview(Direction::Down).await,
+            KeyEvent::CtrlN => ctx.next_input().await,
+            KeyEvent::CtrlP => ctx.previous_input().await,
+            KeyEvent::CtrlR => {
+                ctx.toggle_project_scope()?;
+                self.on_typed(ctx).await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn on_load_more(&mut self, ctx: &mut Context) -> Result<()> {
+        let cwd = ctx.cwd.to_string();
+
+        let (display_lines, has_more) = {
+            let mut ranked = self.lines.lock();
+            let displayed = self.displayed.load(Ordering::SeqCst);
+            if displayed >= ranked.len() {
+                return Ok(());
+            }
+
+            prune_missing_in_view(&mut ranked, displayed, self.visible, &cwd);
+
+            let mut display_lines =
+                self.printer
+                    .to_page_display_lines(&ranked, displayed, self.visible);
+            let (lines, indices) = relative_display_lines(
+                std::mem::take(&mut display_lines.lines),
+                std::mem::take(&mut display_lines.indices),
+                &cwd,
+            );
+            display_lines.lines = lines;
+            display_lines.indices = indices;
+
+            let displayed = displayed + display_lines.lines.len();
+            self.displayed.store(displayed, Ordering::SeqCst);
+
+            (display_lines, ranked.len() > displayed)
+        };
+
+        ctx.append_page(display_lines, has_more)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_missing_in_view() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        std::fs::write(dir.join("a.txt"), "").unwrap();
+        std::fs::write(dir.join("c.txt"), "").unwrap();
+        std::fs::write(dir.join("d.txt"), "").unwrap();
+
+        let mut ranked = [
+            "a.txt",
+            "missing.txt",
+            "c.txt",
+            "missing_beyond_view.txt",
+            "d.txt",
+            "missing_on_next_page.txt",
+        ]
+        .into_iter()
+        .map(|name| {
+            let item: Arc<dyn ClapItem> = Arc::new(name.to_string());
+            MatchedItem::new(item, Default::default(), Default::default())
+        })
+        .collect::<Vec<_>>();
+        let raw_texts = |ranked: &[MatchedItem]| {
+            ranked
+                .iter()
+                .map(|matched_item| matched_item.item.raw_text().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        prune_missing_in_view(&mut ranked, 0, 2, &dir.display().to_string());
+        assert_eq!(
+            raw_texts(&ranked),
+            vec![
+                "a.txt",
+                "c.txt",
+                "missing_beyond_view.txt",
+                "d.txt",
+                "missing_on_next_page.txt"
+            ]
+        );
+
+        // The next page.
+        prune_missing_in_view(&mut ranked, 2, 2, &dir.display().to_string());
+        assert_eq!(raw_texts(&ranked), vec!["a.txt", "c.txt", "d.txt"]);
+    }
 }
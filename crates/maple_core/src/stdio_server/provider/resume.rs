@@ -0,0 +1,80 @@
+//! Snapshot of the last provider session, restored by `:Clap ++resume`.
+
+use super::{Context, ProviderId};
+use anyhow::Result;
+use printer::LinesTruncatedMap;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// State of the provider session taken by Vim right before exiting.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub provider_id: ProviderId,
+    /// Arguments the provider was invoked with.
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub query: String,
+    /// Lines in the display window, i.e., the matched results rendered.
+    pub lines: Vec<String>,
+    /// Line number of the selected entry.
+    pub curlnum: usize,
+    /// First line in view of the display window.
+    pub topline: usize,
+    /// Original lines of the truncated ones in `lines`.
+    #[serde(default)]
+    pub truncated_map: LinesTruncatedMap,
+    /// Whether the icons in `lines` are added by maple.
+    #[serde(default)]
+    pub icon_added: bool,
+}
+
+impl Context {
+    /// Restores the query, the results and the scroll position of the last session.
+    ///
+    /// The query is set without refiltering, the results are refreshed once the query is
+    /// changed.
+    pub async fn restore_last_session(&self) -> Result<()> {
+        let Some(snapshot) = crate::datastore::load_last_session() else {
+            return Ok(());
+        };
+
+        if snapshot.provider_id != self.env.provider_id {
+            return Ok(());
+        }
+
+        self.set_query(&snapshot.query, None)?;
+        self.vim.exec(
+            "clap#state#restore_session",
+            json!([
+                snapshot.lines,
+                snapshot.curlnum,
+                snapshot.topline,
+                snapshot.truncated_map,
+                snapshot.icon_added
+            ]),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_snapshot_from_vim() {
+        let snapshot: SessionSnapshot = serde_json::from_str(
+            r#"{"provider_id":"files","query":"lib","lines":["src/lib.rs"],"curlnum":1,"topline":1}"#,
+        )
+        .unwrap();
+        assert_eq!(snapshot.provider_id, ProviderId::from("files"));
+        assert!(snapshot.args.is_empty());
+        assert!(snapshot.truncated_map.is_empty());
+
+        let snapshot: SessionSnapshot = serde_json::from_str(
+            r#"{"provider_id":"grep","query":"fn","lines":["src/lib.rs:1:1:..fn"],"curlnum":1,"topline":1,"truncated_map":{"1":"src/lib.rs:1:1:pub fn"},"icon_added":true}"#,
+        )
+        .unwrap();
+        assert_eq!(snapshot.truncated_map[&1], "src/lib.rs:1:1:pub fn");
+        assert!(snapshot.icon_added);
+    }
+}
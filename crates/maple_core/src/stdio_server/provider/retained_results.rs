@@ -0,0 +1,370 @@
+//! Matched items retained for paging the results on scroll.
+//!
+//! Only the top ranked items up to a cap are kept in memory, the rest are spilled to a temp file
+//! as they are consumed and read back page by page, so that a pathological query matching
+//! millions of items does not blow up the memory usage. The matched items of a large source are
+//! spilled by [`SpillCollector`] while the parallel filter is still running.
+//!
+//! The spilling does blocking I/O, it must not be done on the async runtime directly.
+
+use filter::MatchSink;
+use parking_lot::Mutex;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::sync::Arc;
+use tempfile::TempPath;
+use types::{ClapItem, MatchScope, MatchedItem, Rank};
+
+/// Writes the spilled items in the form of `[raw_text, indices]` per line, `raw_text` is null
+/// if the item itself is kept in memory.
+#[derive(Debug)]
+struct SpillWriter {
+    path: TempPath,
+    writer: BufWriter<File>,
+    offset: u64,
+}
+
+impl SpillWriter {
+    /// Creates a spill file only accessible to the user, which is removed once its path is
+    /// dropped.
+    fn create() -> std::io::Result<Self> {
+        let (file, path) = tempfile::Builder::new()
+            .prefix("clap_spill_")
+            .tempfile()?
+            .into_parts();
+        let writer = BufWriter::new(file);
+        Ok(Self {
+            path,
+            writer,
+            offset: 0,
+        })
+    }
+
+    /// Writes an item and returns its byte offset.
+    fn write(&mut self, raw_text: Option<&str>, indices: &[usize]) -> std::io::Result<u64> {
+        let mut line = serde_json::to_vec(&(raw_text, indices))?;
+        line.push(b'\n');
+        self.writer.write_all(&line)?;
+        let offset = self.offset;
+        self.offset += line.len() as u64;
+        Ok(offset)
+    }
+
+    fn finish(mut self) -> std::io::Result<TempPath> {
+        self.writer.flush()?;
+        Ok(self.path)
+    }
+}
+
+/// How the spilled items are restored.
+#[derive(Debug)]
+enum SpilledItems {
+    /// Items of an in-memory source in rank order, only their indices are spilled.
+    InMemory(Vec<Arc<dyn ClapItem>>),
+    /// Items of a large source, rebuilt from the spilled raw text in the same way as the
+    /// parallel filter creates them.
+    Rebuilt(MatchScope),
+}
+
+#[derive(Debug)]
+struct SpillFile {
+    path: TempPath,
+    /// Byte offset of each spilled item in rank order.
+    offsets: Vec<u64>,
+    items: SpilledItems,
+}
+
+impl SpillFile {
+    fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Reads `count` items starting from `start`.
+    fn read(&self, start: usize, count: usize) -> std::io::Result<Vec<MatchedItem>> {
+        let end = (start + count).min(self.len());
+        if start >= end {
+            return Ok(Vec::new());
+        }
+
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        let mut line = String::new();
+        let mut page = Vec::with_capacity(end - start);
+
+        for index in start..end {
+            reader.seek(SeekFrom::Start(self.offsets[index]))?;
+            line.clear();
+            reader.read_line(&mut line)?;
+
+            let (raw_text, indices): (Option<String>, Vec<usize>) = serde_json::from_str(&line)?;
+            let maybe_item = match &self.items {
+                SpilledItems::InMemory(items) => Some(items[index].clone()),
+                SpilledItems::Rebuilt(match_scope) => {
+                    raw_text.and_then(|raw_text| filter::to_clap_item(*match_scope, raw_text))
+                }
+            };
+            if let Some(item) = maybe_item {
+                page.push(MatchedItem::new(item, Rank::default(), indices));
+            }
+        }
+
+        Ok(page)
+    }
+}
+
+/// Ranked matched items of the current query.
+#[derive(Debug, Default)]
+pub struct RetainedResults {
+    /// Top ranked items kept in memory.
+    items: Vec<MatchedItem>,
+    /// Items beyond the cap.
+    spilled: Option<SpillFile>,
+}
+
+impl RetainedResults {
+    /// Retains the ranked `matched_items`, the items beyond `cap` are spilled to the disk and
+    /// dropped one by one as they are consumed.
+    pub fn new(matched_items: impl IntoIterator<Item = MatchedItem>, cap: usize) -> Self {
+        let mut matched_items = matched_items.into_iter();
+        let items = matched_items.by_ref().take(cap).collect::<Vec<_>>();
+
+        let spilled = match spill_in_memory_items(matched_items) {
+            Ok(spilled) => spilled,
+            Err(err) => {
+                tracing::error!(?err, "Failed to spill the matched items");
+                None
+            }
+        };
+
+        Self { items, spilled }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len() + self.spilled.as_ref().map(|s| s.len()).unwrap_or(0)
+    }
+
+    /// Returns `count` items starting from `start`.
+    pub fn page(&self, start: usize, count: usize) -> Vec<MatchedItem> {
+        let mut page = self
+            .items
+            .iter()
+            .skip(start)
+            .take(count)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if page.len() < count {
+            if let Some(spilled) = &self.spilled {
+                let spilled_start = (start + page.len()).saturating_sub(self.items.len());
+                match spilled.read(spilled_start, count - page.len()) {
+                    Ok(items) => page.extend(items),
+                    Err(err) => tracing::error!(?err, "Failed to read the spilled items"),
+                }
+            }
+        }
+
+        page
+    }
+}
+
+/// Spills the rest of the ranked items of an in-memory source.
+fn spill_in_memory_items(
+    rest: impl Iterator<Item = MatchedItem>,
+) -> std::io::Result<Option<SpillFile>> {
+    let mut rest = rest.peekable();
+    if rest.peek().is_none() {
+        return Ok(None);
+    }
+
+    let mut writer = SpillWriter::create()?;
+    let mut offsets = Vec::new();
+    let mut items = Vec::new();
+
+    for matched_item in rest {
+        offsets.push(writer.write(None, &matched_item.indices)?);
+        items.push(matched_item.item);
+    }
+
+    Ok(Some(SpillFile {
+        path: writer.finish()?,
+        offsets,
+        items: SpilledItems::InMemory(items),
+    }))
+}
+
+#[derive(Debug, Default)]
+struct CollectorState {
+    writer: Option<SpillWriter>,
+    /// Rank and byte offset of each spilled item.
+    ranked_offsets: Vec<(Rank, u64)>,
+    error: Option<std::io::Error>,
+}
+
+/// Spills all the matched items of a large source as the parallel filter streams them, the
+/// spilled items are ranked once the filtering is done.
+#[derive(Debug)]
+pub struct SpillCollector {
+    match_scope: MatchScope,
+    state: Mutex<CollectorState>,
+}
+
+impl SpillCollector {
+    pub fn new(match_scope: MatchScope) -> Self {
+        Self {
+            match_scope,
+            state: Mutex::new(CollectorState::default()),
+        }
+    }
+
+    /// Ranks the spilled items, returns `None` if nothing was matched or the spilling failed.
+    pub fn finish(self) -> Option<RetainedResults> {
+        let CollectorState {
+            writer,
+            mut ranked_offsets,
+            error,
+        } = self.state.into_inner();
+
+        if let Some(err) = error {
+            tracing::error!(?err, "Failed to spill the matched items");
+            return None;
+        }
+
+        let path = match writer?.finish() {
+            Ok(path) => path,
+            Err(err) => {
+                tracing::error!(?err, "Failed to spill the matched items");
+                return None;
+            }
+        };
+
+        // Same order as the best items, the earlier item comes first on a tie.
+        ranked_offsets.sort_unstable_by(|(rank1, offset1), (rank2, offset2)| {
+            rank2.cmp(rank1).then(offset1.cmp(offset2))
+        });
+
+        Some(RetainedResults {
+            items: Vec::new(),
+            spilled: Some(SpillFile {
+                path,
+                offsets: ranked_offsets
+                    .into_iter()
+                    .map(|(_rank, offset)| offset)
+                    .collect(),
+                items: SpilledItems::Rebuilt(self.match_scope),
+            }),
+        })
+    }
+}
+
+impl MatchSink for SpillCollector {
+    fn on_match(&self, matched_item: &MatchedItem) {
+        let mut state = self.state.lock();
+        let CollectorState {
+            writer,
+            ranked_offsets,
+            error,
+        } = &mut *state;
+
+        if error.is_some() {
+            return;
+        }
+
+        if writer.is_none() {
+            match SpillWriter::create() {
+                Ok(new_writer) => {
+                    writer.replace(new_writer);
+                }
+                Err(err) => {
+                    error.replace(err);
+                    return;
+                }
+            }
+        }
+        let Some(writer) = writer.as_mut() else {
+            return;
+        };
+
+        match writer.write(Some(matched_item.item.raw_text()), &matched_item.indices) {
+            Ok(offset) => ranked_offsets.push((matched_item.rank, offset)),
+            Err(err) => {
+                error.replace(err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::GrepItem;
+
+    #[test]
+    fn test_page_spilled_items() {
+        let matched_items = (0..3000)
+            .map(|i| MatchedItem::new(Arc::new(format!("item-{i}")), Rank::default(), vec![i % 7]))
+            .collect::<Vec<_>>();
+
+        let results = RetainedResults::new(matched_items, 100);
+        assert_eq!(results.len(), 3000);
+        assert_eq!(results.items.len(), 100);
+
+        let page = results.page(95, 10);
+        let texts = page
+            .iter()
+            .map(|item| item.item.output_text().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            (95..105).map(|i| format!("item-{i}")).collect::<Vec<_>>()
+        );
+
+        let page = results.page(2500, 600);
+        assert_eq!(page.len(), 500);
+        assert_eq!(page[0].item.output_text(), "item-2500");
+        assert_eq!(page[0].indices, vec![2500 % 7]);
+    }
+
+    #[test]
+    fn test_spilled_items_keep_type() {
+        let matched_items = (0..10)
+            .map(|i| {
+                let item: Arc<dyn ClapItem> = Arc::new(types::SourceItem::from(format!("{i}")));
+                MatchedItem::new(item, Rank::default(), vec![0])
+            })
+            .collect::<Vec<_>>();
+        let spilled_item = matched_items[5].item.clone();
+
+        let results = RetainedResults::new(matched_items, 3);
+        let page = results.page(5, 1);
+        assert!(Arc::ptr_eq(&page[0].item, &spilled_item));
+    }
+
+    #[test]
+    fn test_spill_collector() {
+        let collector = SpillCollector::new(MatchScope::GrepLine);
+        for (line, score) in [
+            ("src/a.rs:1:1:foo", 1),
+            ("not a grep line", 3),
+            ("src/b.rs:2:1:foo", 5),
+            ("src/c.rs:3:1:foo", 3),
+        ] {
+            let item: Arc<dyn ClapItem> = Arc::new(line.to_string());
+            collector.on_match(&MatchedItem::new(item, [score, 0, 0, 0], vec![0, 1]));
+        }
+
+        let results = collector.finish().unwrap();
+        assert_eq!(results.len(), 4);
+
+        // The items are ranked and rebuilt as grep items, invalid ones are skipped.
+        let page = results.page(0, 4);
+        assert_eq!(
+            page.iter()
+                .map(|item| item.item.raw_text())
+                .collect::<Vec<_>>(),
+            vec!["src/b.rs:2:1:foo", "src/c.rs:3:1:foo", "src/a.rs:1:1:foo"]
+        );
+        assert!(page[0].item.as_any().downcast_ref::<GrepItem>().is_some());
+        assert_eq!(page[0].indices, vec![0, 1]);
+
+        assert!(SpillCollector::new(MatchScope::Full).finish().is_none());
+    }
+}
@@ -0,0 +1,201 @@
+use crate::paths::query_with_main_separator;
+use crate::stdio_server::provider::{ClapProvider, Context, SearcherControl};
+use anyhow::Result;
+use clap::Parser;
+use matcher::{Bonus, MatchScope, Score};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use types::Query;
+
+use super::BaseArgs;
+
+/// Bonus of the open buffers.
+const BUFFER_BONUS: Score = 60;
+
+/// Bonus of the most frecent file, the other recent files are given a share of it in
+/// proportion to their frecent scores.
+const MAX_FRECENCY_BONUS: Score = 40;
+
+/// Number of the recent files taken into account.
+const MAX_RECENT_FILES: usize = 100;
+
+#[derive(Debug, Parser, PartialEq, Eq, Default)]
+#[command(name = ":Clap smart_open")]
+#[command(about = "smart_open provider", long_about = None)]
+struct SmartOpenArgs {
+    #[clap(flatten)]
+    base: BaseArgs,
+}
+
+/// Returns the bonus scores of the open buffers and the recent files keyed by the paths shown
+/// in the list, i.e., relative to `cwd` if under it, otherwise absolute.
+///
+/// A file both open and recent is given the sum of the bonuses.
+fn preferred_scores(
+    cwd: &Path,
+    buffer_paths: &[PathBuf],
+    recent_files: &[(PathBuf, f64)],
+) -> HashMap<String, Score> {
+    let display_path = |path: &Path| {
+        path.strip_prefix(cwd)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    };
+
+    let mut scores = HashMap::new();
+
+    for path in buffer_paths {
+        *scores.entry(display_path(path)).or_default() += BUFFER_BONUS;
+    }
+
+    let top_frecent_score = recent_files
+        .iter()
+        .map(|(_, frecent_score)| *frecent_score)
+        .fold(0.0, f64::max);
+    if top_frecent_score > 0.0 {
+        for (path, frecent_score) in recent_files {
+            let bonus = (MAX_FRECENCY_BONUS as f64 * frecent_score / top_frecent_score).ceil();
+            *scores.entry(display_path(path)).or_default() += bonus as Score;
+        }
+    }
+
+    scores
+}
+
+/// Blends the open buffers, the recent files and the project files into one list.
+///
+/// The project files are searched like the `files` provider, with the open buffers and the
+/// recent files boosted. The ones outside the project are listed in the absolute form.
+#[derive(Debug)]
+pub struct SmartOpenProvider {
+    preferred_bonus: Bonus,
+    priority_paths: Vec<PathBuf>,
+    searcher_control: Option<SearcherControl>,
+}
+
+impl SmartOpenProvider {
+    pub async fn new(ctx: &Context) -> Result<Self> {
+        let args: SmartOpenArgs = ctx.parse_provider_args().await?;
+        ctx.handle_base_args(&args.base).await?;
+
+        let buffer_paths = ctx
+            .vim
+            .listed_buffer_paths()
+            .await?
+            .into_iter()
+            .map(PathBuf::from)
+            .collect::<Vec<_>>();
+
+        let recent_files = {
+            let cwd = ctx.cwd.to_string();
+            crate::datastore::RECENT_FILES_IN_MEMORY
+                .lock()
+                .scoped_entries(&cwd, ctx.recent_files_project_scoped())
+                .take(MAX_RECENT_FILES)
+                .map(|entry| (PathBuf::from(&entry.fpath), entry.frecent_score))
+                .collect::<Vec<_>>()
+        };
+
+        let preferred_scores = preferred_scores(&ctx.cwd, &buffer_paths, &recent_files);
+
+        let mut priority_paths = buffer_paths;
+        for (path, _) in recent_files {
+            if !priority_paths.contains(&path) {
+                priority_paths.push(path);
+            }
+        }
+
+        Ok(Self {
+            preferred_bonus: Bonus::Preferred(preferred_scores.into()),
+            priority_paths,
+            searcher_control: None,
+        })
+    }
+
+    fn process_query(&mut self, query: String, ctx: &Context) {
+        if let Some(control) = self.searcher_control.take() {
+            tokio::task::spawn_blocking(move || {
+                control.kill();
+            });
+        }
+
+        let matcher = ctx
+            .matcher_builder()
+            .match_scope(MatchScope::Full)
+            .bonuses(vec![self.preferred_bonus.clone()])
+            .build(Query::from(query_with_main_separator(&query)));
+
+        let new_control = {
+            let stop_signal = Arc::new(AtomicBool::new(false));
+
+            let join_handle = {
+                let mut search_context = ctx.search_context(stop_signal.clone());
+                search_context.priority_paths = self.priority_paths.clone();
+                let vim = ctx.vim.clone();
+                tokio::spawn(async move {
+                    let _ = vim.bare_exec("clap#spinner#set_busy");
+                    crate::searcher::files::search(query, matcher, search_context).await;
+                    let _ = vim.bare_exec("clap#spinner#set_idle");
+                })
+            };
+
+            SearcherControl {
+                stop_signal,
+                join_handle,
+            }
+        };
+
+        self.searcher_control.replace(new_control);
+    }
+}
+
+#[async_trait::async_trait]
+impl ClapProvider for SmartOpenProvider {
+    async fn on_initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        let query = ctx.vim.context_query_or_input().await?;
+        // The open buffers and the recent files are on top if query is empty.
+        self.process_query(query, ctx);
+        Ok(())
+    }
+
+    async fn on_typed(&mut self, ctx: &mut Context) -> Result<()> {
+        let query = ctx.vim.input_get().await?;
+        self.process_query(query, ctx);
+        Ok(())
+    }
+
+    fn on_terminate(&mut self, ctx: &mut Context, session_id: u64) {
+        if let Some(control) = self.searcher_control.take() {
+            // NOTE: The kill operation can not block current task.
+            tokio::task::spawn_blocking(move || control.kill());
+        }
+        ctx.signify_terminated(session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferred_scores() {
+        let cwd = Path::new("/home/xlc/src");
+        let buffer_paths = vec![
+            PathBuf::from("/home/xlc/src/foo.rs"),
+            PathBuf::from("/tmp/bar.txt"),
+        ];
+        let recent_files = vec![
+            (PathBuf::from("/home/xlc/src/baz.rs"), 8.0),
+            (PathBuf::from("/home/xlc/src/foo.rs"), 2.0),
+        ];
+
+        let scores = preferred_scores(cwd, &buffer_paths, &recent_files);
+
+        assert_eq!(scores["foo.rs"], BUFFER_BONUS + MAX_FRECENCY_BONUS / 4);
+        assert_eq!(scores["/tmp/bar.txt"], BUFFER_BONUS);
+        assert_eq!(scores["baz.rs"], MAX_FRECENCY_BONUS);
+    }
+}
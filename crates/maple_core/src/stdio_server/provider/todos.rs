@@ -0,0 +1,555 @@
+use crate::searcher::walker::{walker, Walker};
+use crate::searcher::WalkConfig;
+use crate::stdio_server::provider::{ClapProvider, Context, SearcherControl};
+use crate::stdio_server::vim::Vim;
+use anyhow::Result;
+use matcher::MatcherBuilder;
+use parking_lot::Mutex;
+use printer::{DisplayLines, Printer};
+use rayon::prelude::*;
+use regex::Regex;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use types::{ClapItem, MatchedItem, Query, Rank};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+impl Severity {
+    fn of_tag(tag: &str) -> Self {
+        match tag {
+            "FIXME" | "XXX" => Self::High,
+            "HACK" => Self::Medium,
+            _ => Self::Low,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "low" => Some(Self::Low),
+            "medium" | "med" => Some(Self::Medium),
+            "high" => Some(Self::High),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BlameInfo {
+    author: String,
+    /// Unix timestamp of the commit.
+    time: u64,
+}
+
+#[derive(Debug, Clone)]
+struct TodoItem {
+    /// Path relative to the project root.
+    path: PathBuf,
+    line_number: usize,
+    column: usize,
+    tag: String,
+    line: String,
+    severity: Severity,
+    blame: Option<BlameInfo>,
+}
+
+impl TodoItem {
+    /// Formats the item as `[TAG] [severity] [@author age] path:lnum:col:line`, the part after
+    /// the bracketed columns is in the grep format.
+    fn display_line(&self, now: u64) -> String {
+        let blame = match &self.blame {
+            Some(BlameInfo { author, time }) => {
+                format!("@{author} {}", format_age(now.saturating_sub(*time)))
+            }
+            None => "@- -".to_string(),
+        };
+        format!(
+            "[{}] [{}] [{blame}] {}:{}:{}:{}",
+            self.tag,
+            self.severity.as_str(),
+            self.path.display(),
+            self.line_number,
+            self.column,
+            self.line
+        )
+    }
+}
+
+pub(super) fn format_age(secs: u64) -> String {
+    const DAY: u64 = 24 * 60 * 60;
+    match secs {
+        s if s < 60 * 60 => format!("{}m", s / 60),
+        s if s < DAY => format!("{}h", s / (60 * 60)),
+        s if s < 365 * DAY => format!("{}d", s / DAY),
+        s => format!("{}y", s / (365 * DAY)),
+    }
+}
+
+/// Query of the todos provider, `sev:high` and `@author` are extracted for filtering the items
+/// exactly and the rest is used for the fuzzy matching.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct TodoQuery {
+    severity: Option<Severity>,
+    author: Option<String>,
+    fuzzy: String,
+}
+
+impl TodoQuery {
+    fn parse(query: &str) -> Self {
+        let mut todo_query = Self::default();
+        let mut fuzzy = Vec::new();
+
+        for token in query.split_whitespace() {
+            if let Some(severity) = token
+                .strip_prefix("sev:")
+                .or_else(|| token.strip_prefix("severity:"))
+                .and_then(Severity::parse)
+            {
+                todo_query.severity.replace(severity);
+            } else if let Some(author) = token.strip_prefix('@').filter(|a| !a.is_empty()) {
+                todo_query.author.replace(author.to_lowercase());
+            } else {
+                fuzzy.push(token);
+            }
+        }
+
+        todo_query.fuzzy = fuzzy.join(" ");
+
+        todo_query
+    }
+
+    fn is_match(&self, item: &TodoItem) -> bool {
+        self.severity.map(|s| s == item.severity).unwrap_or(true)
+            && self
+                .author
+                .as_ref()
+                .map(|author| {
+                    item.blame
+                        .as_ref()
+                        .map(|b| b.author.to_lowercase().contains(author))
+                        .unwrap_or(false)
+                })
+                .unwrap_or(true)
+    }
+}
+
+fn collect_todos(
+    walker: &dyn Walker,
+    root: &Path,
+    tags: &[String],
+    walk_config: WalkConfig,
+    stop_signal: &AtomicBool,
+) -> Vec<TodoItem> {
+    let tags = tags
+        .iter()
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| regex::escape(tag))
+        .collect::<Vec<_>>();
+
+    // `\b()\b` would match every line.
+    if tags.is_empty() {
+        return Vec::new();
+    }
+
+    let Ok(tag_regex) = Regex::new(&format!(r"\b({})\b", tags.join("|"))) else {
+        return Vec::new();
+    };
+
+    let todos = Mutex::new(Vec::new());
+
+    walker.walk_files(vec![root.to_path_buf()], walk_config, stop_signal, &|| {
+        let todos = &todos;
+        let tag_regex = &tag_regex;
+        Box::new(move |path: &Path| {
+            let Ok(content) = std::fs::read_to_string(path) else {
+                return true;
+            };
+
+            let relative_path = path.strip_prefix(root).unwrap_or(path);
+
+            let file_todos = content
+                .lines()
+                .enumerate()
+                .filter_map(|(idx, line)| {
+                    let tag = tag_regex.find(line)?;
+                    Some(TodoItem {
+                        path: relative_path.to_path_buf(),
+                        line_number: idx + 1,
+                        column: tag.start() + 1,
+                        tag: tag.as_str().to_string(),
+                        line: line.trim().to_string(),
+                        severity: Severity::of_tag(tag.as_str()),
+                        blame: None,
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            if !file_todos.is_empty() {
+                todos.lock().extend(file_todos);
+            }
+
+            true
+        })
+    });
+
+    todos.into_inner()
+}
+
+/// Parses the output of `git blame --line-porcelain` into a map of the final line number to
+/// the blame info.
+fn parse_blame(output: &str) -> HashMap<usize, BlameInfo> {
+    let mut blames = HashMap::new();
+
+    let mut line_number = None;
+    let mut author = None;
+    let mut time = None;
+
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            if let (Some(line_number), Some(author), Some(time)) =
+                (line_number.take(), author.take(), time.take())
+            {
+                blames.insert(line_number, BlameInfo { author, time });
+            }
+        } else if let Some(a) = line.strip_prefix("author ") {
+            author.replace(a.to_string());
+        } else if let Some(t) = line.strip_prefix("author-time ") {
+            time = t.parse::<u64>().ok();
+        } else {
+            let mut iter = line.split_whitespace();
+            if let (Some(sha), Some(_orig), Some(final_lnum)) =
+                (iter.next(), iter.next(), iter.next())
+            {
+                if sha.len() == 40 && sha.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    line_number = final_lnum.parse::<usize>().ok();
+                }
+            }
+        }
+    }
+
+    blames
+}
+
+/// Fills in the author and time of each todo via `git blame`.
+fn blame_todos(root: &Path, todos: &mut [TodoItem]) {
+    let mut by_path: HashMap<PathBuf, Vec<&mut TodoItem>> = HashMap::new();
+    for todo in todos.iter_mut() {
+        by_path.entry(todo.path.clone()).or_default().push(todo);
+    }
+
+    by_path.into_par_iter().for_each(|(path, mut todos)| {
+        let mut cmd = crate::process::wsl::command_in("git", root);
+        cmd.args(["blame", "--line-porcelain"]);
+        for todo in todos.iter() {
+            cmd.arg("-L").arg(format!("{0},{0}", todo.line_number));
+        }
+        cmd.arg("--")
+            .arg(crate::process::wsl::tool_arg("git", &path, root));
+
+        let Ok(output) = cmd.output() else {
+            return;
+        };
+
+        if !output.status.success() {
+            return;
+        }
+
+        let blames = parse_blame(&String::from_utf8_lossy(&output.stdout));
+        for todo in todos.iter_mut() {
+            todo.blame = blames.get(&todo.line_number).cloned();
+        }
+    });
+}
+
+/// Todos collected in the background and the results of the current query.
+#[derive(Debug, Default)]
+struct TodosState {
+    todos: Vec<TodoItem>,
+    /// Todos matching the query, paged in on scrolling.
+    matched_items: Vec<MatchedItem>,
+    /// Number of the matched items sent to the display window.
+    displayed: usize,
+}
+
+/// Renders the todos to the display window, shared with the collecting task.
+#[derive(Debug, Clone)]
+struct TodosView {
+    printer: Printer,
+    matcher_builder: MatcherBuilder,
+    display_winheight: usize,
+    vim: Vim,
+    state: Arc<Mutex<TodosState>>,
+}
+
+impl TodosView {
+    fn process_query(&self, query: &str) -> Result<()> {
+        let todo_query = TodoQuery::parse(query);
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        let mut state = self.state.lock();
+
+        let items = state
+            .todos
+            .iter()
+            .filter(|todo| todo_query.is_match(todo))
+            .map(|todo| Arc::new(todo.display_line(now)) as Arc<dyn ClapItem>)
+            .collect::<Vec<_>>();
+
+        let matched_items = if todo_query.fuzzy.is_empty() {
+            items
+                .into_iter()
+                .map(|item| MatchedItem::new(item, Rank::default(), Vec::new()))
+                .collect()
+        } else {
+            let matcher = self
+                .matcher_builder
+                .clone()
+                .build(Query::from(&todo_query.fuzzy));
+            filter::par_filter_items(&items, &matcher)
+        };
+
+        let DisplayLines {
+            lines,
+            indices,
+            truncated_map,
+            icon_added,
+        } = self
+            .printer
+            .to_visible_display_lines(&matched_items, self.display_winheight);
+        let lines_len = lines.len();
+
+        self.vim.exec(
+            "clap#state#process_filter_message",
+            json!([{
+                "total": matched_items.len(),
+                "lines": lines,
+                "indices": indices,
+                "icon_added": icon_added,
+                "truncated_map": truncated_map,
+                "has_more": matched_items.len() > lines_len,
+            }, true]),
+        )?;
+
+        state.displayed = lines_len;
+        state.matched_items = matched_items;
+
+        Ok(())
+    }
+
+    /// Shows the todos once they are found and again once they are blamed, which can be slow
+    /// in a large repo.
+    async fn collect(
+        &self,
+        root: PathBuf,
+        tags: Vec<String>,
+        walk_config: WalkConfig,
+        stop_signal: Arc<AtomicBool>,
+    ) -> Result<()> {
+        let mut todos = tokio::task::spawn_blocking({
+            let root = root.clone();
+            let stop_signal = stop_signal.clone();
+            move || collect_todos(&*walker(), &root, &tags, walk_config, &stop_signal)
+        })
+        .await?;
+
+        // Most severe first.
+        todos.sort_by(|a, b| {
+            b.severity
+                .cmp(&a.severity)
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.line_number.cmp(&b.line_number))
+        });
+
+        self.state.lock().todos = todos.clone();
+        self.process_query(&self.vim.input_get().await?)?;
+
+        let todos = tokio::task::spawn_blocking(move || {
+            blame_todos(&root, &mut todos);
+            todos
+        })
+        .await?;
+
+        if stop_signal.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        self.state.lock().todos = todos;
+        self.process_query(&self.vim.input_get().await?)
+    }
+}
+
+#[derive(Debug)]
+pub struct TodosProvider {
+    view: TodosView,
+    searcher_control: Option<SearcherControl>,
+}
+
+impl TodosProvider {
+    pub fn new(ctx: &Context) -> Self {
+        Self {
+            view: TodosView {
+                printer: Printer::new(ctx.env.display_winwidth, icon::Icon::Null),
+                matcher_builder: ctx.matcher_builder(),
+                display_winheight: ctx.env.display_winheight,
+                vim: ctx.vim.clone(),
+                state: Default::default(),
+            },
+            searcher_control: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ClapProvider for TodosProvider {
+    async fn on_initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        let root = ctx.cwd.to_path_buf();
+        let tags = ctx.config().provider.todos.tags.clone();
+        let walk_config = ctx.config().walker.walk_config(Some("todos"));
+
+        let stop_signal = Arc::new(AtomicBool::new(false));
+
+        let join_handle = tokio::spawn({
+            let view = self.view.clone();
+            let stop_signal = stop_signal.clone();
+            async move {
+                let _ = view.vim.bare_exec("clap#spinner#set_busy");
+                if let Err(err) = view.collect(root, tags, walk_config, stop_signal).await {
+                    tracing::error!(?err, "Failed to collect the todos");
+                }
+                let _ = view.vim.bare_exec("clap#spinner#set_idle");
+            }
+        });
+
+        self.searcher_control.replace(SearcherControl {
+            stop_signal,
+            join_handle,
+        });
+
+        Ok(())
+    }
+
+    async fn on_typed(&mut self, ctx: &mut Context) -> Result<()> {
+        let query = ctx.vim.input_get().await?;
+        // The case matching may be toggled in the session.
+        self.view.matcher_builder = ctx.matcher_builder();
+        self.view.process_query(&query)
+    }
+
+    fn matched_lines(&self) -> Option<Vec<String>> {
+        Some(
+            self.view
+                .state
+                .lock()
+                .matched_items
+                .iter()
+                .map(|matched_item| matched_item.item.raw_text().to_string())
+                .collect(),
+        )
+    }
+
+    async fn on_load_more(&mut self, ctx: &mut Context) -> Result<()> {
+        let mut state = self.view.state.lock();
+
+        if state.displayed >= state.matched_items.len() {
+            return Ok(());
+        }
+
+        let display_lines = self.view.printer.to_page_display_lines(
+            &state.matched_items,
+            state.displayed,
+            ctx.env.display_winheight,
+        );
+        state.displayed += display_lines.lines.len();
+
+        ctx.append_page(display_lines, state.matched_items.len() > state.displayed)
+    }
+
+    fn on_terminate(&mut self, ctx: &mut Context, session_id: u64) {
+        if let Some(control) = self.searcher_control.take() {
+            // NOTE: The kill operation can not block current task.
+            tokio::task::spawn_blocking(move || control.kill());
+        }
+        ctx.signify_terminated(session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::searcher::walker::IgnoreWalker;
+
+    #[test]
+    fn test_parse_todo_query() {
+        assert_eq!(
+            TodoQuery::parse("sev:high @Alice parser"),
+            TodoQuery {
+                severity: Some(Severity::High),
+                author: Some("alice".into()),
+                fuzzy: "parser".into(),
+            }
+        );
+        assert_eq!(TodoQuery::parse("sev:unknown").fuzzy, "sev:unknown");
+    }
+
+    #[test]
+    fn test_collect_todos_without_tags() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("main.rs"),
+            "// TODO: fix me\nfn main() {}\n",
+        )
+        .unwrap();
+
+        let collect = |tags: &[String]| {
+            collect_todos(
+                &IgnoreWalker,
+                dir.path(),
+                tags,
+                WalkConfig::default(),
+                &AtomicBool::new(false),
+            )
+        };
+
+        assert!(collect(&[]).is_empty());
+        assert!(collect(&[String::new()]).is_empty());
+        assert_eq!(collect(&["TODO".to_string()]).len(), 1);
+    }
+
+    #[test]
+    fn test_parse_blame() {
+        let output = "\
+0123456789abcdef0123456789abcdef01234567 3 5 1
+author Alice
+author-time 1700000000
+summary init
+\t// TODO: fix me
+";
+        assert_eq!(
+            parse_blame(output).get(&5),
+            Some(&BlameInfo {
+                author: "Alice".into(),
+                time: 1700000000
+            })
+        );
+    }
+}
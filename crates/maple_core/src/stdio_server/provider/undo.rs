@@ -0,0 +1,467 @@
+use crate::stdio_server::handler::Preview;
+use crate::stdio_server::provider::todos::format_age;
+use crate::stdio_server::provider::{ClapProvider, Context};
+use anyhow::Result;
+use printer::{DisplayLines, Printer};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use types::{ClapItem, MatchedItem, Rank};
+
+/// Lines of context around each change in the diff preview.
+const DIFF_CONTEXT: usize = 3;
+
+/// Beyond this size the changed range is shown as a whole instead of being diffed line by line,
+/// the LCS table is computed for each undo state.
+const MAX_LCS_CELLS: usize = 250_000;
+
+/// Entry of `undotree().entries`.
+#[derive(Debug, Deserialize)]
+struct UndoTreeEntry {
+    seq: usize,
+    time: u64,
+    #[serde(default)]
+    save: Option<usize>,
+    #[serde(default)]
+    alt: Vec<UndoTreeEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UndoTree {
+    seq_cur: usize,
+    entries: Vec<UndoTreeEntry>,
+}
+
+/// `[start, count, lines]`, replaces `count` lines from the 0-based `start` with `lines`.
+type Hunk = (usize, usize, Vec<String>);
+
+/// Sent from `clap#provider#undo#history()`.
+#[derive(Debug, Deserialize)]
+struct UndoHistory {
+    tree: UndoTree,
+    /// Buffer text of the current undo state, and of the other states if Vim can not diff,
+    /// keyed by the undo sequence number.
+    texts: HashMap<String, Vec<String>>,
+    /// Hunks from the current text to the text of each undo state.
+    #[serde(default)]
+    hunks: HashMap<String, Vec<Hunk>>,
+}
+
+/// Returns the text by applying the ordered `hunks` to `base`.
+fn apply_hunks(base: &[String], hunks: &[Hunk]) -> Vec<String> {
+    let mut text = Vec::with_capacity(base.len());
+    let mut next = 0;
+    for (start, count, lines) in hunks {
+        let start = (*start).clamp(next, base.len());
+        text.extend_from_slice(&base[next..start]);
+        text.extend(lines.iter().cloned());
+        next = (start + count).min(base.len());
+    }
+    text.extend_from_slice(&base[next..]);
+    text
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct UndoState {
+    seq: usize,
+    /// Undo sequence number of the state this change is made on, 0 for the original text.
+    parent: usize,
+    time: u64,
+    saved: bool,
+}
+
+/// Flattens the undo tree into a list of states with the parent of each state.
+///
+/// In each list of entries, an entry is made on top of the previous entry, an alternate branch
+/// is made on the same state as the entry owning it.
+fn flatten_undo_tree(entries: &[UndoTreeEntry], parent: usize, states: &mut Vec<UndoState>) {
+    let mut parent = parent;
+    for entry in entries {
+        flatten_undo_tree(&entry.alt, parent, states);
+        states.push(UndoState {
+            seq: entry.seq,
+            parent,
+            time: entry.time,
+            saved: entry.save.is_some(),
+        });
+        parent = entry.seq;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffLine<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Returns the line diff from `old` to `new`.
+fn diff_lines<'a>(old: &'a [String], new: &'a [String]) -> Vec<DiffLine<'a>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut diff = old[..prefix]
+        .iter()
+        .map(|line| DiffLine::Equal(line))
+        .collect::<Vec<_>>();
+
+    if old_middle.len() * new_middle.len() > MAX_LCS_CELLS {
+        diff.extend(old_middle.iter().map(|line| DiffLine::Delete(line)));
+        diff.extend(new_middle.iter().map(|line| DiffLine::Insert(line)));
+    } else {
+        // lcs[i][j] is the length of LCS of old_middle[i..] and new_middle[j..].
+        let (n, m) = (old_middle.len(), new_middle.len());
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if old_middle[i] == new_middle[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && old_middle[i] == new_middle[j] {
+                diff.push(DiffLine::Equal(&old_middle[i]));
+                i += 1;
+                j += 1;
+            } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+                diff.push(DiffLine::Insert(&new_middle[j]));
+                j += 1;
+            } else {
+                diff.push(DiffLine::Delete(&old_middle[i]));
+                i += 1;
+            }
+        }
+    }
+
+    diff.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| DiffLine::Equal(line)),
+    );
+
+    diff
+}
+
+/// Returns the summary of a change in the format of `+inserted -deleted first_changed_line`.
+fn change_summary(diff: &[DiffLine]) -> String {
+    let inserted = diff
+        .iter()
+        .filter(|d| matches!(d, DiffLine::Insert(_)))
+        .count();
+    let deleted = diff
+        .iter()
+        .filter(|d| matches!(d, DiffLine::Delete(_)))
+        .count();
+    let first_changed = diff
+        .iter()
+        .find_map(|d| match d {
+            DiffLine::Insert(line) | DiffLine::Delete(line) if !line.trim().is_empty() => {
+                Some(line.trim())
+            }
+            _ => None,
+        })
+        .unwrap_or_default();
+    format!("+{inserted} -{deleted} {first_changed}")
+}
+
+/// Renders the diff in the unified format with [`DIFF_CONTEXT`] lines of context.
+fn unified_diff(diff: &[DiffLine]) -> Vec<String> {
+    let is_change = |d: &DiffLine| !matches!(d, DiffLine::Equal(_));
+
+    let mut lines = Vec::new();
+    let mut last_shown = None;
+
+    for (idx, d) in diff.iter().enumerate() {
+        let near_change = diff
+            [idx.saturating_sub(DIFF_CONTEXT)..(idx + DIFF_CONTEXT + 1).min(diff.len())]
+            .iter()
+            .any(is_change);
+
+        if !near_change {
+            continue;
+        }
+
+        if last_shown.map(|last| last + 1 != idx).unwrap_or(true) {
+            let (old_lnum, new_lnum) = diff[..idx].iter().fold((1, 1), |(o, n), d| match d {
+                DiffLine::Equal(_) => (o + 1, n + 1),
+                DiffLine::Delete(_) => (o + 1, n),
+                DiffLine::Insert(_) => (o, n + 1),
+            });
+            lines.push(format!("@@ -{old_lnum} +{new_lnum} @@"));
+        }
+
+        lines.push(match d {
+            DiffLine::Equal(line) => format!(" {line}"),
+            DiffLine::Delete(line) => format!("-{line}"),
+            DiffLine::Insert(line) => format!("+{line}"),
+        });
+        last_shown.replace(idx);
+    }
+
+    lines
+}
+
+/// Extracts the undo sequence number from the display line `[*] seq  age  summary`.
+fn extract_seq(line: &str) -> Option<usize> {
+    line.trim_start_matches('*')
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+#[derive(Debug)]
+pub struct UndoProvider {
+    printer: Printer,
+    lines: Arc<Vec<String>>,
+    /// Undo states matching the query, paged in on scrolling.
+    matched_items: Vec<MatchedItem>,
+    /// Number of the matched items sent to the display window.
+    displayed: usize,
+    texts: HashMap<usize, Vec<String>>,
+    seq_cur: usize,
+}
+
+impl UndoProvider {
+    pub fn new(ctx: &Context) -> Self {
+        Self {
+            printer: Printer::new(ctx.env.display_winwidth, icon::Icon::Null),
+            lines: Default::default(),
+            matched_items: Vec::new(),
+            displayed: 0,
+            texts: Default::default(),
+            seq_cur: 0,
+        }
+    }
+
+    fn process_query(&mut self, query: &str, ctx: &Context) -> Result<()> {
+        let items = self
+            .lines
+            .iter()
+            .map(|line| Arc::new(line.clone()) as Arc<dyn ClapItem>)
+            .collect::<Vec<_>>();
+
+        let matched_items = if query.is_empty() {
+            items
+                .into_iter()
+                .map(|item| MatchedItem::new(item, Rank::default(), Vec::new()))
+                .collect()
+        } else {
+            filter::par_filter_items(&items, &ctx.matcher(query))
+        };
+
+        let DisplayLines {
+            lines,
+            indices,
+            truncated_map,
+            icon_added,
+        } = self
+            .printer
+            .to_visible_display_lines(&matched_items, ctx.env.display_winheight);
+        let lines_len = lines.len();
+
+        ctx.vim.exec(
+            "clap#state#process_filter_message",
+            json!([{
+                "total": matched_items.len(),
+                "lines": lines,
+                "indices": indices,
+                "icon_added": icon_added,
+                "truncated_map": truncated_map,
+                "has_more": matched_items.len() > lines_len,
+            }, true]),
+        )?;
+
+        self.displayed = lines_len;
+        self.matched_items = matched_items;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ClapProvider for UndoProvider {
+    async fn on_initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        let UndoHistory { tree, texts, hunks } = ctx
+            .vim
+            .call("clap#provider#undo#history", json!([ctx.env.start.winid]))
+            .await?;
+
+        self.texts = texts
+            .into_iter()
+            .filter_map(|(seq, text)| Some((seq.parse().ok()?, text)))
+            .collect();
+        self.seq_cur = tree.seq_cur;
+
+        if let Some(current_text) = self.texts.get(&self.seq_cur).cloned() {
+            for (seq, hunks) in hunks {
+                if let Ok(seq) = seq.parse() {
+                    self.texts.insert(seq, apply_hunks(&current_text, &hunks));
+                }
+            }
+        }
+
+        let mut states = Vec::new();
+        flatten_undo_tree(&tree.entries, 0, &mut states);
+        states.sort_by_key(|state| std::cmp::Reverse(state.seq));
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        let lines = states
+            .iter()
+            .filter_map(|state| {
+                let text = self.texts.get(&state.seq)?;
+                let summary = self
+                    .texts
+                    .get(&state.parent)
+                    .map(|parent_text| change_summary(&diff_lines(parent_text, text)))
+                    .unwrap_or_default();
+                Some(format!(
+                    "{}{:>5}  {:>4}  {}{summary}",
+                    if state.seq == self.seq_cur { "*" } else { " " },
+                    state.seq,
+                    format_age(now.saturating_sub(state.time)),
+                    if state.saved { "[saved] " } else { "" },
+                ))
+            })
+            .chain(std::iter::once(format!(
+                "{}{:>5}  {:>4}  original",
+                if self.seq_cur == 0 { "*" } else { " " },
+                0,
+                "-"
+            )))
+            .collect();
+
+        self.lines = Arc::new(lines);
+
+        let query = ctx.vim.context_query_or_input().await?;
+        self.process_query(&query, ctx)
+    }
+
+    async fn on_move(&mut self, ctx: &mut Context) -> Result<()> {
+        if !ctx.preview_enabled() {
+            return Ok(());
+        }
+
+        let curline = ctx.vim.display_getcurline().await?;
+        let Some(seq) = extract_seq(&curline) else {
+            return Ok(());
+        };
+
+        let (Some(text), Some(current_text)) =
+            (self.texts.get(&seq), self.texts.get(&self.seq_cur))
+        else {
+            return Ok(());
+        };
+
+        let diff = unified_diff(&diff_lines(current_text, text));
+        let header = format!("Undo state {seq} vs the current state {}", self.seq_cur);
+        let lines = if diff.is_empty() {
+            vec![header, "No changes".to_string()]
+        } else {
+            std::iter::once(header).chain(diff).collect()
+        };
+
+        ctx.render_preview(Preview {
+            lines,
+            syntax: Some("diff".into()),
+            hi_lnum: Some(1),
+            ..Default::default()
+        })
+    }
+
+    async fn on_typed(&mut self, ctx: &mut Context) -> Result<()> {
+        let query = ctx.vim.input_get().await?;
+        self.process_query(&query, ctx)
+    }
+
+    async fn on_load_more(&mut self, ctx: &mut Context) -> Result<()> {
+        if self.displayed >= self.matched_items.len() {
+            return Ok(());
+        }
+
+        let display_lines = self.printer.to_page_display_lines(
+            &self.matched_items,
+            self.displayed,
+            ctx.env.display_winheight,
+        );
+        self.displayed += display_lines.lines.len();
+
+        ctx.append_page(display_lines, self.matched_items.len() > self.displayed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let to_lines = |s: &str| s.split(' ').map(Into::into).collect::<Vec<String>>();
+        let old = to_lines("a b c d e");
+        let new = to_lines("a c d x e");
+        let diff = diff_lines(&old, &new);
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Equal("a"),
+                DiffLine::Delete("b"),
+                DiffLine::Equal("c"),
+                DiffLine::Equal("d"),
+                DiffLine::Insert("x"),
+                DiffLine::Equal("e"),
+            ]
+        );
+        assert_eq!(change_summary(&diff), "+1 -1 b");
+    }
+
+    #[test]
+    fn test_apply_hunks() {
+        let to_lines = |s: &str| s.split(' ').map(Into::into).collect::<Vec<String>>();
+        let base = to_lines("a b c d e");
+        // Replace `b`, insert `x` before `e` and delete `e`.
+        let hunks = vec![
+            (1, 1, to_lines("y")),
+            (4, 0, to_lines("x")),
+            (4, 1, Vec::new()),
+        ];
+        assert_eq!(apply_hunks(&base, &hunks), to_lines("a y c d x"));
+        assert_eq!(apply_hunks(&base, &[]), base);
+    }
+
+    #[test]
+    fn test_flatten_undo_tree() {
+        let tree: Vec<UndoTreeEntry> = serde_json::from_str(
+            r#"[
+                {"seq": 1, "time": 10},
+                {"seq": 3, "time": 30, "alt": [{"seq": 2, "time": 20}]},
+                {"seq": 4, "time": 40, "save": 1}
+            ]"#,
+        )
+        .unwrap();
+        let mut states = Vec::new();
+        flatten_undo_tree(&tree, 0, &mut states);
+        let parents = states.iter().map(|s| (s.seq, s.parent)).collect::<Vec<_>>();
+        assert_eq!(parents, vec![(1, 0), (2, 1), (3, 1), (4, 3)]);
+        assert!(states[3].saved);
+        assert_eq!(extract_seq("*    4    1h  +1 -0 foo"), Some(4));
+    }
+}
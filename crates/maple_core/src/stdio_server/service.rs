@@ -1,10 +1,13 @@
 //! Each invocation of Clap provider is a session. When you exit the provider, the session ends.
 
 use crate::stdio_server::input::{
-    InternalProviderEvent, PluginEvent, ProviderEvent, ProviderEventSender,
+    Autocmd, InternalProviderEvent, PluginEvent, ProviderEvent, ProviderEventSender,
 };
 use crate::stdio_server::plugin::ClapPlugin;
-use crate::stdio_server::provider::{ClapProvider, Context, ProviderSource};
+use crate::stdio_server::provider::{
+    handle_custom_key, handle_key_event, handle_mouse_event, ClapProvider, Context, ProviderSource,
+};
+use serde_json::json;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::fmt::Debug;
@@ -21,6 +24,8 @@ pub struct ProviderSession {
     /// Each provider session can have its own message processing logic.
     provider: Box<dyn ClapProvider>,
     provider_events: UnboundedReceiver<ProviderEvent>,
+    /// Query processed by the last `on_typed`.
+    last_query: Option<String>,
 }
 
 impl ProviderSession {
@@ -36,6 +41,7 @@ impl ProviderSession {
             provider_session_id,
             provider,
             provider_events: provider_event_receiver,
+            last_query: None,
         };
 
         (provider_session, provider_event_sender)
@@ -58,6 +64,33 @@ impl ProviderSession {
         });
     }
 
+    /// Records the current query and returns `false` if it equals the query processed by the
+    /// last `on_typed` or the query set by the provider, in which case the refiltering is
+    /// unnecessary.
+    async fn is_query_changed(&mut self) -> bool {
+        let Ok(query) = self.ctx.record_input().await else {
+            return true;
+        };
+
+        if self.ctx.is_query_echo(&query) {
+            tracing::trace!(
+                ?query,
+                "Skipping on_typed as the query is set by the provider"
+            );
+            self.last_query.replace(query);
+            return false;
+        }
+
+        if self.last_query.as_ref() == Some(&query) {
+            tracing::trace!(?query, "Skipping on_typed as the query is unchanged");
+            return false;
+        }
+
+        self.last_query.replace(query);
+
+        true
+    }
+
     async fn run_event_loop_with_debounce(mut self) {
         // https://github.com/denoland/deno/blob/1fb5858009f598ce3f917f9f49c466db81f4d9b0/cli/lsp/diagnostics.rs#L141
         //
@@ -100,8 +133,13 @@ impl ProviderSession {
                                     match internal_event {
                                         InternalProviderEvent::Terminate => {
                                             self.provider.on_terminate(&mut self.ctx, self.provider_session_id);
+                                            self.ctx.run_hooks(|hooks| &hooks.on_session_terminated, json!({}));
                                             break;
                                         }
+                                        InternalProviderEvent::EntriesAccepted(entries) => {
+                                            self.ctx.note_accepted_files(&entries);
+                                            self.ctx.run_hooks(|hooks| &hooks.on_entry_accepted, json!({ "entries": entries }));
+                                        }
                                         InternalProviderEvent::OnInitialize => {
                                             match self.provider.on_initialize(&mut self.ctx).await {
                                                 Ok(()) => {
@@ -119,6 +157,12 @@ impl ProviderSession {
                                                             on_typed_delay = Duration::from_millis(100);
                                                         }
                                                     }
+                                                    self.ctx.run_hooks(|hooks| &hooks.on_initialize_done, json!({}));
+                                                    if self.ctx.env.resume {
+                                                        if let Err(err) = self.ctx.restore_last_session().await {
+                                                            tracing::error!(?err, "Failed to restore the last session");
+                                                        }
+                                                    }
                                                     // Try to fulfill the preview window
                                                     if let Err(err) = self.provider.on_move(&mut self.ctx).await {
                                                         tracing::debug!(?err, "Failed to preview after on_initialize completed");
@@ -133,6 +177,7 @@ impl ProviderSession {
                                 }
                                 ProviderEvent::Exit => {
                                     self.provider.on_terminate(&mut self.ctx, self.provider_session_id);
+                                    self.ctx.run_hooks(|hooks| &hooks.on_session_terminated, json!({}));
                                     break;
                                 }
                                 ProviderEvent::OnMove => {
@@ -143,8 +188,37 @@ impl ProviderSession {
                                     on_typed_dirty = true;
                                     on_typed_timer.as_mut().reset(Instant::now() + on_typed_delay);
                                 }
+                                ProviderEvent::LoadMore => {
+                                    if let Err(err) = self.provider.on_load_more(&mut self.ctx).await {
+                                        tracing::error!(?err, "Failed to process {event:?}");
+                                    }
+                                }
                                 ProviderEvent::Key(key_event) => {
-                                    if let Err(err) = self.provider.on_key_event(&mut self.ctx, key_event).await {
+                                    if let Err(err) = handle_key_event(self.provider.as_mut(), &mut self.ctx, key_event).await {
+                                        tracing::error!(?err, "Failed to process {event:?}");
+                                    }
+                                }
+                                ProviderEvent::CustomKey(ref key) => {
+                                    if let Err(err) = handle_custom_key(self.provider.as_mut(), &mut self.ctx, key).await {
+                                        tracing::error!(?err, "Failed to process {event:?}");
+                                    }
+                                }
+                                ProviderEvent::Mouse(mouse_event) => {
+                                    if let Err(err) = handle_mouse_event(self.provider.as_mut(), &mut self.ctx, mouse_event).await {
+                                        tracing::error!(?err, "Failed to process {event:?}");
+                                    }
+                                }
+                                ProviderEvent::Action(action) => {
+                                    if let Err(err) = self.ctx.handle_entry_action(action).await {
+                                        tracing::error!(?err, "Failed to process {event:?}");
+                                    }
+                                }
+                                ProviderEvent::Selection { line, selected } => {
+                                    self.ctx.set_selected(line, selected);
+                                }
+                                ProviderEvent::ClearSelection => self.ctx.selected_lines.clear(),
+                                ProviderEvent::ReloadSource => {
+                                    if let Err(err) = self.provider.reload_source(&mut self.ctx).await {
                                         tracing::error!(?err, "Failed to process {event:?}");
                                     }
                                 }
@@ -165,7 +239,9 @@ impl ProviderSession {
                     on_typed_dirty = false;
                     on_typed_timer.as_mut().reset(Instant::now() + NEVER);
 
-                    let _ = self.ctx.record_input().await;
+                    if !self.is_query_changed().await {
+                        continue;
+                    }
 
                     if let Err(err) = self.provider.on_typed(&mut self.ctx).await {
                         tracing::error!(?err, "Failed to process ProviderEvent::OnTyped");
@@ -190,6 +266,13 @@ impl ProviderSession {
                                 tracing::error!(?err, "Failed at process {internal_event:?}");
                                 continue;
                             }
+                            self.ctx
+                                .run_hooks(|hooks| &hooks.on_initialize_done, json!({}));
+                            if self.ctx.env.resume {
+                                if let Err(err) = self.ctx.restore_last_session().await {
+                                    tracing::error!(?err, "Failed to restore the last session");
+                                }
+                            }
                             // Try to fulfill the preview window
                             if let Err(err) = self.provider.on_move(&mut self.ctx).await {
                                 tracing::debug!(
@@ -201,13 +284,24 @@ impl ProviderSession {
                         InternalProviderEvent::Terminate => {
                             self.provider
                                 .on_terminate(&mut self.ctx, self.provider_session_id);
+                            self.ctx
+                                .run_hooks(|hooks| &hooks.on_session_terminated, json!({}));
                             break;
                         }
+                        InternalProviderEvent::EntriesAccepted(entries) => {
+                            self.ctx.note_accepted_files(&entries);
+                            self.ctx.run_hooks(
+                                |hooks| &hooks.on_entry_accepted,
+                                json!({ "entries": entries }),
+                            );
+                        }
                     }
                 }
                 ProviderEvent::Exit => {
                     self.provider
                         .on_terminate(&mut self.ctx, self.provider_session_id);
+                    self.ctx
+                        .run_hooks(|hooks| &hooks.on_session_terminated, json!({}));
                     break;
                 }
                 ProviderEvent::OnMove => {
@@ -216,16 +310,53 @@ impl ProviderSession {
                     }
                 }
                 ProviderEvent::OnTyped => {
-                    let _ = self.ctx.record_input().await;
+                    if !self.is_query_changed().await {
+                        continue;
+                    }
                     if let Err(err) = self.provider.on_typed(&mut self.ctx).await {
                         tracing::debug!(?err, "Failed to process {event:?}");
                     }
                 }
+                ProviderEvent::LoadMore => {
+                    if let Err(err) = self.provider.on_load_more(&mut self.ctx).await {
+                        tracing::debug!(?err, "Failed to process {event:?}");
+                    }
+                }
                 ProviderEvent::Key(key_event) => {
-                    if let Err(err) = self.provider.on_key_event(&mut self.ctx, key_event).await {
+                    if let Err(err) =
+                        handle_key_event(self.provider.as_mut(), &mut self.ctx, key_event).await
+                    {
                         tracing::error!(?err, "Failed to process {key_event:?}");
                     }
                 }
+                ProviderEvent::CustomKey(key) => {
+                    if let Err(err) =
+                        handle_custom_key(self.provider.as_mut(), &mut self.ctx, &key).await
+                    {
+                        tracing::error!(?err, "Failed to process {key:?}");
+                    }
+                }
+                ProviderEvent::Mouse(mouse_event) => {
+                    if let Err(err) =
+                        handle_mouse_event(self.provider.as_mut(), &mut self.ctx, mouse_event).await
+                    {
+                        tracing::error!(?err, "Failed to process {mouse_event:?}");
+                    }
+                }
+                ProviderEvent::Action(action) => {
+                    if let Err(err) = self.ctx.handle_entry_action(action).await {
+                        tracing::error!(?err, "Failed to process {action:?}");
+                    }
+                }
+                ProviderEvent::Selection { line, selected } => {
+                    self.ctx.set_selected(line, selected);
+                }
+                ProviderEvent::ClearSelection => self.ctx.selected_lines.clear(),
+                ProviderEvent::ReloadSource => {
+                    if let Err(err) = self.provider.reload_source(&mut self.ctx).await {
+                        tracing::error!(?err, "Failed to process {event:?}");
+                    }
+                }
             }
         }
     }
@@ -275,8 +406,8 @@ impl PluginSession {
                         match maybe_plugin_event {
                             Some(plugin_event) => {
                                 match plugin_event {
-                                    PluginEvent::Autocmd(autocmd) => {
-                                        pending_autocmd.replace(autocmd);
+                                    PluginEvent::Autocmd(autocmd_event) => {
+                                        pending_autocmd.replace(autocmd_event);
                                         notification_dirty = true;
                                         notification_timer
                                             .as_mut()
@@ -291,8 +422,9 @@ impl PluginSession {
                         notification_dirty = false;
                         notification_timer.as_mut().reset(Instant::now() + NEVER);
 
-                        if let Some(autocmd) = pending_autocmd.take() {
-                            if let Err(err) = self.plugin.on_autocmd(autocmd).await {
+                        if let Some(autocmd_event) = pending_autocmd.take() {
+                            let autocmd = autocmd_event.0;
+                            if let Err(err) = self.plugin.on_autocmd(autocmd_event).await {
                                 tracing::error!(?err, "Failed at process {autocmd:?}");
                             }
                         }
@@ -303,6 +435,21 @@ impl PluginSession {
     }
 }
 
+/// A plugin registered but not yet started.
+struct PendingPlugin {
+    /// The plugin session is started on the first of these autocmds.
+    autocmds: &'static [Autocmd],
+    init: Box<dyn FnOnce() -> Box<dyn ClapPlugin> + Send>,
+}
+
+impl Debug for PendingPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingPlugin")
+            .field("autocmds", &self.autocmds)
+            .finish()
+    }
+}
+
 /// This structs manages all the created sessions.
 ///
 /// A plugin is a general service, a provider is a specialized plugin
@@ -310,7 +457,8 @@ impl PluginSession {
 #[derive(Debug, Default)]
 pub struct ServiceManager {
     providers: HashMap<ProviderSessionId, ProviderEventSender>,
-    plugins: Vec<UnboundedSender<PluginEvent>>,
+    plugins: HashMap<&'static str, UnboundedSender<PluginEvent>>,
+    pending_plugins: HashMap<&'static str, PendingPlugin>,
 }
 
 impl ServiceManager {
@@ -349,13 +497,62 @@ impl ServiceManager {
 
     /// Creates a new plugin session with the default debounce setting.
     pub fn new_plugin(&mut self, plugin: Box<dyn ClapPlugin>) {
-        self.plugins
-            .push(PluginSession::create(plugin, Duration::from_millis(50)));
+        let plugin_id = plugin.id();
+        self.plugins.insert(
+            plugin_id,
+            PluginSession::create(plugin, Duration::from_millis(50)),
+        );
+    }
+
+    /// Registers a plugin whose session is created by `init` once any of `autocmds` arrives,
+    /// sparing the startup work for the plugins that are never triggered.
+    pub fn register_lazy_plugin(
+        &mut self,
+        plugin_id: &'static str,
+        autocmds: &'static [Autocmd],
+        init: impl FnOnce() -> Box<dyn ClapPlugin> + Send + 'static,
+    ) {
+        self.pending_plugins.insert(
+            plugin_id,
+            PendingPlugin {
+                autocmds,
+                init: Box::new(init),
+            },
+        );
+    }
+
+    pub fn has_plugin(&self, plugin_id: &str) -> bool {
+        self.plugins.contains_key(plugin_id) || self.pending_plugins.contains_key(plugin_id)
+    }
+
+    /// Stops the plugin session by dropping the event sender.
+    pub fn remove_plugin(&mut self, plugin_id: &str) {
+        self.pending_plugins.remove(plugin_id);
+        if self.plugins.remove(plugin_id).is_some() {
+            tracing::debug!(plugin_id, "Plugin session removed");
+        }
     }
 
     pub fn notify_plugins(&mut self, plugin_event: PluginEvent) {
+        let PluginEvent::Autocmd((autocmd, _)) = &plugin_event;
+
+        let triggered = self
+            .pending_plugins
+            .iter()
+            .filter_map(|(plugin_id, pending)| {
+                pending.autocmds.contains(autocmd).then_some(*plugin_id)
+            })
+            .collect::<Vec<_>>();
+
+        for plugin_id in triggered {
+            if let Some(pending) = self.pending_plugins.remove(plugin_id) {
+                tracing::debug!(plugin_id, ?autocmd, "Starting the lazy plugin");
+                self.new_plugin((pending.init)());
+            }
+        }
+
         self.plugins
-            .retain(|plugin_sender| plugin_sender.send(plugin_event.clone()).is_ok())
+            .retain(|_plugin_id, plugin_sender| plugin_sender.send(plugin_event.clone()).is_ok())
     }
 
     pub fn exists(&self, provider_session_id: ProviderSessionId) -> bool {
@@ -4,15 +4,16 @@ use anyhow::{anyhow, Result};
 use once_cell::sync::{Lazy, OnceCell};
 use printer::DisplayLines;
 use rayon::prelude::*;
-use rpc::RpcClient;
+use rpc::{NvimClient, RpcClient};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::ops::Deref;
 use std::path::Path;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use types::ProgressUpdate;
 
 static FILENAME_SYNTAX_MAP: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
@@ -47,10 +48,8 @@ static FILENAME_SYNTAX_MAP: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
     .collect()
 });
 
-/// Map of file extension to vim syntax mapping.
-static SYNTAX_MAP: OnceCell<HashMap<String, String>> = OnceCell::new();
-
-pub fn initialize_syntax_map(output: &str) -> HashMap<&str, &str> {
+/// Parses the map of file extension to vim syntax from the output of `autocmd filetypedetect`.
+fn parse_syntax_map(output: &str) -> HashMap<&str, &str> {
     let ext_map: HashMap<&str, &str> = output
         .par_split(|x| x == '\n')
         .filter(|s| s.contains("setf"))
@@ -101,37 +100,9 @@ pub fn initialize_syntax_map(output: &str) -> HashMap<&str, &str> {
         .map(|(ext, ft)| (ext, ft))
         .collect();
 
-    if let Err(e) = SYNTAX_MAP.set(
-        ext_map
-            .par_iter()
-            .map(|(k, v)| (String::from(*k), String::from(*v)))
-            .collect(),
-    ) {
-        tracing::debug!(error = ?e, "Failed to initialized SYNTAX_MAP");
-    } else {
-        tracing::debug!("SYNTAX_MAP initialized successfully");
-    }
-
     ext_map
 }
 
-/// Returns the value of `&syntax` for given path for the preview buffer highlight.
-///
-/// Try the file name first and then the file extension.
-pub fn preview_syntax(path: &Path) -> Option<&str> {
-    match path
-        .file_name()
-        .and_then(|x| x.to_str())
-        .and_then(|filename| FILENAME_SYNTAX_MAP.deref().get(filename))
-    {
-        None => path
-            .extension()
-            .and_then(|x| x.to_str())
-            .and_then(|ext| SYNTAX_MAP.get().and_then(|m| m.get(ext).map(AsRef::as_ref))),
-        Some(s) => Some(s),
-    }
-}
-
 #[derive(Debug, Clone)]
 pub enum PreviewConfig {
     Number(u64),
@@ -169,14 +140,146 @@ impl PreviewConfig {
     }
 }
 
+/// Default interval between the picker updates until the render latency has been observed.
+const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Bounds of the adaptive update interval.
+const MIN_UPDATE_INTERVAL: Duration = Duration::from_millis(50);
+const MAX_UPDATE_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Multiple of the render latency between two updates, keeping Vim busy with rendering for a
+/// fraction of the time at most.
+const LATENCY_FACTOR: u32 = 4;
+
+/// Round-trip latency of rendering a picker update in Vim, from sending the update to Vim
+/// reporting back via `picker-rendered`.
+#[derive(Debug, Default)]
+struct RenderLatency {
+    next_render_id: AtomicU64,
+    /// Updates waiting for the report of Vim, only the latest few are tracked.
+    pending: parking_lot::Mutex<Vec<(u64, Instant)>>,
+    /// Exponential moving average of the latency in microseconds, 0 if not yet observed.
+    smoothed_micros: AtomicU64,
+}
+
+impl RenderLatency {
+    const MAX_PENDING: usize = 8;
+
+    fn on_sent(&self) -> u64 {
+        let render_id = self.next_render_id.fetch_add(1, Ordering::Relaxed);
+        let mut pending = self.pending.lock();
+        if pending.len() == Self::MAX_PENDING {
+            pending.remove(0);
+        }
+        pending.push((render_id, Instant::now()));
+        render_id
+    }
+
+    fn on_rendered(&self, render_id: u64) {
+        let sent_at = {
+            let mut pending = self.pending.lock();
+            let Some(index) = pending.iter().position(|(id, _)| *id == render_id) else {
+                return;
+            };
+            let (_, sent_at) = pending[index];
+            pending.drain(..=index);
+            sent_at
+        };
+
+        let sample = sent_at.elapsed().as_micros() as u64;
+        let smoothed = match self.smoothed_micros.load(Ordering::Relaxed) {
+            0 => sample,
+            old => (old * 3 + sample) / 4,
+        };
+        self.smoothed_micros
+            .store(smoothed.max(1), Ordering::Relaxed);
+    }
+
+    fn update_interval(&self) -> Duration {
+        match self.smoothed_micros.load(Ordering::Relaxed) {
+            0 => DEFAULT_UPDATE_INTERVAL,
+            micros => (Duration::from_micros(micros) * LATENCY_FACTOR)
+                .clamp(MIN_UPDATE_INTERVAL, MAX_UPDATE_INTERVAL),
+        }
+    }
+}
+
+/// Sends the filtering progress to Vim via `clap#state#update_picker`.
+///
+/// The partial results are sent as they come, only the lines changed since the last update are
+/// included in the message:
+///
+/// ```json
+/// {
+///   "matched": 10, "processed": 100, "partial": true,
+///   "start": 3, "lines": ["..."], "total_lines": 10,
+///   "indices": [[1, 2]], "icon_added": false, "truncated_map": {}, "render_id": 42
+/// }
+/// ```
+///
+/// `lines` replaces the displayed lines from `start` (0-based) and the display is truncated
+/// to `total_lines`. The line fields are absent if the lines are unchanged, `indices` always
+/// covers all the lines. `render_id` is sent back via `picker-rendered` once the update is
+/// rendered to measure the render latency.
 pub struct VimProgressor {
     vim: Vim,
     stopped: Arc<AtomicBool>,
+    last_lines: parking_lot::Mutex<Vec<String>>,
 }
 
 impl VimProgressor {
     pub fn new(vim: Vim, stopped: Arc<AtomicBool>) -> Self {
-        Self { vim, stopped }
+        Self {
+            vim,
+            stopped,
+            last_lines: Default::default(),
+        }
+    }
+
+    /// Returns the interval between the picker updates of this editor.
+    pub fn update_interval(&self) -> Duration {
+        self.vim.picker_update_interval()
+    }
+
+    fn update_picker(
+        &self,
+        display_lines: &DisplayLines,
+        total_matched: usize,
+        total_processed: usize,
+        partial: bool,
+    ) {
+        let DisplayLines {
+            lines,
+            indices,
+            truncated_map,
+            icon_added,
+        } = display_lines;
+
+        let mut last_lines = self.last_lines.lock();
+
+        let mut update = json!({
+            "matched": total_matched,
+            "processed": total_processed,
+            "partial": partial,
+            "indices": indices,
+            "icon_added": icon_added,
+            "truncated_map": truncated_map,
+            "render_id": self.vim.connection.render_latency.on_sent(),
+        });
+
+        if *last_lines != *lines {
+            let start = last_lines
+                .iter()
+                .zip(lines.iter())
+                .take_while(|(old, new)| old == new)
+                .count();
+            update["start"] = start.into();
+            update["lines"] = lines[start..].into();
+            update["total_lines"] = lines.len().into();
+            last_lines.clone_from(lines);
+        }
+
+        let _ = self.vim.exec("clap#state#update_picker", [update]);
     }
 }
 
@@ -201,10 +304,7 @@ impl ProgressUpdate<DisplayLines> for VimProgressor {
         if self.stopped.load(Ordering::Relaxed) {
             return;
         }
-        let _ = self.vim.exec(
-            "clap#state#process_progress_full",
-            json!([display_lines, total_matched, total_processed]),
-        );
+        self.update_picker(display_lines, total_matched, total_processed, true);
     }
 
     fn on_finished(
@@ -216,10 +316,7 @@ impl ProgressUpdate<DisplayLines> for VimProgressor {
         if self.stopped.load(Ordering::Relaxed) {
             return;
         }
-        let _ = self.vim.exec(
-            "clap#state#process_progress_full",
-            json!([display_lines, total_matched, total_processed]),
-        );
+        self.update_picker(&display_lines, total_matched, total_processed, false);
     }
 }
 
@@ -237,12 +334,84 @@ fn from_vim_bool(value: Value) -> bool {
 #[derive(Debug, Clone)]
 pub struct Vim {
     rpc_client: Arc<RpcClient>,
+    /// Client of the msgpack-rpc API of Neovim, available if attached.
+    nvim_client: Option<Arc<NvimClient>>,
+    connection: Arc<ConnectionState>,
+}
+
+/// State of the connection to an editor, each client of the shared daemon has its own.
+#[derive(Debug, Default)]
+struct ConnectionState {
+    render_latency: RenderLatency,
+    /// Map of file extension to vim syntax, initialized from the filetype detection of the
+    /// editor.
+    syntax_map: OnceCell<HashMap<String, String>>,
 }
 
 impl Vim {
     /// Constructs a [`Vim`].
     pub fn new(rpc_client: Arc<RpcClient>) -> Self {
-        Self { rpc_client }
+        Self {
+            rpc_client,
+            nvim_client: None,
+            connection: Default::default(),
+        }
+    }
+
+    /// Initializes the map of file extension to vim syntax of this editor from the output of
+    /// `autocmd filetypedetect`, returns the parsed map.
+    pub fn initialize_syntax_map<'a>(&self, output: &'a str) -> HashMap<&'a str, &'a str> {
+        let ext_map = parse_syntax_map(output);
+        let syntax_map = ext_map
+            .iter()
+            .map(|(k, v)| (String::from(*k), String::from(*v)))
+            .collect();
+        if self.connection.syntax_map.set(syntax_map).is_err() {
+            tracing::debug!("Syntax map has been initialized");
+        }
+        ext_map
+    }
+
+    /// Returns the value of `&syntax` for given path for the preview buffer highlight.
+    ///
+    /// Try the file name first and then the file extension.
+    pub fn preview_syntax(&self, path: &Path) -> Option<&str> {
+        match path
+            .file_name()
+            .and_then(|x| x.to_str())
+            .and_then(|filename| FILENAME_SYNTAX_MAP.deref().get(filename))
+        {
+            None => path.extension().and_then(|x| x.to_str()).and_then(|ext| {
+                self.connection
+                    .syntax_map
+                    .get()
+                    .and_then(|m| m.get(ext).map(AsRef::as_ref))
+            }),
+            Some(s) => Some(s),
+        }
+    }
+
+    /// Records that Vim has rendered the picker update `render_id`.
+    pub fn on_picker_rendered(&self, render_id: u64) {
+        self.connection.render_latency.on_rendered(render_id);
+    }
+
+    /// Returns the interval between the picker updates adapted to the observed render latency,
+    /// the fast terminals are updated more often while the slow remote sessions are not
+    /// flooded.
+    pub fn picker_update_interval(&self) -> Duration {
+        self.connection.render_latency.update_interval()
+    }
+
+    /// Attaches to the msgpack-rpc API of Neovim.
+    pub fn with_nvim_client(mut self, nvim_client: NvimClient) -> Self {
+        self.nvim_client.replace(Arc::new(nvim_client));
+        self
+    }
+
+    /// Returns the client of the Neovim API if maple is attached to Neovim.
+    pub fn nvim_client(&self) -> Option<&Arc<NvimClient>> {
+        self.nvim_client.as_ref()
     }
 
     /// Calls the method with given params in Vim and return the call result.
@@ -325,6 +494,25 @@ impl Vim {
         self.call("line", json![expr]).await
     }
 
+    pub async fn getbufline(&self, bufnr: usize, start: usize, end: usize) -> Result<Vec<String>> {
+        if let Some(nvim_client) = self.nvim_client() {
+            // The lines are read from the buffer directly, `start` is 0-based and `end` is
+            // exclusive in the Neovim API.
+            return nvim_client
+                .request(
+                    "nvim_buf_get_lines",
+                    json!([bufnr, start.saturating_sub(1), end, false]),
+                )
+                .await
+                .map_err(|e| anyhow!("RpcError: {e:?}"));
+        }
+        self.call("getbufline", json!([bufnr, start, end])).await
+    }
+
+    pub async fn getbufvar<R: DeserializeOwned>(&self, bufname: &str, varname: &str) -> Result<R> {
+        self.call("getbufvar", json!([bufname, varname])).await
+    }
+
     pub async fn getpos(&self, expr: &str) -> Result<[usize; 4]> {
         self.call("getpos", json![expr]).await
     }
@@ -372,6 +560,20 @@ impl Vim {
         }
     }
 
+    /// Returns all the lines in display window, with icon stripped.
+    pub async fn display_matched_lines(&self) -> Result<Vec<String>> {
+        let (lines, icon_added_by_maple): (Vec<String>, bool) =
+            self.bare_call("display_matched_lines").await?;
+        if icon_added_by_maple {
+            Ok(lines
+                .into_iter()
+                .map(|line| line.chars().skip(2).collect())
+                .collect())
+        } else {
+            Ok(lines)
+        }
+    }
+
     pub async fn display_getcurlnum(&self) -> Result<usize> {
         self.eval("g:clap.display.getcurlnum()").await
     }
@@ -397,13 +599,37 @@ impl Vim {
     }
 
     pub async fn current_buffer_path(&self) -> Result<String> {
-        self.bare_call("current_buffer_path").await
+        let path: String = self.bare_call("current_buffer_path").await?;
+        Ok(crate::paths::normalize_path(&path).into_owned())
+    }
+
+    /// Returns the absolute paths of the listed buffers backed by a file name.
+    pub async fn listed_buffer_paths(&self) -> Result<Vec<String>> {
+        let paths: Vec<String> = self
+            .eval(
+                "map(filter(range(1, bufnr('$')), 'buflisted(v:val) && !empty(bufname(v:val))'), \
+                 'fnamemodify(bufname(v:val), \":p\")')",
+            )
+            .await?;
+        Ok(paths
+            .iter()
+            .map(|path| crate::paths::normalize_path(path).into_owned())
+            .collect())
     }
 
     pub async fn curbufline(&self, lnum: usize) -> Result<Option<String>> {
         self.call("curbufline", json!([lnum])).await
     }
 
+    /// Returns the name of the syntax item at given position in current buffer and the name of
+    /// the highlight group it's eventually linked to.
+    pub async fn syntax_groups_at(&self, lnum: usize, col: usize) -> Result<[String; 2]> {
+        self.eval(&format!(
+            "[synIDattr(synID({lnum}, {col}, 1), 'name'), synIDattr(synIDtrans(synID({lnum}, {col}, 1)), 'name')]"
+        ))
+        .await
+    }
+
     pub fn set_preview_syntax(&self, syntax: &str) -> Result<()> {
         self.exec("eval", [format!("g:clap.preview.set_syntax('{syntax}')")])
     }
@@ -481,4 +707,23 @@ mod tests {
         let v: Value = serde_json::json!({"filer": 10, "files": 5});
         let _config: PreviewConfig = v.into();
     }
+
+    #[test]
+    fn test_render_latency_update_interval() {
+        let render_latency = RenderLatency::default();
+        assert_eq!(render_latency.update_interval(), DEFAULT_UPDATE_INTERVAL);
+
+        // Unknown render ids are ignored.
+        render_latency.on_rendered(42);
+        assert_eq!(render_latency.update_interval(), DEFAULT_UPDATE_INTERVAL);
+
+        let render_id = render_latency.on_sent();
+        render_latency.on_rendered(render_id);
+        assert_eq!(render_latency.update_interval(), MIN_UPDATE_INTERVAL);
+
+        render_latency
+            .smoothed_micros
+            .store(10_000_000, Ordering::Relaxed);
+        assert_eq!(render_latency.update_interval(), MAX_UPDATE_INTERVAL);
+    }
 }
@@ -0,0 +1,237 @@
+//! Detection of the external tools used by maple.
+//!
+//! The tools are probed once, on startup of the server or on the first use otherwise. The
+//! providers check [`is_available`] to fall back to the builtin implementations, the missing
+//! tools are reported to the user and the whole picture is exposed via the `capabilities` RPC
+//! request.
+
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::process::{Command, Stdio};
+
+/// External tool used by maple.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tool {
+    Rg,
+    Fd,
+    Ctags,
+    Git,
+    Gtags,
+}
+
+impl Tool {
+    pub const ALL: [Self; 5] = [Self::Rg, Self::Fd, Self::Ctags, Self::Git, Self::Gtags];
+
+    pub fn executable(&self) -> &'static str {
+        match self {
+            Self::Rg => "rg",
+            Self::Fd => "fd",
+            Self::Ctags => "ctags",
+            Self::Git => "git",
+            Self::Gtags => "gtags",
+        }
+    }
+
+    /// Returns what degrades if the tool is missing, `None` if the tool is merely optional and
+    /// not worth a notification.
+    fn missing_impact(&self) -> Option<&'static str> {
+        match self {
+            Self::Rg => Some("dumb_jump falls back to the builtin regex search"),
+            Self::Ctags => Some("no tags for dumb_jump, tags and proj_tags"),
+            Self::Git => Some("git_files, git_diff_files and the git blame are unavailable"),
+            Self::Fd | Self::Gtags => None,
+        }
+    }
+
+    /// Returns true if `first_line` of the `--version` output is from the expected tool.
+    fn is_expected(&self, first_line: &str) -> bool {
+        match self {
+            Self::Rg => first_line.starts_with("ripgrep"),
+            Self::Fd => first_line.starts_with("fd"),
+            // Exuberant Ctags lacks the features maple relies on.
+            Self::Ctags => first_line.starts_with("Universal Ctags"),
+            Self::Git => first_line.starts_with("git version"),
+            Self::Gtags => first_line.starts_with("gtags"),
+        }
+    }
+}
+
+/// Detected capabilities of a tool.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolInfo {
+    pub tool: Tool,
+    pub available: bool,
+    pub version: Option<String>,
+    /// Optional features the tool is built with, e.g., `json` of ctags, `pcre2` of rg.
+    pub features: Vec<String>,
+}
+
+impl ToolInfo {
+    fn missing(tool: Tool) -> Self {
+        Self {
+            tool,
+            available: false,
+            version: None,
+            features: Vec::new(),
+        }
+    }
+}
+
+static CAPABILITIES: Lazy<Vec<ToolInfo>> =
+    Lazy::new(|| Tool::ALL.par_iter().map(|tool| probe(*tool)).collect());
+
+/// Returns the capabilities of all the tools.
+pub fn capabilities() -> &'static [ToolInfo] {
+    &CAPABILITIES
+}
+
+pub fn tool_info(tool: Tool) -> &'static ToolInfo {
+    CAPABILITIES
+        .iter()
+        .find(|info| info.tool == tool)
+        .expect("All tools are probed")
+}
+
+pub fn is_available(tool: Tool) -> bool {
+    tool_info(tool).available
+}
+
+pub fn has_feature(tool: Tool, feature: &str) -> bool {
+    tool_info(tool).features.iter().any(|f| f == feature)
+}
+
+/// Returns the notice of the missing tools worth noting, `None` if there are none.
+pub fn missing_tools_notice() -> Option<String> {
+    let missing = capabilities()
+        .iter()
+        .filter(|info| !info.available)
+        .filter_map(|info| {
+            info.tool
+                .missing_impact()
+                .map(|impact| format!("{} ({impact})", info.tool.executable()))
+        })
+        .collect::<Vec<_>>();
+
+    if missing.is_empty() {
+        None
+    } else {
+        Some(format!("Missing tools: {}", missing.join(", ")))
+    }
+}
+
+fn output_of(program: &str, arg: &str) -> Option<String> {
+    let output = Command::new(program)
+        .arg(arg)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn probe(tool: Tool) -> ToolInfo {
+    let Some(version_output) = output_of(tool.executable(), "--version") else {
+        return ToolInfo::missing(tool);
+    };
+
+    let first_line = version_output.lines().next().unwrap_or_default();
+    if !tool.is_expected(first_line) {
+        tracing::debug!(?tool, first_line, "Unexpected executable");
+        return ToolInfo::missing(tool);
+    }
+
+    let features = match tool {
+        Tool::Rg => parse_rg_features(&version_output),
+        Tool::Ctags => output_of(tool.executable(), "--list-features")
+            .map(|output| parse_ctags_features(&output))
+            .unwrap_or_default(),
+        Tool::Fd | Tool::Git | Tool::Gtags => Vec::new(),
+    };
+
+    ToolInfo {
+        tool,
+        available: true,
+        version: parse_version(first_line),
+        features,
+    }
+}
+
+/// Parses the version from the first line of the `--version` output, e.g., `13.0.0` from
+/// `ripgrep 13.0.0 (rev af6b6c543b)`.
+fn parse_version(first_line: &str) -> Option<String> {
+    first_line
+        .split_whitespace()
+        .find(|token| token.starts_with(|c: char| c.is_ascii_digit()))
+        .map(|token| {
+            let end = token
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(token.len());
+            token[..end].trim_end_matches('.').to_string()
+        })
+}
+
+fn parse_rg_features(version_output: &str) -> Vec<String> {
+    // ripgrep 14: `features:+pcre2`, ripgrep 13: `PCRE2 10.40 is available`.
+    let has_pcre2 = version_output.lines().any(|line| {
+        line.split(|c: char| c == ',' || c == ':' || c.is_whitespace())
+            .any(|feature| feature == "+pcre2")
+            || (line.starts_with("PCRE2") && line.contains("is available"))
+    });
+    if has_pcre2 {
+        vec!["pcre2".into()]
+    } else {
+        Vec::new()
+    }
+}
+
+fn parse_ctags_features(list_features_output: &str) -> Vec<String> {
+    // The first line is the header `#NAME DESCRIPTION`.
+    list_features_output
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(Into::into)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        let cases = [
+            ("ripgrep 13.0.0 (rev af6b6c543b)", "13.0.0"),
+            (
+                "Universal Ctags 6.0.0(p6.0.20230101), Copyright (C) 2015-2022",
+                "6.0.0",
+            ),
+            ("git version 2.40.1.windows.1", "2.40.1"),
+            ("gtags (GNU GLOBAL) 6.6.9", "6.6.9"),
+            ("fd 8.7.0", "8.7.0"),
+        ];
+        for (first_line, version) in cases {
+            assert_eq!(parse_version(first_line).as_deref(), Some(version));
+        }
+        assert_eq!(parse_version("ripgrep"), None);
+    }
+
+    #[test]
+    fn test_parse_features() {
+        let rg_14 = "ripgrep 14.0.3\n\nfeatures:+pcre2\nsimd(compile):+SSE2,-SSSE3\n";
+        assert_eq!(parse_rg_features(rg_14), vec!["pcre2".to_string()]);
+        let rg_13 = "ripgrep 13.0.0\n-SIMD -AVX (compiled)\nPCRE2 is not available\n";
+        assert!(parse_rg_features(rg_13).is_empty());
+
+        let ctags = "#NAME DESCRIPTION\nwildcards can use glob matching\njson supports json format output\n";
+        assert_eq!(
+            parse_ctags_features(ctags),
+            vec!["wildcards".to_string(), "json".to_string()]
+        );
+    }
+}
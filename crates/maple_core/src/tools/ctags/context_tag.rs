@@ -1,8 +1,7 @@
 use super::BufferTag;
-use crate::tools::ctags::CTAGS_HAS_JSON_FEATURE;
+use crate::tooling::Tool;
 use rayon::prelude::*;
 use std::io::Result;
-use std::ops::Deref;
 use std::path::Path;
 use std::process::Stdio;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -95,7 +94,7 @@ fn find_context_tag(superset_tags: Vec<BufferTag>, at: usize) -> Option<BufferTa
 
 /// Async version of [`current_context_tag`].
 pub async fn current_context_tag_async(file: &Path, at: usize) -> Option<BufferTag> {
-    let superset_tags = if *CTAGS_HAS_JSON_FEATURE.deref() {
+    let superset_tags = if crate::tooling::has_feature(Tool::Ctags, "json") {
         let cmd = tokio_cmd_in_json_format(file);
         collect_superset_context_tags_async(cmd, BufferTag::from_ctags_json, at)
             .await
@@ -112,7 +111,7 @@ pub async fn current_context_tag_async(file: &Path, at: usize) -> Option<BufferT
 
 /// Returns the method/function context associated with line `at`.
 pub fn current_context_tag(file: &Path, at: usize) -> Option<BufferTag> {
-    let superset_tags = if *CTAGS_HAS_JSON_FEATURE.deref() {
+    let superset_tags = if crate::tooling::has_feature(Tool::Ctags, "json") {
         let cmd = subprocess_cmd_in_json_format(file);
         collect_superset_context_tags(cmd, BufferTag::from_ctags_json, at).ok()?
     } else {
@@ -127,7 +126,7 @@ pub fn buffer_tags_lines(
     file: impl AsRef<std::ffi::OsStr>,
     force_raw: bool,
 ) -> Result<Vec<String>> {
-    let (tags, max_name_len) = if *CTAGS_HAS_JSON_FEATURE.deref() && !force_raw {
+    let (tags, max_name_len) = if crate::tooling::has_feature(Tool::Ctags, "json") && !force_raw {
         let cmd = subprocess_cmd_in_json_format(file);
         collect_buffer_tags(cmd, BufferTag::from_ctags_json)?
     } else {
@@ -145,7 +144,7 @@ pub fn buffer_tag_items(
     file: impl AsRef<std::ffi::OsStr>,
     force_raw: bool,
 ) -> Result<Vec<Arc<dyn ClapItem>>> {
-    let (tags, max_name_len) = if *CTAGS_HAS_JSON_FEATURE.deref() && !force_raw {
+    let (tags, max_name_len) = if crate::tooling::has_feature(Tool::Ctags, "json") && !force_raw {
         let cmd = subprocess_cmd_in_json_format(file);
         collect_buffer_tags(cmd, BufferTag::from_ctags_json)?
     } else {
@@ -2,9 +2,12 @@ mod buffer_tag;
 mod context_tag;
 mod project_tag;
 
-use crate::dirs::PROJECT_DIRS;
+use crate::cache::Digest;
+use crate::datastore::CACHE_INFO_IN_MEMORY;
 use crate::paths::AbsPathBuf;
 use crate::process::ShellCommand;
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
 use itertools::Itertools;
 use once_cell::sync::Lazy;
 use rayon::prelude::*;
@@ -13,6 +16,7 @@ use std::hash::Hash;
 use std::io::{BufRead, BufReader, Error, ErrorKind, Result};
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use subprocess::{Exec, NullFile};
 
 pub use self::buffer_tag::{BufferTag, BufferTagItem};
@@ -32,7 +36,7 @@ pub static DEFAULT_EXCLUDE_OPT: Lazy<String> = Lazy::new(|| {
 
 /// Directory for the `tags` files.
 pub static CTAGS_TAGS_DIR: Lazy<PathBuf> = Lazy::new(|| {
-    let mut tags_dir = PROJECT_DIRS.data_dir().to_path_buf();
+    let mut tags_dir = crate::dirs::data_dir().to_path_buf();
     tags_dir.push("tags");
 
     std::fs::create_dir_all(&tags_dir).expect("Couldn't create tags directory for vim-clap");
@@ -40,40 +44,6 @@ pub static CTAGS_TAGS_DIR: Lazy<PathBuf> = Lazy::new(|| {
     tags_dir
 });
 
-pub static CTAGS_EXISTS: Lazy<bool> = Lazy::new(|| {
-    std::process::Command::new("ctags")
-        .arg("--version")
-        .stderr(std::process::Stdio::inherit())
-        .output()
-        .ok()
-        .and_then(|output| {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            stdout
-                .split('\n')
-                .next()
-                .map(|line| line.starts_with("Universal Ctags"))
-        })
-        .unwrap_or(false)
-});
-
-/// If the ctags executable supports `--output-format=json`.
-pub static CTAGS_HAS_JSON_FEATURE: Lazy<bool> = Lazy::new(|| {
-    fn detect_json_feature() -> std::io::Result<bool> {
-        let output = std::process::Command::new("ctags")
-            .arg("--list-features")
-            .stderr(std::process::Stdio::inherit())
-            .output()?;
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        if stdout.split('\n').any(|x| x.starts_with("json")) {
-            Ok(true)
-        } else {
-            Err(Error::new(ErrorKind::Other, "ctags has no +json feature"))
-        }
-    }
-
-    detect_json_feature().unwrap_or(false)
-});
-
 /// Used to specify the language when working with `readtags`.
 static LANG_MAPS: Lazy<HashMap<String, String>> = Lazy::new(|| {
     fn generate_lang_maps() -> Result<HashMap<String, String>> {
@@ -229,8 +199,8 @@ impl ProjectCtagsCommand {
     }
 
     pub fn with_cwd(cwd: PathBuf) -> Self {
-        let mut std_cmd = std::process::Command::new(Self::TAGS_CMD[0]);
-        std_cmd.current_dir(&cwd).args(&Self::TAGS_CMD[1..]).args(
+        let mut std_cmd = crate::process::wsl::command_in(Self::TAGS_CMD[0], &cwd);
+        std_cmd.args(&Self::TAGS_CMD[1..]).args(
             EXCLUDE
                 .split(',')
                 .map(|exclude| format!("--exclude={exclude}")),
@@ -298,6 +268,10 @@ impl ProjectCtagsCommand {
         }))
     }
 
+    pub fn cwd(&self) -> &Path {
+        &self.shell_cmd.cwd
+    }
+
     /// Returns a tuple of (total, cache_path) if the cache exists.
     pub fn ctags_cache(&self) -> Option<(usize, PathBuf)> {
         self.shell_cmd
@@ -305,6 +279,22 @@ impl ProjectCtagsCommand {
             .map(|digest| (digest.total, digest.cached_path))
     }
 
+    /// Returns the digest of the cache even if it's no longer usable.
+    pub fn cache_digest(&self) -> Option<Digest> {
+        CACHE_INFO_IN_MEMORY
+            .lock()
+            .to_digests()
+            .into_iter()
+            .find(|digest| digest.shell_cmd == self.shell_cmd)
+    }
+
+    /// Returns the files modified since the cache was created, `None` if there is no cache.
+    pub fn stale_files(&self) -> Option<Vec<PathBuf>> {
+        self.cache_digest().map(|digest| {
+            files_modified_since(&self.shell_cmd.cwd, SystemTime::from(digest.execution_time))
+        })
+    }
+
     /// Runs the command and writes the cache to the disk.
     #[allow(unused)]
     fn create_cache(&self) -> Result<(usize, PathBuf)> {
@@ -324,6 +314,9 @@ impl ProjectCtagsCommand {
     }
 
     /// Parallel version of `create_cache`.
+    ///
+    /// The cache digest has been stored once it returns, so that the cache created outside the
+    /// server, e.g., by `maple tags`, is not lost on exit.
     pub fn par_create_cache(&mut self) -> Result<(usize, PathBuf)> {
         // TODO: do not store all the output in memory and redirect them to a file directly.
         let lines = self.par_formatted_lines()?;
@@ -333,7 +326,7 @@ impl ProjectCtagsCommand {
         let cache_path = self
             .shell_cmd
             .clone()
-            .write_cache(total, lines.as_bytes())?;
+            .write_cache_blocking(total, lines.as_bytes())?;
 
         Ok((total, cache_path))
     }
@@ -355,6 +348,70 @@ impl ProjectCtagsCommand {
     }
 }
 
+/// Returns the files under `dir` modified after `since`.
+///
+/// The ignored files and the ones matching [`EXCLUDE`] are skipped as they are unlikely to be
+/// of interest for the tags.
+pub fn files_modified_since(dir: &Path, since: SystemTime) -> Vec<PathBuf> {
+    let mut override_builder = OverrideBuilder::new(dir);
+    for exclude in EXCLUDE.split(',') {
+        if let Err(err) = override_builder.add(&format!("!{exclude}")) {
+            tracing::error!(?err, exclude, "Invalid exclude pattern");
+        }
+    }
+
+    // The project index, if ready, saves walking the whole project.
+    let indexed_files = crate::config::try_config().and_then(|config| {
+        let walk_config = config.walker.walk_config(Some("indexer"));
+        crate::indexer::project_files(dir, &walk_config)
+    });
+    if let Some(indexed_files) = indexed_files {
+        let overrides = override_builder.build().ok();
+        // The excluded directories are skipped by the walker, check the parents as well.
+        let is_excluded = |path: &Path| {
+            overrides.as_ref().map_or(false, |overrides| {
+                path.ancestors()
+                    .take_while(|p| p.starts_with(dir) && *p != dir)
+                    .enumerate()
+                    .any(|(i, p)| overrides.matched(p, i > 0).is_ignore())
+            })
+        };
+        let mut files = indexed_files
+            .into_iter()
+            .map(|file| dir.join(file))
+            .filter(|path| {
+                !is_excluded(path)
+                    && std::fs::metadata(path)
+                        .and_then(|metadata| metadata.modified())
+                        .map_or(false, |modified| modified > since)
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        return files;
+    }
+
+    let mut walk_builder = WalkBuilder::new(dir);
+    if let Ok(overrides) = override_builder.build() {
+        walk_builder.overrides(overrides);
+    }
+
+    let mut files = walk_builder
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry.file_type().map_or(false, |ft| ft.is_file())
+                && entry
+                    .metadata()
+                    .ok()
+                    .and_then(|metadata| metadata.modified().ok())
+                    .map_or(false, |modified| modified > since)
+        })
+        .map(|entry| entry.into_path())
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
 // /pattern/, /^pattern$/
 pub fn trim_pattern(pattern: &str) -> &str {
     let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
@@ -365,3 +422,24 @@ pub fn trim_pattern(pattern: &str) -> &str {
 
     pattern.trim()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_files_modified_since() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = tempdir.path();
+        std::fs::create_dir_all(dir.join("node_modules")).unwrap();
+        std::fs::write(dir.join("lib.rs"), "fn foo() {}").unwrap();
+        std::fs::write(dir.join("data.json"), "{}").unwrap();
+        std::fs::write(dir.join("node_modules").join("index.js"), "").unwrap();
+
+        let files = files_modified_since(dir, SystemTime::UNIX_EPOCH);
+        assert_eq!(files, vec![dir.join("lib.rs")]);
+
+        let since = SystemTime::now() + std::time::Duration::from_secs(60);
+        assert!(files_modified_since(dir, since).is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+use std::path::{Path, PathBuf};
+
+/// Returns the git directory of the repo containing `dir`.
+///
+/// The `.git` file of worktree and submodule is followed to the actual git directory.
+fn find_git_dir(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors().find_map(|ancestor| {
+        let dot_git = ancestor.join(".git");
+        if dot_git.is_dir() {
+            Some(dot_git)
+        } else if dot_git.is_file() {
+            let content = std::fs::read_to_string(&dot_git).ok()?;
+            let git_dir = content.strip_prefix("gitdir:")?.trim();
+            Some(ancestor.join(git_dir))
+        } else {
+            None
+        }
+    })
+}
+
+/// Returns the commit id of `reference` by looking up the loose ref and then `packed-refs`.
+fn resolve_ref(git_dir: &Path, reference: &str) -> Option<String> {
+    // The refs of worktree are stored in the common git directory.
+    let common_dir = std::fs::read_to_string(git_dir.join("commondir"))
+        .map(|common_dir| git_dir.join(common_dir.trim()))
+        .unwrap_or_else(|_| git_dir.to_path_buf());
+
+    [git_dir, common_dir.as_path()]
+        .iter()
+        .find_map(|dir| std::fs::read_to_string(dir.join(reference)).ok())
+        .map(|commit| commit.trim().to_string())
+        .or_else(|| {
+            std::fs::read_to_string(common_dir.join("packed-refs"))
+                .ok()?
+                .lines()
+                .find_map(|line| {
+                    let (commit, name) = line.split_once(' ')?;
+                    (name == reference).then(|| commit.to_string())
+                })
+        })
+}
+
+/// Returns the commit id of HEAD of the repo containing `dir`, `None` if `dir` is not in a git
+/// repo.
+///
+/// The git files are read directly, which is much cheaper than running `git rev-parse HEAD`.
+pub fn head_commit(dir: &Path) -> Option<String> {
+    let git_dir = find_git_dir(dir)?;
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    match head.trim().strip_prefix("ref:") {
+        Some(reference) => resolve_ref(&git_dir, reference.trim()),
+        // Detached HEAD.
+        None => Some(head.trim().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_head_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().to_path_buf();
+        let git_dir = repo.join(".git");
+        std::fs::create_dir_all(git_dir.join("refs").join("heads")).unwrap();
+        let sub_dir = repo.join("src");
+        std::fs::create_dir_all(&sub_dir).unwrap();
+
+        let commit = "1f1b0c7e0c5a1ea8b43c2d8e7a5bc0bd6b3c1a9e";
+        let other_commit = "8a4e1f1c1d5a6d31cbe2a2e30d0f2ca4f0b0a4d2";
+
+        std::fs::write(git_dir.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        std::fs::write(
+            git_dir.join("packed-refs"),
+            format!("# pack-refs with: peeled fully-peeled sorted\n{commit} refs/heads/main\n"),
+        )
+        .unwrap();
+        assert_eq!(head_commit(&sub_dir), Some(commit.to_string()));
+
+        // The loose ref takes precedence over the packed one.
+        std::fs::write(
+            git_dir.join("refs").join("heads").join("main"),
+            format!("{other_commit}\n"),
+        )
+        .unwrap();
+        assert_eq!(head_commit(&sub_dir), Some(other_commit.to_string()));
+
+        // Detached HEAD.
+        std::fs::write(git_dir.join("HEAD"), format!("{commit}\n")).unwrap();
+        assert_eq!(head_commit(&repo), Some(commit.to_string()));
+    }
+}
@@ -1,30 +1,11 @@
-use crate::dirs::PROJECT_DIRS;
 use once_cell::sync::Lazy;
 use std::path::PathBuf;
 
-pub static GTAGS_EXISTS: Lazy<bool> = Lazy::new(|| gtags_executable_exists().unwrap_or(false));
-
 /// Directory for `GTAGS`/`GRTAGS`.
 pub static GTAGS_DIR: Lazy<PathBuf> = Lazy::new(|| {
-    let gtags_dir = PROJECT_DIRS.data_dir().join("gtags");
+    let gtags_dir = crate::dirs::data_dir().join("gtags");
 
     std::fs::create_dir_all(&gtags_dir).expect("Couldn't create gtags directory for vim-clap");
 
     gtags_dir
 });
-
-fn gtags_executable_exists() -> std::io::Result<bool> {
-    let output = std::process::Command::new("gtags")
-        .arg("--version")
-        .stderr(std::process::Stdio::inherit())
-        .output()?;
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    if let Some(line) = stdout.split('\n').next() {
-        Ok(line.starts_with("gtags"))
-    } else {
-        Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "gtags executable not found",
-        ))
-    }
-}
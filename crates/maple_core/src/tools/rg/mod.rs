@@ -14,16 +14,7 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 use utils::display_width;
 
-pub use self::jsont::{Match, Message, SubMatch};
-
-pub static RG_EXISTS: Lazy<bool> = Lazy::new(|| {
-    std::process::Command::new("rg")
-        .arg("--version")
-        .stdout(std::process::Stdio::null())
-        .status()
-        .map(|exit_status| exit_status.success())
-        .unwrap_or(false)
-});
+pub use self::jsont::{Data, Match, Message, SubMatch};
 
 /// Map of file extension to ripgrep language.
 ///
@@ -311,9 +302,9 @@ impl RgTokioCommand {
 
 pub fn rg_command<P: AsRef<Path>>(dir: P) -> Command {
     // Can not use StdCommand as it joins the args which does not work somehow.
-    let mut cmd = Command::new(RG_ARGS[0]);
+    let mut cmd = crate::process::wsl::command_in(RG_ARGS[0], dir.as_ref());
     // Do not use --vimgrep here.
-    cmd.args(&RG_ARGS[1..]).current_dir(dir);
+    cmd.args(&RG_ARGS[1..]);
     cmd
 }
 
@@ -245,7 +245,7 @@ impl Matcher {
 
         // Apply the word matcher against the line content.
         if !word_indices.is_empty() {
-            fuzzy_score += word_score;
+            fuzzy_score = fuzzy_score.saturating_add(word_score);
             fuzzy_indices.extend(word_indices)
         }
 
@@ -259,7 +259,7 @@ impl Matcher {
             exact_indices.sort_unstable();
             exact_indices.dedup();
 
-            let score = exact_score + bonus_score;
+            let score = exact_score.saturating_add(bonus_score);
 
             if exact_indices_in_path {
                 (score, exact_indices, Vec::new())
@@ -274,7 +274,10 @@ impl Matcher {
                 .bonus_matcher
                 .calc_text_bonus(line, fuzzy_score, &fuzzy_indices);
 
-            let score = exact_score + bonus_score + fuzzy_score;
+            // The perfect fuzzy match is scored as the maximum.
+            let score = exact_score
+                .saturating_add(bonus_score)
+                .saturating_add(fuzzy_score);
 
             if exact_indices_in_path {
                 (score, exact_indices, fuzzy_indices)
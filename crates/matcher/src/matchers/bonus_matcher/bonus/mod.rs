@@ -1,11 +1,13 @@
 pub mod cwd;
 pub mod filename;
 pub mod language;
+pub mod preferred;
 pub mod recent_files;
 
 use self::cwd::Cwd;
 use self::filename::calc_bonus_file_name;
 use self::language::Language;
+use self::preferred::Preferred;
 use self::recent_files::RecentFiles;
 use crate::Score;
 use std::sync::Arc;
@@ -23,6 +25,10 @@ pub enum Bonus {
     /// Give a bonus if the item is in the list of recently opened files.
     RecentFiles(RecentFiles),
 
+    /// Give the bonus of the item in the map of preferred items regardless of the base score,
+    /// e.g., the open buffers and the frecent files.
+    Preferred(Preferred),
+
     /// Give a bonus if the item is a file path and the matches are in the file name.
     ///
     /// Ref https://github.com/liuchengxu/vim-clap/issues/561
@@ -63,6 +69,7 @@ impl Bonus {
             Self::Cwd(cwd) => cwd.calc_bonus(bonus_text, score),
             Self::Language(language) => language.calc_bonus(bonus_text, score),
             Self::RecentFiles(recent_files) => recent_files.calc_bonus(bonus_text, score),
+            Self::Preferred(preferred) => preferred.calc_bonus(bonus_text),
             Self::FileName => calc_bonus_file_name(bonus_text, score, indices),
             Self::None => 0,
         }
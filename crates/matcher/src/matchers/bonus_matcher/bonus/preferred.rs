@@ -0,0 +1,19 @@
+use crate::Score;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Bonus scores of the preferred items, keyed by the bonus text.
+#[derive(Debug, Clone, Default)]
+pub struct Preferred(Arc<HashMap<String, Score>>);
+
+impl From<HashMap<String, Score>> for Preferred {
+    fn from(inner: HashMap<String, Score>) -> Self {
+        Self(Arc::new(inner))
+    }
+}
+
+impl Preferred {
+    pub fn calc_bonus(&self, bonus_text: &str) -> Score {
+        self.0.get(bonus_text).copied().unwrap_or_default()
+    }
+}
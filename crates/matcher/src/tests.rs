@@ -1,5 +1,6 @@
 use super::*;
 use crate::algo::fzy;
+use std::collections::HashMap;
 use types::SourceItem;
 
 #[test]
@@ -102,6 +103,26 @@ fn test_language_keyword_bonus() {
     assert!(matched_item1.rank < matched_item2.rank);
 }
 
+#[test]
+fn test_preferred_bonus() {
+    let lines = vec!["src/foo.rs", "src/fob.rs"];
+    let query: Query = "fo".into();
+    let preferred = [("src/fob.rs".to_string(), 100)]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+    let matcher = MatcherBuilder::new()
+        .bonuses(vec![Bonus::Preferred(preferred.into())])
+        .build(query);
+    let matched_item1 = matcher
+        .match_item(Arc::new(lines[0]) as Arc<dyn ClapItem>)
+        .unwrap();
+    let matched_item2 = matcher
+        .match_item(Arc::new(lines[1]) as Arc<dyn ClapItem>)
+        .unwrap();
+    assert!(matched_item1.indices == matched_item2.indices);
+    assert!(matched_item1.rank < matched_item2.rank);
+}
+
 #[test]
 fn test_exact_search_term_bonus() {
     let lines = vec!["function foo qwer", "function foo"];
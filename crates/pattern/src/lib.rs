@@ -182,6 +182,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_grep_regex_windows_path() {
+        let line = r#"C:\src\vim-clap\crates\pattern\src\lib.rs:36:1:let path = "D:\foo:1:2:";"#;
+        let (fpath, lnum, col, line_content) = extract_grep_position(line).unwrap();
+        assert_eq!(fpath, r#"C:\src\vim-clap\crates\pattern\src\lib.rs"#);
+        assert_eq!((lnum, col), (36, 1));
+        assert_eq!(line_content, r#"let path = "D:\foo:1:2:";"#);
+
+        let (end_of_path, start_of_line) = parse_grep_item(line).unwrap();
+        assert_eq!(&line[..end_of_path], fpath);
+        assert_eq!(&line[start_of_line..], line_content);
+
+        let line = r#"\\server\share\lib.rs:2:3:fn main() {}"#;
+        assert_eq!(
+            extract_grep_file_path(line).unwrap(),
+            r#"\\server\share\lib.rs"#
+        );
+    }
+
     #[test]
     fn test_dumb_jump_line() {
         let line = "[variable]crates/maple_cli/src/stdio_server/session/context.rs:36:8:        let cwd = msg.get_cwd().into();";
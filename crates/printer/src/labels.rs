@@ -0,0 +1,39 @@
+//! Labels of the quick-select mode, overlaid on the visible entries so that each entry can be
+//! accepted by typing its label.
+
+/// Characters of the labels, the home row keys come first.
+pub const LABEL_CHARS: &str = "asdfghjklqwertyuiopzxcvbnm";
+
+/// Returns the labels of `count` entries, from the top to the bottom.
+///
+/// The labels are single chars if there are enough of them, otherwise all the labels are two
+/// chars so that no label is the prefix of another. At most `chars^2` labels are returned.
+pub fn quick_select_labels(count: usize, chars: &str) -> Vec<String> {
+    let chars = chars.chars().collect::<Vec<_>>();
+
+    if count <= chars.len() {
+        return chars[..count].iter().map(|c| c.to_string()).collect();
+    }
+
+    chars
+        .iter()
+        .flat_map(|first| chars.iter().map(move |second| format!("{first}{second}")))
+        .take(count)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quick_select_labels() {
+        assert_eq!(quick_select_labels(3, "asd"), vec!["a", "s", "d"]);
+        assert_eq!(
+            quick_select_labels(5, "asd"),
+            vec!["aa", "as", "ad", "sa", "ss"]
+        );
+        assert_eq!(quick_select_labels(20, "asd").len(), 9);
+        assert_eq!(quick_select_labels(40, LABEL_CHARS).len(), 40);
+    }
+}
@@ -1,19 +1,24 @@
 //! This crate provides the feature of diplaying the information of filtered lines
 //! by printing them to stdout in JSON format.
 
+mod labels;
+mod path_display;
 mod trimmer;
 mod truncation;
 
-use self::truncation::LinesTruncatedMap;
 use icon::{Icon, ICON_CHAR_LEN};
 use serde::Serialize;
 use std::path::PathBuf;
+use std::slice::IterMut;
 use truncation::truncate_grep_results;
 use types::MatchedItem;
 
+pub use self::labels::{quick_select_labels, LABEL_CHARS};
+pub use self::path_display::PathDisplay;
 pub use self::trimmer::v1::{trim_text, TrimInfo, TrimmedText};
 pub use self::truncation::{
     truncate_grep_lines, truncate_item_output_text, truncate_item_output_text_v0,
+    LinesTruncatedMap,
 };
 
 /// Combine json and println macro.
@@ -147,11 +152,49 @@ fn convert_truncated_matched_items_to_display_lines(
     }
 }
 
+/// Applies `path_display` to the path of `matched_item` in the first `path_len` bytes.
+///
+/// Returns the original output text if the path is displayed differently.
+fn apply_path_display(
+    matched_item: &mut MatchedItem,
+    path_display: &PathDisplay,
+    path_len: impl FnOnce(&str) -> usize,
+) -> Option<String> {
+    let output_text = matched_item.output_text().into_owned();
+    let (displayed, positions) = path_display.display(&output_text, path_len(&output_text))?;
+    matched_item.indices = matched_item
+        .indices
+        .iter()
+        .filter_map(|idx| positions.get(*idx).copied().flatten())
+        .collect();
+    matched_item.output_text.replace(displayed);
+    Some(output_text)
+}
+
+/// Applies `path_display` to the items whose output texts are file paths.
+///
+/// Returns the original output texts of the items displayed differently, which are sent along
+/// with the truncated lines so that the real paths are used on selection.
+fn apply_path_display_to_items(
+    matched_items: IterMut<MatchedItem>,
+    path_display: &PathDisplay,
+) -> LinesTruncatedMap {
+    matched_items
+        .enumerate()
+        .filter_map(|(idx, matched_item)| {
+            apply_path_display(matched_item, path_display, str::len)
+                .map(|original| (idx + 1, original))
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone)]
 pub struct Printer {
     pub line_width: usize,
     pub icon: Icon,
     pub truncate_text: bool,
+    /// Style of displaying the items, which must be file paths if specified.
+    pub path_display: Option<PathDisplay>,
 }
 
 impl Printer {
@@ -161,24 +204,77 @@ impl Printer {
             line_width,
             icon,
             truncate_text: true,
+            path_display: None,
         }
     }
 
+    pub fn with_path_display(mut self, path_display: Option<PathDisplay>) -> Self {
+        self.path_display = path_display;
+        self
+    }
+
     pub fn to_display_lines(&self, mut matched_items: Vec<MatchedItem>) -> DisplayLines {
         let Self {
             line_width,
             icon,
             truncate_text,
+            path_display,
         } = self;
 
-        let truncated_map = if *truncate_text {
+        let original_texts = match path_display {
+            Some(path_display) => {
+                apply_path_display_to_items(matched_items.iter_mut(), path_display)
+            }
+            None => Default::default(),
+        };
+
+        let mut truncated_map = if *truncate_text {
             truncate_item_output_text(matched_items.iter_mut(), *line_width, None)
         } else {
             Default::default()
         };
+        truncated_map.extend(original_texts);
 
         convert_truncated_matched_items_to_display_lines(matched_items, *icon, truncated_map)
     }
+
+    /// Converts the first `visible` items to [`DisplayLines`].
+    ///
+    /// The icon, truncation and highlight offsets are only applied to the lines actually sent
+    /// to the display window instead of all the matched items.
+    pub fn to_visible_display_lines(
+        &self,
+        matched_items: &[MatchedItem],
+        visible: usize,
+    ) -> DisplayLines {
+        self.to_display_lines(matched_items.iter().take(visible).cloned().collect())
+    }
+
+    /// Converts the `count` items starting from `start` to [`DisplayLines`], which are appended
+    /// to the display window on scrolling to the bottom.
+    ///
+    /// The line numbers in the truncated map are relative to the whole display window.
+    pub fn to_page_display_lines(
+        &self,
+        matched_items: &[MatchedItem],
+        start: usize,
+        count: usize,
+    ) -> DisplayLines {
+        let mut display_lines = self.to_display_lines(
+            matched_items
+                .iter()
+                .skip(start)
+                .take(count)
+                .cloned()
+                .collect(),
+        );
+        display_lines.truncated_map = display_lines
+            .truncated_map
+            .into_iter()
+            .map(|(lnum, line)| (lnum + start, line))
+            .collect();
+        display_lines
+    }
 }
 
 #[derive(Debug)]
@@ -195,8 +291,26 @@ pub fn grep_results_to_display_lines(
     mut grep_results: Vec<GrepResult>,
     line_width: usize,
     icon: Icon,
+    path_display: Option<&PathDisplay>,
 ) -> DisplayLines {
-    let truncated_map = truncate_grep_results(grep_results.iter_mut(), line_width, None);
+    let mut original_texts = LinesTruncatedMap::new();
+    if let Some(path_display) = path_display {
+        for (idx, grep_result) in grep_results.iter_mut().enumerate() {
+            let path = grep_result.path.to_string_lossy().into_owned();
+            if let Some(original) =
+                apply_path_display(&mut grep_result.matched_item, path_display, |_| path.len())
+            {
+                let output_text = grep_result.matched_item.output_text();
+                let displayed_path_len = output_text.len() - (original.len() - path.len());
+                grep_result.path = output_text[..displayed_path_len].into();
+                grep_result.column_end = grep_result.column_end - path.len() + displayed_path_len;
+                original_texts.insert(idx + 1, original);
+            }
+        }
+    }
+
+    let mut truncated_map = truncate_grep_results(grep_results.iter_mut(), line_width, None);
+    truncated_map.extend(original_texts);
     convert_truncated_matched_items_to_display_lines(
         grep_results.into_iter().map(|i| i.matched_item),
         icon,
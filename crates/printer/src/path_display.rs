@@ -0,0 +1,185 @@
+/// Style of displaying the file paths, the original paths are kept in the truncated map so that
+/// the selection still resolves to the real files.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathDisplay {
+    /// Paths under it are abbreviated to `~`.
+    home: Option<String>,
+    /// Paths under it are displayed relative to it.
+    root: Option<String>,
+    /// Whether to shorten the parent directories to their first chars, e.g., `s/c/p/file.rs`.
+    shorten: bool,
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || c == std::path::MAIN_SEPARATOR
+}
+
+/// Returns the number of chars of `dir` if `path` is under it.
+fn dir_prefix_len(path: &str, dir: &str) -> Option<usize> {
+    let rest = path.strip_prefix(dir)?;
+    rest.starts_with(is_separator).then(|| dir.chars().count())
+}
+
+impl PathDisplay {
+    pub fn new(home: Option<String>, root: Option<String>, shorten: bool) -> Self {
+        let trim = |dir: String| dir.trim_end_matches(is_separator).to_string();
+        Self {
+            home: home.map(trim).filter(|dir| !dir.is_empty()),
+            root: root.map(trim).filter(|dir| !dir.is_empty()),
+            shorten,
+        }
+    }
+
+    /// Displays the path at the beginning of `text`, which is `path_len` bytes long.
+    ///
+    /// Returns the new text and the new char positions of the original chars, `None` for the
+    /// chars no longer displayed, or `None` if the text is not changed.
+    pub fn display(&self, text: &str, path_len: usize) -> Option<(String, Vec<Option<usize>>)> {
+        let (path, rest) = text.split_at(path_len);
+        let chars = path.chars().collect::<Vec<_>>();
+
+        let mut displayed = String::with_capacity(text.len());
+        let mut positions = vec![None; chars.len()];
+
+        // Number of the leading chars replaced by the root or `~`.
+        let skipped = match self
+            .root
+            .as_deref()
+            .and_then(|root| dir_prefix_len(path, root))
+        {
+            // The separator following the root is skipped too.
+            Some(root_len) => root_len + 1,
+            None => match self
+                .home
+                .as_deref()
+                .and_then(|home| dir_prefix_len(path, home))
+            {
+                Some(home_len) => {
+                    displayed.push('~');
+                    home_len
+                }
+                None => 0,
+            },
+        };
+
+        let remaining = &chars[skipped..];
+        // The file name is always displayed in full.
+        let last_separator = if self.shorten {
+            remaining.iter().rposition(|c| is_separator(*c))
+        } else {
+            None
+        };
+        let has_drive = remaining.get(1) == Some(&':');
+
+        let mut component_index = 0;
+        let mut pos_in_component = 0;
+        let mut dot_prefixed = false;
+
+        for (i, c) in remaining.iter().enumerate() {
+            let keep = match last_separator {
+                Some(last_separator) if i < last_separator => {
+                    if is_separator(*c) {
+                        component_index += 1;
+                        pos_in_component = 0;
+                        true
+                    } else {
+                        if pos_in_component == 0 {
+                            dot_prefixed = *c == '.';
+                        }
+                        pos_in_component += 1;
+                        // Keep `.c` for `.config` and the drive letter.
+                        pos_in_component == 1
+                            || (pos_in_component == 2 && dot_prefixed)
+                            || (component_index == 0 && has_drive)
+                    }
+                }
+                _ => true,
+            };
+
+            if keep {
+                positions[skipped + i] = Some(displayed.chars().count());
+                displayed.push(*c);
+            }
+        }
+
+        if displayed == path {
+            return None;
+        }
+
+        let shift = displayed.chars().count();
+        let rest_chars = rest.chars().count();
+        positions.extend((0..rest_chars).map(|i| Some(shift + i)));
+        displayed.push_str(rest);
+
+        Some((displayed, positions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display(path_display: &PathDisplay, text: &str) -> Option<String> {
+        path_display
+            .display(text, text.len())
+            .map(|(displayed, _)| displayed)
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_path_display() {
+        let home = Some("/home/xlc".to_string());
+        let root = Some("/home/xlc/src/vim-clap".to_string());
+
+        let tilde = PathDisplay::new(home.clone(), None, false);
+        assert_eq!(
+            display(&tilde, "/home/xlc/.vimrc").as_deref(),
+            Some("~/.vimrc")
+        );
+        assert_eq!(display(&tilde, "/home/xlcx/.vimrc"), None);
+
+        let relative = PathDisplay::new(home.clone(), root.clone(), false);
+        assert_eq!(
+            display(
+                &relative,
+                "/home/xlc/src/vim-clap/crates/printer/src/lib.rs"
+            )
+            .as_deref(),
+            Some("crates/printer/src/lib.rs")
+        );
+        assert_eq!(
+            display(&relative, "/home/xlc/.config/nvim/init.lua").as_deref(),
+            Some("~/.config/nvim/init.lua")
+        );
+
+        let shorten = PathDisplay::new(home, root, true);
+        assert_eq!(
+            display(&shorten, "/home/xlc/src/vim-clap/crates/printer/src/lib.rs").as_deref(),
+            Some("c/p/s/lib.rs")
+        );
+        assert_eq!(
+            display(&shorten, "/home/xlc/.config/nvim/init.lua").as_deref(),
+            Some("~/.c/n/init.lua")
+        );
+        assert_eq!(display(&shorten, "lib.rs"), None);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_path_display_positions() {
+        let shorten = PathDisplay::new(None, None, true);
+        // Grep line, the path is followed by `:line:column:text`.
+        let text = "crates/printer/lib.rs:1:1:mod tests;";
+        let (displayed, positions) = shorten
+            .display(text, "crates/printer/lib.rs".len())
+            .unwrap();
+        assert_eq!(displayed, "c/p/lib.rs:1:1:mod tests;");
+        // `c` of `crates` and `p` of `printer`.
+        assert_eq!(positions[0], Some(0));
+        assert_eq!(positions[1], None);
+        assert_eq!(positions[7], Some(2));
+        // `l` of `lib.rs` and `m` of `mod`.
+        assert_eq!(positions[15], Some(4));
+        assert_eq!(positions[text.find("mod").unwrap()], displayed.find("mod"));
+    }
+}
@@ -111,6 +111,19 @@ pub fn truncate_item_output_text(
     truncated_map
 }
 
+/// Returns the file name of `path`, the char position of the file name and the separator before
+/// it, which can be either `/` or `\` on Windows.
+fn file_name_position(path: &str) -> Option<(&str, usize, char)> {
+    let (file_name, file_name_start) = pattern::extract_file_name(path)?;
+    let parent = &path[..file_name_start];
+    let separator = parent
+        .chars()
+        .last()
+        .filter(|c| *c == '/' || *c == '\\')
+        .unwrap_or(MAIN_SEPARATOR);
+    Some((file_name, parent.chars().count(), separator))
+}
+
 /// Truncate the output text of item if it's too long.
 ///
 /// # Arguments
@@ -148,16 +161,16 @@ pub fn truncate_grep_results(
             // Adjust the trimmed text further.
             let (better_trimmed_text, indices) = match trim_info.left_trim_start() {
                 Some(start) => {
-                    match grep_result.path.to_str().and_then(pattern::extract_file_name) {
-                        Some((file_name, file_name_start)) if start > file_name_start => {
+                    match grep_result.path.to_str().and_then(file_name_position) {
+                        Some((file_name, file_name_start, separator)) if start > file_name_start => {
                             let line_number = grep_result.line_number;
                             let column = grep_result.column;
                             let column_end = grep_result.column_end;
 
-                            // dots + MAIN_SEPARATOR
+                            // dots + separator
                             let mut offset = UnicodeDots::CHAR_LEN
                                 + 1
-                                + file_name.len()
+                                + file_name.chars().count()
                                 + utils::display_width(line_number)
                                 + utils::display_width(column)
                                 + 2; // : + :
@@ -170,9 +183,9 @@ pub fn truncate_grep_results(
                                 let trimmed_pattern = trimmed_text_chars.as_str();
                                 offset -= column_end - start;
 
-                                format!("{}{MAIN_SEPARATOR}{file_name}:{line_number}:{column}{trimmed_pattern}", UnicodeDots::DOTS)
+                                format!("{}{separator}{file_name}:{line_number}:{column}{trimmed_pattern}", UnicodeDots::DOTS)
                             } else {
-                                format!("{}{MAIN_SEPARATOR}{file_name}:{line_number}:{column}{trimmed_text}", UnicodeDots::DOTS)
+                                format!("{}{separator}{file_name}:{line_number}:{column}{trimmed_text}", UnicodeDots::DOTS)
                             };
 
                             let mut indices = indices;
@@ -257,6 +270,24 @@ mod tests {
     use std::sync::Arc;
     use types::ClapItem;
 
+    #[test]
+    fn test_file_name_position() {
+        assert_eq!(
+            file_name_position("/home/用户/src/lib.rs"),
+            Some(("lib.rs", 13, '/'))
+        );
+        #[cfg(windows)]
+        assert_eq!(
+            file_name_position(r"C:\Users\用户\lib.rs"),
+            Some(("lib.rs", 12, '\\'))
+        );
+        #[cfg(windows)]
+        assert_eq!(
+            file_name_position("C:/Users/用户/lib.rs"),
+            Some(("lib.rs", 12, '/'))
+        );
+    }
+
     #[test]
     fn test_grep_print() {
         // GrepResult { matched_item: MatchedItem { item: "crates/maple_core/src/paths.rs:198:31:let expected = \"~/.rustup/.../src/rust/library/alloc/src/string.rs\";", rank: [874, -30, -68, 0], indices: [68, 69, 77, 91, 92], display_text: None, output_text: None }, path: "/home/xlc/.vim/plugged/vim-clap/crates/maple_core/src/paths.rs", line_number: 198, line_number_start: 32, line_number_end: 35, column: 31, column_start: 36, column_end: 38 }, winwidth: 62, icon: Enabled(Grep)
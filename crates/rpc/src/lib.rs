@@ -1,14 +1,17 @@
+mod nvim;
 mod types;
 
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::io::{BufRead, Write};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
 use tokio::sync::mpsc::error::SendError;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 use tokio::sync::oneshot;
 
+pub use self::nvim::NvimClient;
 pub use self::types::{
     Error, ErrorCode, Failure, Params, RpcMessage, RpcNotification, RpcRequest, RpcResponse,
     Success, VimMessage,
@@ -22,6 +25,7 @@ pub enum RpcError {
     SendResponse(RpcResponse),
     OneshotRecv(tokio::sync::oneshot::error::RecvError),
     SerdeJson(serde_json::Error),
+    MessagePack(rmp_serde::encode::Error),
     IO(std::io::Error),
     Request(String),
 }
@@ -62,12 +66,24 @@ impl From<serde_json::Error> for RpcError {
     }
 }
 
+impl From<rmp_serde::encode::Error> for RpcError {
+    fn from(e: rmp_serde::encode::Error) -> Self {
+        Self::MessagePack(e)
+    }
+}
+
 impl From<std::io::Error> for RpcError {
     fn from(e: std::io::Error) -> Self {
         Self::IO(e)
     }
 }
 
+/// Notification from the client for switching the encoding of the messages sent to the client,
+/// e.g., `{"method": "rpc/set-encoding", "params": ["msgpack"]}`.
+///
+/// Only Neovim can decode MessagePack, the messages are encoded in JSON by default.
+const SET_ENCODING_METHOD: &str = "rpc/set-encoding";
+
 #[derive(Serialize, Debug)]
 pub struct RpcClient {
     /// Id of request to Vim created from the Rust side.
@@ -99,17 +115,23 @@ impl RpcClient {
             _,
         ) = unbounded_channel();
 
+        // Whether to encode the outgoing messages in MessagePack.
+        let msgpack = Arc::new(AtomicBool::new(false));
+
         // A blocking task is necessary!
-        tokio::task::spawn_blocking(move || {
-            if let Err(error) = loop_read(reader, response_sender_rx, &sink) {
-                tracing::error!(?error, "Thread stdio-reader exited");
+        tokio::task::spawn_blocking({
+            let msgpack = msgpack.clone();
+            move || {
+                if let Err(error) = loop_read(reader, response_sender_rx, &sink, &msgpack) {
+                    tracing::error!(?error, "Thread stdio-reader exited");
+                }
             }
         });
 
         let (writer_sender, io_writer_receiver) = unbounded_channel();
         // No blocking task.
         tokio::spawn(async move {
-            if let Err(error) = loop_write(writer, io_writer_receiver).await {
+            if let Err(error) = loop_write(writer, io_writer_receiver, &msgpack).await {
                 tracing::error!(?error, "Thread stdio-writer exited");
             }
         });
@@ -195,6 +217,7 @@ fn loop_read(
     mut reader: impl BufRead,
     mut response_sender_rx: UnboundedReceiver<(u64, oneshot::Sender<RpcResponse>)>,
     sink: &UnboundedSender<VimMessage>,
+    msgpack: &AtomicBool,
 ) -> Result<(), RpcError> {
     let mut pending_response_senders = HashMap::new();
 
@@ -208,6 +231,16 @@ fn loop_read(
                             RpcMessage::Request(rpc_request) => {
                                 sink.send(VimMessage::Request(rpc_request))?;
                             }
+                            RpcMessage::Notification(notification)
+                                if notification.method == SET_ENCODING_METHOD =>
+                            {
+                                let use_msgpack = matches!(
+                                    notification.params,
+                                    Params::Array(ref params) if params.first().and_then(|p| p.as_str()) == Some("msgpack")
+                                );
+                                tracing::debug!(use_msgpack, "Set the encoding of messages");
+                                msgpack.store(use_msgpack, Ordering::SeqCst);
+                            }
                             RpcMessage::Notification(notification) => {
                                 sink.send(VimMessage::Notification(notification))?;
                             }
@@ -232,10 +265,12 @@ fn loop_read(
                         }
                     }
                 } else {
-                    println!("EOF reached");
+                    // The editor has gone, e.g., the client of the daemon is disconnected.
+                    tracing::debug!("EOF reached");
+                    return Ok(());
                 }
             }
-            Err(error) => println!("Failed to read_line, error: {error}"),
+            Err(error) => return Err(RpcError::IO(error)),
         }
     }
 }
@@ -244,14 +279,21 @@ fn loop_read(
 async fn loop_write(
     mut writer: impl Write,
     mut io_writer_receiver: UnboundedReceiver<RpcMessage>,
+    msgpack: &AtomicBool,
 ) -> Result<(), RpcError> {
     while let Some(msg) = io_writer_receiver.recv().await {
-        let s = serde_json::to_string(&msg)?;
+        let use_msgpack = msgpack.load(Ordering::SeqCst);
+
+        let payload = if use_msgpack {
+            rmp_serde::to_vec_named(&msg)?
+        } else {
+            serde_json::to_vec(&msg)?
+        };
 
-        if s.len() < 128 {
+        if payload.len() < 128 {
             tracing::trace!(?msg, "=> Vim");
         } else {
-            let msg_size = s.len();
+            let msg_size = payload.len();
             match msg {
                 RpcMessage::Request(request) => {
                     tracing::trace!(method = ?request.method, msg_size, "=> Vim Request")
@@ -273,7 +315,18 @@ async fn loop_write(
         // 1. If using '\r\ncontent', nvim will receive output as `\r` + `content`, while vim
         // receives `content`.
         // 2. Without last line ending, vim output handler won't be triggered.
-        write!(writer, "Content-length: {}\n\n{}\n", s.len(), s)?;
+        if use_msgpack {
+            // The payload follows the headers immediately as it may start with a newline byte.
+            write!(
+                writer,
+                "Content-length: {}\nContent-type: msgpack\n",
+                payload.len()
+            )?;
+        } else {
+            write!(writer, "Content-length: {}\n\n", payload.len())?;
+        }
+        writer.write_all(&payload)?;
+        writer.write_all(b"\n")?;
         writer.flush()?;
     }
 
@@ -0,0 +1,297 @@
+//! Client of the native [msgpack-rpc API](https://neovim.io/doc/user/api.html#RPC) of Neovim.
+//!
+//! Unlike [`crate::RpcClient`] which talks to the Vim script side of vim-clap over stdio, this
+//! client attaches to the RPC server of Neovim directly, e.g., `$NVIM` or `v:servername`, so that
+//! the API functions like `nvim_buf_get_lines` can be invoked without going through Vim script.
+//!
+//! The messages are MessagePack arrays:
+//!
+//! - Request: `[0, msgid, method, params]`
+//! - Response: `[1, msgid, error, result]`
+//! - Notification: `[2, method, params]`
+
+use crate::RpcError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+const REQUEST: u64 = 0;
+const RESPONSE: u64 = 1;
+const NOTIFICATION: u64 = 2;
+
+type PendingResponses = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value, Value>>>>>;
+
+pub struct NvimClient {
+    id: AtomicU64,
+    writer: Mutex<Box<dyn Write + Send>>,
+    pending_responses: PendingResponses,
+}
+
+impl std::fmt::Debug for NvimClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NvimClient")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl NvimClient {
+    /// Connects to the RPC server of Neovim at `address`.
+    ///
+    /// `address` is either a path of the unix domain socket or `host:port` of a TCP socket.
+    pub fn connect(address: &str) -> std::io::Result<Self> {
+        #[cfg(unix)]
+        if !address.contains(':') || std::path::Path::new(address).exists() {
+            let stream = std::os::unix::net::UnixStream::connect(address)?;
+            return Ok(Self::new(stream.try_clone()?, stream));
+        }
+
+        let stream = std::net::TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+        Ok(Self::new(stream.try_clone()?, stream))
+    }
+
+    /// Creates a new instance of [`NvimClient`] on top of the given streams, e.g., the stdio of
+    /// a process spawned by `jobstart(cmd, {'rpc': v:true})`.
+    pub fn new(reader: impl Read + Send + 'static, writer: impl Write + Send + 'static) -> Self {
+        let pending_responses = PendingResponses::default();
+
+        std::thread::Builder::new()
+            .name("nvim-rpc-reader".into())
+            .spawn({
+                let pending_responses = pending_responses.clone();
+                move || {
+                    if let Err(error) = loop_read(BufReader::new(reader), &pending_responses) {
+                        tracing::error!(?error, "Thread nvim-rpc-reader exited");
+                    }
+                    // Wake up all the pending requests.
+                    pending_responses.lock().unwrap().clear();
+                }
+            })
+            .expect("Failed to spawn the nvim-rpc-reader thread");
+
+        Self {
+            id: Default::default(),
+            writer: Mutex::new(Box::new(BufWriter::new(writer))),
+            pending_responses,
+        }
+    }
+
+    /// Calls the API `method` with `params` in Neovim and returns the result.
+    pub async fn request<R: DeserializeOwned>(
+        &self,
+        method: impl AsRef<str>,
+        params: impl Serialize,
+    ) -> Result<R, RpcError> {
+        let id = self.id.fetch_add(1, Ordering::SeqCst);
+        let (result_tx, result_rx) = oneshot::channel();
+        self.pending_responses.lock().unwrap().insert(id, result_tx);
+
+        if let Err(err) = self.write(&(REQUEST, id, method.as_ref(), params)) {
+            self.pending_responses.lock().unwrap().remove(&id);
+            return Err(err);
+        }
+
+        match result_rx.await? {
+            Ok(result) => Ok(serde_json::from_value(result)?),
+            Err(error) => Err(RpcError::Request(format!(
+                "Neovim request `{}` error: {error}",
+                method.as_ref()
+            ))),
+        }
+    }
+
+    /// Sends a notification to Neovim, no response will be sent back.
+    ///
+    /// Use `nvim_call_atomic` to send a batch of API calls in one message.
+    pub fn notify(&self, method: impl AsRef<str>, params: impl Serialize) -> Result<(), RpcError> {
+        self.write(&(NOTIFICATION, method.as_ref(), params))
+    }
+
+    fn write(&self, msg: &impl Serialize) -> Result<(), RpcError> {
+        let payload = rmp_serde::to_vec(msg)?;
+        let mut writer = self.writer.lock().unwrap();
+        writer.write_all(&payload)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Keep reading the messages from Neovim until the stream is closed.
+fn loop_read(mut reader: impl Read, pending_responses: &PendingResponses) -> std::io::Result<()> {
+    loop {
+        let message = match read_value(&mut reader) {
+            Ok(message) => message,
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        let Value::Array(mut message) = message else {
+            tracing::error!(?message, "Invalid Neovim message");
+            continue;
+        };
+
+        match message.first().and_then(Value::as_u64) {
+            Some(RESPONSE) if message.len() == 4 => {
+                let result = message.pop().unwrap_or_default();
+                let error = message.pop().unwrap_or_default();
+                let Some(id) = message.get(1).and_then(Value::as_u64) else {
+                    continue;
+                };
+                if let Some(sender) = pending_responses.lock().unwrap().remove(&id) {
+                    let response = if error.is_null() {
+                        Ok(result)
+                    } else {
+                        Err(error)
+                    };
+                    let _ = sender.send(response);
+                }
+            }
+            Some(REQUEST) | Some(NOTIFICATION) => {
+                // maple does not register any method to Neovim.
+                tracing::debug!(?message, "Ignored message from Neovim");
+            }
+            _ => {
+                tracing::error!(?message, "Invalid Neovim message");
+            }
+        }
+    }
+}
+
+fn invalid_data(error: impl std::fmt::Debug) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{error:?}"))
+}
+
+/// Reads a big-endian unsigned integer of `n` bytes.
+fn read_uint(reader: &mut impl Read, n: usize) -> std::io::Result<u64> {
+    let bytes = read_exact_vec(reader, n)?;
+    Ok(bytes.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b)))
+}
+
+fn read_exact_vec(reader: &mut impl Read, len: usize) -> std::io::Result<Vec<u8>> {
+    let mut buf = vec![0; len];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// Reads a MessagePack value as JSON.
+///
+/// The binary is converted to string lossily and the EXT types, i.e., the handles of
+/// Buffer/Window/Tabpage in Neovim, are converted to their numbers.
+fn read_value(reader: &mut impl Read) -> std::io::Result<Value> {
+    use rmp::Marker;
+
+    let marker = rmp::decode::read_marker(reader).map_err(|e| e.0)?;
+
+    let value = match marker {
+        Marker::Null => Value::Null,
+        Marker::True => Value::Bool(true),
+        Marker::False => Value::Bool(false),
+        Marker::FixPos(n) => Value::from(n),
+        Marker::FixNeg(n) => Value::from(n),
+        Marker::U8 => Value::from(read_uint(reader, 1)?),
+        Marker::U16 => Value::from(read_uint(reader, 2)?),
+        Marker::U32 => Value::from(read_uint(reader, 4)?),
+        Marker::U64 => Value::from(read_uint(reader, 8)?),
+        Marker::I8 => Value::from(read_uint(reader, 1)? as u8 as i8),
+        Marker::I16 => Value::from(read_uint(reader, 2)? as u16 as i16),
+        Marker::I32 => Value::from(read_uint(reader, 4)? as u32 as i32),
+        Marker::I64 => Value::from(read_uint(reader, 8)? as i64),
+        Marker::F32 => Value::from(f32::from_bits(read_uint(reader, 4)? as u32)),
+        Marker::F64 => Value::from(f64::from_bits(read_uint(reader, 8)?)),
+        Marker::FixStr(_) | Marker::Str8 | Marker::Str16 | Marker::Str32 => {
+            let len = match marker {
+                Marker::FixStr(n) => n as usize,
+                Marker::Str8 => read_uint(reader, 1)? as usize,
+                Marker::Str16 => read_uint(reader, 2)? as usize,
+                _ => read_uint(reader, 4)? as usize,
+            };
+            let bytes = read_exact_vec(reader, len)?;
+            Value::String(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        Marker::Bin8 | Marker::Bin16 | Marker::Bin32 => {
+            let len = match marker {
+                Marker::Bin8 => read_uint(reader, 1)? as usize,
+                Marker::Bin16 => read_uint(reader, 2)? as usize,
+                _ => read_uint(reader, 4)? as usize,
+            };
+            let bytes = read_exact_vec(reader, len)?;
+            Value::String(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        Marker::FixArray(_) | Marker::Array16 | Marker::Array32 => {
+            let len = match marker {
+                Marker::FixArray(n) => n as usize,
+                Marker::Array16 => read_uint(reader, 2)? as usize,
+                _ => read_uint(reader, 4)? as usize,
+            };
+            let array = (0..len)
+                .map(|_| read_value(reader))
+                .collect::<std::io::Result<Vec<_>>>()?;
+            Value::Array(array)
+        }
+        Marker::FixMap(_) | Marker::Map16 | Marker::Map32 => {
+            let len = match marker {
+                Marker::FixMap(n) => n as usize,
+                Marker::Map16 => read_uint(reader, 2)? as usize,
+                _ => read_uint(reader, 4)? as usize,
+            };
+            let mut map = serde_json::Map::with_capacity(len);
+            for _ in 0..len {
+                let key = match read_value(reader)? {
+                    Value::String(key) => key,
+                    key => key.to_string(),
+                };
+                map.insert(key, read_value(reader)?);
+            }
+            Value::Object(map)
+        }
+        Marker::FixExt1
+        | Marker::FixExt2
+        | Marker::FixExt4
+        | Marker::FixExt8
+        | Marker::FixExt16
+        | Marker::Ext8
+        | Marker::Ext16
+        | Marker::Ext32 => {
+            let len = match marker {
+                Marker::FixExt1 => 1,
+                Marker::FixExt2 => 2,
+                Marker::FixExt4 => 4,
+                Marker::FixExt8 => 8,
+                Marker::FixExt16 => 16,
+                Marker::Ext8 => read_uint(reader, 1)? as usize,
+                Marker::Ext16 => read_uint(reader, 2)? as usize,
+                _ => read_uint(reader, 4)? as usize,
+            };
+            // Skip the type byte, the data of EXT types in Neovim is the handle encoded in
+            // MessagePack.
+            let bytes = read_exact_vec(reader, len + 1)?;
+            read_value(&mut &bytes[1..]).unwrap_or(Value::Null)
+        }
+        Marker::Reserved => return Err(invalid_data("Reserved marker")),
+    };
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_read_value() {
+        let message = json!([1, 3, null, ["foo", -1, 65536, 1.5, {"bar": true}]]);
+        let payload = rmp_serde::to_vec(&message).unwrap();
+        assert_eq!(read_value(&mut payload.as_slice()).unwrap(), message);
+
+        // Buffer handle 5 sent by Neovim as EXT type 0.
+        let payload = [0xd4, 0x00, 0x05];
+        assert_eq!(read_value(&mut payload.as_slice()).unwrap(), json!(5));
+    }
+}
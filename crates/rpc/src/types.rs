@@ -303,7 +303,7 @@ impl From<Params> for Value {
 
 #[cfg(test)]
 mod tests {
-    use super::{Error, ErrorCode, Params, Value};
+    use super::{Error, ErrorCode, Params, RpcMessage, RpcNotification, Value};
 
     #[test]
     fn params_deserialization() {
@@ -361,4 +361,21 @@ mod tests {
         let params: (u64,) = Params::Array(vec![Value::from(1)]).parse().unwrap();
         assert_eq!(params, (1,));
     }
+
+    #[test]
+    fn message_msgpack_round_trip() {
+        let message = RpcMessage::Notification(RpcNotification {
+            method: "clap#state#update_picker".to_string(),
+            params: Params::Array(vec![serde_json::json!({
+                "lines": ["src/lib.rs", "src/main.rs"],
+                "indices": [[0, 1], []],
+                "partial": true,
+            })]),
+        });
+        let encoded = rmp_serde::to_vec_named(&message).unwrap();
+        assert_eq!(
+            rmp_serde::from_slice::<RpcMessage>(&encoded).unwrap(),
+            message
+        );
+    }
 }
@@ -0,0 +1,155 @@
+//! Transcoding of the legacy encoded files, e.g., Latin-1, Windows-1252 and UTF-16.
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, WINDOWS_1252};
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use std::io::{BufRead, BufReader, Read, Result};
+
+/// Number of the leading bytes inspected for detecting the encoding.
+const SNIFF_LEN: usize = 8 * 1024;
+
+/// Detects the encoding of a file from its leading bytes `head`.
+///
+/// Returns `None` if the content is UTF-8 or binary, i.e., no transcoding is needed. The BOM
+/// is respected, otherwise UTF-16 is guessed from the zero bytes of ASCII text and the invalid
+/// UTF-8 looking like a single-byte encoding is assumed to be Windows-1252, a superset of
+/// Latin-1. The other invalid UTF-8, e.g., Shift-JIS or a stray byte in UTF-8, is kept as is
+/// and decoded lossily.
+pub fn detect_encoding(head: &[u8]) -> Option<&'static Encoding> {
+    let head = &head[..head.len().min(SNIFF_LEN)];
+
+    if let Some((encoding, _bom_len)) = Encoding::for_bom(head) {
+        return Some(encoding);
+    }
+
+    let pairs = head.len() / 2;
+    if pairs > 0 {
+        let (even_zeros, odd_zeros) = head.iter().enumerate().filter(|(_, b)| **b == 0).fold(
+            (0, 0),
+            |(even, odd), (idx, _)| {
+                if idx % 2 == 0 {
+                    (even + 1, odd)
+                } else {
+                    (even, odd + 1)
+                }
+            },
+        );
+        if odd_zeros * 2 > pairs && even_zeros * 10 < pairs {
+            return Some(UTF_16LE);
+        }
+        if even_zeros * 2 > pairs && odd_zeros * 10 < pairs {
+            return Some(UTF_16BE);
+        }
+    }
+
+    if head.contains(&0) {
+        return None;
+    }
+
+    match std::str::from_utf8(head) {
+        Ok(_) => None,
+        // The head may end in the middle of a multi-byte char.
+        Err(err) if err.error_len().is_none() => None,
+        Err(_) if looks_single_byte(head) => Some(WINDOWS_1252),
+        Err(_) => None,
+    }
+}
+
+/// Returns true if `head` contains no valid multi-byte UTF-8 char and its non-ASCII bytes are
+/// mostly isolated, unlike the runs of the multi-byte encodings like Shift-JIS, EUC-JP or GBK.
+fn looks_single_byte(head: &[u8]) -> bool {
+    let mut rest = head;
+    while !rest.is_empty() {
+        let (valid_len, invalid_len) = match std::str::from_utf8(rest) {
+            Ok(_) => (rest.len(), 0),
+            Err(err) => (
+                err.valid_up_to(),
+                err.error_len().unwrap_or(rest.len() - err.valid_up_to()),
+            ),
+        };
+        // The valid non-ASCII bytes are multi-byte chars.
+        if !rest[..valid_len].is_ascii() {
+            return false;
+        }
+        rest = &rest[valid_len + invalid_len..];
+    }
+
+    let is_non_ascii_at = |idx: usize| head.get(idx).is_some_and(|b| !b.is_ascii());
+    let mut non_ascii = 0;
+    let mut isolated = 0;
+    for idx in (0..head.len()).filter(|idx| is_non_ascii_at(*idx)) {
+        non_ascii += 1;
+        if (idx == 0 || !is_non_ascii_at(idx - 1)) && !is_non_ascii_at(idx + 1) {
+            isolated += 1;
+        }
+    }
+
+    isolated * 2 > non_ascii
+}
+
+/// Transcodes `bytes` to UTF-8 if a legacy encoding is detected, otherwise returns them as is.
+pub fn transcode_to_utf8(bytes: Vec<u8>) -> Vec<u8> {
+    match detect_encoding(&bytes) {
+        Some(encoding) => encoding
+            .decode_with_bom_removal(&bytes)
+            .0
+            .into_owned()
+            .into_bytes(),
+        None => bytes,
+    }
+}
+
+/// Wraps `reader` to transcode its content to UTF-8 on the fly if a legacy encoding is
+/// detected from the leading bytes.
+pub fn transcoding_reader<R: Read>(reader: R) -> Result<impl Read> {
+    let mut reader = BufReader::with_capacity(SNIFF_LEN, reader);
+    let encoding = detect_encoding(reader.fill_buf()?);
+    Ok(DecodeReaderBytesBuilder::new()
+        .encoding(encoding)
+        .utf8_passthru(true)
+        .build(reader))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_utf16le(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(|u| u.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_detect_encoding() {
+        assert_eq!(detect_encoding(b"fn main() {}\n"), None);
+        assert_eq!(detect_encoding("caf\u{e9}\n".as_bytes()), None);
+        assert_eq!(detect_encoding(b"caf\xe9\n"), Some(WINDOWS_1252));
+        assert_eq!(detect_encoding(b"caf\xe9 na\xefve\n"), Some(WINDOWS_1252));
+        // A stray byte in UTF-8.
+        assert_eq!(detect_encoding(b"caf\xc3\xa9 \xff\n"), None);
+        // EUC-JP and Shift-JIS of `こんにちは`.
+        assert_eq!(
+            detect_encoding(b"\xa4\xb3\xa4\xf3\xa4\xcb\xa4\xc1\xa4\xcf\n"),
+            None
+        );
+        assert_eq!(
+            detect_encoding(b"\x82\xb1\x82\xf1\x82\xc9\x82\xbf\x82\xcd\n"),
+            None
+        );
+        assert_eq!(detect_encoding(&encode_utf16le("hello\n")), Some(UTF_16LE));
+        assert_eq!(detect_encoding(b"\xff\xfeh\x00i\x00"), Some(UTF_16LE));
+        assert_eq!(detect_encoding(b"\x7fELF\x00\x01\x02"), None);
+    }
+
+    #[test]
+    fn test_transcoding_reader() {
+        let mut content = String::new();
+        transcoding_reader(&b"caf\xe9\nna\xefve\n"[..])
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "caf\u{e9}\nna\u{ef}ve\n");
+
+        let mut bytes = vec![0xff, 0xfe];
+        bytes.extend(encode_utf16le("foo\nbar\n"));
+        assert_eq!(transcode_to_utf8(bytes), b"foo\nbar\n");
+    }
+}
@@ -1,6 +1,10 @@
 use std::fs::{read_dir, remove_dir_all, remove_file, File};
-use std::io::{BufRead, BufReader, Lines, Result};
-use std::path::Path;
+use std::io::{BufRead, BufReader, Lines, Read, Result, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+
+/// Magic number of the zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
 
 /// Counts lines in the source `handle`.
 ///
@@ -62,6 +66,114 @@ pub fn create_or_overwrite<P: AsRef<Path>>(path: P, buf: &[u8]) -> Result<()> {
     Ok(())
 }
 
+/// Returns true if `bytes` starts with the zstd magic number.
+pub fn is_zstd_compressed(bytes: &[u8]) -> bool {
+    bytes.starts_with(&ZSTD_MAGIC)
+}
+
+/// Opens the file for reading, the content of a `.zst` file compressed with zstd, e.g., the
+/// cache file compressed by [`compress_file`], is decompressed on the fly.
+pub fn open_reader<P: AsRef<Path>>(path: P) -> Result<Box<dyn Read + Send>> {
+    let path = path.as_ref();
+    let mut file = File::open(path)?;
+
+    if path.extension().and_then(|ext| ext.to_str()) != Some("zst") {
+        return Ok(Box::new(file));
+    }
+
+    let mut magic = [0u8; 4];
+    let is_zstd = match file.read_exact(&mut magic) {
+        Ok(()) => is_zstd_compressed(&magic),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => false,
+        Err(e) => return Err(e),
+    };
+    file.seek(SeekFrom::Start(0))?;
+
+    if is_zstd {
+        Ok(Box::new(zstd::stream::read::Decoder::new(file)?))
+    } else {
+        Ok(Box::new(file))
+    }
+}
+
+/// Reader of the stdout of a decompression command, the command is killed on drop.
+struct DecompressionReader {
+    child: Child,
+    stdout: ChildStdout,
+}
+
+impl Read for DecompressionReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.stdout.read(buf)
+    }
+}
+
+impl Drop for DecompressionReader {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Returns true if `path` has the extension of a compressed file supported by
+/// [`open_decompressed`], i.e., `.gz`, `.xz` and `.zst`.
+pub fn is_compressed_path<P: AsRef<Path>>(path: P) -> bool {
+    matches!(
+        path.as_ref().extension().and_then(|ext| ext.to_str()),
+        Some("gz" | "tgz" | "xz" | "txz" | "zst")
+    )
+}
+
+/// Opens the compressed file for reading its decompressed content, returns `None` if `path`
+/// is not a supported compressed file.
+///
+/// zstd is decompressed in process, gzip and xz are decompressed by the `gzip` and `xz`
+/// executables like `rg -z`.
+pub fn open_decompressed<P: AsRef<Path>>(path: P) -> Result<Option<Box<dyn Read + Send>>> {
+    let path = path.as_ref();
+
+    let decompressor = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("gz" | "tgz") => "gzip",
+        Some("xz" | "txz") => "xz",
+        Some("zst") => {
+            let decoder = zstd::stream::read::Decoder::new(File::open(path)?)?;
+            return Ok(Some(Box::new(decoder)));
+        }
+        _ => return Ok(None),
+    };
+
+    let mut child = Command::new(decompressor)
+        .arg("-dc")
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+
+    Ok(Some(Box::new(DecompressionReader { child, stdout })))
+}
+
+/// Compresses the file with zstd to `<path>.zst`, the original file is removed.
+///
+/// Returns the path of the compressed file.
+pub fn compress_file<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    let path = path.as_ref();
+
+    let mut compressed = path.as_os_str().to_owned();
+    compressed.push(".zst");
+
+    zstd::stream::copy_encode(
+        File::open(path)?,
+        File::create(&compressed)?,
+        zstd::DEFAULT_COMPRESSION_LEVEL,
+    )?;
+
+    remove_file(path)?;
+
+    Ok(compressed.into())
+}
+
 /// Returns an Iterator to the Reader of the lines of the file.
 ///
 /// The output is wrapped in a Result to allow matching on errors.
@@ -82,13 +194,15 @@ pub fn read_first_lines<P: AsRef<Path>>(
 }
 
 /// Returns a `number` of lines starting from the line number `from`.
+///
+/// The legacy encoded content is transcoded to UTF-8.
 pub fn read_lines_from<P: AsRef<Path>>(
     path: P,
     from: usize,
     number: usize,
 ) -> Result<impl Iterator<Item = String>> {
-    let file = File::open(path)?;
-    Ok(BufReader::new(file)
+    let reader = crate::transcoding_reader(open_reader(path)?)?;
+    Ok(BufReader::new(reader)
         .lines()
         .skip(from)
         .filter_map(Result::ok)
@@ -119,4 +233,50 @@ mod tests {
         let f: &[u8] = b"some text\nwith\nfour\nlines\n";
         assert_eq!(count_lines(f).unwrap(), 4);
     }
+
+    #[test]
+    fn test_compress_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache");
+        let content = "line 1\nline 2\nline 3\n".repeat(100);
+        create_or_overwrite(&path, content.as_bytes()).unwrap();
+
+        let compressed_path = compress_file(&path).unwrap();
+
+        assert!(!path.exists());
+        assert_eq!(compressed_path, dir.path().join("cache.zst"));
+        assert!(std::fs::metadata(&compressed_path).unwrap().len() < content.len() as u64);
+        assert_eq!(
+            count_lines(open_reader(&compressed_path).unwrap()).unwrap(),
+            300
+        );
+        assert_eq!(
+            read_lines_from(&compressed_path, 1, 2)
+                .unwrap()
+                .collect::<Vec<_>>(),
+            vec!["line 2", "line 3"]
+        );
+
+        // Only the `.zst` files are decompressed.
+        std::fs::rename(&compressed_path, &path).unwrap();
+        assert_ne!(count_lines(open_reader(&path).unwrap()).unwrap(), 300);
+    }
+
+    #[test]
+    fn test_open_decompressed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("foo.txt");
+        create_or_overwrite(&path, b"foo\nbar\n").unwrap();
+        let compressed_path = compress_file(&path).unwrap();
+
+        assert!(is_compressed_path(&compressed_path));
+        let mut content = String::new();
+        open_decompressed(&compressed_path)
+            .unwrap()
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "foo\nbar\n");
+        assert!(open_decompressed(&path).unwrap().is_none());
+    }
 }
@@ -7,10 +7,13 @@ use std::path::Path;
 use std::process::{Command, Output};
 
 pub mod bytelines;
+mod encoding;
 mod io;
 
+pub use self::encoding::{detect_encoding, transcode_to_utf8, transcoding_reader};
 pub use self::io::{
-    count_lines, create_or_overwrite, read_first_lines, read_lines, read_lines_from,
+    compress_file, count_lines, create_or_overwrite, is_compressed_path, is_zstd_compressed,
+    open_decompressed, open_reader, read_first_lines, read_lines, read_lines_from,
     remove_dir_contents,
 };
 
@@ -52,11 +52,16 @@ inoremap <silent> <buffer> <C-c> <Esc>:<c-u>call clap#handler#exit()<CR>
 inoremap <silent> <buffer> <C-g> <Esc>:<c-u>call clap#handler#exit()<CR>
 inoremap <silent> <buffer> <CR>  <Esc>:<c-u>call clap#handler#handle_mapping("\<CR\>")<CR>
 
-inoremap <silent> <buffer> <Down> <C-R>=clap#navigation#linewise_scroll('down')<CR>
-inoremap <silent> <buffer> <Up>   <C-R>=clap#navigation#linewise_scroll('up')<CR>
+if g:clap_input_history_with_arrows
+  inoremap <silent> <buffer> <Down> <C-R>=<SID>Notify('ctrl-n')<CR>
+  inoremap <silent> <buffer> <Up>   <C-R>=<SID>Notify('ctrl-p')<CR>
+else
+  inoremap <silent> <buffer> <Down> <C-R>=clap#navigation#linewise_scroll('down')<CR>
+  inoremap <silent> <buffer> <Up>   <C-R>=clap#navigation#linewise_scroll('up')<CR>
+endif
 
-inoremap <silent> <buffer> <ScrollWheelDown> <C-R>=clap#navigation#linewise_scroll('down')<CR>
-inoremap <silent> <buffer> <ScrollWheelUp>   <C-R>=clap#navigation#linewise_scroll('up')<CR>
+inoremap <silent> <buffer> <ScrollWheelDown> <C-R>=clap#mouse#notify('wheel-down')<CR>
+inoremap <silent> <buffer> <ScrollWheelUp>   <C-R>=clap#mouse#notify('wheel-up')<CR>
 
 inoremap <silent> <buffer> <PageDown> <C-R>=clap#navigation#scroll('down')<CR>
 inoremap <silent> <buffer> <PageUp>   <C-R>=clap#navigation#scroll('up')<CR>
@@ -65,7 +70,8 @@ inoremap <silent> <buffer> <Tab>       <C-R>=clap#handler#handle_mapping("\<Tab\
 inoremap <silent> <buffer> <Backspace> <C-R>=clap#handler#handle_mapping("\<BS\>")<CR>
 inoremap <silent> <buffer> <A-u>       <C-R>=clap#handler#handle_mapping("\<A-U\>")<CR>
 
-inoremap <silent> <buffer> <LeftMouse>       <C-R>=clap#handler#handle_mapping("\<Tab\>")<CR>
+inoremap <silent> <buffer> <LeftMouse>       <C-R>=clap#mouse#notify('click')<CR>
+inoremap <silent> <buffer> <2-LeftMouse>     <C-R>=clap#mouse#notify('double-click')<CR>
 inoremap <silent> <buffer> <RightMouse>      <C-R>=clap#handler#handle_mapping("\<Tab\>")<CR>
 
 inoremap <silent> <buffer> <C-j> <C-R>=clap#navigation#linewise_scroll('down')<CR>
@@ -86,13 +92,19 @@ nnoremap <silent> <buffer> <C-c>     :<c-u>call clap#handler#exit()<CR>
 nnoremap <silent> <buffer> <C-g>     :<c-u>call clap#handler#exit()<CR>
 nnoremap <silent> <buffer> <CR>      :<c-u>call clap#handler#handle_mapping("\<CR\>")<CR>
 
-nnoremap <silent> <buffer> <Down> :<c-u>call clap#navigation#linewise_scroll('down')<CR>
-nnoremap <silent> <buffer> <Up>   :<c-u>call clap#navigation#linewise_scroll('up')<CR>
+if g:clap_input_history_with_arrows
+  nnoremap <silent> <buffer> <Down> :<c-u>call clap#client#notify_provider('ctrl-n')<CR>
+  nnoremap <silent> <buffer> <Up>   :<c-u>call clap#client#notify_provider('ctrl-p')<CR>
+else
+  nnoremap <silent> <buffer> <Down> :<c-u>call clap#navigation#linewise_scroll('down')<CR>
+  nnoremap <silent> <buffer> <Up>   :<c-u>call clap#navigation#linewise_scroll('up')<CR>
+endif
 
-nnoremap <silent> <buffer> <ScrollWheelDown> :<c-u>call clap#navigation#linewise_scroll('down')<CR>
-nnoremap <silent> <buffer> <ScrollWheelUp>   :<c-u>call clap#navigation#linewise_scroll('up')<CR>
+nnoremap <silent> <buffer> <ScrollWheelDown> :<c-u>call clap#mouse#notify('wheel-down')<CR>
+nnoremap <silent> <buffer> <ScrollWheelUp>   :<c-u>call clap#mouse#notify('wheel-up')<CR>
 
-nnoremap <silent> <buffer> <LeftMouse>       :<c-u>call clap#handler#handle_mapping("\<CR\>")<CR>
+nnoremap <silent> <buffer> <LeftMouse>       :<c-u>call clap#mouse#notify('click')<CR>
+nnoremap <silent> <buffer> <2-LeftMouse>     :<c-u>call clap#mouse#notify('double-click')<CR>
 nnoremap <silent> <buffer> <RightMouse>      :<c-u>call clap#handler#handle_mapping("\<CR\>")<CR>
 
 nnoremap <silent> <buffer> <PageDown> :<c-u>call clap#navigation#scroll('down')<CR>
@@ -123,6 +135,12 @@ nnoremap <silent> <buffer> <C-p> :<c-u>call clap#client#notify_provider('ctrl-p'
 inoremap <silent> <buffer> <C-n> <C-R>=<SID>Notify('ctrl-n')<CR>
 inoremap <silent> <buffer> <C-p> <C-R>=<SID>Notify('ctrl-p')<CR>
 
+nnoremap <silent> <buffer> <C-q> :<c-u>call clap#client#notify_provider('ctrl-q')<CR>
+inoremap <silent> <buffer> <C-q> <C-R>=<SID>Notify('ctrl-q')<CR>
+
+nnoremap <silent> <buffer> <C-r> :<c-u>call clap#client#notify_provider('ctrl-r')<CR>
+inoremap <silent> <buffer> <C-r> <C-R>=<SID>Notify('ctrl-r')<CR>
+
 " Preview scroll
 nnoremap <silent> <buffer> <S-Up>   :<c-u>call clap#client#notify_provider('shift-up')<CR>
 nnoremap <silent> <buffer> <S-Down> :<c-u>call clap#client#notify_provider('shift-down')<CR>
@@ -53,10 +53,31 @@ augroup VimClap
   autocmd BufWinEnter,WinEnter * let g:__clap_buffers[bufnr('')] = reltimefloat(reltime())
 
   autocmd BufAdd      * call clap#client#notify('note_recent_files', [+expand('<abuf>')])
+  autocmd BufWritePost * call clap#client#notify('note_recent_files', [+expand('<abuf>'), 'edit'])
+  " The colorscheme clears the highlight groups defined by the palette of config.toml.
+  autocmd ColorScheme * call clap#client#notify('apply-palette')
 
   if get(g:, 'clap_plugin_experimental', 0)
-    autocmd CursorMoved * call clap#client#notify('CursorMoved')
+    autocmd CursorMoved * call clap#client#notify_autocmd('CursorMoved')
     autocmd InsertEnter * call clap#client#notify('InsertEnter')
+    if exists('##WinScrolled')
+      autocmd WinScrolled * if +expand('<amatch>') == win_getid() | call clap#client#notify_autocmd('WinScrolled') | endif
+    endif
+    if exists('##TextYankPost')
+      autocmd TextYankPost * call clap#client#notify('TextYankPost', [{
+            \ 'regcontents': v:event.regcontents,
+            \ 'regtype': v:event.regtype,
+            \ 'filetype': &filetype,
+            \ }])
+    endif
+  endif
+
+  " quickfix_history provider
+  autocmd QuickFixCmdPost * call clap#provider#quickfix_history#note_created(expand('<amatch>'))
+
+  " zoxide provider
+  if exists('##DirChanged') && get(g:, 'clap_provider_zoxide_track_dirs', v:false)
+    autocmd DirChanged * call clap#provider#zoxide#note_dir(get(v:event, 'cwd', getcwd()))
   endif
 
   " yanks provider
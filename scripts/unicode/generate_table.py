@@ -0,0 +1,185 @@
+#!/usr/bin/env python3
+# -*- coding: utf-8 -*-
+
+#  Generates unicode_table.txt used by the unicode provider from the Unicode
+#  database bundled with Python, rerun this script to update the table:
+#    ./generate_table.py
+#
+#  Each line is in the format of `char  U+XXXX  name  :alias:...`.
+import unicodedata
+
+LETTER_PREFIXES = (
+    'LATIN',
+    'GREEK',
+    'CYRILLIC',
+    'DIGIT',
+    'ROMAN NUMERAL',
+    'CIRCLED',
+    'PARENTHESIZED',
+    'SUPERSCRIPT',
+    'SUBSCRIPT',
+    'VULGAR FRACTION',
+    'MATHEMATICAL',
+)
+
+# Common emoji shortcodes.
+ALIASES = {
+    0x1F600: ['grinning'],
+    0x1F603: ['smiley'],
+    0x1F604: ['smile'],
+    0x1F601: ['grin'],
+    0x1F606: ['laughing', 'satisfied'],
+    0x1F605: ['sweat_smile'],
+    0x1F602: ['joy'],
+    0x1F923: ['rofl'],
+    0x1F642: ['slightly_smiling_face'],
+    0x1F643: ['upside_down_face'],
+    0x1F609: ['wink'],
+    0x1F60A: ['blush'],
+    0x1F607: ['innocent'],
+    0x1F60D: ['heart_eyes'],
+    0x1F618: ['kissing_heart'],
+    0x1F60B: ['yum'],
+    0x1F61C: ['stuck_out_tongue_winking_eye'],
+    0x1F914: ['thinking'],
+    0x1F610: ['neutral_face'],
+    0x1F611: ['expressionless'],
+    0x1F644: ['roll_eyes'],
+    0x1F60F: ['smirk'],
+    0x1F60C: ['relieved'],
+    0x1F614: ['pensive'],
+    0x1F634: ['sleeping'],
+    0x1F637: ['mask'],
+    0x1F92F: ['exploding_head'],
+    0x1F60E: ['sunglasses'],
+    0x1F913: ['nerd_face'],
+    0x1F615: ['confused'],
+    0x1F61F: ['worried'],
+    0x1F62E: ['open_mouth'],
+    0x1F633: ['flushed'],
+    0x1F622: ['cry'],
+    0x1F62D: ['sob'],
+    0x1F631: ['scream'],
+    0x1F621: ['rage'],
+    0x1F620: ['angry'],
+    0x1F608: ['smiling_imp'],
+    0x1F480: ['skull'],
+    0x1F4A9: ['poop', 'hankey'],
+    0x1F921: ['clown_face'],
+    0x1F47B: ['ghost'],
+    0x1F47D: ['alien'],
+    0x1F916: ['robot'],
+    0x1F648: ['see_no_evil'],
+    0x1F44D: ['+1', 'thumbsup'],
+    0x1F44E: ['-1', 'thumbsdown'],
+    0x1F44C: ['ok_hand'],
+    0x270C: ['v'],
+    0x1F91E: ['crossed_fingers'],
+    0x1F44F: ['clap'],
+    0x1F64C: ['raised_hands'],
+    0x1F64F: ['pray'],
+    0x1F4AA: ['muscle'],
+    0x1F44B: ['wave'],
+    0x1F440: ['eyes'],
+    0x2764: ['heart'],
+    0x1F494: ['broken_heart'],
+    0x1F4AF: ['100'],
+    0x1F4A5: ['boom', 'collision'],
+    0x1F4AB: ['dizzy'],
+    0x1F4A4: ['zzz'],
+    0x1F525: ['fire'],
+    0x2728: ['sparkles'],
+    0x2B50: ['star'],
+    0x1F31F: ['star2'],
+    0x26A1: ['zap'],
+    0x2600: ['sunny'],
+    0x1F308: ['rainbow'],
+    0x2744: ['snowflake'],
+    0x1F389: ['tada'],
+    0x1F38A: ['confetti_ball'],
+    0x1F381: ['gift'],
+    0x1F3C6: ['trophy'],
+    0x1F680: ['rocket'],
+    0x1F41B: ['bug'],
+    0x1F528: ['hammer'],
+    0x1F527: ['wrench'],
+    0x1F6A7: ['construction'],
+    0x1F512: ['lock'],
+    0x1F511: ['key'],
+    0x1F4DD: ['memo', 'pencil'],
+    0x1F4A1: ['bulb'],
+    0x1F4E6: ['package'],
+    0x1F4C8: ['chart_with_upwards_trend'],
+    0x1F50D: ['mag'],
+    0x1F517: ['link'],
+    0x1F3A8: ['art'],
+    0x267B: ['recycle'],
+    0x2705: ['white_check_mark'],
+    0x2714: ['heavy_check_mark'],
+    0x274C: ['x'],
+    0x26A0: ['warning'],
+    0x2757: ['exclamation', 'heavy_exclamation_mark'],
+    0x2753: ['question'],
+    0x1F6AB: ['no_entry_sign'],
+    0x1F195: ['new'],
+    0x1F197: ['ok'],
+    0x1F19A: ['vs'],
+    0x2615: ['coffee'],
+    0x1F37A: ['beer'],
+    0x1F355: ['pizza'],
+    0x1F34E: ['apple'],
+    0x1F436: ['dog'],
+    0x1F431: ['cat'],
+    0x1F98A: ['fox_face'],
+    0x1F422: ['turtle'],
+    0x1F40D: ['snake'],
+    0x1F980: ['crab'],
+    0x1F40C: ['snail'],
+    0x1F333: ['deciduous_tree'],
+    0x1F30D: ['earth_africa'],
+    0x1F319: ['crescent_moon'],
+    0x23F0: ['alarm_clock'],
+    0x231B: ['hourglass'],
+    0x1F4BB: ['computer'],
+    0x2328: ['keyboard'],
+    0x1F5A5: ['desktop_computer'],
+    0x1F4F1: ['iphone'],
+    0x1F4E7: ['email', 'e-mail'],
+    0x1F4DA: ['books'],
+    0x1F4D6: ['book', 'open_book'],
+    0x1F4CC: ['pushpin'],
+    0x1F4CE: ['paperclip'],
+    0x2702: ['scissors'],
+    0x1F5D1: ['wastebasket'],
+    0x1F3C3: ['runner', 'running'],
+    0x1F6B2: ['bike'],
+    0x1F697: ['car', 'red_car'],
+    0x2708: ['airplane'],
+}
+
+
+def is_bundled(char, name):
+    category = unicodedata.category(char)
+    if category[0] in 'SP':
+        return True
+    return category[0] in 'LN' and name.startswith(LETTER_PREFIXES)
+
+
+lines = []
+for codepoint in range(0x110000):
+    char = chr(codepoint)
+    name = unicodedata.name(char, '')
+    if not name or not is_bundled(char, name):
+        continue
+    line = '%s  U+%04X  %s' % (char, codepoint, name.lower())
+    aliases = ALIASES.get(codepoint)
+    if aliases:
+        line += '  ' + ' '.join(':%s:' % alias for alias in aliases)
+    lines.append(line)
+
+missing = [hex(codepoint) for codepoint in ALIASES if not is_bundled(chr(codepoint), unicodedata.name(chr(codepoint), ''))]
+assert not missing, 'Aliases of the characters not in the table: %s' % missing
+
+with open('unicode_table.txt', 'w', encoding='utf-8') as f:
+    f.write('\n'.join(lines))
+    f.write('\n')
@@ -0,0 +1,7 @@
+syntax match ClapChange /^[ >]\s*\d\+/ nextgroup=ClapChangeLineCol
+syntax match ClapChangesHeader /change line  col text/
+syntax match ClapChangeLineCol /\s\+\zs\d\+\ze\s\+/
+
+hi default link ClapChange        Function
+hi default link ClapChangesHeader Title
+hi default link ClapChangeLineCol Number
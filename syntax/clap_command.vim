@@ -1,5 +1,7 @@
 
-syntax match  ClapCommand                /\v^\s+\S+\s+\S+\s+/   contains=ClapCommandArgs,ClapCommandArgsNone
+syntax match  ClapCommand                /\v^\s+\S+\s+\S+\s+/   contains=ClapCommandArgs,ClapCommandArgsNone,ClapCommandBuiltin
+syntax match  ClapCommandBuiltin         /\v\[:\]\s+/         contained nextgroup=ClapCommandNameB
+syntax match  ClapCommandNameB           /\v\l\w*/              contained skipwhite nextgroup=ClapCommandRest
 syntax match  ClapCommandArgs            /\v\[[1?*+]\]\s+/      contained nextgroup=ClapCommandName
 syntax match  ClapCommandArgsNone        /\v\[0\]\s+/           contained nextgroup=ClapCommandNameI
 syntax match  ClapCommandName            /\v\u\w*/              contained skipwhite nextgroup=ClapCommandRest
@@ -11,3 +13,5 @@ hi default link ClapCommandArgsNone      Keyword
 hi default link ClapCommandName          ModeMsg
 hi default link ClapCommandNameI         WarningMsg
 hi default link ClapCommandRest          Comment
+hi default link ClapCommandBuiltin       Special
+hi default link ClapCommandNameB         Statement
@@ -16,8 +16,8 @@ hi def link gitAuthor String
 
 syn match gitAdded     "^\W*\zsA\t.*"
 syn match gitDeleted   "^\W*\zsD\t.*"
-hi def link gitAdded    diffAdded
-hi def link gitDeleted  diffRemoved
+hi def link gitAdded    ClapDiffAdded
+hi def link gitDeleted  ClapDiffRemoved
 
 
 syn match diffAdded   "^+.*"
@@ -28,7 +28,9 @@ syn match diffFile    "^+++ .*"
 syn match diffNewFile "^--- .*"
 hi def link diffFile    Type
 hi def link diffNewFile diffFile
-hi def link diffAdded   Identifier
-hi def link diffRemoved Special
+hi def link diffAdded   ClapDiffAdded
+hi def link diffRemoved ClapDiffRemoved
+hi def link ClapDiffAdded   Identifier
+hi def link ClapDiffRemoved Special
 hi def link diffFile    Type
 hi def link diffLine    Statement
@@ -0,0 +1,9 @@
+syntax match ClapDockerId /^\x\{12}/ nextgroup=ClapDockerName skipwhite
+syntax match ClapDockerName /\S\+/ contained
+syntax match ClapDockerUp /\<Up\>/
+syntax match ClapDockerExited /\<Exited\>/
+
+hi default link ClapDockerId     Number
+hi default link ClapDockerName   Function
+hi default link ClapDockerUp     String
+hi default link ClapDockerExited Comment
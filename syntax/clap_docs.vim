@@ -0,0 +1,5 @@
+syntax match ClapDocsType /\t\zs\[[^\]]*\]\ze\t/
+syntax match ClapDocsLocation /\t\zs[^\t]\+$/
+
+hi default link ClapDocsType     Type
+hi default link ClapDocsLocation Comment
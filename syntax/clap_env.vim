@@ -0,0 +1,5 @@
+syntax match ClapEnvSource /^\[\a\+\]/ nextgroup=ClapEnvName skipwhite
+syntax match ClapEnvName /[^=]\+\ze=/ contained
+
+hi default link ClapEnvSource Comment
+hi default link ClapEnvName   Identifier
@@ -0,0 +1,11 @@
+syntax match ClapHealthOk /^\[OK\s*\]/ nextgroup=ClapHealthSubject skipwhite
+syntax match ClapHealthWarn /^\[WARN\s*\]/ nextgroup=ClapHealthSubject skipwhite
+syntax match ClapHealthError /^\[ERROR\]/ nextgroup=ClapHealthSubject skipwhite
+syntax match ClapHealthSubject /[^:]\+:/ contained
+syntax match ClapHealthFix /, fix: .*$/
+
+hi default link ClapHealthOk      String
+hi default link ClapHealthWarn    WarningMsg
+hi default link ClapHealthError   Error
+hi default link ClapHealthSubject Identifier
+hi default link ClapHealthFix     Comment
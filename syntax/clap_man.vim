@@ -0,0 +1,5 @@
+syntax match ClapManName /^[^ (]\+/ nextgroup=ClapManSection
+syntax match ClapManSection /([^)]\+)/ contained
+
+hi default link ClapManName    Function
+hi default link ClapManSection Number
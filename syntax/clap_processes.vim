@@ -0,0 +1,5 @@
+syntax match ClapProcessesPid /^\s*\d\+/ nextgroup=ClapProcessesUsage skipwhite
+syntax match ClapProcessesUsage /\S\+%\s\+\S\+%/ contained
+
+hi default link ClapProcessesPid   Number
+hi default link ClapProcessesUsage Comment
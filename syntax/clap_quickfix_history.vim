@@ -0,0 +1,9 @@
+syntax match ClapQuickfixHistoryCurrent /^>/
+syntax match ClapQuickfixHistoryKind /^.\s\zs\(qf\|loc\)\ze\s/
+syntax match ClapQuickfixHistorySize /\d\+ items/
+syntax match ClapQuickfixHistoryAge /\d\+[smhd] ago/
+
+hi default link ClapQuickfixHistoryCurrent Special
+hi default link ClapQuickfixHistoryKind    Keyword
+hi default link ClapQuickfixHistorySize    Number
+hi default link ClapQuickfixHistoryAge     Comment
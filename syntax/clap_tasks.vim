@@ -0,0 +1,5 @@
+syntax match ClapTasksKind /^\[\a\+\]/ nextgroup=ClapTasksName skipwhite
+syntax match ClapTasksName /.*/ contained
+
+hi default link ClapTasksKind Type
+hi default link ClapTasksName Function
@@ -0,0 +1,11 @@
+syntax match ClapTodoTag /^\[[^]]*\]/ nextgroup=ClapTodoSeverity skipwhite
+syntax match ClapTodoSeverity /\[\(low\|medium\|high\)\]/ contained contains=ClapTodoHigh nextgroup=ClapTodoBlame skipwhite
+syntax match ClapTodoHigh /high/ contained
+syntax match ClapTodoBlame /\[@[^]]*\]/ contained nextgroup=ClapTodoPath skipwhite
+syntax match ClapTodoPath /[^:]*:\d\+:\d\+:/ contained
+
+hi default link ClapTodoTag      Todo
+hi default link ClapTodoSeverity Type
+hi default link ClapTodoHigh     Error
+hi default link ClapTodoBlame    Comment
+hi default link ClapTodoPath     Directory
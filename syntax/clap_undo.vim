@@ -0,0 +1,13 @@
+syntax match ClapUndoCurrent /^\*/
+syntax match ClapUndoSeq /^\*\?\s*\zs\d\+/ nextgroup=ClapUndoAge skipwhite
+syntax match ClapUndoAge /\S\+/ contained nextgroup=ClapUndoSaved,ClapUndoAdded skipwhite
+syntax match ClapUndoSaved /\[saved\]/ contained nextgroup=ClapUndoAdded skipwhite
+syntax match ClapUndoAdded /+\d\+/ contained nextgroup=ClapUndoDeleted skipwhite
+syntax match ClapUndoDeleted /-\d\+/ contained
+
+hi default link ClapUndoCurrent Special
+hi default link ClapUndoSeq     Number
+hi default link ClapUndoAge     Comment
+hi default link ClapUndoSaved   Type
+hi default link ClapUndoAdded   DiffAdd
+hi default link ClapUndoDeleted DiffDelete
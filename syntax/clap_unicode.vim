@@ -0,0 +1,5 @@
+syntax match ClapUnicodeCodepoint /\<U+\x\{4,6}\>/
+syntax match ClapUnicodeAlias /:[^: ]\+:/
+
+hi default link ClapUnicodeCodepoint Number
+hi default link ClapUnicodeAlias Identifier
@@ -0,0 +1,61 @@
+" Tests of autoload/clap/quick_select.vim, run from the root of the repo:
+"
+"   vim -Nu NONE -Es -S test/vim/quick_select_test.vim
+"
+" The failures are printed and Vim exits with a non-zero status.
+
+set runtimepath^=.
+
+let s:accepted = []
+
+" Stub of the sink accepting the entry under the label, which replaces the
+" autoloaded one.
+runtime autoload/clap/mouse.vim
+function! clap#mouse#accept_line(lnum) abort
+  call add(s:accepted, a:lnum)
+endfunction
+
+function! s:test_lookup() abort
+  let labels = {'aa': 3, 'as': 4, 'sa': 5}
+  call assert_equal(0, clap#quick_select#lookup(labels, 'a'))
+  call assert_equal(4, clap#quick_select#lookup(labels, 'as'))
+  call assert_equal(5, clap#quick_select#lookup(labels, 'sa'))
+  call assert_equal(-1, clap#quick_select#lookup(labels, 'd'))
+  call assert_equal(-1, clap#quick_select#lookup(labels, 'ad'))
+endfunction
+
+" Types `keys` once the labels are shown and returns the accepted lines.
+function! s:quick_select(labels, keys) abort
+  let s:accepted = []
+  call feedkeys(a:keys, 't')
+  call clap#quick_select#start(a:labels)
+  sleep 200m
+  return s:accepted
+endfunction
+
+function! s:test_typed_label() abort
+  call setline(1, map(range(1, 10), '"line ".v:val'))
+  let g:clap = {'display': {'winid': win_getid(), 'bufnr': bufnr('')}}
+
+  let labels = [[2, 'a'], [3, 's'], [4, 'd']]
+  call assert_equal([3], s:quick_select(labels, 's'))
+  call assert_equal([], s:quick_select(labels, "\<Esc>"))
+  call assert_equal([], s:quick_select(labels, 'x'))
+
+  let labels = [[2, 'aa'], [3, 'as'], [4, 'sa']]
+  call assert_equal([4], s:quick_select(labels, 'sa'))
+  call assert_equal([3], s:quick_select(labels, 'as'))
+  call assert_equal([], s:quick_select(labels, "a\<Esc>"))
+  call assert_equal([], s:quick_select(labels, 'ad'))
+endfunction
+
+call s:test_lookup()
+call s:test_typed_label()
+
+if empty(v:errors)
+  qall!
+endif
+for s:error in v:errors
+  verbose echo s:error
+endfor
+cquit!